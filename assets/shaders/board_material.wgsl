@@ -0,0 +1,42 @@
+#import bevy_sprite::mesh2d_types
+#import bevy_sprite::mesh2d_view_bindings
+
+@group(1) @binding(0)
+var state: texture_2d<f32>;
+@group(1) @binding(1)
+var empty_texture: texture_2d<f32>;
+@group(1) @binding(2)
+var empty_sampler: sampler;
+@group(1) @binding(3)
+var filled_texture: texture_2d<f32>;
+@group(1) @binding(4)
+var filled_sampler: sampler;
+@group(1) @binding(5)
+var crossed_texture: texture_2d<f32>;
+@group(1) @binding(6)
+var crossed_sampler: sampler;
+
+@fragment
+fn fragment(
+    #import bevy_sprite::mesh2d_vertex_output
+) -> @location(0) vec4<f32> {
+    let size = textureDimensions(state);
+    // quad uvs start at the top, board rows start at the bottom
+    let board_uv = vec2<f32>(uv.x, 1.0 - uv.y) * vec2<f32>(size);
+    let cell = min(vec2<i32>(board_uv), size - vec2<i32>(1, 1));
+    let value = textureLoad(state, cell, 0).r;
+
+    // sample every texture before branching, sampling needs uniform control flow
+    let tile_uv = vec2<f32>(fract(board_uv.x), 1.0 - fract(board_uv.y));
+    let empty = textureSample(empty_texture, empty_sampler, tile_uv);
+    let filled = textureSample(filled_texture, filled_sampler, tile_uv);
+    let crossed = textureSample(crossed_texture, crossed_sampler, tile_uv);
+
+    if (value > 0.75) {
+        return crossed;
+    }
+    if (value > 0.25) {
+        return filled;
+    }
+    return empty;
+}
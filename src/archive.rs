@@ -0,0 +1,224 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+use picross_handler::Cell;
+use wasm_bindgen::JsValue;
+
+use crate::board::{cell_to_char, char_to_cell, Board, BoardLoadedEvent};
+use crate::bridge::{check_len, reply_error, MAX_CELLS_LEN, MAX_CLUES_LEN};
+use crate::flip_book::FlipBook;
+use crate::history::History;
+use crate::options::BoardOptions;
+use crate::playlist::Playlist;
+use crate::stats::Stats;
+use crate::timer::SolveTimer;
+use crate::trace::json_string;
+use crate::win_goal::WinGoal;
+use crate::{BridgeCommandEvent, NewBoardEvent, WASMSendChannel};
+
+// endregion
+
+// region:      CONSTANTS
+
+const ARCHIVE_VERSION: u32 = 1;
+const COMPRESSION_LEVEL: u8 = 9;
+/// Most a bundle may inflate to, far past any real round.
+const MAX_ARCHIVE_LEN: usize = 16 * 1024 * 1024;
+
+// endregion
+
+// region:      RESOURCES
+
+/// Whether the board is an imported archive, which is only looked at: the
+/// player's moves on it are rejected like a spectator's.
+#[derive(Resource, Default)]
+pub struct ArchiveView {
+    pub viewing: bool,
+    /// The archive's board is still loading, its load doesn't end the view.
+    loading: bool,
+}
+
+// endregion
+
+pub struct ArchivePlugin;
+
+impl Plugin for ArchivePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ArchiveView>()
+            .add_system(archive_export_system)
+            .add_system(archive_import_system)
+            .add_system(archive_view_system.after(archive_import_system));
+    }
+}
+
+/// Everything there is to keep of the round as json: the final board, every
+/// change in the log marked `"p"` for this player's or `"r"` for everyone
+/// else's, the hints taken and the stats. Cells are in the puzzle's own
+/// layout whatever the board was randomized to.
+fn archive_json(
+    board: &Board,
+    history: &History,
+    clues: &str,
+    player: &str,
+    stats_json: &str,
+) -> String {
+    let (w, h) = (board.p.get_width(), board.p.get_height());
+    let cell = |cell: Cell| format!("\"{}\"", cell_to_char(cell));
+    let changes: Vec<String> = history
+        .entries
+        .iter()
+        .filter(|entry| entry.x < w && entry.y < h)
+        .map(|entry| {
+            let (x, y) = board.orientation.to_original(entry.x, entry.y, w, h);
+            format!(
+                "[{},{},{},{},{},\"{}\"]",
+                (entry.t * 1000.) as u64,
+                x,
+                y,
+                cell(entry.old),
+                cell(entry.cell),
+                if entry.from_player { 'p' } else { 'r' }
+            )
+        })
+        .collect();
+    let hints: Vec<String> = history
+        .hints
+        .iter()
+        .map(|t| ((t * 1000.) as u64).to_string())
+        .collect();
+    let moves = history.player_entries().count();
+    format!(
+        "{{\"version\":{},\"id\":{},\"clues\":{},\"cells\":\"{}\",\"participants\":{{\"player\":{},\"player_moves\":{},\"other_moves\":{}}},\"history\":[{}],\"hints\":[{}],\"stats\":{}}}",
+        ARCHIVE_VERSION,
+        json_string(&board.id),
+        json_string(clues),
+        board.original_cells(),
+        json_string(player),
+        moves,
+        history.entries.len() - moves,
+        changes.join(","),
+        hints.join(","),
+        stats_json
+    )
+}
+
+/// A json bundle deflated and base64 encoded for the bridge.
+fn pack(json: &str) -> String {
+    base64::encode(miniz_oxide::deflate::compress_to_vec(
+        json.as_bytes(),
+        COMPRESSION_LEVEL,
+    ))
+}
+
+fn unpack(bundle: &str) -> Result<String, String> {
+    let bytes = base64::decode(bundle.trim()).map_err(|err| format!("archive: {}", err))?;
+    let json = miniz_oxide::inflate::decompress_to_vec_with_limit(&bytes, MAX_ARCHIVE_LEN)
+        .map_err(|_| String::from("archive: corrupt or oversized bundle"))?;
+    String::from_utf8(json).map_err(|_| String::from("archive: bundle isn't text"))
+}
+
+/// What an import needs of a bundle: the clues, the final cells, and the
+/// stats to hand back.
+fn read_archive(json: &str) -> Result<(String, String, String), String> {
+    let json = js_sys::JSON::parse(json)
+        .ok()
+        .filter(|json| json.is_object())
+        .ok_or_else(|| String::from("archive: invalid json"))?;
+    let field = |key: &str| js_sys::Reflect::get(&json, &JsValue::from_str(key)).ok();
+    let version = field("version").and_then(|value| value.as_f64());
+    if version != Some(ARCHIVE_VERSION as f64) {
+        return Err(String::from("archive: unknown version"));
+    }
+    let text = |key: &str| {
+        field(key)
+            .and_then(|value| value.as_string())
+            .ok_or_else(|| format!("archive: missing {}", key))
+    };
+    let (clues, cells) = (text("clues")?, text("cells")?);
+    check_len("clues", &clues, MAX_CLUES_LEN)?;
+    check_len("cells", &cells, MAX_CELLS_LEN)?;
+    if cells.chars().any(|c| char_to_cell(c).is_none()) {
+        return Err(String::from("archive: invalid cells"));
+    }
+    let stats = field("stats")
+        .and_then(|stats| js_sys::JSON::stringify(&stats).ok())
+        .map(String::from)
+        .unwrap_or_else(|| String::from("{}"));
+    Ok((clues, cells, stats))
+}
+
+/// `("round_end", "")` replies `("archive", bundle)`, the round's
+/// `archive_json` deflated and base64 encoded for the server to keep.
+fn archive_export_system(
+    time: Res<Time>,
+    board: Res<Board>,
+    history: Res<History>,
+    stats: Res<Stats>,
+    solve_timer: Res<SolveTimer>,
+    playlist: Res<Playlist>,
+    options: Res<BoardOptions>,
+    send_channel: Res<WASMSendChannel>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+) {
+    for event in bridge_command_event_reader.iter() {
+        if event.command != "round_end" {
+            continue;
+        }
+        let clues = playlist.puzzles.get(playlist.current);
+        let json = archive_json(
+            &board,
+            &history,
+            clues.map_or("", String::as_str),
+            options.get("player_name").unwrap_or("You"),
+            &stats.to_json(&time, &solve_timer),
+        );
+        send_channel.tx.send((String::from("archive"), pack(&json)));
+    }
+}
+
+/// `("archive_view", bundle)` loads the final board of an archived round to
+/// look at, replying `("archive_view", stats)` with the round's stats. The
+/// next join goes back to playing.
+fn archive_import_system(
+    send_channel: Res<WASMSendChannel>,
+    mut view: ResMut<ArchiveView>,
+    mut playlist: ResMut<Playlist>,
+    mut flip_book: ResMut<FlipBook>,
+    mut win_goal: ResMut<WinGoal>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+    mut new_board_event_writer: EventWriter<NewBoardEvent>,
+) {
+    for event in bridge_command_event_reader.iter() {
+        if event.command != "archive_view" {
+            continue;
+        }
+        let (clues, cells, stats) = match unpack(&event.data).and_then(|json| read_archive(&json)) {
+            Ok(archive) => archive,
+            Err(err) => {
+                reply_error(&send_channel, err);
+                continue;
+            }
+        };
+        // nothing of the round before carries over, as with a plain join
+        playlist.start(clues.clone(), Vec::new());
+        flip_book.start(Vec::new());
+        win_goal.start(None);
+        view.viewing = true;
+        view.loading = true;
+        new_board_event_writer.send(NewBoardEvent { clues, cells });
+        send_channel.tx.send((String::from("archive_view"), stats));
+    }
+}
+
+fn archive_view_system(
+    mut view: ResMut<ArchiveView>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+) {
+    for _ in board_loaded_event_reader.iter() {
+        if view.loading {
+            view.loading = false;
+        } else if view.viewing {
+            view.viewing = false;
+        }
+    }
+}
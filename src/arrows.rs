@@ -0,0 +1,318 @@
+// region:      IMPORTS
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use crate::board::{Board, BoardLoadedEvent, SpawnTilesEvent};
+use crate::bridge::reply_error;
+use crate::layers::CURSOR_Z;
+use crate::marks::ClueLine;
+use crate::screens::Screen;
+use crate::settings::Settings;
+use crate::{BridgeCommandEvent, WASMSendChannel};
+
+// endregion
+
+// region:      CONSTANTS
+
+const ARROW_PIXELS: u32 = 64;
+const ARROW_COLOR: [u8; 3] = [240, 120, 20];
+const BOUNCE_TILES: f32 = 0.3;
+const BOUNCE_SPEED: f32 = 1.5; // bounces per second
+const MAX_ARROWS: usize = 16;
+
+// endregion
+
+// region:      COMPONENTS
+
+/// An arrow's sprite, with its index in `Arrows`.
+#[derive(Component)]
+pub struct ArrowPointer(usize);
+
+// endregion
+
+// region:      RESOURCES
+
+/// What an arrow points at, a puzzle cell or the clues of a line.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ArrowTarget {
+    Cell(usize, usize),
+    Clues(ClueLine),
+}
+
+impl ArrowTarget {
+    /// `x,y` for a cell, `r3` or `c0` for the clues of a line.
+    fn parse(target: &str) -> Option<Self> {
+        if let Some((x, y)) = target.split_once(',') {
+            return Some(ArrowTarget::Cell(x.parse().ok()?, y.parse().ok()?));
+        }
+        ClueLine::parse(target).map(ArrowTarget::Clues)
+    }
+}
+
+/// The way an arrow points, it sits on the other side of its target.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ArrowDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl ArrowDirection {
+    fn parse(direction: &str) -> Option<Self> {
+        match direction {
+            "up" => Some(ArrowDirection::Up),
+            "down" => Some(ArrowDirection::Down),
+            "left" => Some(ArrowDirection::Left),
+            "right" => Some(ArrowDirection::Right),
+            _ => None,
+        }
+    }
+
+    fn vector(&self) -> Vec2 {
+        match self {
+            ArrowDirection::Up => Vec2::Y,
+            ArrowDirection::Down => Vec2::NEG_Y,
+            ArrowDirection::Left => Vec2::NEG_X,
+            ArrowDirection::Right => Vec2::X,
+        }
+    }
+
+    /// Rotation of the arrow image, which points up.
+    fn angle(&self) -> f32 {
+        match self {
+            ArrowDirection::Up => 0.,
+            ArrowDirection::Down => PI,
+            ArrowDirection::Left => FRAC_PI_2,
+            ArrowDirection::Right => -FRAC_PI_2,
+        }
+    }
+}
+
+/// Bouncing arrows for guided content, at most one per target. Hosts place
+/// them with the `"arrow"` command, systems by calling `point` and `clear`
+/// directly. A new board clears them.
+#[derive(Resource, Default)]
+pub struct Arrows {
+    pub arrows: Vec<(ArrowTarget, ArrowDirection)>,
+    image: Handle<Image>,
+}
+
+impl Arrows {
+    pub fn point(&mut self, target: ArrowTarget, direction: ArrowDirection) {
+        self.arrows.retain(|(other, _)| *other != target);
+        self.arrows.push((target, direction));
+    }
+
+    pub fn clear(&mut self) {
+        self.arrows.clear();
+    }
+}
+
+// endregion
+
+pub struct ArrowPlugin;
+
+impl Plugin for ArrowPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Arrows>()
+            .add_startup_system(arrow_image_system)
+            .add_system(arrow_command_system)
+            .add_system(arrow_draw_system.after(arrow_command_system));
+    }
+}
+
+/// An arrow pointing up, a head over a shaft, antialiased by a texel.
+fn arrow_image() -> Image {
+    let size = ARROW_PIXELS as f32;
+    let mut data = Vec::with_capacity((ARROW_PIXELS * ARROW_PIXELS * 4) as usize);
+    for y in 0..ARROW_PIXELS {
+        for x in 0..ARROW_PIXELS {
+            // texel rows run top down, the tip is at the top
+            let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+            let from_center = (px - size / 2.).abs();
+            let head_bottom = size * 0.5;
+            let head = if py <= head_bottom {
+                // half width grows from 0 at the tip to 0.4 of the image
+                let half_width = (py / head_bottom) * size * 0.4;
+                half_width - from_center
+            } else {
+                f32::NEG_INFINITY
+            };
+            let shaft = if py > head_bottom - 1. {
+                (size * 0.12 - from_center).min(size - py)
+            } else {
+                f32::NEG_INFINITY
+            };
+            let coverage = (head.max(shaft) + 0.5).clamp(0., 1.);
+            let [r, g, b] = ARROW_COLOR;
+            data.extend_from_slice(&[r, g, b, (coverage * 255.).round() as u8]);
+        }
+    }
+    Image::new(
+        Extent3d {
+            width: ARROW_PIXELS,
+            height: ARROW_PIXELS,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+fn arrow_image_system(mut arrows: ResMut<Arrows>, mut images: ResMut<Assets<Image>>) {
+    arrows.image = images.add(arrow_image());
+}
+
+/// `("arrow", "target;direction")` points an arrow, e.g. `3,4;down` at a
+/// cell or `r2;right` at the clues of row 2. `("arrow", "")` removes them
+/// all, `("arrow", "target;")` just that target's.
+fn arrow_command_system(
+    board: Res<Board>,
+    send_channel: Res<WASMSendChannel>,
+    mut arrows: ResMut<Arrows>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+) {
+    if board_loaded_event_reader.iter().last().is_some() && !arrows.arrows.is_empty() {
+        arrows.clear();
+    }
+    for event in bridge_command_event_reader.iter() {
+        if event.command != "arrow" {
+            continue;
+        }
+        if event.data.is_empty() {
+            arrows.clear();
+            continue;
+        }
+        let (target, direction) = event.data.split_once(';').unwrap_or((&event.data, ""));
+        let target = match ArrowTarget::parse(target) {
+            Some(target) if on_board(&board, target) => target,
+            _ => {
+                reply_error(&send_channel, format!("arrow: invalid target {}", target));
+                continue;
+            }
+        };
+        if direction.is_empty() {
+            arrows.arrows.retain(|(other, _)| *other != target);
+            continue;
+        }
+        let replacing = arrows.arrows.iter().any(|(other, _)| *other == target);
+        match ArrowDirection::parse(direction) {
+            Some(_) if !replacing && arrows.arrows.len() >= MAX_ARROWS => reply_error(
+                &send_channel,
+                format!("arrow: more than {} arrows", MAX_ARROWS),
+            ),
+            Some(direction) => arrows.point(target, direction),
+            None => reply_error(
+                &send_channel,
+                format!("arrow: invalid direction {}", direction),
+            ),
+        }
+    }
+}
+
+fn on_board(board: &Board, target: ArrowTarget) -> bool {
+    let (width, height) = (board.p.get_width(), board.p.get_height());
+    match target {
+        ArrowTarget::Cell(x, y) => x < width && y < height,
+        ArrowTarget::Clues(ClueLine::Row(y)) => y < height && board.row_band > 0,
+        ArrowTarget::Clues(ClueLine::Column(x)) => x < width && board.h > height,
+    }
+}
+
+/// Center and size of a target, in tiles.
+fn target_rect(board: &Board, target: ArrowTarget) -> (Vec2, Vec2) {
+    let height = board.p.get_height() as f32;
+    match target {
+        ArrowTarget::Cell(x, y) => {
+            let (tile_x, tile_y) = board.cell_to_tile(x, y);
+            (Vec2::new(tile_x + 0.5, tile_y + 0.5), Vec2::ONE)
+        }
+        ArrowTarget::Clues(ClueLine::Row(y)) => {
+            let band = board.row_band as f32;
+            let left = board.row_band_left() as f32;
+            (
+                Vec2::new(left + band / 2., y as f32 + 0.5),
+                Vec2::new(band, 1.),
+            )
+        }
+        ArrowTarget::Clues(ClueLine::Column(x)) => {
+            let band = board.h as f32 - height;
+            let (tile_x, _) = board.cell_to_tile(x, 0);
+            (
+                Vec2::new(tile_x + 0.5, height + band / 2.),
+                Vec2::new(1., band),
+            )
+        }
+    }
+}
+
+fn arrow_translation(
+    board: &Board,
+    target: ArrowTarget,
+    direction: ArrowDirection,
+    bounce: f32,
+) -> Vec3 {
+    let (center, size) = target_rect(board, target);
+    let toward = direction.vector();
+    // back from the target's edge by half an arrow and the bounce
+    let reach = (size * toward.abs()).max_element() / 2. + 0.5 + bounce;
+    let position = center - toward * reach;
+    board.tile_translation(position.x, position.y, CURSOR_Z)
+}
+
+/// One sprite per arrow, respawned when the arrows or the layout change and
+/// otherwise only moved, its tip bouncing against the edge of its target.
+/// With reduced motion the tip rests on the edge.
+fn arrow_draw_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    board: Res<Board>,
+    arrows: Res<Arrows>,
+    settings: Res<Settings>,
+    screen: Res<State<Screen>>,
+    mut spawn_tiles_event_reader: EventReader<SpawnTilesEvent>,
+    mut arrow_query: Query<(Entity, &ArrowPointer, &mut Transform)>,
+) {
+    let bounce = match settings.reduced_motion {
+        true => 0.,
+        false => BOUNCE_TILES * (0.5 - 0.5 * (time.elapsed_seconds() * BOUNCE_SPEED * TAU).cos()),
+    };
+    let respawned = spawn_tiles_event_reader.iter().last().is_some();
+    if !(arrows.is_changed() || respawned || screen.is_changed()) {
+        for (_, pointer, mut transform) in arrow_query.iter_mut() {
+            if let Some((target, direction)) = arrows.arrows.get(pointer.0) {
+                transform.translation = arrow_translation(&board, *target, *direction, bounce);
+            }
+        }
+        return;
+    }
+
+    for (entity, _, _) in arrow_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    if *screen.current() != Screen::Board {
+        return;
+    }
+    for (i, (target, direction)) in arrows.arrows.iter().enumerate() {
+        let translation = arrow_translation(&board, *target, *direction, bounce);
+        commands
+            .spawn(SpriteBundle {
+                texture: arrows.image.clone(),
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(board.pixels_per_tile)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(translation)
+                    .with_rotation(Quat::from_rotation_z(direction.angle())),
+                ..Default::default()
+            })
+            .insert(ArrowPointer(i));
+    }
+}
@@ -0,0 +1,142 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+
+use crate::board::Board;
+use crate::layers::{BACKGROUND_Z, FRAME_Z};
+use crate::options::BoardOptions;
+use crate::WinSize;
+
+// endregion
+
+// region:      CONSTANTS
+
+const DEFAULT_FRAME_MARGIN: f32 = 0.5; // in tiles
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct ArtworkBackground;
+
+#[derive(Component)]
+pub struct ArtworkFrame;
+
+// endregion
+
+// region:      RESOURCES
+
+/// Decorative images set with the `background` and `frame` options, given as
+/// paths inside the assets folder. `frame_margin` is how far the frame
+/// extends past the board, in tiles.
+#[derive(Resource, Default)]
+pub struct Artwork {
+    background: Option<(String, Entity)>,
+    frame: Option<(String, Entity)>,
+    frame_margin: f32,
+}
+
+// endregion
+
+pub struct ArtworkPlugin;
+
+impl Plugin for ArtworkPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Artwork>()
+            .add_system(artwork_options_system)
+            .add_system(artwork_layout_system.after(artwork_options_system));
+    }
+}
+
+fn artwork_options_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    options: Res<BoardOptions>,
+    mut artwork: ResMut<Artwork>,
+) {
+    if !options.is_changed() {
+        return;
+    }
+
+    artwork.frame_margin = options.get_f32("frame_margin", DEFAULT_FRAME_MARGIN);
+
+    let artwork = artwork.as_mut();
+    swap_artwork(
+        &mut commands,
+        &asset_server,
+        &mut artwork.background,
+        options.get("background"),
+        BACKGROUND_Z,
+        ArtworkBackground,
+    );
+    swap_artwork(
+        &mut commands,
+        &asset_server,
+        &mut artwork.frame,
+        options.get("frame"),
+        FRAME_Z,
+        ArtworkFrame,
+    );
+}
+
+fn swap_artwork<M: Component>(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    current: &mut Option<(String, Entity)>,
+    path: Option<&str>,
+    z: f32,
+    marker: M,
+) {
+    if current.as_ref().map(|(p, _)| p.as_str()) == path {
+        return;
+    }
+    if let Some((_, entity)) = current.take() {
+        commands.entity(entity).despawn();
+    }
+    if let Some(path) = path {
+        let entity = commands
+            .spawn(SpriteBundle {
+                texture: asset_server.load(path),
+                transform: Transform::from_xyz(0., 0., z),
+                ..Default::default()
+            })
+            .insert(marker)
+            .id();
+        *current = Some((path.to_string(), entity));
+    }
+}
+
+fn artwork_layout_system(
+    board: Res<Board>,
+    win_size: Res<WinSize>,
+    artwork: Res<Artwork>,
+    mut background_query: Query<(&mut Sprite, &mut Transform), With<ArtworkBackground>>,
+    mut frame_query: Query<
+        (&mut Sprite, &mut Transform),
+        (With<ArtworkFrame>, Without<ArtworkBackground>),
+    >,
+    added_query: Query<(), Or<(Added<ArtworkBackground>, Added<ArtworkFrame>)>>,
+) {
+    // newly spawned artwork needs a layout too
+    let spawned = !added_query.is_empty();
+    if !board.is_changed() && !win_size.is_changed() && !artwork.is_changed() && !spawned {
+        return;
+    }
+
+    // background covers the whole window
+    for (mut sprite, mut transform) in background_query.iter_mut() {
+        sprite.custom_size = Some(Vec2::new(win_size.w, win_size.h));
+        transform.translation.x = win_size.w / 2.;
+        transform.translation.y = win_size.h / 2.;
+    }
+
+    // frame surrounds the board including its clues
+    let board_size = Vec2::new(board.w as f32, board.h as f32) * board.pixels_per_tile;
+    let margin = artwork.frame_margin * board.pixels_per_tile;
+    for (mut sprite, mut transform) in frame_query.iter_mut() {
+        sprite.custom_size = Some(board_size + Vec2::splat(margin * 2.));
+        transform.translation.x = board.origin.0 + board_size.x / 2.;
+        transform.translation.y = board.origin.1 + board_size.y / 2.;
+    }
+}
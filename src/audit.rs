@@ -0,0 +1,172 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+use picross_handler::Cell;
+
+use crate::board::Board;
+use crate::feed::{column_label, row_label};
+use crate::history::History;
+use crate::lines::{column_clues, line_satisfied, row_clues};
+use crate::marks::ClueLine;
+use crate::{BridgeCommandEvent, WASMSendChannel};
+
+// endregion
+
+pub struct AuditPlugin;
+
+impl Plugin for AuditPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(audit_command_system);
+    }
+}
+
+/// Replies to `("audit", "")` with `audit_log` of the current puzzle.
+fn audit_command_system(
+    board: Res<Board>,
+    history: Res<History>,
+    send_channel: Res<WASMSendChannel>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+) {
+    for event in bridge_command_event_reader.iter() {
+        if event.command == "audit" {
+            send_channel
+                .tx
+                .send((String::from("audit"), audit_log(&board, &history)));
+        }
+    }
+}
+
+/// Work put into one line since it was last completed.
+#[derive(Default, Clone, Copy)]
+struct LineWork {
+    moves: usize,
+    undone: usize,
+    remote: usize,
+    reopened: usize,
+}
+
+fn format_time(t: f64) -> String {
+    let t = t.max(0.);
+    format!("{:02}:{:04.1}", (t / 60.) as u64, t % 60.)
+}
+
+fn line_name(board: &Board, line: ClueLine) -> String {
+    match line {
+        ClueLine::Row(y) => format!("Row {}", row_label(board, y)),
+        ClueLine::Column(x) => format!("Column {}", column_label(x)),
+    }
+}
+
+/// Plain text account of the solve for puzzle authors, one line of the
+/// puzzle per line of text in the order they were completed: when, how many
+/// moves went into it and how many of those were taken back, help from other
+/// players, and hints taken since the previous completion. Lines completed
+/// more than once are listed each time.
+pub fn audit_log(board: &Board, history: &History) -> String {
+    let p = &board.p;
+    let (width, height) = (p.get_width(), p.get_height());
+
+    // replayed forward from how the board was loaded
+    let mut cells = history.cells_at(0);
+    if cells.len() != width * height {
+        return String::new();
+    }
+    let row = |cells: &[Cell], y: usize| cells[y * width..(y + 1) * width].to_vec();
+    let column = |cells: &[Cell], x: usize| {
+        (0..height)
+            .map(|y| cells[y * width + x])
+            .collect::<Vec<_>>()
+    };
+    let satisfied = |cells: &[Cell], line: ClueLine| match line {
+        ClueLine::Row(y) => line_satisfied(&row_clues(p, y), &row(cells, y)),
+        ClueLine::Column(x) => line_satisfied(&column_clues(p, x), &column(cells, x)),
+    };
+    let lines: Vec<ClueLine> = (0..height)
+        .map(ClueLine::Row)
+        .chain((0..width).map(ClueLine::Column))
+        .collect();
+    let index = |line: ClueLine| match line {
+        ClueLine::Row(y) => y,
+        ClueLine::Column(x) => height + x,
+    };
+
+    let mut done: Vec<bool> = lines.iter().map(|line| satisfied(&cells, *line)).collect();
+    let mut work = vec![LineWork::default(); lines.len()];
+    let mut log = Vec::new();
+    let already: Vec<String> = lines
+        .iter()
+        .filter(|line| done[index(**line)])
+        .map(|line| line_name(board, *line))
+        .collect();
+    if !already.is_empty() {
+        log.push(format!("--:--.-  done at load: {}", already.join(", ")));
+    }
+
+    let mut hints = history.hints.iter().peekable();
+    for entry in history.entries.iter() {
+        cells[entry.y * width + entry.x] = entry.cell;
+        for line in [ClueLine::Row(entry.y), ClueLine::Column(entry.x)] {
+            let i = index(line);
+            let line_work = &mut work[i];
+            if !entry.from_player {
+                line_work.remote += 1;
+            } else if entry.cell == Cell::Empty || entry.old == Cell::Filled {
+                line_work.undone += 1;
+            } else {
+                line_work.moves += 1;
+            }
+
+            let now = satisfied(&cells, line);
+            if now == done[i] {
+                continue;
+            }
+            done[i] = now;
+            if !now {
+                line_work.reopened += 1;
+                continue;
+            }
+
+            let mut notes = vec![format!("{} moves", line_work.moves)];
+            if line_work.undone > 0 {
+                notes.push(format!("{} taken back", line_work.undone));
+            }
+            if line_work.remote > 0 {
+                notes.push(format!("{} by other players", line_work.remote));
+            }
+            if line_work.reopened > 0 {
+                notes.push(format!("reopened {} times", line_work.reopened));
+            }
+            let mut taken = 0;
+            while hints.next_if(|t| **t <= entry.t).is_some() {
+                taken += 1;
+            }
+            if taken > 0 {
+                notes.push(format!("{} hints since the last line", taken));
+            }
+            log.push(format!(
+                "{}  {}  ({})",
+                format_time(entry.t),
+                line_name(board, line),
+                notes.join(", ")
+            ));
+            *line_work = LineWork::default();
+        }
+    }
+
+    let open: Vec<String> = lines
+        .iter()
+        .filter(|line| !done[index(**line)])
+        .map(|line| line_name(board, *line))
+        .collect();
+    if !open.is_empty() {
+        log.push(format!("unfinished: {}", open.join(", ")));
+    }
+    format!(
+        "{}x{} puzzle {}, {} moves\n{}",
+        width,
+        height,
+        board.id,
+        history.entries.len(),
+        log.join("\n")
+    )
+}
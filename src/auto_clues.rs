@@ -0,0 +1,169 @@
+// region:      IMPORTS
+
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::board::{Board, BoardLoadedEvent, CellChangedEvent, Clue, ClueIndex, ClueMarkedEvent};
+use crate::lines::{column_cells, column_clues, finished_clues, row_cells, row_clues};
+use crate::marks::ClueLine;
+use crate::options::BoardOptions;
+
+// endregion
+
+// region:      RESOURCES
+
+/// Clues grayed because their run is done, and clues the player or a
+/// teammate marked by hand since the puzzle loaded, which are left as they
+/// were marked.
+#[derive(Resource, Default)]
+pub struct AutoClues {
+    grayed: HashSet<(ClueLine, usize)>,
+    marked: HashSet<(ClueLine, usize)>,
+}
+
+// endregion
+
+pub struct AutoCluesPlugin;
+
+impl Plugin for AutoCluesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AutoClues>()
+            // after this frame's marks and clue spawns are applied
+            .add_system_to_stage(CoreStage::PostUpdate, auto_clue_system);
+    }
+}
+
+/// Grays a clue once `finished_clues` says its run is done and ungrays it
+/// when the run is undone, rechecking only the lines a change touched.
+/// Everything is rechecked when the clues are respawned. Off with the
+/// `auto_gray_clues` option.
+#[allow(clippy::too_many_arguments)]
+fn auto_clue_system(
+    board: Res<Board>,
+    options: Res<BoardOptions>,
+    clue_index: Res<ClueIndex>,
+    mut auto_clues: ResMut<AutoClues>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut cell_changed_event_reader: EventReader<CellChangedEvent>,
+    mut clue_marked_event_reader: EventReader<ClueMarkedEvent>,
+    added_query: Query<(), Added<Clue>>,
+    mut clue_query: Query<&mut Text, With<Clue>>,
+) {
+    if board_loaded_event_reader.iter().last().is_some() {
+        auto_clues.grayed.clear();
+        auto_clues.marked.clear();
+    }
+    for event in clue_marked_event_reader.iter() {
+        auto_clues.grayed.remove(&(event.line, event.index));
+        auto_clues.marked.insert((event.line, event.index));
+    }
+
+    if !options.get_bool("auto_gray_clues", true) {
+        cell_changed_event_reader.iter().last();
+        for clue in std::mem::take(&mut auto_clues.grayed) {
+            let text = clue_index.get(clue.0, clue.1);
+            if let Some(mut text) = text.and_then(|entity| clue_query.get_mut(entity).ok()) {
+                text.sections[0].style.color = Color::BLACK;
+            }
+        }
+        return;
+    }
+
+    let (width, height) = (board.p.get_width(), board.p.get_height());
+    let mut lines: HashSet<ClueLine> = HashSet::default();
+    if !added_query.is_empty() || options.is_changed() {
+        // respawned clues come back black
+        cell_changed_event_reader.iter().last();
+        auto_clues.grayed.clear();
+        lines.extend((0..height).map(ClueLine::Row));
+        lines.extend((0..width).map(ClueLine::Column));
+    } else {
+        for event in cell_changed_event_reader.iter() {
+            lines.insert(ClueLine::Row(event.y));
+            lines.insert(ClueLine::Column(event.x));
+        }
+    }
+
+    for line in lines {
+        let finished = match line {
+            ClueLine::Row(y) if y < height => {
+                finished_clues(&row_clues(&board.p, y), &row_cells(&board.p, y))
+            }
+            ClueLine::Column(x) if x < width => {
+                finished_clues(&column_clues(&board.p, x), &column_cells(&board.p, x))
+            }
+            _ => continue,
+        };
+        for (index, finished) in finished.into_iter().enumerate() {
+            let clue = (line, index);
+            if auto_clues.marked.contains(&clue) {
+                continue;
+            }
+            let text = clue_index.get(line, index);
+            let mut text = match text.and_then(|entity| clue_query.get_mut(entity).ok()) {
+                Some(text) => text,
+                None => continue,
+            };
+            let color = match finished {
+                true => Color::GRAY,
+                false => Color::BLACK,
+            };
+            if text.sections[0].style.color != color {
+                text.sections[0].style.color = color;
+            }
+            match finished {
+                true => auto_clues.grayed.insert(clue),
+                false => auto_clues.grayed.remove(&clue),
+            };
+        }
+    }
+}
+
+// region:      TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{BoardAction, InputEvent};
+    use crate::harness::{plus_puzzle, Harness, SETTLE_TICKS};
+    use bevy::ecs::event::Events;
+
+    #[test]
+    fn finished_clues_gray_out_until_marked_by_hand() {
+        let mut harness = Harness::new();
+        assert!(harness.join(plus_puzzle().as_str(), "000000000"));
+        let color = |harness: &Harness| {
+            let entity = harness
+                .app
+                .world
+                .resource::<ClueIndex>()
+                .get(ClueLine::Row(1), 0)
+                .unwrap();
+            harness.app.world.get::<Text>(entity).unwrap().sections[0]
+                .style
+                .color
+        };
+
+        harness.drag(&[(0, 1), (1, 1), (2, 1)], BoardAction::Fill);
+        assert_eq!(color(&harness), Color::GRAY);
+        harness.input_cell(1, 1, BoardAction::Empty);
+        assert_eq!(color(&harness), Color::BLACK);
+
+        // a hand marking stays however the line changes
+        let (x, y, height) = harness.board().clue_tile(ClueLine::Row(1), 0).unwrap();
+        harness
+            .app
+            .world
+            .resource_mut::<Events<InputEvent>>()
+            .send(InputEvent {
+                x: x + 0.5,
+                y: y + height / 2.,
+                action: BoardAction::Fill,
+                from_player: true,
+            });
+        harness.ticks(SETTLE_TICKS);
+        harness.input_cell(1, 1, BoardAction::Fill);
+        assert_eq!(color(&harness), Color::RED);
+    }
+}
+
+// endregion
@@ -0,0 +1,253 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+
+use crate::board::{Board, BoardLoadedEvent};
+use crate::camera::BoardView;
+use crate::hud_scale::HudScale;
+use crate::layers::HUD_Z;
+use crate::stats::Stats;
+use crate::storage;
+use crate::timer::SolveTimer;
+use crate::trace::json_string;
+use crate::win::PuzzleSolvedEvent;
+use crate::{BridgeCommandEvent, GameTextures, WASMSendChannel, WinSize};
+
+// endregion
+
+// region:      CONSTANTS
+
+const BEST_KEY_PREFIX: &str = "best.";
+// every puzzle id with a record, one per line, storage can't list its keys
+const BEST_IDS_KEY: &str = "best_ids";
+
+const HEADER_SECONDS: f64 = 5.;
+const HEADER_FONT_SIZE: f32 = 20.;
+const HEADER_MARGIN: f32 = 8.;
+const HEADER_COLOR: Color = Color::rgb(0.85, 0.85, 0.6);
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct BestHeader;
+
+// endregion
+
+// region:      RESOURCES
+
+/// Personal bests on one puzzle, each kept on its own: the fastest solve
+/// may not be the one with the fewest hints.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PersonalBest {
+    pub best_ms: u64,
+    pub fewest_hints: u32,
+    pub fewest_mistakes: u32,
+    pub solves: u32,
+}
+
+impl PersonalBest {
+    fn key(id: &str) -> String {
+        format!("{}{}", BEST_KEY_PREFIX, id)
+    }
+
+    pub fn load(id: &str) -> Option<Self> {
+        let saved = storage::load(&Self::key(id))?;
+        let mut fields = saved.split(',');
+        Some(Self {
+            best_ms: fields.next()?.parse().ok()?,
+            fewest_hints: fields.next()?.parse().ok()?,
+            fewest_mistakes: fields.next()?.parse().ok()?,
+            solves: fields.next()?.parse().ok()?,
+        })
+    }
+
+    fn save(&self, id: &str) {
+        storage::save(
+            &Self::key(id),
+            &format!(
+                "{},{},{},{}",
+                self.best_ms, self.fewest_hints, self.fewest_mistakes, self.solves
+            ),
+        );
+    }
+
+    /// This record with one more solve folded in.
+    fn with(self, elapsed_ms: u64, hints: u32, mistakes: u32) -> Self {
+        Self {
+            best_ms: self.best_ms.min(elapsed_ms),
+            fewest_hints: self.fewest_hints.min(hints),
+            fewest_mistakes: self.fewest_mistakes.min(mistakes),
+            solves: self.solves + 1,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"best_ms\":{},\"fewest_hints\":{},\"fewest_mistakes\":{},\"solves\":{}}}",
+            self.best_ms, self.fewest_hints, self.fewest_mistakes, self.solves
+        )
+    }
+
+    /// `Best 3:12  fewest hints 0  fewest mistakes 1`
+    pub fn summary(&self) -> String {
+        format!(
+            "Best {}:{:02}  fewest hints {}  fewest mistakes {}",
+            self.best_ms / 60000,
+            self.best_ms / 1000 % 60,
+            self.fewest_hints,
+            self.fewest_mistakes
+        )
+    }
+}
+
+/// The loaded puzzle's record, and what it was before the last solve so the
+/// win screen can tell a new best.
+#[derive(Resource, Default)]
+pub struct PersonalBests {
+    pub current: Option<PersonalBest>,
+    pub before_solve: Option<PersonalBest>,
+}
+
+impl PersonalBests {
+    pub fn new_best_time(&self) -> bool {
+        match (self.current, self.before_solve) {
+            (Some(current), Some(before)) => current.best_ms < before.best_ms,
+            _ => false,
+        }
+    }
+}
+
+// endregion
+
+pub struct BestsPlugin;
+
+impl Plugin for BestsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PersonalBests>()
+            .add_system(personal_best_system)
+            .add_system(best_header_system.after(personal_best_system))
+            .add_system(bests_command_system);
+    }
+}
+
+fn best_ids() -> Vec<String> {
+    storage::load(BEST_IDS_KEY)
+        .map(|ids| ids.lines().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+/// Loads the record of each puzzle as it loads and folds every solve into it.
+fn personal_best_system(
+    time: Res<Time>,
+    board: Res<Board>,
+    stats: Res<Stats>,
+    solve_timer: Res<SolveTimer>,
+    mut bests: ResMut<PersonalBests>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut puzzle_solved_event_reader: EventReader<PuzzleSolvedEvent>,
+) {
+    if board_loaded_event_reader.iter().last().is_some() {
+        bests.current = PersonalBest::load(&board.id);
+        bests.before_solve = None;
+    }
+
+    for _ in puzzle_solved_event_reader.iter() {
+        let (elapsed_ms, hints, mistakes) = (
+            solve_timer.elapsed_ms(&time),
+            stats.hints_used,
+            stats.penalties,
+        );
+        let record = match bests.current {
+            Some(best) => best.with(elapsed_ms, hints, mistakes),
+            None => {
+                let mut ids = best_ids();
+                if !ids.contains(&board.id) && !board.id.contains('\n') {
+                    ids.push(board.id.clone());
+                    storage::save(BEST_IDS_KEY, &ids.join("\n"));
+                }
+                PersonalBest {
+                    best_ms: elapsed_ms,
+                    fewest_hints: hints,
+                    fewest_mistakes: mistakes,
+                    solves: 1,
+                }
+            }
+        };
+        record.save(&board.id);
+        bests.before_solve = bests.current;
+        bests.current = Some(record);
+    }
+}
+
+/// The puzzle's personal bests along the top of the window for a few seconds
+/// after it loads, nothing the first time.
+#[allow(clippy::too_many_arguments)]
+fn best_header_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    view: Res<BoardView>,
+    win_size: Res<WinSize>,
+    hud_scale: Res<HudScale>,
+    game_textures: Res<GameTextures>,
+    bests: Res<PersonalBests>,
+    mut shown_until: Local<f64>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    header_query: Query<Entity, With<BestHeader>>,
+) {
+    let now = time.elapsed_seconds_f64();
+    let loaded = board_loaded_event_reader.iter().last().is_some();
+    if !loaded && (header_query.is_empty() || now < *shown_until) {
+        return;
+    }
+    for entity in header_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    let best = match bests.current.filter(|_| loaded) {
+        Some(best) => best,
+        None => return,
+    };
+
+    *shown_until = now + HEADER_SECONDS;
+    let margin = HEADER_MARGIN * hud_scale.0;
+    let position = view.screen_to_world(Vec2::new(win_size.w / 2., win_size.h - margin));
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                best.summary(),
+                TextStyle {
+                    font: game_textures.font.clone(),
+                    font_size: HEADER_FONT_SIZE * hud_scale.world(&view),
+                    color: HEADER_COLOR,
+                },
+            )
+            .with_alignment(TextAlignment::TOP_CENTER),
+            transform: Transform::from_translation(position.extend(HUD_Z)),
+            ..Default::default()
+        })
+        .insert(BestHeader);
+}
+
+/// `("bests", "")` replies `("bests", json)` with every puzzle's record by
+/// id, `{"<id>":{"best_ms":..,"fewest_hints":..,"fewest_mistakes":..,
+/// "solves":..}}`, for servers to merge into the player's profile.
+fn bests_command_system(
+    send_channel: Res<WASMSendChannel>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+) {
+    for event in bridge_command_event_reader.iter() {
+        if event.command != "bests" {
+            continue;
+        }
+        let records: Vec<String> = best_ids()
+            .iter()
+            .filter_map(|id| {
+                PersonalBest::load(id).map(|best| format!("{}:{}", json_string(id), best.to_json()))
+            })
+            .collect();
+        send_channel
+            .tx
+            .send((String::from("bests"), format!("{{{}}}", records.join(","))));
+    }
+}
@@ -1,15 +1,39 @@
 // region:      IMPORTS
 
 use bevy::{
-    ecs::component,
+    ecs::{component, system::SystemParam},
     input::{mouse::MouseButtonInput, touch::TouchPhase, ButtonState},
     prelude::{system_adapter::new, *},
     render::render_resource::Texture,
     sprite::Anchor,
+    tasks::AsyncComputeTaskPool,
+    utils::HashMap,
 };
+use crossbeam_channel::{unbounded, Receiver, Sender};
 use picross_handler::{Cell, Puzzle};
 use wasm_bindgen::prelude::*;
 
+use crate::board_commands::{board_command_system, BoardCommand};
+use crate::brackets::BRACKET_KEY;
+use crate::camera::BoardView;
+use crate::clue_effects::ClueEffectLayers;
+use crate::control_profile::ControlProfile;
+use crate::glyphs::{AtlasNumber, FallbackDigits};
+use crate::guess::{toolbar_button_at, Guess};
+use crate::handicap::InputGate;
+use crate::hud_scale::HudScale;
+use crate::instanced::use_instanced;
+use crate::layers::{CLUE_Z, TILE_Z};
+use crate::limits::BoardLimits;
+use crate::marks::ClueLine;
+use crate::options::BoardOptions;
+use crate::pencil::{PencilMarks, PencilToggledEvent};
+use crate::pointer_lock::PointerLock;
+use crate::pressure::{pen_weight, PenInput, PenWeight};
+use crate::randomizer::Orientation;
+use crate::rules::Rules;
+use crate::screens::Screen;
+use crate::settings::{ClickBinding, CompactClues, Settings};
 use crate::{
     BoardUpdateEvent, GameTextures, NewBoardEvent, WASMSendChannel, WinSize, SPRITE_SCALE,
     TILE_SIZE,
@@ -19,8 +43,13 @@ use crate::{
 
 // region:      CONSTANTS
 
-const TILE_Z: f32 = 1.;
-const CLUE_Z: f32 = 2.;
+/// Share of the board width the row clue band may take before stacking.
+const DEFAULT_STACK_FRACTION: f32 = 0.3;
+const CLUE_FONT_SCALE: f32 = 0.5;
+const STACKED_CLUE_FONT_SCALE: f32 = 0.35;
+const DEFAULT_CYCLE_ORDER: &str = "01X";
+/// Depth of the line header strip on the outer edge of the clue bands.
+pub const LINE_HEADER_TILES: f32 = 0.25;
 
 // endregion
 
@@ -49,16 +78,108 @@ pub struct ControlTile {
 // region:      RESOURCES
 
 #[derive(Resource)]
-struct Board {
+pub struct Board {
     pub tile_scale: f32,
     pub pixels_per_tile: f32,
     pub origin: (f32, f32),
     pub h: usize,
     pub w: usize,
+    /// The latest state of the `History` log, only ever changed through
+    /// `BoardCommand`s and reset when a board is loaded.
     pub p: Puzzle,
+    pub id: String,
+    pub orientation: Orientation,
+    /// Tiles across the row clue band, half the longest row clue when the
+    /// row clues are stacked on two lines.
+    pub row_band: usize,
+    pub stacked_rows: bool,
+    /// Row clues on the right of the grid and the control tile in the top
+    /// right corner, from the player's `mirror_clues` setting.
+    pub mirrored: bool,
 }
 
-#[derive(Clone, Copy)]
+struct ParsedPuzzle {
+    generation: u64,
+    clues: String,
+    result: Result<(Puzzle, Orientation), String>,
+}
+
+/// Hands parsed puzzles from the task pool back to `new_board_event_system`.
+/// Natively the parse runs on another thread. On wasm32 the pool has no
+/// threads and runs it on the main thread once the frame is done, so a big
+/// puzzle still stalls the page there.
+#[derive(Resource)]
+struct PuzzleParser {
+    tx: Sender<ParsedPuzzle>,
+    rx: Receiver<ParsedPuzzle>,
+    latest: u64,
+}
+
+impl Default for PuzzleParser {
+    fn default() -> Self {
+        let (tx, rx) = unbounded();
+        Self { tx, rx, latest: 0 }
+    }
+}
+
+/// True while a joined puzzle is being parsed.
+#[derive(Resource, Default)]
+pub struct Loading(pub bool);
+
+/// Hidden tile and clue entities kept around between board loads, so
+/// switching puzzles only spawns entities when the new board is larger.
+#[derive(Resource, Default)]
+pub struct EntityPool {
+    pub tiles: Vec<Entity>,
+    pub clues: Vec<Entity>,
+}
+
+/// Tile entities by board coordinates, rebuilt whenever the tiles are respawned.
+#[derive(Resource, Default)]
+pub struct TileIndex(pub HashMap<(usize, usize), Entity>);
+
+/// Clue entities by clue address and by the line they belong to, so
+/// marking a clue or restyling a line only touches that line's texts.
+#[derive(Resource, Default)]
+pub struct ClueIndex {
+    pub clues: HashMap<(ClueLine, usize), Entity>,
+    pub rows: Vec<Vec<Entity>>,
+    pub columns: Vec<Vec<Entity>>,
+}
+
+impl ClueIndex {
+    fn clear(&mut self) {
+        self.clues.clear();
+        self.rows.clear();
+        self.columns.clear();
+    }
+
+    fn insert(&mut self, line: ClueLine, index: usize, entity: Entity) {
+        self.clues.insert((line, index), entity);
+        let (lines, line) = match line {
+            ClueLine::Row(y) => (&mut self.rows, y),
+            ClueLine::Column(x) => (&mut self.columns, x),
+        };
+        if lines.len() <= line {
+            lines.resize_with(line + 1, Vec::new);
+        }
+        lines[line].push(entity);
+    }
+
+    pub fn get(&self, line: ClueLine, index: usize) -> Option<Entity> {
+        self.clues.get(&(line, index)).copied()
+    }
+
+    pub fn row(&self, y: usize) -> &[Entity] {
+        self.rows.get(y).map_or(&[], |row| row.as_slice())
+    }
+
+    pub fn column(&self, x: usize) -> &[Entity] {
+        self.columns.get(x).map_or(&[], |column| column.as_slice())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum BoardAction {
     Fill,
     Cross,
@@ -73,6 +194,50 @@ pub struct CurrentAction(pub BoardAction);
 #[derive(Resource)]
 pub struct ControlAction(pub BoardAction);
 
+/// Presses that started painting so far. Every cell a drag paints is changed
+/// under the count of the press that began it, which is how the history
+/// groups a whole stroke into one undo step.
+#[derive(Resource, Default)]
+pub struct Stroke(pub u64);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum LockAxis {
+    Row,
+    Column,
+}
+
+/// Line a drag is held to once it leaves the cell it started on, row or
+/// column by which way it left, so a long run can't slip into the next line.
+/// Turned off with the `line_lock` setting.
+#[derive(Resource, Default)]
+pub struct LineLock {
+    start: Option<(f32, f32)>,
+    axis: Option<LockAxis>,
+}
+
+impl LineLock {
+    /// The tile a drag over `(x, y)` paints, on the locked line.
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        match (self.start, self.axis) {
+            (Some((_, start_y)), Some(LockAxis::Row)) => (x, start_y),
+            (Some((start_x, _)), Some(LockAxis::Column)) => (start_x, y),
+            _ => (x, y),
+        }
+    }
+
+    fn follow(&mut self, x: f32, y: f32) {
+        if let (Some((start_x, start_y)), None) = (self.start, self.axis) {
+            if (x, y) != (start_x, start_y) {
+                self.axis = Some(if (x - start_x).abs() >= (y - start_y).abs() {
+                    LockAxis::Row
+                } else {
+                    LockAxis::Column
+                });
+            }
+        }
+    }
+}
+
 impl Default for Board {
     fn default() -> Self {
         Self {
@@ -82,8 +247,309 @@ impl Default for Board {
             h: Default::default(),
             w: Default::default(),
             p: Default::default(),
+            id: Default::default(),
+            orientation: Default::default(),
+            row_band: Default::default(),
+            stacked_rows: Default::default(),
+            mirrored: Default::default(),
+        }
+    }
+}
+
+impl Board {
+    /// Position of the cell at `(x, y)` as the bridge counts it, in the
+    /// puzzle's own layout whatever the board was randomized to.
+    pub fn original_pos(&self, x: usize, y: usize) -> usize {
+        let (w, h) = (self.p.get_width(), self.p.get_height());
+        self.orientation.to_original_pos(x, y, w, h)
+    }
+
+    /// The board in the puzzle's own layout, as a join or `"u"` gives its
+    /// cells.
+    pub fn original_cells(&self) -> String {
+        let (w, h) = (self.p.get_width(), self.p.get_height());
+        (0..w * h)
+            .map(|i| {
+                let (x, y) = self.orientation.from_original_pos(i, w, h);
+                cell_to_char(self.p.get_cell(x, y))
+            })
+            .collect()
+    }
+
+    /// World position of the bottom left corner of a tile, in board coordinates.
+    pub fn tile_translation(&self, x: f32, y: f32, z: f32) -> Vec3 {
+        Vec3::new(
+            self.origin.0 + x * self.pixels_per_tile,
+            self.origin.1 + y * self.pixels_per_tile,
+            z,
+        )
+    }
+
+    /// Tile column the grid starts at, past the row clue band unless the
+    /// layout is mirrored.
+    pub fn grid_left(&self) -> usize {
+        match self.mirrored {
+            true => 0,
+            false => self.row_band,
+        }
+    }
+
+    /// Tile column the row clue band starts at.
+    pub fn row_band_left(&self) -> usize {
+        match self.mirrored {
+            true => self.p.get_width(),
+            false => 0,
+        }
+    }
+
+    /// Whether a point in board coordinates is on the row clue band's side
+    /// of the grid.
+    pub fn in_row_band(&self, x: f32) -> bool {
+        match self.mirrored {
+            true => x >= self.p.get_width() as f32,
+            false => x < self.row_band as f32,
+        }
+    }
+
+    /// Whether a point in board coordinates is in the corner between the
+    /// clues, where the control tile sits.
+    pub fn in_corner(&self, x: f32, y: f32) -> bool {
+        self.in_row_band(x) && y >= self.p.get_height() as f32
+    }
+
+    /// Board coordinates of a puzzle cell.
+    pub fn cell_to_tile(&self, x: usize, y: usize) -> (f32, f32) {
+        ((x + self.grid_left()) as f32, y as f32)
+    }
+
+    /// Scale of the control tile, which fills most of the corner between the clues.
+    pub fn control_tile_scale(&self) -> f32 {
+        let control_tile_max_size = self.p.get_longest_column_clue_len().min(self.row_band);
+        self.tile_scale * control_tile_max_size as f32 * 0.8
+    }
+
+    /// Puzzle cell under a tile, `None` for clue and control tiles.
+    pub fn tile_to_cell(&self, x: f32, y: f32) -> Option<(usize, usize)> {
+        let x_diff = self.grid_left() as f32;
+        if x < x_diff || y < 0. || y >= self.p.get_height() as f32 {
+            return None;
+        }
+        let cell_x = (x - x_diff) as usize;
+        if cell_x >= self.p.get_width() {
+            return None;
+        }
+        Some((cell_x, y as usize))
+    }
+
+    /// Clue under a point in board coordinates. Stacked row clues take the
+    /// top or bottom half of their row's band.
+    pub fn clue_at(&self, x: f32, y: f32) -> Option<(ClueLine, usize)> {
+        if x < 0. || y < 0. {
+            return None;
+        }
+        let (tile_x, tile_y) = (x as usize, y as usize);
+        if self.in_row_band(x) && tile_y < self.p.get_height() {
+            // row clues are aligned against the grid
+            let line = usize::from(self.stacked_rows && y.fract() < 0.5);
+            let (start, len) = self.row_clue_line(tile_y, line);
+            let first = self.row_clue_left(len);
+            (tile_x >= first && tile_x < first + len)
+                .then(|| (ClueLine::Row(tile_y), start + tile_x - first))
+        } else if !self.in_row_band(x) && tile_y >= self.p.get_height() {
+            // column clues stack upwards from the grid, the first clue on top
+            let column = tile_x.checked_sub(self.grid_left())?;
+            let len = self.p.column_clues.get(column)?.len();
+            let above = tile_y - self.p.get_height();
+            (above < len).then(|| (ClueLine::Column(column), len - 1 - above))
+        } else {
+            None
         }
     }
+
+    /// Line whose header, the outer edge of its clue band, is under a point
+    /// in board coordinates. Headers are a thin strip over the outermost
+    /// clues, so those stay clickable everywhere else.
+    pub fn line_header_at(&self, x: f32, y: f32) -> Option<ClueLine> {
+        let (width, height) = (self.p.get_width(), self.p.get_height());
+        let band = self.grid_left() as f32;
+        let outer = self.row_header_left();
+        if (outer..outer + LINE_HEADER_TILES).contains(&x)
+            && (0. ..height as f32).contains(&y)
+            && self.row_band > 0
+        {
+            Some(ClueLine::Row(y as usize))
+        } else if (self.h as f32 - LINE_HEADER_TILES..self.h as f32).contains(&y)
+            && (band..band + width as f32).contains(&x)
+            && self.h > height
+        {
+            Some(ClueLine::Column((x - band) as usize))
+        } else {
+            None
+        }
+    }
+
+    /// Left edge of the row headers, on the right of the board when mirrored.
+    pub fn row_header_left(&self) -> f32 {
+        match self.mirrored {
+            true => (self.row_band_left() + self.row_band) as f32 - LINE_HEADER_TILES,
+            false => 0.,
+        }
+    }
+
+    /// Bottom left corner and height in tiles of a clue, inverse of `clue_at`.
+    pub fn clue_tile(&self, line: ClueLine, index: usize) -> Option<(f32, f32, f32)> {
+        match line {
+            ClueLine::Row(y) => {
+                if index >= self.p.row_clues.get(y)?.len() {
+                    return None;
+                }
+                let (top_start, top_len) = self.row_clue_line(y, 0);
+                if !self.stacked_rows {
+                    let x = self.row_clue_left(top_len) + index;
+                    return Some((x as f32, y as f32, 1.));
+                }
+                let (line, start, len) = match index < top_start + top_len {
+                    true => (0, top_start, top_len),
+                    false => {
+                        let (start, len) = self.row_clue_line(y, 1);
+                        (1, start, len)
+                    }
+                };
+                let x = self.row_clue_left(len) + index - start;
+                let y = y as f32 + if line == 0 { 0.5 } else { 0. };
+                Some((x as f32, y, 0.5))
+            }
+            ClueLine::Column(x) => {
+                let len = self.p.column_clues.get(x)?.len();
+                (index < len).then(|| {
+                    let y = self.p.get_height() + len - 1 - index;
+                    ((self.grid_left() + x) as f32, y as f32, 1.)
+                })
+            }
+        }
+    }
+
+    /// Tile column of the first of `len` row clues on a line, which end
+    /// against the grid, or start against it when mirrored.
+    fn row_clue_left(&self, len: usize) -> usize {
+        match self.mirrored {
+            true => self.p.get_width(),
+            false => self.row_band - len,
+        }
+    }
+
+    /// First clue index and clue count on one line of a row's clues, the top
+    /// line (0) holding the larger half when stacked.
+    fn row_clue_line(&self, y: usize, line: usize) -> (usize, usize) {
+        let len = self.p.row_clues.get(y).map_or(0, |clues| clues.len());
+        if !self.stacked_rows {
+            return (0, len);
+        }
+        let top = (len + 1) / 2;
+        match line {
+            0 => (0, top),
+            _ => (top, len - top),
+        }
+    }
+}
+
+/// Row clue band layout for a puzzle. Row clues go on two lines once the
+/// longest takes more than the `stack_clues_fraction` option of the board
+/// width, and only while the window's width is what limits the tile size,
+/// so it is reevaluated whenever the window is resized. The player's
+/// `compact_clues` setting, or a restored view's, can force either layout.
+pub fn row_band(
+    p: &Puzzle,
+    options: &BoardOptions,
+    settings: &Settings,
+    win_size: &WinSize,
+) -> (usize, bool) {
+    let row_clue_len = p.get_longest_row_clue_len();
+    let stacked = ((row_clue_len + 1) / 2, true);
+    match settings.compact() {
+        CompactClues::Always if row_clue_len > 1 => return stacked,
+        CompactClues::Always | CompactClues::Never => return (row_clue_len, false),
+        CompactClues::Auto => {}
+    }
+    let fraction = options.get_f32("stack_clues_fraction", DEFAULT_STACK_FRACTION);
+    let total = (p.get_width() + row_clue_len) as f32;
+    let total_height = (p.get_height() + p.get_longest_column_clue_len()) as f32;
+    // stacking only makes tiles bigger when the board is narrower than its window
+    let width_bound = total / total_height >= win_size.w / win_size.h;
+    if row_clue_len > 1 && fraction > 0. && row_clue_len as f32 > fraction * total && width_bound {
+        stacked
+    } else {
+        (row_clue_len, false)
+    }
+}
+
+/// Next state for the `cycle` input mode. The `cycle_order` option lists the
+/// states as cell chars, `01X` (empty, filled, crossed) by default.
+pub fn cycle_action(options: &BoardOptions, cell: Cell) -> BoardAction {
+    let mut order: Vec<Cell> = Vec::new();
+    for cell in options
+        .get("cycle_order")
+        .unwrap_or(DEFAULT_CYCLE_ORDER)
+        .chars()
+        .filter_map(char_to_cell)
+    {
+        if !order.contains(&cell) {
+            order.push(cell);
+        }
+    }
+    if order.len() < 2 {
+        order = DEFAULT_CYCLE_ORDER
+            .chars()
+            .filter_map(char_to_cell)
+            .collect();
+    }
+
+    // a state left out of the cycle starts it over
+    let next = match order.iter().position(|c| *c == cell) {
+        Some(i) => order[(i + 1) % order.len()],
+        None => order[0],
+    };
+    match next {
+        Cell::Filled => BoardAction::Fill,
+        Cell::Crossed => BoardAction::Cross,
+        Cell::Empty => BoardAction::Empty,
+    }
+}
+
+pub fn cell_texture(game_textures: &GameTextures, cell: Cell) -> Handle<Image> {
+    match cell {
+        Cell::Empty => game_textures.tile_empty.clone(),
+        Cell::Filled => game_textures.tile_filled.clone(),
+        Cell::Crossed => game_textures.tile_crossed.clone(),
+    }
+}
+
+/// Cell encoding shared with the server, `0` empty, `1` filled and `X` crossed.
+pub fn cell_to_char(cell: Cell) -> char {
+    match cell {
+        Cell::Empty => '0',
+        Cell::Filled => '1',
+        Cell::Crossed => 'X',
+    }
+}
+
+pub fn char_to_cell(c: char) -> Option<Cell> {
+    match c {
+        '0' => Some(Cell::Empty),
+        '1' => Some(Cell::Filled),
+        'X' => Some(Cell::Crossed),
+        _ => None,
+    }
+}
+
+/// Stable FNV-1a hash, used for puzzle ids and board checksums.
+pub fn fnv1a(data: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in data.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
 }
 
 // endregion
@@ -94,6 +560,38 @@ pub struct SpawnTilesEvent;
 pub struct DeleteTilesEvent;
 pub struct DeletedTilesEvent;
 
+/// Sent when the player switches the control tile between fill and cross.
+pub struct ControlToggledEvent;
+
+/// Sent after a joined puzzle has been parsed into `Board`.
+pub struct BoardLoadedEvent;
+
+/// Sent when a clue's marking changes.
+pub struct ClueMarkedEvent {
+    pub line: ClueLine,
+    pub index: usize,
+    pub action: BoardAction,
+    pub from_player: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ChangeSource {
+    Player,
+    Remote,
+    /// Local help from a handicap, never sent to the server.
+    Handicap,
+}
+
+/// Sent for every cell change applied to `Board`, whoever made it.
+pub struct CellChangedEvent {
+    pub x: usize,
+    pub y: usize,
+    pub old: Cell,
+    pub new: Cell,
+    pub source: ChangeSource,
+}
+
+#[derive(Clone)]
 pub struct InputEvent {
     pub x: f32,
     pub y: f32,
@@ -108,6 +606,29 @@ struct RedrawEvent {
 
 // endregion
 
+// region:      SYSTEM PARAMS
+
+/// What decides how a press is read, besides the board itself.
+#[derive(SystemParam)]
+struct InputContext<'w, 's> {
+    keys: Res<'w, Input<KeyCode>>,
+    view: Res<'w, BoardView>,
+    screen: Res<'w, State<Screen>>,
+    options: Res<'w, BoardOptions>,
+    settings: Res<'w, Settings>,
+    guess: Res<'w, Guess>,
+    hud_scale: Res<'w, HudScale>,
+    pencil_toggled_event_writer: EventWriter<'w, 's, PencilToggledEvent>,
+    stroke: ResMut<'w, Stroke>,
+    line_lock: ResMut<'w, LineLock>,
+    pencil_marks: Res<'w, PencilMarks>,
+    pen: ResMut<'w, PenInput>,
+    pointer_lock: Res<'w, PointerLock>,
+    profile: Res<'w, ControlProfile>,
+}
+
+// endregion
+
 pub struct BoardPlugin;
 
 impl Plugin for BoardPlugin {
@@ -115,16 +636,32 @@ impl Plugin for BoardPlugin {
         app.add_event::<SpawnTilesEvent>()
             .add_event::<DeleteTilesEvent>()
             .add_event::<DeletedTilesEvent>()
+            .add_event::<BoardLoadedEvent>()
+            .add_event::<ControlToggledEvent>()
             .add_event::<InputEvent>()
+            .add_event::<CellChangedEvent>()
+            .add_event::<BoardCommand>()
+            .add_event::<ClueMarkedEvent>()
             .add_event::<RedrawEvent>()
+            .init_resource::<TileIndex>()
+            .init_resource::<ClueIndex>()
+            .init_resource::<EntityPool>()
+            .init_resource::<PuzzleParser>()
+            .init_resource::<Loading>()
+            .init_resource::<Stroke>()
+            .init_resource::<LineLock>()
             .add_startup_system_to_stage(StartupStage::PostStartup, startup_system)
             .add_system(spawn_tiles_event_system)
             .add_system(delete_tiles_event_system)
             .add_system(input_event_system)
+            .add_system(board_command_system.after(input_event_system))
+            .add_system(send_cell_changes_system.after(board_command_system))
             .add_system(redraw_event_system)
-            .add_system(input_and_resizing_system)
-            .add_system(new_board_event_system)
-            .add_system(board_update_event_system);
+            .add_system(new_board_event_system);
+
+        // reads the browser canvas directly, headless test apps send InputEvent instead
+        #[cfg(not(test))]
+        app.add_system(input_and_resizing_system);
     }
 }
 
@@ -155,14 +692,28 @@ fn redraw_event_system(
     mut delete_tiles_event_writer: EventWriter<DeleteTilesEvent>,
     mut win_size: ResMut<WinSize>,
     mut board: ResMut<Board>,
+    options: Res<BoardOptions>,
+    settings: Res<Settings>,
 ) {
     for event in redraw_event_reader.iter() {
         win_size.w = event.width;
         win_size.h = event.height;
         delete_tiles_event_writer.send(DeleteTilesEvent);
     }
+    // the player switched clue layouts mid puzzle
+    if settings.is_changed()
+        && (row_band(&board.p, &options, &settings, win_size.as_ref())
+            != (board.row_band, board.stacked_rows)
+            || settings.mirror_clues != board.mirrored)
+    {
+        delete_tiles_event_writer.send(DeleteTilesEvent);
+    }
 
     for event in deleted_tiles_event_reader.iter() {
+        // a rotated window may want the other clue layout
+        (board.row_band, board.stacked_rows) =
+            row_band(&board.p, &options, &settings, win_size.as_ref());
+        board.mirrored = settings.mirror_clues;
         resize_board_struct(board.as_mut(), win_size.as_ref());
         spawn_tiles_event_writer.send(SpawnTilesEvent);
     }
@@ -176,7 +727,7 @@ fn resize_board_struct(mut board: &mut Board, win_size: &WinSize) {
     let pixels_per_tile;
     let tile_scale;
 
-    let total_board_width = (board.p.get_width() + board.p.get_longest_row_clue_len());
+    let total_board_width = (board.p.get_width() + board.row_band);
     let total_board_height = (board.p.get_height() + board.p.get_longest_column_clue_len());
 
     // account for aspect ratio
@@ -214,14 +765,30 @@ fn delete_tiles_event_system(
     mut control_tile_query: Query<Entity, With<ControlTile>>,
     mut delete_tiles_event_reader: EventReader<DeleteTilesEvent>,
     mut deleted_tiles_event_writer: EventWriter<DeletedTilesEvent>,
+    mut tile_index: ResMut<TileIndex>,
+    mut clue_index: ResMut<ClueIndex>,
+    mut entity_pool: ResMut<EntityPool>,
 ) {
     for _ in delete_tiles_event_reader.iter() {
+        tile_index.0.clear();
+        clue_index.clear();
+        // tiles and clues are hidden and pooled for the next spawn instead of despawned
         for entity in tile_query.iter_mut() {
-            commands.entity(entity).despawn();
+            commands
+                .entity(entity)
+                .remove::<Tile>()
+                .insert(Visibility::INVISIBLE);
+            entity_pool.tiles.push(entity);
         }
 
+        // clue children (fallback digits, atlas numbers, effect layers) are rebuilt for the new text
         for entity in clue_query.iter_mut() {
-            commands.entity(entity).despawn();
+            commands
+                .entity(entity)
+                .despawn_descendants()
+                .remove::<(Clue, FallbackDigits, AtlasNumber, ClueEffectLayers)>()
+                .insert(Visibility::INVISIBLE);
+            entity_pool.clues.push(entity);
         }
 
         for entity in control_tile_query.iter_mut() {
@@ -238,14 +805,14 @@ fn spawn_tiles_event_system(
     mut spawn_tiles_event_reader: EventReader<SpawnTilesEvent>,
     board: Res<Board>,
     control_action: Res<ControlAction>,
+    mut tile_index: ResMut<TileIndex>,
+    mut clue_index: ResMut<ClueIndex>,
+    mut entity_pool: ResMut<EntityPool>,
+    options: Res<BoardOptions>,
 ) {
     for _ in spawn_tiles_event_reader.iter() {
-        let control_tile_max_size;
-        if (board.p.get_longest_column_clue_len() < board.p.get_longest_row_clue_len()) {
-            control_tile_max_size = board.p.get_longest_column_clue_len();
-        } else {
-            control_tile_max_size = board.p.get_longest_row_clue_len();
-        }
+        let instanced = use_instanced(&board, &options);
+        let control_tile_scale = board.control_tile_scale();
         // spawn ControlTile sprite
         commands
             .spawn(SpriteBundle {
@@ -255,32 +822,31 @@ fn spawn_tiles_event_system(
                     BoardAction::Empty => game_textures.tile_empty.clone(),
                 },
                 sprite: Sprite {
+                    // the textures are rebaked at whatever resolution suits the zoom
+                    custom_size: Some(Vec2::splat(TILE_SIZE.0)),
                     anchor: Anchor::Center,
                     ..Default::default()
                 },
                 transform: Transform {
                     translation: Vec3::new(
                         board.origin.0
-                            + board.p.get_longest_row_clue_len() as f32 * board.pixels_per_tile
-                                / 2.,
+                            + (board.row_band_left() as f32 + board.row_band as f32 / 2.)
+                                * board.pixels_per_tile,
                         board.origin.1
                             + board.p.get_height() as f32 * board.pixels_per_tile
                             + board.p.get_longest_column_clue_len() as f32 * board.pixels_per_tile
                                 / 2.,
                         TILE_Z,
                     ),
-                    scale: Vec3::new(
-                        board.tile_scale * control_tile_max_size as f32 * 0.8,
-                        board.tile_scale * control_tile_max_size as f32 * 0.8,
-                        1.,
-                    ),
+                    scale: Vec3::new(control_tile_scale, control_tile_scale, 1.),
                     ..Default::default()
                 },
                 ..Default::default()
             })
             .insert(ControlTile {
                 x: board.origin.0
-                    + board.p.get_longest_row_clue_len() as f32 * board.pixels_per_tile / 2.,
+                    + (board.row_band_left() as f32 + board.row_band as f32 / 2.)
+                        * board.pixels_per_tile,
                 y: board.origin.1
                     + board.p.get_height() as f32 * board.pixels_per_tile
                     + board.p.get_longest_column_clue_len() as f32 * board.pixels_per_tile / 2.,
@@ -291,11 +857,15 @@ fn spawn_tiles_event_system(
             for y in (0..board.h as usize) {
                 // set texture
                 let texture;
-                if (x >= board.p.get_longest_row_clue_len()
+                if (!board.in_row_band(x as f32)
                     && y < board.h - board.p.get_longest_column_clue_len())
                 {
                     // if tile is not a clue tile
-                    let x = x - board.p.get_longest_row_clue_len();
+                    if instanced {
+                        // drawn by the board material instead
+                        continue;
+                    }
+                    let x = x - board.grid_left();
                     let y = y;
                     if (board.p.get_cell(x, y) == Cell::Filled) {
                         texture = game_textures.tile_filled.clone();
@@ -304,90 +874,104 @@ fn spawn_tiles_event_system(
                     } else {
                         texture = game_textures.tile_empty.clone();
                     }
-                } else if x >= board.p.get_longest_row_clue_len()
-                    || y < board.h - board.p.get_longest_column_clue_len()
-                {
+                } else if !board.in_corner(x as f32, y as f32) {
                     // else if tile is a clue tile
                     texture = game_textures.tile_clue.clone();
-                    // set the clue value, if applicable
-                    let mut clue_str = String::from("");
-                    if x >= board.p.get_longest_row_clue_len() {
-                        // column clue
-                        if board.p.column_clues[x - board.p.get_longest_row_clue_len()].len()
-                            > y - (board.p.get_height())
-                        {
-                            let y = y - board.p.get_height();
-                            let x = x - board.p.get_longest_row_clue_len();
-                            clue_str = board.p.column_clues[x]
-                                [(0 + board.p.column_clues[x].len() - 1) - y]
-                                .to_string();
+                    // stacked row clues share the tile, one line in each half
+                    let halves: &[f32] = if board.in_row_band(x as f32) && board.stacked_rows {
+                        &[0.5, 0.]
+                    } else {
+                        &[0.]
+                    };
+                    for half in halves {
+                        let (line, index) = match board.clue_at(x as f32, y as f32 + half) {
+                            Some(address) => address,
+                            None => continue,
                         };
-                    } else if (board.p.get_longest_row_clue_len() - board.p.row_clues[y].len()) <= x
-                    {
-                        // row clue
-                        clue_str = board.p.row_clues[y]
-                            [x - (board.p.get_longest_row_clue_len() - board.p.row_clues[y].len())]
-                        .to_string();
-                    }
-                    // spawn text for clue
-                    commands
-                        .spawn(Text2dBundle {
+                        let (clue_x, clue_y, height) = match board.clue_tile(line, index) {
+                            Some(tile) => tile,
+                            None => continue,
+                        };
+                        let value = match line {
+                            ClueLine::Row(y) => board.p.row_clues[y][index],
+                            ClueLine::Column(x) => board.p.column_clues[x][index],
+                        };
+                        let font_scale = if height < 1. {
+                            STACKED_CLUE_FONT_SCALE
+                        } else {
+                            CLUE_FONT_SCALE
+                        };
+                        // spawn text for clue, reusing a pooled entity when there is one
+                        let clue_bundle = Text2dBundle {
                             text: Text::from_section(
-                                clue_str,
+                                value.to_string(),
                                 TextStyle {
                                     font: game_textures.font.clone(),
-                                    font_size: TILE_SIZE.0 * 0.5 * board.tile_scale,
+                                    font_size: TILE_SIZE.0 * font_scale * board.tile_scale,
                                     color: Color::BLACK,
                                 },
                             )
                             .with_alignment(TextAlignment::CENTER),
                             transform: Transform {
-                                translation: Vec3::new(
-                                    board.origin.0
-                                        + x as f32 * board.pixels_per_tile
-                                        + board.pixels_per_tile / 2.,
-                                    board.origin.1
-                                        + y as f32 * board.pixels_per_tile
-                                        + board.pixels_per_tile / 2.,
+                                translation: board.tile_translation(
+                                    clue_x + 0.5,
+                                    clue_y + height / 2.,
                                     CLUE_Z,
                                 ),
                                 ..Default::default()
                             },
                             ..Default::default()
-                        })
-                        .insert(Clue {
-                            x: x as f32,
-                            y: y as f32,
-                        });
+                        };
+                        let clue = Clue {
+                            x: clue_x,
+                            y: clue_y,
+                        };
+                        let entity = match entity_pool.clues.pop() {
+                            Some(entity) => {
+                                commands.entity(entity).insert(clue_bundle).insert(clue);
+                                entity
+                            }
+                            None => commands.spawn(clue_bundle).insert(clue).id(),
+                        };
+                        clue_index.insert(line, index, entity);
+                    }
                 } else {
                     // else not a tile, continue
                     continue;
                 }
 
-                // spawn tile sprite
-                commands
-                    .spawn(SpriteBundle {
-                        texture,
-                        sprite: Sprite {
-                            anchor: Anchor::BottomLeft,
-                            ..Default::default()
-                        },
-                        transform: Transform {
-                            translation: Vec3::new(
-                                board.origin.0 + x as f32 * board.pixels_per_tile,
-                                board.origin.1 + y as f32 * board.pixels_per_tile,
-                                TILE_Z,
-                            ),
-
-                            scale: Vec3::new(board.tile_scale, board.tile_scale, 1.),
-                            ..Default::default()
-                        },
+                // spawn tile sprite, reusing a pooled entity when there is one
+                let tile_bundle = SpriteBundle {
+                    texture,
+                    sprite: Sprite {
+                        custom_size: Some(Vec2::splat(TILE_SIZE.0)),
+                        anchor: Anchor::BottomLeft,
                         ..Default::default()
-                    })
-                    .insert(Tile {
-                        x: x as f32,
-                        y: y as f32,
-                    });
+                    },
+                    transform: Transform {
+                        translation: Vec3::new(
+                            board.origin.0 + x as f32 * board.pixels_per_tile,
+                            board.origin.1 + y as f32 * board.pixels_per_tile,
+                            TILE_Z,
+                        ),
+
+                        scale: Vec3::new(board.tile_scale, board.tile_scale, 1.),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                };
+                let tile = Tile {
+                    x: x as f32,
+                    y: y as f32,
+                };
+                let entity = match entity_pool.tiles.pop() {
+                    Some(entity) => {
+                        commands.entity(entity).insert(tile_bundle).insert(tile);
+                        entity
+                    }
+                    None => commands.spawn(tile_bundle).insert(tile).id(),
+                };
+                tile_index.0.insert((x, y), entity);
             }
         }
     }
@@ -405,6 +989,9 @@ fn input_and_resizing_system(
     mut redraw_event_writer: EventWriter<RedrawEvent>,
     mut tile_query: Query<(&mut Handle<Image>, &Tile)>,
     mut clue_query: Query<(&mut Text, &Clue)>,
+    clue_index: Res<ClueIndex>,
+    rules: Res<Rules>,
+    mut input_context: InputContext,
 ) {
     let window = windows.get_primary().unwrap();
 
@@ -461,82 +1048,163 @@ fn input_and_resizing_system(
     // endregion
 
     // region:      Handle Input
-    if let Some(screen_pos) = window.cursor_position() {
+    // a press or touch anywhere ends the stroke before it, even off the board
+    let pressed = buttons.get_just_pressed().next().is_some();
+    if pressed || touches.iter_just_pressed().next().is_some() {
+        input_context.stroke.0 += 1;
+    }
+    if buttons.get_pressed().next().is_none() && input_context.line_lock.start.is_some() {
+        *input_context.line_lock = LineLock::default();
+    }
+    // other screens are drawn over the board and take the input themselves
+    let on_board = *input_context.screen.current() == Screen::Board;
+    // a captured pointer moves the virtual cursor instead of the window's
+    let cursor = input_context
+        .pointer_lock
+        .cursor
+        .or(window.cursor_position());
+    if let Some(screen_pos) = cursor.filter(|_| on_board) {
+        // the guess toolbar sits over the board and takes its own clicks
+        let hud_scale = input_context.hud_scale.0;
+        let (guessing, profile) = (input_context.guess.active(), &*input_context.profile);
+        if toolbar_button_at(screen_pos, guessing, hud_scale, profile).is_some() {
+            return;
+        }
+        // clicks place bracket markers while their key is held
+        if input_context.keys.pressed(BRACKET_KEY) {
+            return;
+        }
         // convert screen coordinates to board coordinates
-        let mut pos = Vec2::new(screen_pos.x, screen_pos.y);
+        let mut pos = input_context.view.screen_to_world(screen_pos);
         pos = pos - Vec2::new(board.origin.0, board.origin.1);
         pos = pos / board.pixels_per_tile;
+        // line headers only preview, they never mark anything
+        if board.line_header_at(pos.x, pos.y).is_some() {
+            return;
+        }
         let x = pos.x.floor();
-        let y = pos.y.floor();
+        // stacked row clues are picked by half tile
+        let y = if board.stacked_rows && board.in_row_band(x) {
+            (pos.y * 2.).floor() / 2.
+        } else {
+            pos.y.floor()
+        };
 
         // region: Mouse Input
 
-        if buttons.just_pressed(MouseButton::Left) {
-            current_action.0 = control_action.0;
-        } else if buttons.just_pressed(MouseButton::Right) {
-            current_action.0 = BoardAction::Cross;
-        } else if buttons.just_pressed(MouseButton::Middle) {
-            current_action.0 = BoardAction::Empty;
+        let mouse_buttons = [MouseButton::Left, MouseButton::Right, MouseButton::Middle];
+        let binding = |button| match button {
+            MouseButton::Right => input_context.settings.right_click,
+            MouseButton::Middle => input_context.settings.middle_click,
+            _ => ClickBinding::Action(control_action.0),
+        };
+        // only buttons bound to an action paint, the others never reach the board
+        let painting: Vec<MouseButton> = mouse_buttons
+            .into_iter()
+            .filter(|button| matches!(binding(*button), ClickBinding::Action(_)))
+            .collect();
+
+        if let Some(button) = mouse_buttons.into_iter().find(|b| buttons.just_pressed(*b)) {
+            match binding(button) {
+                ClickBinding::Action(action) => current_action.0 = action,
+                ClickBinding::Flag => {
+                    if let Some((x, y)) = board.tile_to_cell(x, y) {
+                        input_context
+                            .pencil_toggled_event_writer
+                            .send(PencilToggledEvent { x, y });
+                    }
+                }
+                ClickBinding::Disabled => {}
+            }
         }
 
         // account for cases where the action already matches the current state of object under cursor
-        if buttons.any_just_pressed([MouseButton::Left, MouseButton::Right]) {
-            if x < board.p.get_longest_row_clue_len() as f32 && y >= board.p.get_height() as f32 {
-                input_event_writer.send(InputEvent {
-                    x,
-                    y,
-                    action: current_action.0,
-                    from_player: true,
-                });
-            } else if x < board.p.get_longest_row_clue_len() as f32
-                || y >= board.p.get_height() as f32
-            {
+        let adjusting = painting
+            .iter()
+            .copied()
+            .filter(|b| *b != MouseButton::Middle);
+        if buttons.any_just_pressed(adjusting) {
+            if board.in_corner(x, y) {
+                // the corner is empty when the control tile is off
+                if input_context
+                    .profile
+                    .shows_control_tile(&input_context.settings)
+                {
+                    input_event_writer.send(InputEvent {
+                        x,
+                        y,
+                        action: current_action.0,
+                        from_player: true,
+                    });
+                }
+            } else if board.in_row_band(x) || y >= board.p.get_height() as f32 {
                 // account for clues matching action here
-                let red = Color::RED;
-                let gray = Color::GRAY;
-                for (mut text, clue) in clue_query.iter_mut() {
-                    if clue.x == x && clue.y == y {
-                        match (current_action.0) {
-                            (BoardAction::Fill) => {
-                                if text.sections[0].style.color == Color::RED {
-                                    current_action.0 = BoardAction::Empty;
-                                }
+                let entity = board
+                    .clue_at(x, y)
+                    .and_then(|(line, index)| clue_index.get(line, index));
+                if let Some((text, _)) = entity.and_then(|e| clue_query.get(e).ok()) {
+                    match (current_action.0) {
+                        (BoardAction::Fill) => {
+                            if text.sections[0].style.color == Color::RED {
+                                current_action.0 = BoardAction::Empty;
                             }
-                            (BoardAction::Cross) => {
-                                if text.sections[0].style.color == Color::GRAY {
-                                    current_action.0 = BoardAction::Empty;
-                                }
+                        }
+                        (BoardAction::Cross) => {
+                            if text.sections[0].style.color == Color::GRAY {
+                                current_action.0 = BoardAction::Empty;
                             }
-                            _ => {}
                         }
+                        _ => {}
                     }
                 }
             } else {
                 // account for tiles matching action here
-                for (mut texture, tile) in tile_query.iter_mut() {
-                    if tile.x == x && tile.y == y {
-                        let texture_copy = texture.clone();
-                        let filled = game_textures.tile_filled.clone();
-                        let crossed = game_textures.tile_crossed.clone();
-                        match (current_action.0) {
-                            (BoardAction::Fill) => {
-                                if texture_copy == filled {
-                                    current_action.0 = BoardAction::Empty;
-                                }
-                            }
-                            (BoardAction::Cross) => {
-                                if texture_copy == crossed {
-                                    current_action.0 = BoardAction::Empty;
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
+                if let Some((cell_x, cell_y)) = board.tile_to_cell(x, y) {
+                    let cell = board.p.get_cell(cell_x, cell_y);
+                    let options = &input_context.options;
+                    current_action.0 = match options.get("input_mode") {
+                        // one button moves the cell on, the drag paints that state
+                        Some("cycle") => cycle_action(options, cell),
+                        _ => rules.0.drag_action(current_action.0, cell),
+                    };
                 }
             }
         }
-        if buttons.any_pressed([MouseButton::Left, MouseButton::Right, MouseButton::Middle])
-            && !(x < board.p.get_longest_row_clue_len() as f32 && y >= board.p.get_height() as f32)
+        if buttons.any_just_pressed(painting.iter().copied()) {
+            // only strokes begun on a cell lock, clue marking drags freely
+            let locking = input_context.settings.line_lock && board.tile_to_cell(x, y).is_some();
+            *input_context.line_lock = LineLock {
+                start: Some((x, y)).filter(|_| locking),
+                axis: None,
+            };
+            input_context.pen.pencilled = None;
+        }
+        let locked = input_context.line_lock.start.is_some();
+        if locked {
+            input_context.line_lock.follow(x, y);
+        }
+        let (x, y) = input_context.line_lock.apply(x, y);
+        // a pen pressed lightly pencils instead of painting
+        let pen = pen_weight(&input_context.settings);
+        if pen == Some(PenWeight::Light) && buttons.any_pressed(painting.iter().copied()) {
+            if let Some((cell_x, cell_y)) = board.tile_to_cell(x, y) {
+                let cell = (cell_x, cell_y);
+                let marked = input_context.pencil_marks.0.contains(&cell);
+                if !marked && input_context.pen.pencilled != Some(cell) {
+                    input_context.pen.pencilled = Some(cell);
+                    input_context
+                        .pencil_toggled_event_writer
+                        .send(PencilToggledEvent {
+                            x: cell_x,
+                            y: cell_y,
+                        });
+                }
+            }
+        } else if pen != Some(PenWeight::Unsure)
+            && buttons.any_pressed(painting)
+            && !board.in_corner(x, y)
+            // a locked stroke stays in the grid
+            && !(locked && board.tile_to_cell(x, y).is_none())
         // && not in control tile
         {
             input_event_writer.send(InputEvent {
@@ -554,90 +1222,70 @@ fn input_and_resizing_system(
 
 fn input_event_system(
     game_textures: Res<GameTextures>,
-    send_channel: Res<WASMSendChannel>,
-    mut board: ResMut<Board>,
+    board: Res<Board>,
     mut input_event_reader: EventReader<InputEvent>,
-    mut tile_query: Query<(&mut Handle<Image>, &Tile), Without<ControlTile>>,
     mut clue_query: Query<(&mut Text, &Clue)>,
     mut control_tile_query: Query<(&mut Handle<Image>), (With<ControlTile>, Without<Tile>)>,
     mut current_action: ResMut<CurrentAction>,
     mut control_action: ResMut<ControlAction>,
+    mut control_toggled_event_writer: EventWriter<ControlToggledEvent>,
+    mut board_command_event_writer: EventWriter<BoardCommand>,
+    mut clue_marked_event_writer: EventWriter<ClueMarkedEvent>,
+    clue_index: Res<ClueIndex>,
+    rules: Res<Rules>,
+    mut input_gate: InputGate,
 ) {
-    for event in input_event_reader.iter() {
+    for event in input_gate.admit(input_event_reader.iter()).iter() {
         // convert cursor position to tile coordinates
 
         let x = event.x;
         let y = event.y;
 
-        if x < board.p.get_longest_row_clue_len() as f32 && y >= board.p.get_height() as f32 {
+        if board.in_corner(x, y) {
             // switch between cross and fill modes here for touch
             control_action.0 = match control_action.0 {
                 BoardAction::Fill => BoardAction::Cross,
                 BoardAction::Cross => BoardAction::Fill,
                 BoardAction::Empty => BoardAction::Fill,
             };
-        } else if x < board.p.get_longest_row_clue_len() as f32 || y >= board.p.get_height() as f32
+            if event.from_player {
+                control_toggled_event_writer.send(ControlToggledEvent);
+            }
+        } else if board.in_row_band(x) || y >= board.p.get_height() as f32
         // handle clues
         {
-            for (mut text, clue) in clue_query.iter_mut() {
-                if clue.x == x && clue.y == y {
-                    match event.action {
-                        BoardAction::Fill => {
-                            text.sections[0].style.color = Color::RED;
-                        }
-                        BoardAction::Cross => {
-                            text.sections[0].style.color = Color::GRAY;
-                        }
-                        BoardAction::Empty => {
-                            text.sections[0].style.color = Color::BLACK;
-                        }
-                    }
+            let clue = board.clue_at(x, y).and_then(|(line, index)| {
+                let entity = clue_index.get(line, index)?;
+                Some((line, index, clue_query.get_mut(entity).ok()?))
+            });
+            if let Some((line, index, (mut text, _))) = clue {
+                let color = match event.action {
+                    BoardAction::Fill => Color::RED,
+                    BoardAction::Cross => Color::GRAY,
+                    BoardAction::Empty => Color::BLACK,
+                };
+                if text.sections[0].style.color != color {
+                    text.sections[0].style.color = color;
+                    clue_marked_event_writer.send(ClueMarkedEvent {
+                        line,
+                        index,
+                        action: event.action,
+                        from_player: event.from_player,
+                    });
                 }
             }
         } else {
-            // handle tiles
-            for (mut texture, tile) in tile_query.iter_mut() {
-                if tile.x == x && tile.y == y {
-                    // closure to set board and texture easier
-                    let mut set_cell = |x: f32, y: f32, cell: Cell, t: Handle<Image>| {
-                        let x_diff = board.p.get_longest_row_clue_len();
-                        let current_cell = board.p.get_cell(x as usize - x_diff, y as usize);
-                        // update and send changes if cell is different, otherwise do nothing
-                        if current_cell != cell {
-                            *texture = t;
-                            board.p.set_cell(x as usize - x_diff, y as usize, cell);
-                            if event.from_player {
-                                let cell_str;
-                                match cell {
-                                    Cell::Empty => cell_str = String::from("0"),
-                                    Cell::Filled => cell_str = String::from("1"),
-                                    Cell::Crossed => cell_str = String::from("X"),
-                                }
-                                // send update to server if the player made the action
-                                send_channel.tx.send((
-                                    String::from("c"),
-                                    format!(
-                                        "{},{}",
-                                        board.p.get_pos(x as usize - x_diff, y as usize),
-                                        cell_str
-                                    ),
-                                ));
-                            }
-                        }
-                    };
-
-                    match event.action {
-                        BoardAction::Fill => {
-                            set_cell(x, y, Cell::Filled, game_textures.tile_filled.clone());
-                        }
-                        BoardAction::Cross => {
-                            set_cell(x, y, Cell::Crossed, game_textures.tile_crossed.clone());
-                        }
-                        BoardAction::Empty => {
-                            set_cell(x, y, Cell::Empty, game_textures.tile_empty.clone());
-                        }
-                    }
-                }
+            // handle tiles, the command layer validates and applies the change
+            if let Some((cell_x, cell_y)) = board.tile_to_cell(x, y) {
+                board_command_event_writer.send(BoardCommand {
+                    x: cell_x,
+                    y: cell_y,
+                    cell: rules.0.apply(event.action),
+                    source: match event.from_player {
+                        true => ChangeSource::Player,
+                        false => ChangeSource::Remote,
+                    },
+                });
             }
         }
         // update control tile
@@ -651,76 +1299,167 @@ fn input_event_system(
     }
 }
 
+fn send_cell_changes_system(
+    board: Res<Board>,
+    send_channel: Res<WASMSendChannel>,
+    mut cell_changed_event_reader: EventReader<CellChangedEvent>,
+) {
+    for event in cell_changed_event_reader.iter() {
+        // send update to server if the player made the change
+        if event.source == ChangeSource::Player {
+            send_channel.tx.send((
+                String::from("c"),
+                format!(
+                    "{},{}",
+                    board.original_pos(event.x, event.y),
+                    cell_to_char(event.new)
+                ),
+            ));
+        }
+    }
+}
+
 fn new_board_event_system(
     win_size: Res<WinSize>,
+    options: Res<BoardOptions>,
+    settings: Res<Settings>,
     mut board: ResMut<Board>,
     mut redraw_event_writer: EventWriter<RedrawEvent>,
     mut new_board_event_reader: EventReader<NewBoardEvent>,
+    mut board_loaded_event_writer: EventWriter<BoardLoadedEvent>,
+    mut puzzle_parser: ResMut<PuzzleParser>,
+    mut loading: ResMut<Loading>,
+    limits: Res<BoardLimits>,
+    send_channel: Res<WASMSendChannel>,
 ) {
+    // parse outside the frame, large clue strings can take a while, on
+    // another thread only natively, see `PuzzleParser`
     for event in new_board_event_reader.iter() {
-        match Puzzle::from_string(event.clues.as_str()) {
-            Ok(mut new_p) => {
-                new_p.set_board_from_string(event.cells.as_str());
+        puzzle_parser.latest += 1;
+        loading.0 = true;
+
+        let generation = puzzle_parser.latest;
+        let tx = puzzle_parser.tx.clone();
+        let clues = event.clues.clone();
+        let cells = event.cells.clone();
+        let orientation = match options.get_bool("randomize", false) {
+            true => Orientation::random(),
+            false => Orientation::default(),
+        };
+        AsyncComputeTaskPool::get()
+            .spawn(async move {
+                let result = Puzzle::from_string(clues.as_str())
+                    .map_err(|err| err.to_string())
+                    .and_then(|mut new_p| {
+                        // the handler expects one char per cell, never hand it anything else
+                        let expected = new_p.get_width() * new_p.get_height();
+                        let count = cells.chars().count();
+                        if !cells.is_empty() && count != expected {
+                            return Err(format!(
+                                "cells: {} for a {}x{} board, expected {}",
+                                count,
+                                new_p.get_width(),
+                                new_p.get_height(),
+                                expected
+                            ));
+                        }
+                        new_p.set_board_from_string(cells.as_str());
+                        let orientation = orientation.fit(&new_p);
+                        orientation.apply(&mut new_p);
+                        Ok((new_p, orientation))
+                    });
+                tx.send(ParsedPuzzle {
+                    generation,
+                    clues,
+                    result,
+                });
+            })
+            .detach();
+    }
+
+    while let Ok(parsed) = puzzle_parser.rx.try_recv() {
+        // a newer join superseded this one
+        if parsed.generation != puzzle_parser.latest {
+            continue;
+        }
+        loading.0 = false;
 
+        // refuse boards that would spawn more entities than the device can take
+        let result = parsed
+            .result
+            .and_then(|parsed| limits.check(&parsed.0).map(|_| parsed));
+
+        match result {
+            Ok((new_p, orientation)) => {
                 board.p = new_p;
+                board.orientation = orientation;
+                (board.row_band, board.stacked_rows) =
+                    row_band(&board.p, &options, &settings, win_size.as_ref());
+                board.mirrored = settings.mirror_clues;
+                // hosts can name puzzles, otherwise the clues identify them
+                board.id = match options.get("puzzle_id") {
+                    Some(id) => id.to_string(),
+                    None => format!("{:016x}", fnv1a(parsed.clues.as_str())),
+                };
                 resize_board_struct(board.as_mut(), win_size.as_ref());
                 redraw_event_writer.send(RedrawEvent {
                     width: win_size.w,
                     height: win_size.h,
-                })
+                });
+                board_loaded_event_writer.send(BoardLoadedEvent);
+            }
+            Err(err) => {
+                warn!("{}", err);
+                send_channel.tx.send((String::from("error"), err));
             }
-            Err(err) => warn!(err),
         }
     }
 }
 
-fn board_update_event_system(
-    board: Res<Board>,
-    mut input_event_writer: EventWriter<InputEvent>,
-    mut board_update_event_reader: EventReader<BoardUpdateEvent>,
-) {
-    for event in board_update_event_reader.iter() {
-        let cells = &event.0;
-        let mut cells_iter = cells.chars().into_iter();
-
-        if (board.p.get_width() * board.p.get_height() == cells.chars().count()) {
-            for y in 0..board.p.get_height() {
-                for x in 0..board.p.get_width() {
-                    let cell = cells_iter.next();
-                    let mut input_event = InputEvent {
-                        x: (x + board.p.get_longest_row_clue_len()) as f32,
-                        y: y as f32,
-                        action: BoardAction::Empty,
-                        from_player: false,
-                    };
-                    match cell {
-                        Some('0') => {
-                            input_event.action = BoardAction::Empty;
-                            input_event_writer.send(input_event);
-                        }
-                        Some('1') => {
-                            input_event.action = BoardAction::Fill;
-                            input_event_writer.send(input_event);
-                        }
-                        Some('X') => {
-                            input_event.action = BoardAction::Cross;
-                            input_event_writer.send(input_event);
-                        }
-                        Some(c) => {
-                            warn!("Invalid BoardUpdateEvent, Incorrect Cell: {}", c)
-                        }
-                        None => {
-                            warn!("Invalid BoardUpdateEvent, Ran out of input") // should never happen
-                        }
-                    }
-                }
-            }
-        } else {
-            warn!(
-                "Invalid BoardUpdateEvent, Incorrect size: {}, Expected: {}",
-                cells.chars().count(),
-                board.p.get_width() * board.p.get_height()
-            )
-        }
+// region:      TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::harness::{plus_puzzle, Harness, SETTLE_TICKS};
+
+    #[test]
+    fn compact_clues_setting_overrides_the_clue_layout() {
+        let mut harness = Harness::new();
+        harness.app.world.resource_mut::<Settings>().compact_clues = CompactClues::Always;
+        assert!(harness.join(plus_puzzle().as_str(), "000000000"));
+        assert!(harness.board().stacked_rows);
+        assert_eq!(harness.board().row_band, 1);
+
+        harness.app.world.resource_mut::<Settings>().compact_clues = CompactClues::Never;
+        harness.ticks(SETTLE_TICKS);
+        assert!(!harness.board().stacked_rows);
+        assert_eq!(harness.board().row_band, 2);
+    }
+
+    #[test]
+    fn mirrored_clues_move_the_row_band_right_of_the_grid() {
+        let mut harness = Harness::new();
+        harness.app.world.resource_mut::<Settings>().mirror_clues = true;
+        assert!(harness.join(plus_puzzle().as_str(), "000000000"));
+        assert!(harness.board().mirrored);
+        assert_eq!(harness.board().cell_to_tile(0, 0), (0., 0.));
+        assert!(matches!(
+            harness.board().clue_at(3.5, 0.5),
+            Some((ClueLine::Row(0), _))
+        ));
+
+        harness.input_cell(0, 0, BoardAction::Fill);
+        assert_eq!(&harness.cells()[..1], "1");
+
+        harness.app.world.resource_mut::<Settings>().mirror_clues = false;
+        harness.ticks(SETTLE_TICKS);
+        assert!(!harness.board().mirrored);
+        assert_eq!(
+            harness.board().cell_to_tile(0, 0).0,
+            harness.board().row_band as f32
+        );
     }
 }
+
+// endregion
@@ -0,0 +1,124 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+use picross_handler::Cell;
+
+use crate::archive::ArchiveView;
+use crate::board::{cell_texture, Board, CellChangedEvent, ChangeSource, Tile, TileIndex};
+use crate::context_loss::ContextLoss;
+use crate::options::BoardOptions;
+use crate::start::StartLock;
+use crate::updates::PendingVisuals;
+use crate::GameTextures;
+
+// endregion
+
+// region:      EVENTS
+
+/// A request to set one cell. Input, server updates and handicaps all send
+/// these, `board_command_system` is the only place cells change and the
+/// only sender of `CellChangedEvent`.
+pub struct BoardCommand {
+    pub x: usize,
+    pub y: usize,
+    pub cell: Cell,
+    pub source: ChangeSource,
+}
+
+impl BoardCommand {
+    /// Why the command can't be applied, if it can't.
+    fn validate(
+        &self,
+        board: &Board,
+        start_lock: &StartLock,
+        context_loss: &ContextLoss,
+        options: &BoardOptions,
+        archive_view: &ArchiveView,
+    ) -> Result<(), &'static str> {
+        if self.x >= board.p.get_width() || self.y >= board.p.get_height() {
+            return Err("out of bounds");
+        }
+        if self.source == ChangeSource::Player {
+            player_moves_allowed(start_lock, context_loss, options, archive_view)?;
+        }
+        Ok(())
+    }
+}
+
+// endregion
+
+/// Why the player can't change any cell right now, if they can't. Lets a
+/// move made on their behalf be refused before it is sent.
+pub fn player_moves_allowed(
+    start_lock: &StartLock,
+    context_loss: &ContextLoss,
+    options: &BoardOptions,
+    archive_view: &ArchiveView,
+) -> Result<(), &'static str> {
+    if start_lock.locked() {
+        return Err("board locked until the start");
+    }
+    if context_loss.lost() {
+        return Err("graphics context lost");
+    }
+    // spectators watch the server's moves and make none of their own
+    if options.get_bool("spectate", false) {
+        return Err("spectating");
+    }
+    if archive_view.viewing {
+        return Err("viewing an archived round");
+    }
+    Ok(())
+}
+
+pub fn board_command_system(
+    game_textures: Res<GameTextures>,
+    start_lock: Res<StartLock>,
+    context_loss: Res<ContextLoss>,
+    options: Res<BoardOptions>,
+    archive_view: Res<ArchiveView>,
+    tile_index: Res<TileIndex>,
+    mut board: ResMut<Board>,
+    mut pending_visuals: ResMut<PendingVisuals>,
+    mut tile_query: Query<&mut Handle<Image>, With<Tile>>,
+    mut board_command_event_reader: EventReader<BoardCommand>,
+    mut cell_changed_event_writer: EventWriter<CellChangedEvent>,
+) {
+    for command in board_command_event_reader.iter() {
+        if let Err(reason) =
+            command.validate(&board, &start_lock, &context_loss, &options, &archive_view)
+        {
+            warn!(
+                "Rejected board command at {},{}: {}",
+                command.x, command.y, reason
+            );
+            continue;
+        }
+        let (x, y) = (command.x, command.y);
+        let old = board.p.get_cell(x, y);
+        if old == command.cell {
+            continue;
+        }
+        board.p.set_cell(x, y, command.cell);
+
+        // the player sees their own move at once, everything else may come
+        // in bulk and catches up over the next frames
+        if command.source == ChangeSource::Player {
+            let (tile_x, tile_y) = board.cell_to_tile(x, y);
+            // large boards are drawn without tile entities
+            let entity = tile_index.0.get(&(tile_x as usize, tile_y as usize));
+            if let Some(mut texture) = entity.and_then(|e| tile_query.get_mut(*e).ok()) {
+                *texture = cell_texture(&game_textures, command.cell);
+            }
+        } else {
+            pending_visuals.cells.push_back((x, y));
+        }
+        cell_changed_event_writer.send(CellChangedEvent {
+            x,
+            y,
+            old,
+            new: command.cell,
+            source: command.source,
+        });
+    }
+}
@@ -0,0 +1,219 @@
+// region:      IMPORTS
+
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::board::{Board, BoardLoadedEvent, SpawnTilesEvent};
+use crate::camera::BoardView;
+use crate::layers::HIGHLIGHT_Z;
+use crate::marks::ClueLine;
+use crate::options::BoardOptions;
+use crate::screens::Screen;
+use crate::storage;
+
+// endregion
+
+// region:      CONSTANTS
+
+const BRACKETS_KEY_PREFIX: &str = "brackets.";
+// overridable with the bracket_color option
+const BRACKET_COLOR: Color = Color::rgba(0.1, 0.45, 0.95, 0.9);
+const BRACKET_WIDTH: f32 = 0.08; // of a tile
+
+/// Held while clicking to place a bracket instead of painting.
+pub const BRACKET_KEY: KeyCode = KeyCode::B;
+const CLEAR_KEY: KeyCode = KeyCode::Delete;
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct BracketMarker;
+
+// endregion
+
+// region:      RESOURCES
+
+/// Run boundaries the player worked out, as on paper: a line and the index
+/// of the cell a boundary sits before. Placed by clicking near a cell edge
+/// with B held, cleared a line at a time with Delete, kept per puzzle in
+/// localStorage and never sent to the server.
+#[derive(Resource, Default)]
+pub struct Brackets(pub HashSet<(ClueLine, usize)>);
+
+impl Brackets {
+    fn key(board: &Board) -> String {
+        format!("{}{}", BRACKETS_KEY_PREFIX, board.id)
+    }
+
+    /// `c0:5,r3:2`, a line and a boundary per entry, sorted.
+    fn to_storage(&self) -> String {
+        let mut entries: Vec<String> = self
+            .0
+            .iter()
+            .map(|(line, at)| format!("{}:{}", line, at))
+            .collect();
+        entries.sort();
+        entries.join(",")
+    }
+
+    fn from_storage(saved: &str, board: &Board) -> Self {
+        let entries = saved.split(',').filter_map(|entry| {
+            let (line, at) = entry.split_once(':')?;
+            let (line, at) = (ClueLine::parse(line)?, at.parse::<usize>().ok()?);
+            valid(board, line, at).then_some((line, at))
+        });
+        Self(entries.collect())
+    }
+}
+
+// endregion
+
+pub struct BracketPlugin;
+
+impl Plugin for BracketPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Brackets>()
+            .add_system(bracket_input_system)
+            .add_system(bracket_storage_system.after(bracket_input_system))
+            .add_system(bracket_draw_system.after(bracket_input_system));
+    }
+}
+
+/// Boundaries are between two cells of the line, never at its ends.
+fn valid(board: &Board, line: ClueLine, at: usize) -> bool {
+    let (index, len, count) = match line {
+        ClueLine::Row(y) => (y, board.p.get_width(), board.p.get_height()),
+        ClueLine::Column(x) => (x, board.p.get_height(), board.p.get_width()),
+    };
+    index < count && at > 0 && at < len
+}
+
+fn bracket_input_system(
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    board: Res<Board>,
+    view: Res<BoardView>,
+    screen: Res<State<Screen>>,
+    mut brackets: ResMut<Brackets>,
+) {
+    let placing = keys.pressed(BRACKET_KEY) && buttons.just_pressed(MouseButton::Left);
+    let clearing = keys.just_pressed(CLEAR_KEY);
+    if !(placing || clearing) || *screen.current() != Screen::Board {
+        return;
+    }
+    let screen_pos = match windows.get_primary().and_then(|w| w.cursor_position()) {
+        Some(screen_pos) => screen_pos,
+        None => return,
+    };
+    let pos = (view.screen_to_world(screen_pos) - Vec2::new(board.origin.0, board.origin.1))
+        / board.pixels_per_tile;
+
+    if clearing {
+        // a cell clears both its lines, a clue only its own
+        let lines: Vec<ClueLine> = match board.tile_to_cell(pos.x, pos.y) {
+            Some((x, y)) => vec![ClueLine::Row(y), ClueLine::Column(x)],
+            None => board
+                .clue_at(pos.x, pos.y)
+                .map(|(line, _)| line)
+                .into_iter()
+                .collect(),
+        };
+        if brackets.0.iter().any(|(line, _)| lines.contains(line)) {
+            brackets.0.retain(|(line, _)| !lines.contains(line));
+        }
+        return;
+    }
+
+    let (x, y) = match board.tile_to_cell(pos.x, pos.y) {
+        Some(cell) => cell,
+        None => return,
+    };
+    // the closer edge decides, a left or right edge splits the row
+    let (fx, fy) = (pos.x.fract(), pos.y.fract());
+    let bracket = match fx.min(1. - fx) <= fy.min(1. - fy) {
+        true => (ClueLine::Row(y), x + (fx >= 0.5) as usize),
+        false => (ClueLine::Column(x), y + (fy >= 0.5) as usize),
+    };
+    if !valid(&board, bracket.0, bracket.1) {
+        return;
+    }
+    if !brackets.0.remove(&bracket) {
+        brackets.0.insert(bracket);
+    }
+}
+
+fn bracket_storage_system(
+    board: Res<Board>,
+    mut brackets: ResMut<Brackets>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut saved: Local<String>,
+) {
+    if board_loaded_event_reader.iter().last().is_some() {
+        let stored = storage::load(&Brackets::key(&board)).unwrap_or_default();
+        *brackets = Brackets::from_storage(&stored, &board);
+        *saved = brackets.to_storage();
+        return;
+    }
+    if !brackets.is_changed() || board.id.is_empty() {
+        return;
+    }
+    let current = brackets.to_storage();
+    if current == *saved {
+        return;
+    }
+    match current.is_empty() {
+        true => storage::remove(&Brackets::key(&board)),
+        false => storage::save(&Brackets::key(&board), &current),
+    }
+    *saved = current;
+}
+
+fn bracket_draw_system(
+    mut commands: Commands,
+    board: Res<Board>,
+    brackets: Res<Brackets>,
+    options: Res<BoardOptions>,
+    mut spawn_tiles_event_reader: EventReader<SpawnTilesEvent>,
+    marker_query: Query<Entity, With<BracketMarker>>,
+) {
+    let respawned = spawn_tiles_event_reader.iter().last().is_some();
+    if !(brackets.is_changed() || respawned) {
+        return;
+    }
+    for entity in marker_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let color = options.get_color("bracket_color", BRACKET_COLOR);
+    let (thin, long) = (BRACKET_WIDTH * board.pixels_per_tile, board.pixels_per_tile);
+    for (line, at) in brackets.0.iter() {
+        // center of the edge, in tiles
+        let (center, size) = match *line {
+            ClueLine::Row(y) => {
+                let (tile_x, tile_y) = board.cell_to_tile(*at, y);
+                (Vec2::new(tile_x, tile_y + 0.5), Vec2::new(thin, long))
+            }
+            ClueLine::Column(x) => {
+                let (tile_x, tile_y) = board.cell_to_tile(x, *at);
+                (Vec2::new(tile_x + 0.5, tile_y), Vec2::new(long, thin))
+            }
+        };
+        commands
+            .spawn(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(size),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(board.tile_translation(
+                    center.x,
+                    center.y,
+                    HIGHLIGHT_Z,
+                )),
+                ..Default::default()
+            })
+            .insert(BracketMarker);
+    }
+}
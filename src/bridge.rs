@@ -0,0 +1,433 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+
+use crate::board::{char_to_cell, fnv1a};
+use crate::limits::MAX_CELLS;
+use crate::win_goal::{parse_goal, Condition};
+use crate::WASMSendChannel;
+
+// endregion
+
+// region:      CONSTANTS
+
+/// Anything longer is dropped before it is split or parsed.
+pub const MAX_MESSAGE_LEN: usize = 2 * 1024 * 1024;
+pub const MAX_CLUES_LEN: usize = 1024 * 1024;
+pub const MAX_CELLS_LEN: usize = MAX_CELLS;
+pub const MAX_OPTIONS_LEN: usize = 4096;
+pub const MAX_ARTWORK: usize = 32;
+const MAX_ARTWORK_CELLS: usize = 50 * 50; // per picture, they are shown small
+const SEPARATOR: &str = "SPLIT";
+const ARTWORK_PREFIX: &str = "art=";
+const WIN_PREFIX: &str = "win=";
+
+// endregion
+
+/// Rejects a message without touching any state, the host gets the reason back.
+pub fn reply_error(send_channel: &WASMSendChannel, err: String) {
+    warn!("Invalid bridge message, {}", err);
+    send_channel.tx.send((String::from("error"), err));
+}
+
+/// The `"j"` payload, `clues SPLIT cells [SPLIT options [SPLIT clues]...]`.
+/// Clues after the options queue up more puzzles to play after this one,
+/// sections starting with `art=` are instead `width;height;cells` records of
+/// pictures the room already solved from its pack. One section starting with
+/// `win=` replaces solving the board with its condition, see `Condition`.
+pub struct JoinPayload {
+    pub clues: String,
+    pub cells: String,
+    pub options: Option<String>,
+    pub playlist: Vec<String>,
+    pub artwork: Vec<String>,
+    pub win: Option<Condition>,
+}
+
+pub fn parse_join(data: &str) -> Result<JoinPayload, String> {
+    check_len("join", data, MAX_MESSAGE_LEN)?;
+    let mut sections = data.split(SEPARATOR);
+    let clues = sections.next().unwrap_or_default();
+    let cells = sections.next().unwrap_or_default();
+    let options = sections.next();
+
+    check_len("clues", clues, MAX_CLUES_LEN)?;
+    check_clues(clues)?;
+    check_len("cells", cells, MAX_CELLS_LEN)?;
+    check_cells(cells)?;
+    if let Some(options) = options {
+        check_len("options", options, MAX_OPTIONS_LEN)?;
+    }
+    let mut playlist = Vec::new();
+    let mut artwork = Vec::new();
+    let mut win = None;
+    for section in sections {
+        if let Some(script) = section.strip_prefix(WIN_PREFIX) {
+            if win.is_some() {
+                return Err(String::from("win: more than one condition"));
+            }
+            win = Some(parse_goal(script)?);
+            continue;
+        }
+        if let Some(record) = section.strip_prefix(ARTWORK_PREFIX) {
+            check_artwork(record)?;
+            if artwork.len() == MAX_ARTWORK {
+                return Err(format!("artwork: more than {} pictures", MAX_ARTWORK));
+            }
+            artwork.push(String::from(record));
+            continue;
+        }
+        check_len("playlist clues", section, MAX_CLUES_LEN)?;
+        check_clues(section)?;
+        playlist.push(String::from(section));
+    }
+
+    Ok(JoinPayload {
+        clues: String::from(clues),
+        cells: String::from(cells),
+        options: options.map(String::from),
+        playlist,
+        artwork,
+        win,
+    })
+}
+
+/// A `width;height;cells` picture, small and exactly `width * height` cells.
+fn check_artwork(record: &str) -> Result<(), String> {
+    let mut fields = record.splitn(3, ';');
+    let mut size = || fields.next().and_then(|field| field.parse::<usize>().ok());
+    let (width, height) = match (size(), size()) {
+        (Some(width), Some(height)) => (width, height),
+        _ => return Err(String::from("artwork: expected width;height;cells")),
+    };
+    let cells = fields.next().unwrap_or_default();
+    let count = match width.checked_mul(height) {
+        Some(count) if count > 0 && count <= MAX_ARTWORK_CELLS => count,
+        _ => return Err(format!("artwork: picture is {}x{}", width, height)),
+    };
+    if cells.len() != count {
+        return Err(format!(
+            "artwork: {} cells, expected {}",
+            cells.len(),
+            count
+        ));
+    }
+    check_cells(cells)
+}
+
+/// Validates a `"u"` payload, `cells [SPLIT checksum]`, the size against the
+/// board is checked when it is applied. A checksum that doesn't match the
+/// cells means the message was mangled on the way.
+pub fn parse_update(data: &str) -> Result<String, String> {
+    let mut sections = data.splitn(2, SEPARATOR);
+    let cells = sections.next().unwrap_or_default();
+    check_len("update", cells, MAX_CELLS_LEN)?;
+    check_cells(cells)?;
+    if let Some(checksum) = sections.next() {
+        let expected = cells_checksum(cells);
+        if checksum != expected {
+            return Err(format!(
+                "update: checksum {} does not match cells, expected {}",
+                checksum, expected
+            ));
+        }
+    }
+    Ok(String::from(cells))
+}
+
+/// Checksum of a cell string as sent in `"u"`, also the heartbeat payload.
+pub fn cells_checksum(cells: &str) -> String {
+    format!("{:016x}", fnv1a(cells))
+}
+
+/// Byte length cap, checked before anything walks the string.
+pub fn check_len(what: &str, s: &str, max: usize) -> Result<(), String> {
+    if s.len() > max {
+        return Err(format!("{}: {} bytes, limit is {}", what, s.len(), max));
+    }
+    Ok(())
+}
+
+pub fn check_cells(cells: &str) -> Result<(), String> {
+    match cells
+        .char_indices()
+        .find(|(_, c)| char_to_cell(*c).is_none())
+    {
+        Some((i, c)) => Err(format!("cells: invalid cell {:?} at {}", c, i)),
+        None => Ok(()),
+    }
+}
+
+/// The page URL's query string, `?key=value&key=value`, as decoded option
+/// pairs. A key without a value is a flag and turns the option on, `+` is a
+/// space and `%xx` an escaped byte, malformed escapes are kept as they are.
+pub fn parse_query(query: &str) -> Result<Vec<(String, String)>, String> {
+    check_len("query", query, MAX_OPTIONS_LEN)?;
+    let pairs = query
+        .trim_start_matches('?')
+        .split('&')
+        .filter_map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, "1"));
+            let key = percent_decode(key);
+            let key = key.trim();
+            (!key.is_empty()).then(|| (key.to_string(), percent_decode(value).trim().to_string()))
+        })
+        .collect();
+    Ok(pairs)
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = match bytes[i] {
+            b'%' => s
+                .get(i + 1..i + 3)
+                .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match (bytes[i], escaped) {
+            (_, Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+            (b'+', None) => decoded.push(b' '),
+            (byte, None) => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Clues are plain ASCII text, anything else can't be a clue string.
+pub fn check_clues(clues: &str) -> Result<(), String> {
+    let invalid = |c: &char| !(c.is_ascii_graphic() || *c == ' ' || *c == '\n' || *c == '\r');
+    match clues.char_indices().find(|(_, c)| invalid(c)) {
+        Some((i, c)) => Err(format!("clues: invalid character {:?} at {}", c, i)),
+        None => Ok(()),
+    }
+}
+
+// region:      TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::harness::{clue_string, Harness};
+    use crate::win_goal::{Comparison, Metric};
+
+    const FUZZ_ROUNDS: usize = 2000;
+    const FUZZ_MESSAGES: usize = 200;
+    // pieces that mean something to one of the parsers, plus some that never should
+    const PIECES: [&str; 25] = [
+        "0",
+        "1",
+        "X",
+        "SPLIT",
+        "break",
+        "\n",
+        ",",
+        " ",
+        "[",
+        "]",
+        "é",
+        "\u{0}",
+        "-1",
+        "99999999999",
+        "=",
+        ";",
+        // malformed clues
+        "1,,2",
+        ",0",
+        "0,3",
+        "break\nbreak",
+        "\n\n",
+        // artwork headers, sizes good and bad
+        "art=",
+        "art=2;1;",
+        "art=0;5;",
+        "art=18446744073709551615;2;",
+    ];
+
+    /// xorshift, so every run fuzzes the same inputs
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0 as usize
+        }
+
+        fn message(&mut self) -> String {
+            let len = self.next() % 64;
+            (0..len)
+                .map(|_| PIECES[self.next() % PIECES.len()])
+                .collect()
+        }
+    }
+
+    #[test]
+    fn parsers_never_panic() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..FUZZ_ROUNDS {
+            let message = rng.message();
+            let _ = parse_join(message.as_str());
+            let _ = parse_update(message.as_str());
+            let _ = check_clues(message.as_str());
+            let _ = parse_query(message.as_str());
+        }
+    }
+
+    #[test]
+    fn join_splits_sections() {
+        let join = parse_join("1\nbreak\n1SPLIT1SPLITghost=1").unwrap();
+        assert_eq!(join.clues, "1\nbreak\n1");
+        assert_eq!(join.cells, "1");
+        assert_eq!(join.options.as_deref(), Some("ghost=1"));
+
+        let join = parse_join("1\nbreak\n1").unwrap();
+        assert_eq!(join.cells, "");
+        assert!(join.options.is_none());
+    }
+
+    #[test]
+    fn join_separates_artwork_from_playlist() {
+        let join = parse_join("1SPLIT1SPLITSPLIT2SPLITart=2;1;10SPLIT3").unwrap();
+        assert_eq!(join.playlist, vec!["2", "3"]);
+        assert_eq!(join.artwork, vec!["2;1;10"]);
+
+        assert!(parse_join("1SPLIT1SPLITSPLITart=2;1;1").is_err());
+        assert!(parse_join("1SPLIT1SPLITSPLITart=2;1;1?").is_err());
+        assert!(parse_join("1SPLIT1SPLITSPLITart=0;0;").is_err());
+        assert!(parse_join("1SPLIT1SPLITSPLITart=51;50;").is_err());
+        // wraps to 0 cells if multiplied unchecked
+        let wrapping = format!("1SPLIT1SPLITSPLITart={};2;", usize::MAX / 2 + 1);
+        assert!(parse_join(wrapping.as_str()).is_err());
+        let many = "SPLITart=1;1;1".repeat(MAX_ARTWORK + 1);
+        assert!(parse_join(format!("1SPLIT1SPLIT{}", many).as_str()).is_err());
+    }
+
+    #[test]
+    fn join_parses_the_win_condition() {
+        let join =
+            parse_join("1SPLIT1SPLITSPLITwin=region(0, 0, 1, 2) | correct>=3 & !solved").unwrap();
+        assert!(join.playlist.is_empty());
+        assert_eq!(
+            join.win,
+            Some(Condition::Any(vec![
+                Condition::Region(0, 0, 1, 2),
+                Condition::All(vec![
+                    Condition::Compare(Metric::Correct, Comparison::AtLeast, 3),
+                    Condition::Not(Box::new(Condition::Solved)),
+                ]),
+            ]))
+        );
+
+        assert!(parse_join("1SPLIT1SPLITSPLITwin=(solved").is_err());
+        assert!(parse_join("1SPLIT1SPLITSPLITwin=time<").is_err());
+        assert!(parse_join("1SPLIT1SPLITSPLITwin=region(2,0,1,0)").is_err());
+        assert!(parse_join("1SPLIT1SPLITSPLITwin=solvedSPLITwin=solved").is_err());
+        let deep = format!("{}solved", "!".repeat(100));
+        assert!(parse_join(format!("1SPLIT1SPLITSPLITwin={}", deep).as_str()).is_err());
+    }
+
+    #[test]
+    fn query_pairs_are_decoded() {
+        let pairs =
+            parse_query("?theme=dark&puzzle_id=a%2Fb+c&spectate&=x&locale=fr-FR%zz").unwrap();
+        let pairs: Vec<(&str, &str)> = pairs
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("theme", "dark"),
+                ("puzzle_id", "a/b c"),
+                ("spectate", "1"),
+                ("locale", "fr-FR%zz"),
+            ]
+        );
+        assert!(parse_query("").unwrap().is_empty());
+        assert!(parse_query(&"a".repeat(MAX_OPTIONS_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn oversized_payloads_are_rejected() {
+        assert!(parse_update("1".repeat(MAX_CELLS_LEN + 1).as_str()).is_err());
+        let cells = "0".repeat(MAX_CELLS_LEN + 1);
+        assert!(parse_join(format!("1SPLIT{}", cells).as_str()).is_err());
+        let options = "a".repeat(MAX_OPTIONS_LEN + 1);
+        assert!(parse_join(format!("1SPLIT1SPLIT{}", options).as_str()).is_err());
+    }
+
+    #[test]
+    fn invalid_characters_are_rejected() {
+        assert!(parse_update("01X").is_ok());
+        assert!(parse_update("01x").is_err());
+        assert!(parse_update("0é").is_err());
+        assert!(parse_join("1\u{0}SPLIT0").is_err());
+        assert!(parse_join("1SPLIT02").is_err());
+    }
+
+    #[test]
+    fn update_checksums_are_verified() {
+        let checksum = cells_checksum("01X0");
+        assert_eq!(
+            parse_update(format!("01X0SPLIT{}", checksum).as_str()),
+            Ok(String::from("01X0"))
+        );
+        assert!(parse_update(format!("01XXSPLIT{}", checksum).as_str()).is_err());
+        assert!(parse_update("01X0SPLIT").is_err());
+    }
+
+    #[test]
+    fn bad_messages_reply_with_errors() {
+        let mut harness = Harness::new();
+        let clues = clue_string(&[&[1], &[1]], &[&[1], &[1]]);
+        assert!(harness.join(clues.as_str(), "0000"));
+        harness.replies();
+
+        harness.send("u", "01?0");
+        harness.tick();
+        harness.send("u", "010");
+        harness.tick();
+        harness.send("u", "1".repeat(MAX_CELLS_LEN + 1).as_str());
+        harness.tick();
+
+        let errors = harness
+            .replies()
+            .iter()
+            .filter(|(command, _)| command == "error")
+            .count();
+        assert_eq!(errors, 3);
+        assert_eq!(harness.cells(), "0000");
+    }
+
+    #[test]
+    fn random_messages_never_panic() {
+        let mut harness = Harness::new();
+        let clues = clue_string(&[&[1], &[1]], &[&[1], &[1]]);
+        assert!(harness.join(clues.as_str(), "0000"));
+
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        for _ in 0..FUZZ_MESSAGES {
+            let command = ["u", "d", "o", "j"][rng.next() % 4];
+            // joins that get past validation spawn a parse, keep those well formed
+            let message = match command {
+                "j" => format!("{}SPLIT{}", clues, rng.message()),
+                _ => rng.message(),
+            };
+            harness.send(command, message.as_str());
+            harness.tick();
+        }
+        harness.ticks(10);
+    }
+}
+
+// endregion
@@ -0,0 +1,253 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+
+use crate::board::{Board, SpawnTilesEvent};
+use crate::settings::Settings;
+use crate::WinSize;
+
+// endregion
+
+// region:      CONSTANTS
+
+const QUADRANT_MARGIN: f32 = 0.5; // tiles around a zoomed quadrant
+const ROTATION_FIT_SECONDS: f32 = 0.4;
+/// Cells per block when paging, the blocks of a paper puzzle.
+const PAGE_BLOCK: f32 = 5.;
+
+// endregion
+
+// region:      COMPONENTS
+
+/// The camera drawing the board, as opposed to the magnifier.
+#[derive(Component)]
+pub struct MainCamera;
+
+// endregion
+
+// region:      RESOURCES
+
+/// Where the main camera looks, in world units. The camera origin is the
+/// bottom left of the window, so with the default view screen and world
+/// positions are the same.
+#[derive(Resource, Clone, Copy)]
+pub struct BoardView {
+    pub scale: f32,
+    pub offset: Vec2,
+}
+
+impl Default for BoardView {
+    fn default() -> Self {
+        Self {
+            scale: 1.,
+            offset: Vec2::ZERO,
+        }
+    }
+}
+
+impl BoardView {
+    pub fn screen_to_world(&self, screen: Vec2) -> Vec2 {
+        self.offset + screen * self.scale
+    }
+
+    pub fn world_to_screen(&self, world: Vec2) -> Vec2 {
+        (world - self.offset) / self.scale
+    }
+
+    /// View showing all of `min..min + size`, centered in the window.
+    pub fn fit(min: Vec2, size: Vec2, win_size: &WinSize) -> Self {
+        let window = Vec2::new(win_size.w, win_size.h);
+        let scale = (size.x / window.x).max(size.y / window.y);
+        Self {
+            scale,
+            offset: min + size / 2. - window * scale / 2.,
+        }
+    }
+}
+
+/// View easing back to the default after the device rotated, starting from
+/// one that shows the relaid board at the size it had before.
+#[derive(Resource, Default)]
+pub struct RotationFit {
+    from: Option<BoardView>,
+    elapsed: f32,
+    /// Portrait flag and on screen size of the last layout.
+    last: Option<(bool, Vec2)>,
+}
+
+// endregion
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BoardView>()
+            .init_resource::<RotationFit>()
+            .add_system(zoom_hotkey_system)
+            .add_system(page_scroll_system.after(zoom_hotkey_system))
+            .add_system(rotation_fit_system.after(page_scroll_system))
+            .add_system(apply_view_system.after(rotation_fit_system));
+    }
+}
+
+fn zoom_hotkey_system(
+    keys: Res<Input<KeyCode>>,
+    board: Res<Board>,
+    win_size: Res<WinSize>,
+    mut view: ResMut<BoardView>,
+    mut spawn_tiles_event_reader: EventReader<SpawnTilesEvent>,
+) {
+    // a new layout invalidates whatever was zoomed
+    if spawn_tiles_event_reader.iter().last().is_some() {
+        *view = BoardView::default();
+    }
+
+    let pressed = |a, b| keys.just_pressed(a) || keys.just_pressed(b);
+    // 1 2 on top, 3 4 below, like reading the board
+    let quadrant = if pressed(KeyCode::Key1, KeyCode::Numpad1) {
+        Some((0., 1.))
+    } else if pressed(KeyCode::Key2, KeyCode::Numpad2) {
+        Some((1., 1.))
+    } else if pressed(KeyCode::Key3, KeyCode::Numpad3) {
+        Some((0., 0.))
+    } else if pressed(KeyCode::Key4, KeyCode::Numpad4) {
+        Some((1., 0.))
+    } else {
+        None
+    };
+
+    if pressed(KeyCode::Key0, KeyCode::Numpad0) {
+        *view = BoardView::default();
+    } else if let Some((qx, qy)) = quadrant {
+        let half = Vec2::new(
+            board.p.get_width() as f32 / 2.,
+            board.p.get_height() as f32 / 2.,
+        );
+        let min = board.tile_translation(
+            board.grid_left() as f32 + qx * half.x - QUADRANT_MARGIN,
+            qy * half.y - QUADRANT_MARGIN,
+            0.,
+        );
+        let size = (half + 2. * QUADRANT_MARGIN) * board.pixels_per_tile;
+        *view = BoardView::fit(min.truncate(), size, &win_size);
+    }
+}
+
+/// While zoomed in, PageUp and PageDown scroll up and down and Home and End
+/// left and right, by as many whole 5 cell blocks as fit in the view. The
+/// view lands with a block boundary on its leading edge, counted from the
+/// top left of the grid like on paper, so blocks stay easy to count.
+fn page_scroll_system(
+    keys: Res<Input<KeyCode>>,
+    board: Res<Board>,
+    win_size: Res<WinSize>,
+    mut view: ResMut<BoardView>,
+) {
+    let (up, down) = (
+        keys.just_pressed(KeyCode::PageUp),
+        keys.just_pressed(KeyCode::PageDown),
+    );
+    let (left, right) = (
+        keys.just_pressed(KeyCode::Home),
+        keys.just_pressed(KeyCode::End),
+    );
+    if !(up || down || left || right) || view.scale >= 1. {
+        return;
+    }
+
+    let block = PAGE_BLOCK * board.pixels_per_tile;
+    let window = Vec2::new(win_size.w, win_size.h) * view.scale;
+    let (width, height) = (board.p.get_width() as f32, board.p.get_height() as f32);
+    let grid_left = board.tile_translation(board.grid_left() as f32, 0., 0.).x;
+    let grid_top = board.tile_translation(0., height, 0.).y;
+    let grid = Vec2::new(width, height) * board.pixels_per_tile;
+    // blocks the leading edge can sit after the grid's start, the last one
+    // showing the end of the grid
+    let pages = |size: f32, view: f32| ((size - view) / block).ceil().max(0.);
+    // the next boundary past `at` blocks, or the one before it
+    let step = |at: f32, forward: bool, view: f32| {
+        let page = (view / block).floor().max(1.);
+        match forward {
+            true => (at + 0.01).floor() + page,
+            false => (at - 0.01).ceil() - page,
+        }
+    };
+
+    if up || down {
+        let at = (grid_top - (view.offset.y + window.y)) / block;
+        let to = step(at, down, window.y).clamp(0., pages(grid.y, window.y));
+        view.offset.y = grid_top - to * block - window.y;
+    }
+    if left || right {
+        let at = (view.offset.x - grid_left) / block;
+        let to = step(at, right, window.x).clamp(0., pages(grid.x, window.x));
+        view.offset.x = grid_left + to * block;
+    }
+}
+
+/// A resize that flips the window between portrait and landscape relays the
+/// board for the new shape. Rather than jumping, the camera starts out
+/// showing the new layout as big as the old one was, centered, and zooms to
+/// fit it.
+pub fn rotation_fit_system(
+    time: Res<Time>,
+    board: Res<Board>,
+    settings: Res<Settings>,
+    win_size: Res<WinSize>,
+    mut view: ResMut<BoardView>,
+    mut fit: ResMut<RotationFit>,
+    mut spawn_tiles_event_reader: EventReader<SpawnTilesEvent>,
+) {
+    if spawn_tiles_event_reader.iter().last().is_some() {
+        let portrait = win_size.w < win_size.h;
+        let min = Vec2::new(board.origin.0, board.origin.1);
+        let size = Vec2::new(board.w as f32, board.h as f32) * board.pixels_per_tile;
+        let flipped = fit
+            .last
+            .map_or(false, |(was_portrait, _)| was_portrait != portrait);
+        fit.from = None;
+        if let Some((_, old_size)) = fit.last.filter(|_| flipped && !settings.reduced_motion) {
+            if old_size.x > 0. && size.x > 0. {
+                let window = Vec2::new(win_size.w, win_size.h);
+                let scale = size.x / old_size.x;
+                let start = (window - old_size) / 2.;
+                fit.from = Some(BoardView {
+                    scale,
+                    offset: min - start * scale,
+                });
+                fit.elapsed = 0.;
+            }
+        }
+        fit.last = Some((portrait, size));
+    }
+
+    let from = match fit.from {
+        Some(from) => from,
+        None => return,
+    };
+    fit.elapsed += time.delta_seconds();
+    let t = (fit.elapsed / ROTATION_FIT_SECONDS).min(1.);
+    // smoothstep, so the zoom neither starts nor lands abruptly
+    let t = t * t * (3. - 2. * t);
+    let target = BoardView::default();
+    view.scale = from.scale + (target.scale - from.scale) * t;
+    view.offset = from.offset.lerp(target.offset, t);
+    if t >= 1. {
+        fit.from = None;
+    }
+}
+
+fn apply_view_system(
+    view: Res<BoardView>,
+    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), With<MainCamera>>,
+) {
+    if !view.is_changed() {
+        return;
+    }
+
+    for (mut transform, mut projection) in camera_query.iter_mut() {
+        transform.translation.x = view.offset.x;
+        transform.translation.y = view.offset.y;
+        projection.scale = view.scale;
+    }
+}
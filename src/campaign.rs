@@ -0,0 +1,332 @@
+// region:      IMPORTS
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+    },
+};
+
+use crate::board::{cell_to_char, fnv1a, Board};
+use crate::camera::BoardView;
+use crate::layers::{CHILD_LABEL_Z_OFFSET, CHILD_Z_OFFSET, HUD_PANEL_Z, HUD_Z};
+use crate::playlist::Playlist;
+use crate::screens::Screen;
+use crate::stars::MAX_STARS;
+use crate::storage;
+use crate::win::PuzzleSolvedEvent;
+use crate::{BridgeCommandEvent, GameTextures, NewBoardEvent, WinSize};
+
+// endregion
+
+// region:      CONSTANTS
+
+const MAP_PADDING: f32 = 0.15; // fraction of a slot left around each thumbnail
+const MAP_FONT_SIZE: f32 = 0.25; // fraction of a slot
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct MapEntity;
+
+// endregion
+
+// region:      RESOURCES
+
+#[derive(Resource, Default)]
+pub struct MapSelection(pub usize);
+
+// endregion
+
+pub struct CampaignPlugin;
+
+impl Plugin for CampaignPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MapSelection>()
+            .add_system(record_thumbnail_system)
+            .add_system(open_map_system)
+            .add_system_set(SystemSet::on_enter(Screen::Map).with_system(map_enter_system))
+            .add_system_set(
+                SystemSet::on_update(Screen::Map)
+                    .with_system(map_input_system)
+                    .with_system(map_draw_system.after(map_input_system)),
+            )
+            .add_system_set(SystemSet::on_exit(Screen::Map).with_system(map_exit_system));
+    }
+}
+
+/// Storage key for a pack puzzle, the clue hash is also the default puzzle id.
+pub fn puzzle_key(clues: &str) -> String {
+    format!("{:016x}", fnv1a(clues))
+}
+
+/// Keeps the solved picture of every pack puzzle as `width;height;cells`.
+fn record_thumbnail_system(
+    board: Res<Board>,
+    playlist: Res<Playlist>,
+    mut puzzle_solved_event_reader: EventReader<PuzzleSolvedEvent>,
+) {
+    for _ in puzzle_solved_event_reader.iter() {
+        let clues = match playlist.puzzles.get(playlist.current) {
+            Some(clues) if playlist.len() > 1 => clues,
+            _ => continue,
+        };
+        let p = &board.p;
+        let cells: String = (0..p.get_height())
+            .flat_map(|y| (0..p.get_width()).map(move |x| cell_to_char(p.get_cell(x, y))))
+            .collect();
+        storage::save(
+            &format!("thumb.{}", puzzle_key(clues)),
+            &format!("{};{};{}", p.get_width(), p.get_height(), cells),
+        );
+    }
+}
+
+/// Tab, or the host's `("map", "")`, switches between the board and the map.
+fn open_map_system(
+    keys: Res<Input<KeyCode>>,
+    playlist: Res<Playlist>,
+    mut screen: ResMut<State<Screen>>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+) {
+    let requested = bridge_command_event_reader
+        .iter()
+        .any(|event| event.command == "map");
+    if !(requested || keys.just_pressed(KeyCode::Tab)) || playlist.len() <= 1 {
+        return;
+    }
+
+    let next = match screen.current() {
+        Screen::Board => Screen::Map,
+        Screen::Map => Screen::Board,
+        // previews belong to the lobby and the slots panel to the puzzle
+        Screen::Preview | Screen::Slots | Screen::FlipBook | Screen::Win => return,
+    };
+    screen.set(next).ok();
+}
+
+fn map_enter_system(playlist: Res<Playlist>, mut selection: ResMut<MapSelection>) {
+    selection.0 = playlist.current;
+}
+
+fn map_exit_system(mut commands: Commands, map_query: Query<Entity, With<MapEntity>>) {
+    for entity in map_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Slots laid out in rows, returns the center and side of slot `i` in world units.
+pub fn slot(i: usize, count: usize, win_size: &WinSize, view: &BoardView) -> (Vec2, f32) {
+    let columns = (count as f32).sqrt().ceil().max(1.) as usize;
+    let rows = (count + columns - 1) / columns;
+    let side = (win_size.w / columns as f32).min(win_size.h / rows as f32);
+    let grid = Vec2::new(columns as f32, rows as f32) * side;
+    let origin = (Vec2::new(win_size.w, win_size.h) - grid) / 2.;
+    // first row at the top
+    let screen = origin
+        + Vec2::new(
+            (i % columns) as f32 + 0.5,
+            (rows - 1 - i / columns) as f32 + 0.5,
+        ) * side;
+    (view.screen_to_world(screen), side * view.scale)
+}
+
+fn map_input_system(
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    view: Res<BoardView>,
+    win_size: Res<WinSize>,
+    mut playlist: ResMut<Playlist>,
+    mut selection: ResMut<MapSelection>,
+    mut screen: ResMut<State<Screen>>,
+    mut new_board_event_writer: EventWriter<NewBoardEvent>,
+) {
+    let count = playlist.len();
+    let columns = (count as f32).sqrt().ceil().max(1.) as usize;
+    let mut index = selection.0 as isize;
+    if keys.just_pressed(KeyCode::Left) {
+        index -= 1;
+    }
+    if keys.just_pressed(KeyCode::Right) {
+        index += 1;
+    }
+    if keys.just_pressed(KeyCode::Up) {
+        index -= columns as isize;
+    }
+    if keys.just_pressed(KeyCode::Down) {
+        index += columns as isize;
+    }
+    let index = index.clamp(0, count as isize - 1) as usize;
+    if index != selection.0 {
+        selection.0 = index;
+    }
+
+    let mut chosen = keys.just_pressed(KeyCode::Return).then(|| selection.0);
+    let cursor = windows
+        .get_primary()
+        .and_then(|window| window.cursor_position());
+    if let (true, Some(cursor)) = (buttons.just_pressed(MouseButton::Left), cursor) {
+        let cursor = view.screen_to_world(cursor);
+        chosen = (0..count).find(|i| {
+            let (center, side) = slot(*i, count, &win_size, &view);
+            (cursor - center).abs().max_element() <= side / 2.
+        });
+    }
+
+    if let Some(event) = chosen.and_then(|i| playlist.jump(i)) {
+        new_board_event_writer.send(event);
+        screen.set(Screen::Board).ok();
+    }
+}
+
+fn map_draw_system(
+    mut commands: Commands,
+    view: Res<BoardView>,
+    win_size: Res<WinSize>,
+    playlist: Res<Playlist>,
+    selection: Res<MapSelection>,
+    game_textures: Res<GameTextures>,
+    mut images: ResMut<Assets<Image>>,
+    map_query: Query<Entity, With<MapEntity>>,
+) {
+    // rebuilt whole, the map only changes when the selection moves
+    if !(selection.is_changed() || map_query.is_empty()) {
+        return;
+    }
+    for entity in map_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    // backdrop hiding the board underneath
+    let center = view.screen_to_world(Vec2::new(win_size.w, win_size.h) / 2.);
+    commands
+        .spawn(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgb(0.12, 0.12, 0.14),
+                custom_size: Some(Vec2::new(win_size.w, win_size.h) * view.scale),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(center.x, center.y, HUD_Z),
+            ..Default::default()
+        })
+        .insert(MapEntity);
+
+    let count = playlist.len();
+    for (i, clues) in playlist.puzzles.iter().enumerate() {
+        let (center, side) = slot(i, count, &win_size, &view);
+        let inner = side * (1. - MAP_PADDING);
+        let key = puzzle_key(clues);
+        let thumbnail =
+            storage::load(&format!("thumb.{}", key)).and_then(|saved| thumbnail_image(&saved));
+        let stars = storage::load(&format!("stars.{}", key))
+            .and_then(|stars| stars.parse::<u32>().ok())
+            .unwrap_or(0)
+            .min(MAX_STARS);
+
+        let border = if i == selection.0 {
+            Color::YELLOW
+        } else if thumbnail.is_some() {
+            Color::GREEN
+        } else {
+            Color::GRAY
+        };
+        let mut slot_entity = commands.spawn(SpriteBundle {
+            sprite: Sprite {
+                color: border,
+                custom_size: Some(Vec2::splat(inner)),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(center.x, center.y, HUD_PANEL_Z),
+            ..Default::default()
+        });
+        slot_entity.insert(MapEntity).with_children(|parent| {
+            let picture = inner * 0.9;
+            match thumbnail {
+                Some(image) => {
+                    parent.spawn(SpriteBundle {
+                        texture: images.add(image),
+                        sprite: Sprite {
+                            custom_size: Some(Vec2::splat(picture)),
+                            ..Default::default()
+                        },
+                        transform: Transform::from_xyz(0., 0., CHILD_Z_OFFSET),
+                        ..Default::default()
+                    });
+                }
+                None => {
+                    parent.spawn(SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::WHITE,
+                            custom_size: Some(Vec2::splat(picture)),
+                            ..Default::default()
+                        },
+                        transform: Transform::from_xyz(0., 0., CHILD_Z_OFFSET),
+                        ..Default::default()
+                    });
+                }
+            }
+
+            // puzzle number, then earned stars as asterisks so any font can draw them
+            let label = format!(
+                "{} {}{}",
+                i + 1,
+                "*".repeat(stars as usize),
+                "-".repeat((MAX_STARS - stars) as usize)
+            );
+            parent.spawn(Text2dBundle {
+                text: Text::from_section(
+                    label,
+                    TextStyle {
+                        font: game_textures.font.clone(),
+                        font_size: side * MAP_FONT_SIZE,
+                        color: Color::BLACK,
+                    },
+                )
+                .with_alignment(TextAlignment::CENTER),
+                transform: Transform::from_xyz(0., -picture * 0.35, CHILD_LABEL_Z_OFFSET),
+                ..Default::default()
+            });
+        });
+    }
+}
+
+/// Black and white picture from a saved `width;height;cells` record.
+pub fn thumbnail_image(saved: &str) -> Option<Image> {
+    let mut fields = saved.split(';');
+    let width: usize = fields.next()?.parse().ok()?;
+    let height: usize = fields.next()?.parse().ok()?;
+    let cells = fields.next()?.as_bytes();
+    if width == 0 || height == 0 || cells.len() != width * height {
+        return None;
+    }
+
+    let mut data = Vec::with_capacity(width * height * 4);
+    // texel rows run top down, board rows bottom up
+    for y in (0..height).rev() {
+        for x in 0..width {
+            let value = if cells[y * width + x] == b'1' {
+                30
+            } else {
+                245
+            };
+            data.extend_from_slice(&[value, value, value, 255]);
+        }
+    }
+
+    let mut image = Image::new(
+        Extent3d {
+            width: width as u32,
+            height: height as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    image.sampler_descriptor = ImageSampler::nearest();
+    Some(image)
+}
@@ -0,0 +1,116 @@
+// region:      IMPORTS
+
+use bevy::{prelude::*, transform::TransformSystem};
+use picross_handler::Cell;
+
+use crate::board::{Board, BoardLoadedEvent, Tile};
+use crate::layers::TILE_Z;
+use crate::options::BoardOptions;
+use crate::settings::Settings;
+use crate::win::PuzzleSolvedEvent;
+
+// endregion
+
+// region:      CONSTANTS
+
+const DEFAULT_CELEBRATION_SECONDS: f32 = 1.2;
+/// Share of the celebration each tile spends pulsing, the rest is the stagger.
+const TILE_SHARE: f32 = 0.4;
+/// Most a tile grows at the top of its pulse.
+const PULSE_GROWTH: f32 = 0.3;
+
+// endregion
+
+// region:      RESOURCES
+
+/// Wave of the filled tiles pulsing by diagonal from the top left when the
+/// puzzle is solved, before the win screen covers the board. Lasts
+/// `celebration_seconds`, off when that is 0 or with reduced motion.
+#[derive(Resource, Default)]
+pub struct Celebration {
+    started: Option<f64>,
+    duration: f32,
+}
+
+impl Celebration {
+    pub fn playing(&self) -> bool {
+        self.started.is_some()
+    }
+}
+
+// endregion
+
+pub struct CelebrationPlugin;
+
+impl Plugin for CelebrationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Celebration>()
+            .add_system(celebration_start_system)
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                celebration_system.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+pub fn celebration_start_system(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    options: Res<BoardOptions>,
+    mut celebration: ResMut<Celebration>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut puzzle_solved_event_reader: EventReader<PuzzleSolvedEvent>,
+) {
+    // the tiles it was pulsing are gone with the old board
+    if board_loaded_event_reader.iter().last().is_some() {
+        celebration.started = None;
+    }
+    if puzzle_solved_event_reader.iter().last().is_none() {
+        return;
+    }
+    let duration = options.get_f32("celebration_seconds", DEFAULT_CELEBRATION_SECONDS);
+    if settings.reduced_motion || duration <= 0. {
+        return;
+    }
+    celebration.started = Some(time.elapsed_seconds_f64());
+    celebration.duration = duration;
+}
+
+fn celebration_system(
+    time: Res<Time>,
+    board: Res<Board>,
+    mut celebration: ResMut<Celebration>,
+    mut tile_query: Query<(&Tile, &mut Transform)>,
+) {
+    let started = match celebration.started {
+        Some(started) => started,
+        None => return,
+    };
+    let elapsed = (time.elapsed_seconds_f64() - started) as f32 / celebration.duration;
+    let done = elapsed >= 1.;
+    let diagonals = (board.w + board.h).saturating_sub(2).max(1) as f32;
+
+    for (tile, mut transform) in tile_query.iter_mut() {
+        let filled = board
+            .tile_to_cell(tile.x, tile.y)
+            .map_or(false, |(x, y)| board.p.get_cell(x, y) == Cell::Filled);
+        if !filled {
+            continue;
+        }
+        let diagonal = (tile.x + (board.h as f32 - 1. - tile.y)) / diagonals;
+        let t = ((elapsed - diagonal * (1. - TILE_SHARE)) / TILE_SHARE).clamp(0., 1.);
+        // up and back down, grown from the middle of the tile
+        let scale = match done {
+            true => 1.,
+            false => 1. + PULSE_GROWTH * (t * std::f32::consts::PI).sin(),
+        };
+        let inset = (1. - scale) * board.pixels_per_tile / 2.;
+        transform.translation =
+            board.tile_translation(tile.x, tile.y, TILE_Z) + Vec3::new(inset, inset, 0.);
+        transform.scale = Vec3::new(board.tile_scale * scale, board.tile_scale * scale, 1.);
+    }
+
+    if done {
+        celebration.started = None;
+    }
+}
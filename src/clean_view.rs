@@ -0,0 +1,97 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+
+use crate::board::{Board, InputEvent, Tile};
+use crate::layers::{GHOST_Z, LENS_MASK_Z};
+use crate::screens::Screen;
+use crate::BridgeCommandEvent;
+
+// endregion
+
+// region:      CONSTANTS
+
+const CLEAN_VIEW_KEY: KeyCode = KeyCode::V;
+
+// endregion
+
+// region:      RESOURCES
+
+/// Just the cells, for screenshots and showing off the picture so far: clue
+/// tiles and everything drawn over the tiles (clues, marks, cursors, the
+/// HUD) are hidden until V is pressed again or the player tries to edit.
+#[derive(Resource, Default)]
+pub struct CleanView {
+    pub active: bool,
+    /// Entities this hid, the only ones it shows again.
+    hidden: Vec<Entity>,
+}
+
+// endregion
+
+pub struct CleanViewPlugin;
+
+impl Plugin for CleanViewPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CleanView>()
+            .add_system(clean_view_toggle_system)
+            // after everything else has spawned or shown what it draws
+            .add_system_to_stage(CoreStage::PostUpdate, clean_view_system);
+    }
+}
+
+/// V or `("clean", "")` toggles the clean view, any edit ends it.
+fn clean_view_toggle_system(
+    keys: Res<Input<KeyCode>>,
+    screen: Res<State<Screen>>,
+    mut clean_view: ResMut<CleanView>,
+    mut input_event_reader: EventReader<InputEvent>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+) {
+    let requested = bridge_command_event_reader
+        .iter()
+        .filter(|event| event.command == "clean")
+        .count()
+        % 2
+        == 1;
+    let pressed = keys.just_pressed(CLEAN_VIEW_KEY) && *screen.current() == Screen::Board;
+    if requested != pressed {
+        clean_view.active = !clean_view.active;
+    }
+    let editing = input_event_reader.iter().any(|event| event.from_player);
+    if editing && clean_view.active {
+        clean_view.active = false;
+    }
+}
+
+fn clean_view_system(
+    board: Res<Board>,
+    mut clean_view: ResMut<CleanView>,
+    mut drawn_query: Query<
+        (Entity, &GlobalTransform, &mut Visibility, Option<&Tile>),
+        Or<(With<Sprite>, With<Text>)>,
+    >,
+) {
+    if !clean_view.active {
+        if clean_view.hidden.is_empty() {
+            return;
+        }
+        for entity in std::mem::take(&mut clean_view.hidden) {
+            if let Ok((_, _, mut visibility, _)) = drawn_query.get_mut(entity) {
+                visibility.is_visible = true;
+            }
+        }
+        return;
+    }
+
+    // checked every frame, overlays keep spawning while the view is clean
+    for (entity, transform, mut visibility, tile) in drawn_query.iter_mut() {
+        let z = transform.translation().z;
+        let clue_tile = tile.map_or(false, |tile| board.tile_to_cell(tile.x, tile.y).is_none());
+        let over_tiles = (GHOST_Z..LENS_MASK_Z).contains(&z);
+        if visibility.is_visible && (over_tiles || clue_tile) {
+            visibility.is_visible = false;
+            clean_view.hidden.push(entity);
+        }
+    }
+}
@@ -0,0 +1,177 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+
+use crate::board::Clue;
+use crate::layers::EFFECT_Z_OFFSET;
+use crate::options::BoardOptions;
+
+// endregion
+
+// region:      CONSTANTS
+
+const OUTLINE_DIRECTIONS: [(f32, f32); 8] = [
+    (-1., -1.),
+    (0., -1.),
+    (1., -1.),
+    (-1., 0.),
+    (1., 0.),
+    (-1., 1.),
+    (0., 1.),
+    (1., 1.),
+];
+
+// endregion
+
+// region:      COMPONENTS
+
+/// Marks a clue that already has its effect layers spawned.
+#[derive(Component)]
+pub struct ClueEffectLayers;
+
+#[derive(Component)]
+pub struct ClueEffectLayer;
+
+// endregion
+
+// region:      RESOURCES
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ClueEffect {
+    None,
+    Shadow,
+    Outline,
+}
+
+/// Read from the `clue_effect`, `clue_effect_color` and `clue_effect_width`
+/// options, width being a fraction of the clue font size.
+#[derive(Resource)]
+pub struct ClueEffectStyle {
+    pub effect: ClueEffect,
+    pub color: Color,
+    pub width: f32,
+}
+
+impl Default for ClueEffectStyle {
+    fn default() -> Self {
+        Self {
+            effect: ClueEffect::None,
+            color: Color::rgba(1., 1., 1., 0.8),
+            width: 0.06,
+        }
+    }
+}
+
+// endregion
+
+pub struct ClueEffectPlugin;
+
+impl Plugin for ClueEffectPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ClueEffectStyle>()
+            .add_system(clue_effect_options_system)
+            .add_system(spawn_clue_effects_system)
+            .add_system(clue_effect_text_system);
+    }
+}
+
+fn clue_effect_options_system(
+    mut commands: Commands,
+    options: Res<BoardOptions>,
+    mut style: ResMut<ClueEffectStyle>,
+    layers_query: Query<Entity, With<ClueEffectLayers>>,
+    layer_query: Query<Entity, With<ClueEffectLayer>>,
+) {
+    if !options.is_changed() {
+        return;
+    }
+
+    let defaults = ClueEffectStyle::default();
+    style.effect = match options.get("clue_effect") {
+        Some("shadow") => ClueEffect::Shadow,
+        Some("outline") => ClueEffect::Outline,
+        _ => ClueEffect::None,
+    };
+    style.color = options.get_color("clue_effect_color", defaults.color);
+    style.width = options.get_f32("clue_effect_width", defaults.width);
+
+    // respawn every layer with the new style
+    for entity in layer_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for entity in layers_query.iter() {
+        commands.entity(entity).remove::<ClueEffectLayers>();
+    }
+}
+
+fn spawn_clue_effects_system(
+    mut commands: Commands,
+    style: Res<ClueEffectStyle>,
+    clue_query: Query<(Entity, &Text), (With<Clue>, Without<ClueEffectLayers>)>,
+) {
+    let offsets: &[(f32, f32)] = match style.effect {
+        ClueEffect::None => return,
+        ClueEffect::Shadow => &[(1., -1.)],
+        ClueEffect::Outline => &OUTLINE_DIRECTIONS,
+    };
+
+    for (entity, text) in clue_query.iter() {
+        let section = &text.sections[0];
+        let distance = section.style.font_size * style.width;
+
+        commands
+            .entity(entity)
+            .insert(ClueEffectLayers)
+            .with_children(|parent| {
+                for (dx, dy) in offsets {
+                    let mut color = style.color;
+                    color.set_a(style.color.a() * section.style.color.a());
+                    parent
+                        .spawn(Text2dBundle {
+                            text: Text::from_section(
+                                section.value.clone(),
+                                TextStyle {
+                                    color,
+                                    ..section.style.clone()
+                                },
+                            )
+                            .with_alignment(text.alignment),
+                            transform: Transform::from_xyz(
+                                dx * distance,
+                                dy * distance,
+                                EFFECT_Z_OFFSET,
+                            ),
+                            ..Default::default()
+                        })
+                        .insert(ClueEffectLayer);
+                }
+            });
+    }
+}
+
+fn clue_effect_text_system(
+    style: Res<ClueEffectStyle>,
+    clue_query: Query<
+        (&Text, &Children),
+        (
+            With<ClueEffectLayers>,
+            Without<ClueEffectLayer>,
+            Changed<Text>,
+        ),
+    >,
+    mut layer_query: Query<&mut Text, With<ClueEffectLayer>>,
+) {
+    // keep the layers showing the same value, and fading along with the clue
+    for (text, children) in clue_query.iter() {
+        let section = &text.sections[0];
+        for child in children.iter() {
+            if let Ok(mut layer_text) = layer_query.get_mut(*child) {
+                layer_text.sections[0].value = section.value.clone();
+                layer_text.sections[0]
+                    .style
+                    .color
+                    .set_a(style.color.a() * section.style.color.a());
+            }
+        }
+    }
+}
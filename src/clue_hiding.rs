@@ -0,0 +1,175 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+
+use crate::board::{BoardLoadedEvent, Clue, ClueIndex};
+use crate::camera::BoardView;
+use crate::hud_scale::HudScale;
+use crate::layers::HUD_Z;
+use crate::options::BoardOptions;
+use crate::rules::LineState;
+use crate::validity::LineStates;
+use crate::{GameTextures, WinSize};
+
+// endregion
+
+// region:      CONSTANTS
+
+const DEFAULT_MEMORIZE_SECONDS: f64 = 10.;
+const MEMORIZE_FONT_SIZE: f32 = 28.;
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct MemorizeText;
+
+// endregion
+
+// region:      RESOURCES
+
+/// Hard modes from the `hide_clues` option: `satisfied` hides the clues of
+/// lines that are already satisfied, `memorize` hides every clue once
+/// `memorize_seconds` have passed since the puzzle loaded.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClueHiding {
+    #[default]
+    Off,
+    Satisfied,
+    Memorize,
+}
+
+impl ClueHiding {
+    fn from_options(options: &BoardOptions) -> Self {
+        match options.get("hide_clues") {
+            Some("satisfied") => ClueHiding::Satisfied,
+            Some("memorize") => ClueHiding::Memorize,
+            _ => ClueHiding::Off,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Memorize {
+    loaded: f64,
+    hidden: bool,
+}
+
+// endregion
+
+pub struct ClueHidingPlugin;
+
+impl Plugin for ClueHidingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Memorize>()
+            .add_system(memorize_system)
+            .add_system(clue_hiding_system.after(memorize_system));
+    }
+}
+
+/// Counts down the memorization time, then hides the clues for good.
+fn memorize_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    view: Res<BoardView>,
+    hud_scale: Res<HudScale>,
+    win_size: Res<WinSize>,
+    options: Res<BoardOptions>,
+    game_textures: Res<GameTextures>,
+    mut memorize: ResMut<Memorize>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut text_query: Query<(Entity, &mut Text, &mut Transform), With<MemorizeText>>,
+) {
+    let now = time.elapsed_seconds_f64();
+    if board_loaded_event_reader.iter().last().is_some() {
+        memorize.loaded = now;
+        memorize.hidden = false;
+    }
+
+    let remaining = options
+        .get("memorize_seconds")
+        .and_then(|seconds| seconds.parse::<f64>().ok())
+        .unwrap_or(DEFAULT_MEMORIZE_SECONDS)
+        - (now - memorize.loaded);
+    let counting = ClueHiding::from_options(&options) == ClueHiding::Memorize
+        && !memorize.hidden
+        && remaining > 0.;
+    if !counting {
+        for (entity, _, _) in text_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        let hidden = remaining <= 0.;
+        if memorize.hidden != hidden {
+            memorize.hidden = hidden;
+        }
+        return;
+    }
+
+    let message = format!("Memorize: {}", remaining.ceil());
+    let position = view.screen_to_world(Vec2::new(
+        win_size.w / 2.,
+        win_size.h - MEMORIZE_FONT_SIZE * hud_scale.0,
+    ));
+    if let Some((_, mut text, mut transform)) = text_query.iter_mut().next() {
+        if text.sections[0].value != message {
+            text.sections[0].value = message;
+        }
+        transform.translation = position.extend(HUD_Z);
+        return;
+    }
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                message,
+                TextStyle {
+                    font: game_textures.font.clone(),
+                    font_size: MEMORIZE_FONT_SIZE * hud_scale.world(&view),
+                    color: Color::WHITE,
+                },
+            )
+            .with_alignment(TextAlignment::CENTER),
+            transform: Transform::from_translation(position.extend(HUD_Z)),
+            ..Default::default()
+        })
+        .insert(MemorizeText);
+}
+
+/// Reapplied whenever a line changes state or the clues are respawned.
+fn clue_hiding_system(
+    options: Res<BoardOptions>,
+    memorize: Res<Memorize>,
+    line_states: Res<LineStates>,
+    clue_index: Res<ClueIndex>,
+    respawned_query: Query<(), Changed<Clue>>,
+    mut clue_query: Query<&mut Visibility, With<Clue>>,
+) {
+    if !(options.is_changed()
+        || memorize.is_changed()
+        || line_states.is_changed()
+        || !respawned_query.is_empty())
+    {
+        return;
+    }
+
+    let hiding = ClueHiding::from_options(&options);
+    let lines = clue_index
+        .rows
+        .iter()
+        .zip(line_states.rows.iter())
+        .chain(clue_index.columns.iter().zip(line_states.columns.iter()));
+    for (entities, state) in lines {
+        let visible = match hiding {
+            ClueHiding::Off => true,
+            ClueHiding::Satisfied => *state != LineState::Satisfied,
+            ClueHiding::Memorize => !memorize.hidden,
+        };
+        for entity in entities {
+            if let Ok(mut visibility) = clue_query.get_mut(*entity) {
+                if visibility.is_visible != visible {
+                    visibility.is_visible = visible;
+                }
+            }
+        }
+    }
+}
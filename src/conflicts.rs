@@ -0,0 +1,192 @@
+// region:      IMPORTS
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+    sprite::Anchor,
+    utils::HashMap,
+};
+use picross_handler::Cell;
+
+use crate::board::{Board, BoardLoadedEvent, CellChangedEvent, ChangeSource};
+use crate::layers::HIGHLIGHT_Z;
+use crate::pending::{pending_moves_system, PendingMoves};
+use crate::stats::{ConflictRecord, Stats};
+use crate::timer::SolveTimer;
+
+// endregion
+
+// region:      CONSTANTS
+
+/// How long a pending local move waits for the server before a differing
+/// remote value counts as a normal update rather than a conflict.
+const PENDING_SECONDS: f64 = 2.;
+const CONFLICT_SECONDS: f32 = 2.5;
+const HATCH_TEXTURE_SIZE: u32 = 32;
+const HATCH_SPACING: u32 = 8;
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct ConflictMarker {
+    pub x: usize,
+    pub y: usize,
+    pub remaining: f32,
+}
+
+// endregion
+
+// region:      RESOURCES
+
+#[derive(Resource)]
+pub struct ConflictTexture(pub Handle<Image>);
+
+// endregion
+
+// region:      EVENTS
+
+pub struct ConflictEvent {
+    pub x: usize,
+    pub y: usize,
+    pub local: Cell,
+    pub remote: Cell,
+}
+
+// endregion
+
+pub struct ConflictPlugin;
+
+impl Plugin for ConflictPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ConflictEvent>()
+            .add_startup_system(conflict_setup_system)
+            // reads the moves a remote change replaces before they are dropped
+            .add_system(conflict_detection_system.before(pending_moves_system))
+            .add_system(conflict_marker_system.after(conflict_detection_system));
+    }
+}
+
+fn conflict_setup_system(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    commands.insert_resource(ConflictTexture(images.add(cross_hatch_image())));
+}
+
+fn cross_hatch_image() -> Image {
+    let size = HATCH_TEXTURE_SIZE;
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            // both diagonals, two pixels wide
+            let on = (x + y) % HATCH_SPACING < 2 || (x + size - y) % HATCH_SPACING < 2;
+            if on {
+                data.extend_from_slice(&[220, 40, 40, 200]);
+            } else {
+                data.extend_from_slice(&[220, 40, 40, 40]);
+            }
+        }
+    }
+
+    Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+fn conflict_detection_system(
+    time: Res<Time>,
+    solve_timer: Res<SolveTimer>,
+    pending: Res<PendingMoves>,
+    mut stats: ResMut<Stats>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut cell_changed_event_reader: EventReader<CellChangedEvent>,
+    mut conflict_event_writer: EventWriter<ConflictEvent>,
+) {
+    // the moves pending from the last board don't count against this one
+    let loaded = board_loaded_event_reader.iter().last().is_some();
+
+    let now = solve_timer.elapsed(&time);
+    // this frame's moves and settled cells, PendingMoves catches up after
+    let mut frame: HashMap<(usize, usize), Option<(Cell, f64)>> = HashMap::new();
+    for event in cell_changed_event_reader.iter() {
+        let cell = (event.x, event.y);
+        match event.source {
+            ChangeSource::Player => {
+                frame.insert(cell, Some((event.new, now)));
+                continue;
+            }
+            ChangeSource::Remote => (),
+            _ => continue,
+        }
+
+        let settled = match frame.insert(cell, None) {
+            Some(entry) => entry,
+            None => pending.get(cell.0, cell.1).filter(|_| !loaded),
+        };
+        if let Some((local, t)) = settled {
+            if local != event.new && now - t <= PENDING_SECONDS {
+                stats.conflicts.push(ConflictRecord {
+                    x: event.x,
+                    y: event.y,
+                    t: now,
+                    local,
+                    remote: event.new,
+                });
+                conflict_event_writer.send(ConflictEvent {
+                    x: event.x,
+                    y: event.y,
+                    local,
+                    remote: event.new,
+                });
+            }
+        }
+    }
+}
+
+fn conflict_marker_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    board: Res<Board>,
+    conflict_texture: Res<ConflictTexture>,
+    mut conflict_event_reader: EventReader<ConflictEvent>,
+    mut marker_query: Query<(Entity, &mut ConflictMarker, &mut Sprite, &mut Transform)>,
+) {
+    for event in conflict_event_reader.iter() {
+        let (x, y) = board.cell_to_tile(event.x, event.y);
+        commands
+            .spawn(SpriteBundle {
+                texture: conflict_texture.0.clone(),
+                sprite: Sprite {
+                    anchor: Anchor::BottomLeft,
+                    custom_size: Some(Vec2::splat(board.pixels_per_tile)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(board.tile_translation(x, y, HIGHLIGHT_Z)),
+                ..Default::default()
+            })
+            .insert(ConflictMarker {
+                x: event.x,
+                y: event.y,
+                remaining: CONFLICT_SECONDS,
+            });
+    }
+
+    for (entity, mut marker, mut sprite, mut transform) in marker_query.iter_mut() {
+        marker.remaining -= time.delta_seconds();
+        if marker.remaining <= 0. {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        // fade out over the last second, and follow the board when it is resized
+        sprite.color.set_a(marker.remaining.min(1.));
+        let (x, y) = board.cell_to_tile(marker.x, marker.y);
+        transform.translation = board.tile_translation(x, y, HIGHLIGHT_Z);
+        sprite.custom_size = Some(Vec2::splat(board.pixels_per_tile));
+    }
+}
@@ -0,0 +1,212 @@
+// region:      IMPORTS
+
+use bevy::{prelude::*, sprite::Anchor, utils::Duration};
+
+use crate::board::{Board, BoardLoadedEvent, SpawnTilesEvent};
+use crate::camera::BoardView;
+use crate::control_profile::ControlProfile;
+use crate::feed::{column_label, row_label};
+use crate::guess::{toolbar_button_at, Guess, ToolbarButton};
+use crate::hud_scale::HudScale;
+use crate::keyboard::KeyboardCursor;
+use crate::layers::{HIGHLIGHT_Z, HUD_Z};
+use crate::marks::ClueLine;
+use crate::options::BoardOptions;
+use crate::screens::Screen;
+use crate::solver::{check, Stop};
+use crate::{BridgeCommandEvent, GameTextures, WASMSendChannel, WinSize};
+
+// endregion
+
+// region:      CONSTANTS
+
+const CHECK_KEY: KeyCode = KeyCode::C;
+
+// overridable with the check_budget_ms option, big boards may need more
+const DEFAULT_BUDGET_MS: f32 = 100.;
+
+const RESULT_SECONDS: f64 = 3.;
+const CONTRADICTION_COLOR: Color = Color::rgba(0.9, 0.2, 0.2, 0.3);
+const RESULT_FONT_SIZE: f32 = 20.;
+const RESULT_MARGIN: f32 = 8.;
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct ConsistencyOverlay;
+
+// endregion
+
+// region:      RESOURCES
+
+/// Result of the last check, shown until `until`.
+#[derive(Resource, Default)]
+pub struct ConsistencyResult {
+    result: Option<Result<(), Stop>>,
+    until: f64,
+}
+
+// endregion
+
+pub struct ConsistencyPlugin;
+
+impl Plugin for ConsistencyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ConsistencyResult>()
+            .add_system(consistency_check_system)
+            .add_system(consistency_draw_system.after(consistency_check_system));
+    }
+}
+
+/// What the player sees, `Row 3 can't be completed`.
+fn describe(board: &Board, result: Result<(), Stop>) -> String {
+    match result {
+        Ok(()) => String::from("Consistent so far"),
+        Err(Stop::Contradiction(ClueLine::Row(y))) => {
+            format!("Row {} can't be completed", row_label(board, y))
+        }
+        Err(Stop::Contradiction(ClueLine::Column(x))) => {
+            format!("Column {} can't be completed", column_label(x))
+        }
+        Err(Stop::OutOfTime) => String::from("Check ran out of time"),
+    }
+}
+
+/// C, the toolbar's Check button or `("check", "")` checks the board so far
+/// against the clues, following only what the filled and crossed cells
+/// force, so nothing of the solution is given away. Replies `("check", r)`
+/// with `ok`, the first contradicted line as `r3` or `c2`, or `timeout` once
+/// the `check_budget_ms` option's budget is spent.
+#[allow(clippy::too_many_arguments)]
+fn consistency_check_system(
+    time: Res<Time>,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    board: Res<Board>,
+    guess: Res<Guess>,
+    options: Res<BoardOptions>,
+    screen: Res<State<Screen>>,
+    hud_scale: Res<HudScale>,
+    profile: Res<ControlProfile>,
+    keyboard_cursor: Res<KeyboardCursor>,
+    send_channel: Res<WASMSendChannel>,
+    mut result: ResMut<ConsistencyResult>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+) {
+    if board_loaded_event_reader.iter().last().is_some() && result.result.is_some() {
+        result.result = None;
+    }
+    let now = time.elapsed_seconds_f64();
+    if result.result.is_some() && now >= result.until {
+        result.result = None;
+    }
+
+    let mut requested = bridge_command_event_reader
+        .iter()
+        .filter(|event| event.command == "check")
+        .count()
+        > 0;
+    if *screen.current() == Screen::Board {
+        // C clears the cell under the keyboard cursor while it is shown
+        requested |= keys.just_pressed(CHECK_KEY) && !keyboard_cursor.active;
+        let cursor = windows
+            .get_primary()
+            .and_then(|window| window.cursor_position());
+        if let (true, Some(cursor)) = (buttons.just_pressed(MouseButton::Left), cursor) {
+            requested |= toolbar_button_at(cursor, guess.active(), hud_scale.0, &profile)
+                == Some(ToolbarButton::Check);
+        }
+    }
+    if !requested {
+        return;
+    }
+
+    let budget = options
+        .get_f32("check_budget_ms", DEFAULT_BUDGET_MS)
+        .max(1.);
+    let checked = check(&board.p, Duration::from_secs_f32(budget / 1000.));
+    let reply = match checked {
+        Ok(()) => String::from("ok"),
+        Err(Stop::Contradiction(line)) => line.to_string(),
+        Err(Stop::OutOfTime) => String::from("timeout"),
+    };
+    send_channel.tx.send((String::from("check"), reply));
+    result.result = Some(checked);
+    result.until = now + RESULT_SECONDS;
+}
+
+fn consistency_draw_system(
+    mut commands: Commands,
+    board: Res<Board>,
+    view: Res<BoardView>,
+    win_size: Res<WinSize>,
+    result: Res<ConsistencyResult>,
+    hud_scale: Res<HudScale>,
+    game_textures: Res<GameTextures>,
+    mut spawn_tiles_event_reader: EventReader<SpawnTilesEvent>,
+    overlay_query: Query<Entity, With<ConsistencyOverlay>>,
+) {
+    let respawned = spawn_tiles_event_reader.iter().count() > 0;
+    let resized = view.is_changed() || hud_scale.is_changed();
+    if !(result.is_changed() || resized || respawned) {
+        return;
+    }
+    for entity in overlay_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    let checked = match result.result {
+        Some(checked) => checked,
+        None => return,
+    };
+
+    if let Err(Stop::Contradiction(line)) = checked {
+        // the whole line, clue band included
+        let (min, size) = match line {
+            ClueLine::Row(y) => (Vec2::new(0., y as f32), Vec2::new(board.w as f32, 1.)),
+            ClueLine::Column(x) => (
+                Vec2::new((board.grid_left() + x) as f32, 0.),
+                Vec2::new(1., board.h as f32),
+            ),
+        };
+        commands
+            .spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: CONTRADICTION_COLOR,
+                    custom_size: Some(size * board.pixels_per_tile),
+                    anchor: Anchor::BottomLeft,
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(board.tile_translation(
+                    min.x,
+                    min.y,
+                    HIGHLIGHT_Z,
+                )),
+                ..Default::default()
+            })
+            .insert(ConsistencyOverlay);
+    }
+
+    // a line below where the line preview sums up its line
+    let margin = RESULT_MARGIN * hud_scale.0;
+    let top = win_size.h - 2. * margin - RESULT_FONT_SIZE * hud_scale.0;
+    let position = view.screen_to_world(Vec2::new(win_size.w / 2., top));
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                describe(&board, checked),
+                TextStyle {
+                    font: game_textures.font.clone(),
+                    font_size: RESULT_FONT_SIZE * hud_scale.world(&view),
+                    color: Color::WHITE,
+                },
+            )
+            .with_alignment(TextAlignment::TOP_CENTER),
+            transform: Transform::from_translation(position.extend(HUD_Z)),
+            ..Default::default()
+        })
+        .insert(ConsistencyOverlay);
+}
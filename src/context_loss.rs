@@ -0,0 +1,179 @@
+// region:      IMPORTS
+
+use bevy::{asset::HandleId, prelude::*};
+use std::sync::atomic::{AtomicBool, Ordering};
+use wasm_bindgen::{closure::Closure, JsCast};
+
+use crate::board::{Board, DeleteTilesEvent};
+use crate::history::History;
+use crate::recovery;
+use crate::timer::SolveTimer;
+use crate::WASMSendChannel;
+
+// endregion
+
+// region:      GLOBAL
+
+/// Set by the canvas' `webglcontextlost` and `webglcontextrestored`
+/// listeners, outside of any system.
+static CONTEXT_LOST: AtomicBool = AtomicBool::new(false);
+
+// endregion
+
+// region:      CONSTANTS
+
+const OVERLAY_ID: &str = "picross-w-context-lost";
+const OVERLAY_STYLE: &str = "position:fixed;display:flex;align-items:center;justify-content:center;background:rgba(20,20,24,0.85);color:white;font:20px sans-serif;z-index:1000";
+
+// endregion
+
+// region:      RESOURCES
+
+/// When the WebGL context was lost, while it is. Player moves are rejected
+/// and the solve clock stands still until it is restored.
+#[derive(Resource, Default)]
+pub struct ContextLoss {
+    pub since: Option<f64>,
+}
+
+impl ContextLoss {
+    pub fn lost(&self) -> bool {
+        self.since.is_some()
+    }
+}
+
+// endregion
+
+pub struct ContextLossPlugin;
+
+impl Plugin for ContextLossPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ContextLoss>()
+            .add_startup_system(context_listener_system)
+            .add_system(context_loss_system);
+    }
+}
+
+fn document() -> Option<web_sys::Document> {
+    web_sys::window()?.document()
+}
+
+fn context_listener_system() {
+    let canvas = document().and_then(|document| document.get_element_by_id("bevy-canvas"));
+    let canvas = match canvas {
+        Some(canvas) => canvas,
+        None => {
+            warn!("No canvas to watch for WebGL context loss");
+            return;
+        }
+    };
+
+    // without preventDefault the browser never offers the context back
+    let lost = Closure::<dyn FnMut(web_sys::Event)>::new(|event: web_sys::Event| {
+        event.prevent_default();
+        CONTEXT_LOST.store(true, Ordering::SeqCst);
+    });
+    let restored = Closure::<dyn FnMut(web_sys::Event)>::new(|_: web_sys::Event| {
+        CONTEXT_LOST.store(false, Ordering::SeqCst);
+    });
+    for (name, listener) in [
+        ("webglcontextlost", lost.as_ref()),
+        ("webglcontextrestored", restored.as_ref()),
+    ] {
+        if let Err(err) = canvas.add_event_listener_with_callback(name, listener.unchecked_ref()) {
+            warn!("Unable to listen for {}: {:?}", name, err);
+        }
+    }
+    // the canvas lives as long as the page
+    lost.forget();
+    restored.forget();
+}
+
+/// Nothing can be drawn without a context, so the overlay is a plain element
+/// laid over the canvas.
+fn show_overlay() {
+    let document = match document() {
+        Some(document) => document,
+        None => return,
+    };
+    let (canvas, body) = match (document.get_element_by_id("bevy-canvas"), document.body()) {
+        (Some(canvas), Some(body)) => (canvas, body),
+        _ => return,
+    };
+    let overlay = match document.create_element("div") {
+        Ok(overlay) => overlay,
+        Err(_) => return,
+    };
+    let rect = canvas.get_bounding_client_rect();
+    overlay.set_id(OVERLAY_ID);
+    overlay.set_text_content(Some("Graphics context lost, waiting for the browser..."));
+    overlay
+        .set_attribute(
+            "style",
+            &format!(
+                "{};left:{}px;top:{}px;width:{}px;height:{}px",
+                OVERLAY_STYLE,
+                rect.left(),
+                rect.top(),
+                rect.width(),
+                rect.height()
+            ),
+        )
+        .ok();
+    body.append_child(&overlay).ok();
+}
+
+fn hide_overlay() {
+    if let Some(overlay) = document().and_then(|document| document.get_element_by_id(OVERLAY_ID)) {
+        overlay.remove();
+    }
+}
+
+/// Mobile browsers drop the context under memory pressure. On loss the log
+/// is saved for recovery right away in case the tab is killed next, and
+/// an overlay covers the canvas until the restore, which reuploads every
+/// image and respawns the board. Both are reported as
+/// `("context", "lost" | "restored")`.
+fn context_loss_system(
+    time: Res<Time>,
+    board: Res<Board>,
+    history: Res<History>,
+    send_channel: Res<WASMSendChannel>,
+    mut context_loss: ResMut<ContextLoss>,
+    mut solve_timer: ResMut<SolveTimer>,
+    mut images: ResMut<Assets<Image>>,
+    mut delete_tiles_event_writer: EventWriter<DeleteTilesEvent>,
+) {
+    let lost = CONTEXT_LOST.load(Ordering::SeqCst);
+    if lost == context_loss.lost() {
+        return;
+    }
+
+    let now = time.elapsed_seconds_f64();
+    if lost {
+        context_loss.since = Some(now);
+        recovery::save(&board, &history);
+        show_overlay();
+        send_channel
+            .tx
+            .send((String::from("context"), String::from("lost")));
+        return;
+    }
+
+    // the clock was paused for as long as the board couldn't be seen
+    if let Some(since) = context_loss.since.take() {
+        if solve_timer.finished.is_none() {
+            solve_timer.started += now - since;
+        }
+    }
+    hide_overlay();
+    // every texture went with the old context, touching them reuploads them
+    let ids: Vec<HandleId> = images.ids().collect();
+    for id in ids {
+        images.get_mut(id);
+    }
+    delete_tiles_event_writer.send(DeleteTilesEvent);
+    send_channel
+        .tx
+        .send((String::from("context"), String::from("restored")));
+}
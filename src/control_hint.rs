@@ -0,0 +1,127 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+
+use crate::board::{Board, ControlTile, ControlToggledEvent};
+use crate::control_profile::ControlProfile;
+use crate::layers::HUD_Z;
+use crate::settings::Settings;
+use crate::GameTextures;
+
+// endregion
+
+// region:      CONSTANTS
+
+const PULSE_SPEED: f32 = 3.; // radians per second
+const PULSE_AMOUNT: f32 = 0.06;
+const TOOLTIP_TEXT: &str = "Tap to switch\nfill / cross";
+const TOOLTIP_FONT_SCALE: f32 = 0.3; // of a tile
+const TOOLTIP_MIN_FONT_SIZE: f32 = 10.;
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct ControlTooltip;
+
+// endregion
+
+// until the player has switched modes once, the control tile pulses and
+// carries a tooltip explaining it
+
+pub struct ControlHintPlugin;
+
+impl Plugin for ControlHintPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(control_discovery_system)
+            .add_system(control_pulse_system)
+            .add_system(control_tooltip_system);
+    }
+}
+
+fn control_discovery_system(
+    mut settings: ResMut<Settings>,
+    mut control_toggled_event_reader: EventReader<ControlToggledEvent>,
+) {
+    if control_toggled_event_reader.iter().last().is_some() && !settings.control_toggle_discovered {
+        settings.control_toggle_discovered = true;
+    }
+}
+
+fn control_pulse_system(
+    time: Res<Time>,
+    board: Res<Board>,
+    settings: Res<Settings>,
+    mut control_tile_query: Query<&mut Transform, With<ControlTile>>,
+) {
+    if settings.control_toggle_discovered && !settings.is_changed() {
+        return;
+    }
+
+    let pulse = if settings.control_toggle_discovered {
+        1.
+    } else {
+        1. + PULSE_AMOUNT * (time.elapsed_seconds() * PULSE_SPEED).sin()
+    };
+    let scale = board.control_tile_scale() * pulse;
+    for mut transform in control_tile_query.iter_mut() {
+        transform.scale = Vec3::new(scale, scale, 1.);
+    }
+}
+
+fn control_tooltip_system(
+    mut commands: Commands,
+    board: Res<Board>,
+    settings: Res<Settings>,
+    profile: Res<ControlProfile>,
+    game_textures: Res<GameTextures>,
+    control_tile_query: Query<&ControlTile>,
+    mut tooltip_query: Query<(Entity, &mut Transform, &mut Text), With<ControlTooltip>>,
+) {
+    let control_tile = match control_tile_query.iter().next() {
+        Some(control_tile)
+            if !settings.control_toggle_discovered && profile.shows_control_tile(&settings) =>
+        {
+            control_tile
+        }
+        _ => {
+            for (entity, _, _) in tooltip_query.iter() {
+                commands.entity(entity).despawn();
+            }
+            return;
+        }
+    };
+
+    // along the bottom of the corner, just above the grid
+    let font_size = (board.pixels_per_tile * TOOLTIP_FONT_SCALE).max(TOOLTIP_MIN_FONT_SIZE);
+    let translation = Vec3::new(
+        control_tile.x,
+        board.origin.1 + board.p.get_height() as f32 * board.pixels_per_tile + font_size,
+        HUD_Z,
+    );
+
+    if let Some((_, mut transform, mut text)) = tooltip_query.iter_mut().next() {
+        if transform.translation != translation {
+            transform.translation = translation;
+            text.sections[0].style.font_size = font_size;
+        }
+        return;
+    }
+
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                TOOLTIP_TEXT,
+                TextStyle {
+                    font: game_textures.font.clone(),
+                    font_size,
+                    color: Color::rgb(0.15, 0.15, 0.15),
+                },
+            )
+            .with_alignment(TextAlignment::CENTER),
+            transform: Transform::from_translation(translation),
+            ..Default::default()
+        })
+        .insert(ControlTooltip);
+}
@@ -0,0 +1,200 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+use wasm_bindgen::JsValue;
+
+use crate::board::{BoardAction, ControlAction, ControlTile};
+use crate::bridge::reply_error;
+use crate::settings::{ControlTilePlacement, Settings};
+use crate::{BridgeCommandEvent, WASMSendChannel};
+
+// endregion
+
+// region:      CONSTANTS
+
+/// Touch screens with a shorter side than this, in CSS pixels, are phones.
+const PHONE_SHORT_SIDE: f64 = 600.;
+
+// endregion
+
+// region:      RESOURCES
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DeviceClass {
+    Desktop,
+    Tablet,
+    Phone,
+}
+
+impl DeviceClass {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "desktop" => Some(DeviceClass::Desktop),
+            "tablet" => Some(DeviceClass::Tablet),
+            "phone" => Some(DeviceClass::Phone),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeviceClass::Desktop => "desktop",
+            DeviceClass::Tablet => "tablet",
+            DeviceClass::Phone => "phone",
+        }
+    }
+
+    /// Any touch points make a tablet or a phone, the screen's size decides
+    /// which.
+    fn detect() -> Self {
+        let window = match web_sys::window() {
+            Some(window) => window,
+            None => return DeviceClass::Desktop,
+        };
+        let touch_points =
+            js_sys::Reflect::get(&window.navigator(), &JsValue::from_str("maxTouchPoints"))
+                .ok()
+                .and_then(|points| points.as_f64())
+                .unwrap_or(0.);
+        let short_side = window
+            .screen()
+            .ok()
+            .and_then(|screen| Some(screen.width().ok()?.min(screen.height().ok()?)))
+            .unwrap_or(i32::MAX) as f64;
+        match (touch_points > 0., short_side < PHONE_SHORT_SIDE) {
+            (false, _) => DeviceClass::Desktop,
+            (true, false) => DeviceClass::Tablet,
+            (true, true) => DeviceClass::Phone,
+        }
+    }
+}
+
+/// How input is laid out for the kind of device playing, detected at startup
+/// and overridable with `("profile", class)`. A mouse is precise and has a
+/// right button for crossing, a finger needs bigger targets and the control
+/// tile to switch between fill and cross.
+#[derive(Resource)]
+pub struct ControlProfile {
+    pub class: DeviceClass,
+    /// Factor on the toolbar buttons' size, and so their hit areas.
+    pub toolbar_scale: f32,
+    /// Toolbar stacked up the left edge instead of along the bottom, where
+    /// a narrow screen has no room for a row.
+    pub toolbar_column: bool,
+    pub control_tile: bool,
+    /// A touch held still opens the magnifier.
+    pub long_press_magnifier: bool,
+}
+
+impl ControlProfile {
+    /// Whether the corner holds the control tile, the profile's choice
+    /// unless the player's `control_tile` setting places it.
+    pub fn shows_control_tile(&self, settings: &Settings) -> bool {
+        match settings.control_tile {
+            ControlTilePlacement::Auto => self.control_tile,
+            ControlTilePlacement::Corner => true,
+            ControlTilePlacement::Off => false,
+        }
+    }
+
+    pub fn for_class(class: DeviceClass) -> Self {
+        match class {
+            DeviceClass::Desktop => Self {
+                class,
+                toolbar_scale: 1.,
+                toolbar_column: false,
+                control_tile: false,
+                long_press_magnifier: false,
+            },
+            DeviceClass::Tablet => Self {
+                class,
+                toolbar_scale: 1.25,
+                toolbar_column: false,
+                control_tile: true,
+                long_press_magnifier: true,
+            },
+            DeviceClass::Phone => Self {
+                class,
+                toolbar_scale: 1.5,
+                toolbar_column: true,
+                control_tile: true,
+                long_press_magnifier: true,
+            },
+        }
+    }
+}
+
+/// Tablets are the scheme everything was tuned for before profiles, and the
+/// one that works with both a mouse and a finger.
+impl Default for ControlProfile {
+    fn default() -> Self {
+        Self::for_class(DeviceClass::Tablet)
+    }
+}
+
+// endregion
+
+pub struct ControlProfilePlugin;
+
+impl Plugin for ControlProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ControlProfile>()
+            .add_startup_system(detect_profile_system)
+            .add_system(profile_command_system)
+            .add_system(control_tile_visibility_system.after(profile_command_system));
+    }
+}
+
+fn detect_profile_system(mut profile: ResMut<ControlProfile>) {
+    *profile = ControlProfile::for_class(DeviceClass::detect());
+    info!("Control profile {}", profile.class.as_str());
+}
+
+/// `("profile", "desktop" | "tablet" | "phone")` switches profiles, `"auto"`
+/// goes back to the detected one. Replies `("profile", class)`.
+fn profile_command_system(
+    send_channel: Res<WASMSendChannel>,
+    mut profile: ResMut<ControlProfile>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+) {
+    for event in bridge_command_event_reader.iter() {
+        if event.command != "profile" {
+            continue;
+        }
+        let class = match event.data.as_str() {
+            "auto" => DeviceClass::detect(),
+            data => match DeviceClass::parse(data) {
+                Some(class) => class,
+                None => {
+                    reply_error(&send_channel, format!("profile: unknown class {}", data));
+                    continue;
+                }
+            },
+        };
+        *profile = ControlProfile::for_class(class);
+        send_channel
+            .tx
+            .send((String::from("profile"), String::from(class.as_str())));
+    }
+}
+
+/// Without the control tile the left button always fills, crossing is on
+/// the right one.
+fn control_tile_visibility_system(
+    profile: Res<ControlProfile>,
+    settings: Res<Settings>,
+    mut control_action: ResMut<ControlAction>,
+    mut control_tile_query: Query<(&mut Visibility, ChangeTrackers<ControlTile>)>,
+) {
+    let shown = profile.shows_control_tile(&settings);
+    let changed = profile.is_changed() || settings.is_changed();
+    // the tile is spawned again with every board
+    for (mut visibility, tracker) in control_tile_query.iter_mut() {
+        if (changed || tracker.is_added()) && visibility.is_visible != shown {
+            visibility.is_visible = shown;
+        }
+    }
+    if changed && !shown && control_action.0 != BoardAction::Fill {
+        control_action.0 = BoardAction::Fill;
+    }
+}
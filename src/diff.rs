@@ -0,0 +1,189 @@
+// region:      IMPORTS
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+    sprite::Anchor,
+};
+
+use crate::board::{char_to_cell, Board, BoardLoadedEvent, CellChangedEvent};
+use crate::bridge::{check_cells, check_len, reply_error, MAX_CELLS_LEN};
+use crate::layers::HIGHLIGHT_Z;
+use crate::{BridgeCommandEvent, WASMSendChannel};
+
+// endregion
+
+// region:      CONSTANTS
+
+const OUTLINE_TEXTURE_SIZE: u32 = 32;
+const OUTLINE_WIDTH: u32 = 3;
+const OUTLINE_COLOR: [u8; 4] = [230, 120, 20, 255];
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct DiffOutline {
+    pub x: usize,
+    pub y: usize,
+}
+
+// endregion
+
+// region:      RESOURCES
+
+#[derive(Resource)]
+pub struct DiffTexture(pub Handle<Image>);
+
+/// Board state the current cells are compared against, set with
+/// `("diff", cells)` and cleared with an empty string or a new board.
+#[derive(Resource, Default)]
+pub struct DiffReference(pub Option<String>);
+
+// endregion
+
+pub struct DiffPlugin;
+
+impl Plugin for DiffPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DiffReference>()
+            .add_startup_system(diff_setup_system)
+            .add_system(diff_command_system)
+            .add_system(diff_outline_system.after(diff_command_system));
+    }
+}
+
+fn diff_setup_system(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    commands.insert_resource(DiffTexture(images.add(outline_image())));
+}
+
+fn outline_image() -> Image {
+    let size = OUTLINE_TEXTURE_SIZE;
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let edge = x.min(y).min(size - 1 - x).min(size - 1 - y);
+            if edge < OUTLINE_WIDTH {
+                data.extend_from_slice(&OUTLINE_COLOR);
+            } else {
+                data.extend_from_slice(&[0, 0, 0, 0]);
+            }
+        }
+    }
+
+    Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+/// Replies `("diff", count)` with the number of differing cells.
+fn diff_command_system(
+    board: Res<Board>,
+    send_channel: Res<WASMSendChannel>,
+    mut reference: ResMut<DiffReference>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+) {
+    if board_loaded_event_reader.iter().last().is_some() {
+        reference.0 = None;
+    }
+
+    for event in bridge_command_event_reader.iter() {
+        if event.command != "diff" {
+            continue;
+        }
+        if event.data.is_empty() {
+            reference.0 = None;
+            continue;
+        }
+
+        let expected = board.p.get_width() * board.p.get_height();
+        let checked = check_len("diff", &event.data, MAX_CELLS_LEN)
+            .and_then(|_| check_cells(&event.data))
+            .and_then(|_| match event.data.chars().count() {
+                count if count == expected => Ok(()),
+                count => Err(format!("diff: {} cells, expected {}", count, expected)),
+            });
+        match checked {
+            Ok(()) => {
+                let count = differing_cells(&board, &event.data).len();
+                reference.0 = Some(event.data.clone());
+                send_channel
+                    .tx
+                    .send((String::from("diff"), count.to_string()));
+            }
+            Err(err) => reply_error(&send_channel, err),
+        }
+    }
+}
+
+fn differing_cells(board: &Board, reference: &str) -> Vec<(usize, usize)> {
+    let width = board.p.get_width();
+    reference
+        .chars()
+        .enumerate()
+        .map(|(i, c)| (i % width, i / width, char_to_cell(c)))
+        .filter(|(x, y, cell)| *cell != Some(board.p.get_cell(*x, *y)))
+        .map(|(x, y, _)| (x, y))
+        .collect()
+}
+
+fn diff_outline_system(
+    mut commands: Commands,
+    board: Res<Board>,
+    reference: Res<DiffReference>,
+    diff_texture: Res<DiffTexture>,
+    mut cell_changed_event_reader: EventReader<CellChangedEvent>,
+    outline_query: Query<Entity, With<DiffOutline>>,
+    mut placed_query: Query<(&DiffOutline, &mut Transform, &mut Sprite)>,
+) {
+    let cells_changed = cell_changed_event_reader.iter().last().is_some();
+
+    // outlines are rebuilt when either side of the comparison changes
+    if reference.is_changed() || cells_changed {
+        for entity in outline_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        let reference = match &reference.0 {
+            Some(reference) => reference,
+            None => return,
+        };
+        for (x, y) in differing_cells(&board, reference) {
+            let (tile_x, tile_y) = board.cell_to_tile(x, y);
+            commands
+                .spawn(SpriteBundle {
+                    texture: diff_texture.0.clone(),
+                    sprite: Sprite {
+                        anchor: Anchor::BottomLeft,
+                        custom_size: Some(Vec2::splat(board.pixels_per_tile)),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_translation(board.tile_translation(
+                        tile_x,
+                        tile_y,
+                        HIGHLIGHT_Z,
+                    )),
+                    ..Default::default()
+                })
+                .insert(DiffOutline { x, y });
+        }
+        return;
+    }
+
+    // follow the board when it is resized
+    if board.is_changed() {
+        for (outline, mut transform, mut sprite) in placed_query.iter_mut() {
+            let (x, y) = board.cell_to_tile(outline.x, outline.y);
+            transform.translation = board.tile_translation(x, y, HIGHLIGHT_Z);
+            sprite.custom_size = Some(Vec2::splat(board.pixels_per_tile));
+        }
+    }
+}
@@ -0,0 +1,357 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+use image::{ImageOutputFormat, RgbaImage};
+use picross_handler::{Cell, Puzzle};
+use std::io::Cursor;
+
+use crate::board::Board;
+use crate::glyphs::{DIGIT_BITMAPS, GLYPH_HEIGHT, GLYPH_PADDING, GLYPH_WIDTH};
+use crate::{PrintExportEvent, WASMSendChannel};
+
+// endregion
+
+// region:      CONSTANTS
+
+const DEFAULT_CELL_SIZE: f32 = 24.;
+const MIN_CELL_SIZE: f32 = 4.;
+const MAX_CELL_SIZE: f32 = 200.;
+
+const THIN_LINE: f32 = 1.;
+const BOLD_LINE: f32 = 3.;
+const BOLD_EVERY: usize = 5;
+const PRINT_INK: [u8; 4] = [0, 0, 0, 255];
+const PRINT_GRAY: [u8; 4] = [128, 128, 128, 255];
+const PRINT_FONT_SCALE: f32 = 0.6; // of a cell
+
+const PICTURE_CELL_PIXELS: u32 = 16;
+const PICTURE_FILLED: [u8; 4] = [30, 30, 30, 255];
+const PICTURE_EMPTY: [u8; 4] = [255, 255, 255, 255];
+
+// endregion
+
+pub struct ExportPlugin;
+
+impl Plugin for ExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(print_export_event_system);
+    }
+}
+
+fn print_export_event_system(
+    board: Res<Board>,
+    send_channel: Res<WASMSendChannel>,
+    mut print_export_event_reader: EventReader<PrintExportEvent>,
+) {
+    for event in print_export_event_reader.iter() {
+        let cell_size = event
+            .cell_size
+            .unwrap_or(DEFAULT_CELL_SIZE)
+            .clamp(MIN_CELL_SIZE, MAX_CELL_SIZE);
+        let reply = match event.png {
+            true => (String::from("print_png"), print_png(&board.p, cell_size)),
+            false => (String::from("print"), print_svg(&board.p, cell_size)),
+        };
+        send_channel.tx.send(reply);
+    }
+}
+
+/// Clue numbers by `(column, row)` of the printed layout, counted in cells
+/// from its top left corner.
+fn clue_cells(p: &Puzzle) -> Vec<(usize, usize, String)> {
+    let clue_w = p.get_longest_row_clue_len();
+    let clue_h = p.get_longest_column_clue_len();
+    let height = p.get_height();
+    let mut cells = Vec::new();
+
+    // column clues, the last clue sits right above the grid
+    for (x, clues) in p.column_clues.iter().enumerate() {
+        for (i, clue) in clues.iter().enumerate() {
+            cells.push((clue_w + x, clue_h - clues.len() + i, clue.to_string()));
+        }
+    }
+    // row clues, board row 0 is drawn at the bottom
+    for (y, clues) in p.row_clues.iter().enumerate() {
+        let row = clue_h + (height - 1 - y);
+        for (i, clue) in clues.iter().enumerate() {
+            cells.push((clue_w - clues.len() + i, row, clue.to_string()));
+        }
+    }
+    cells
+}
+
+/// Vertical grid line `x` from the left, bold every 5 columns and at the edges.
+fn bold_column_line(x: usize, width: usize) -> bool {
+    x % BOLD_EVERY == 0 || x == width
+}
+
+/// Horizontal grid line `y` from the top. Board rows count up from the
+/// bottom, so the bold lines are every 5 rows from the bottom edge.
+fn bold_row_line(y: usize, height: usize) -> bool {
+    (height - y) % BOLD_EVERY == 0 || y == 0
+}
+
+/// Renders the clues and an empty grid as a printable SVG, laid out like the
+/// board on screen (column clues above, row clues to the left).
+pub fn print_svg(p: &Puzzle, cell_size: f32) -> String {
+    let clue_w = p.get_longest_row_clue_len();
+    let clue_h = p.get_longest_column_clue_len();
+    let width = p.get_width();
+    let height = p.get_height();
+
+    let grid_left = clue_w as f32 * cell_size;
+    let grid_top = clue_h as f32 * cell_size;
+    let total_w = grid_left + width as f32 * cell_size;
+    let total_h = grid_top + height as f32 * cell_size;
+    let font_size = cell_size * 0.6;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">",
+        total_w + BOLD_LINE,
+        total_h + BOLD_LINE
+    );
+    // keep the outer bold border inside the canvas
+    svg.push_str(&format!(
+        "<g transform=\"translate({0},{0})\" font-family=\"sans-serif\" font-size=\"{1}\" text-anchor=\"middle\" dominant-baseline=\"central\">",
+        BOLD_LINE / 2.,
+        font_size
+    ));
+    svg.push_str(&format!(
+        "<rect x=\"0\" y=\"0\" width=\"{}\" height=\"{}\" fill=\"white\"/>",
+        total_w, total_h
+    ));
+
+    for (column, row, value) in clue_cells(p) {
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\">{}</text>",
+            (column as f32 + 0.5) * cell_size,
+            (row as f32 + 0.5) * cell_size,
+            value
+        ));
+    }
+
+    // vertical lines through the clue band and grid
+    for x in 0..=width {
+        let stroke = if bold_column_line(x, width) {
+            BOLD_LINE
+        } else {
+            THIN_LINE
+        };
+        let line_x = grid_left + x as f32 * cell_size;
+        svg.push_str(&format!(
+            "<line x1=\"{0}\" y1=\"0\" x2=\"{0}\" y2=\"{1}\" stroke=\"black\" stroke-width=\"{2}\"/>",
+            line_x, total_h, stroke
+        ));
+    }
+
+    // horizontal lines through the clue band and grid
+    for y in 0..=height {
+        let stroke = if bold_row_line(y, height) {
+            BOLD_LINE
+        } else {
+            THIN_LINE
+        };
+        let line_y = grid_top + y as f32 * cell_size;
+        svg.push_str(&format!(
+            "<line x1=\"0\" y1=\"{0}\" x2=\"{1}\" y2=\"{0}\" stroke=\"black\" stroke-width=\"{2}\"/>",
+            line_y, total_w, stroke
+        ));
+    }
+
+    // thin separators between clue cells
+    for x in 0..clue_w {
+        let line_x = x as f32 * cell_size;
+        svg.push_str(&format!(
+            "<line x1=\"{0}\" y1=\"{1}\" x2=\"{0}\" y2=\"{2}\" stroke=\"gray\" stroke-width=\"{3}\"/>",
+            line_x, grid_top, total_h, THIN_LINE
+        ));
+    }
+    for y in 0..clue_h {
+        let line_y = y as f32 * cell_size;
+        svg.push_str(&format!(
+            "<line x1=\"{0}\" y1=\"{1}\" x2=\"{2}\" y2=\"{1}\" stroke=\"gray\" stroke-width=\"{3}\"/>",
+            grid_left, line_y, total_w, THIN_LINE
+        ));
+    }
+
+    svg.push_str("</g></svg>");
+    svg
+}
+
+/// The same blank puzzle as `print_svg` as a base64 PNG, for hosts that
+/// can't print SVG. There is no font to rasterize outside the renderer, so
+/// clues are drawn with the fallback digit bitmaps.
+pub fn print_png(p: &Puzzle, cell_size: f32) -> String {
+    let cell = cell_size.round() as u32;
+    let clue_w = p.get_longest_row_clue_len() as u32;
+    let clue_h = p.get_longest_column_clue_len() as u32;
+    let (width, height) = (p.get_width(), p.get_height());
+    let grid_left = clue_w * cell;
+    let grid_top = clue_h * cell;
+    let total_w = grid_left + width as u32 * cell;
+    let total_h = grid_top + height as u32 * cell;
+    // keep the outer bold border inside the image
+    let inset = BOLD_LINE as u32 / 2;
+    let mut image = RgbaImage::from_pixel(
+        total_w + 2 * inset + 1,
+        total_h + 2 * inset + 1,
+        image::Rgba(PICTURE_EMPTY),
+    );
+
+    let scale = ((cell as f32 * PRINT_FONT_SCALE) / GLYPH_HEIGHT as f32).max(1.) as u32;
+    let advance = (GLYPH_WIDTH + GLYPH_PADDING) as u32 * scale;
+    for (column, row, value) in clue_cells(p) {
+        let text_w = value.len() as u32 * advance - GLYPH_PADDING as u32 * scale;
+        let left = inset + column as u32 * cell + cell.saturating_sub(text_w) / 2;
+        let top = inset + row as u32 * cell + cell.saturating_sub(GLYPH_HEIGHT as u32 * scale) / 2;
+        for (i, digit) in value.bytes().enumerate() {
+            let bitmap = DIGIT_BITMAPS[(digit - b'0') as usize];
+            for (glyph_row, bits) in bitmap.iter().enumerate() {
+                for glyph_column in 0..GLYPH_WIDTH {
+                    if bits & (1 << (GLYPH_WIDTH - 1 - glyph_column)) != 0 {
+                        let x = left + i as u32 * advance + glyph_column as u32 * scale;
+                        let y = top + glyph_row as u32 * scale;
+                        fill_rect(&mut image, (x, y), (scale, scale), PRINT_INK);
+                    }
+                }
+            }
+        }
+    }
+
+    // lines are centered on their cell edge like SVG strokes
+    let line = |at: u32, stroke: f32| (inset + at - stroke as u32 / 2, stroke as u32);
+    for x in 0..=width {
+        let stroke = if bold_column_line(x, width) {
+            BOLD_LINE
+        } else {
+            THIN_LINE
+        };
+        let (left, thickness) = line(grid_left + x as u32 * cell, stroke);
+        fill_rect(
+            &mut image,
+            (left, 0),
+            (thickness, total_h + 2 * inset),
+            PRINT_INK,
+        );
+    }
+    for y in 0..=height {
+        let stroke = if bold_row_line(y, height) {
+            BOLD_LINE
+        } else {
+            THIN_LINE
+        };
+        let (top, thickness) = line(grid_top + y as u32 * cell, stroke);
+        fill_rect(
+            &mut image,
+            (0, top),
+            (total_w + 2 * inset, thickness),
+            PRINT_INK,
+        );
+    }
+    // thin separators between clue cells
+    for x in 0..clue_w {
+        let (left, thickness) = line(x * cell, THIN_LINE);
+        let top = inset + grid_top;
+        fill_rect(
+            &mut image,
+            (left, top),
+            (thickness, total_h - grid_top),
+            PRINT_GRAY,
+        );
+    }
+    for y in 0..clue_h {
+        let (top, thickness) = line(y * cell, THIN_LINE);
+        let left = inset + grid_left;
+        fill_rect(
+            &mut image,
+            (left, top),
+            (total_w - grid_left, thickness),
+            PRINT_GRAY,
+        );
+    }
+
+    encode_png(&image)
+}
+
+fn fill_rect(image: &mut RgbaImage, (x, y): (u32, u32), (w, h): (u32, u32), color: [u8; 4]) {
+    for py in y..(y + h).min(image.height()) {
+        for px in x..(x + w).min(image.width()) {
+            image.put_pixel(px, py, image::Rgba(color));
+        }
+    }
+}
+
+fn encode_png(image: &RgbaImage) -> String {
+    let mut png = Vec::new();
+    if let Err(err) = image.write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png) {
+        warn!("image failed to encode: {}", err);
+    }
+    base64::encode(png)
+}
+
+/// The board's filled cells as a base64 PNG, black on white without grid or
+/// clues, for saving the solved picture.
+pub fn picture_png(p: &Puzzle) -> String {
+    let (width, height) = (p.get_width() as u32, p.get_height() as u32);
+    let image = RgbaImage::from_fn(
+        width * PICTURE_CELL_PIXELS,
+        height * PICTURE_CELL_PIXELS,
+        |px, py| {
+            // pixel rows run top down, board rows bottom up
+            let x = (px / PICTURE_CELL_PIXELS) as usize;
+            let y = (height - 1 - py / PICTURE_CELL_PIXELS) as usize;
+            image::Rgba(match p.get_cell(x, y) {
+                Cell::Filled => PICTURE_FILLED,
+                _ => PICTURE_EMPTY,
+            })
+        },
+    );
+    encode_png(&image)
+}
+
+// region:      TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::harness::clue_string;
+
+    // X.X
+    // XXX
+    // X.X
+    fn plus() -> Puzzle {
+        Puzzle::from_string(clue_string(&[&[1, 1], &[3], &[1, 1]], &[&[3], &[1], &[3]]).as_str())
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    #[test]
+    fn bold_rows_count_from_the_bottom_like_the_board() {
+        // the top edge, 5 rows up from the bottom edge and the bottom edge
+        let rows: Vec<usize> = (0..=7).filter(|y| bold_row_line(*y, 7)).collect();
+        assert_eq!(rows, vec![0, 2, 7]);
+        let columns: Vec<usize> = (0..=7).filter(|x| bold_column_line(*x, 7)).collect();
+        assert_eq!(columns, vec![0, 5, 7]);
+    }
+
+    #[test]
+    fn printed_svg_has_every_clue() {
+        let svg = print_svg(&plus(), DEFAULT_CELL_SIZE);
+        assert_eq!(svg.matches("<text").count(), 8);
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>"));
+    }
+
+    #[test]
+    fn printed_png_is_laid_out_like_the_svg() {
+        let png = base64::decode(print_png(&plus(), 10.)).unwrap();
+        let image = image::load_from_memory(&png).unwrap().to_rgba8();
+        // two clue columns and three cells across, one clue row and three
+        // cells down, with the border's overhang
+        assert_eq!(image.dimensions(), (53, 43));
+        // the grid's top left corner is on the bold border
+        assert_eq!(image.get_pixel(21, 11).0, PRINT_INK);
+        // and its cells are blank
+        assert_eq!(image.get_pixel(26, 36).0, PICTURE_EMPTY);
+    }
+}
+
+// endregion
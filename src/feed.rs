@@ -0,0 +1,244 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+use picross_handler::Cell;
+use std::collections::VecDeque;
+
+use crate::board::{Board, BoardLoadedEvent, CellChangedEvent, ChangeSource};
+use crate::camera::BoardView;
+use crate::hud_scale::HudScale;
+use crate::layers::HUD_Z;
+use crate::options::BoardOptions;
+use crate::rules::Rules;
+use crate::settings::Settings;
+use crate::speaking::Speaking;
+use crate::telemetry::HintUsedEvent;
+use crate::win::PuzzleSolvedEvent;
+use crate::{GameTextures, WinSize};
+
+// endregion
+
+// region:      CONSTANTS
+
+const FEED_FONT_SIZE: f32 = 16.;
+const FEED_MARGIN: f32 = 8.;
+const FEED_SECONDS: f64 = 12.;
+const DEFAULT_FEED_ENTRIES: usize = 6;
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct FeedText;
+
+// endregion
+
+// region:      RESOURCES
+
+/// Recent happenings listed in the top right corner, newest first. Toggled
+/// with F, starts on when the `feed` option is set. Names come from the
+/// `player_name` and `teammate_name` options since updates don't say who
+/// made them. Lines naming a player who is speaking say so.
+#[derive(Resource, Default)]
+pub struct ActivityFeed {
+    pub visible: bool,
+    entries: VecDeque<(String, f64)>,
+}
+
+impl ActivityFeed {
+    pub fn push(&mut self, entry: String, now: f64) {
+        self.entries.push_front((entry, now));
+    }
+}
+
+// endregion
+
+pub struct FeedPlugin;
+
+impl Plugin for FeedPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActivityFeed>()
+            .add_system(feed_toggle_system)
+            .add_system(feed_events_system)
+            .add_system(
+                feed_draw_system
+                    .after(feed_toggle_system)
+                    .after(feed_events_system),
+            );
+    }
+}
+
+/// Spreadsheet style column letters, A to Z then AA.
+pub fn column_label(x: usize) -> String {
+    let mut column = String::new();
+    let mut n = x + 1;
+    while n > 0 {
+        column.insert(0, (b'A' + ((n - 1) % 26) as u8) as char);
+        n = (n - 1) / 26;
+    }
+    column
+}
+
+/// Rows are counted from the top, as they are read.
+pub fn row_label(board: &Board, y: usize) -> usize {
+    board.p.get_height() - y
+}
+
+pub fn cell_label(board: &Board, x: usize, y: usize) -> String {
+    format!("{}{}", column_label(x), row_label(board, y))
+}
+
+fn feed_toggle_system(
+    keys: Res<Input<KeyCode>>,
+    options: Res<BoardOptions>,
+    mut feed: ResMut<ActivityFeed>,
+) {
+    if options.is_changed() {
+        feed.visible = options.get_bool("feed", false);
+    }
+    if keys.just_pressed(KeyCode::F) {
+        feed.visible = !feed.visible;
+    }
+}
+
+fn feed_events_system(
+    time: Res<Time>,
+    board: Res<Board>,
+    rules: Res<Rules>,
+    options: Res<BoardOptions>,
+    mut feed: ResMut<ActivityFeed>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut cell_changed_event_reader: EventReader<CellChangedEvent>,
+    mut hint_used_event_reader: EventReader<HintUsedEvent>,
+    mut puzzle_solved_event_reader: EventReader<PuzzleSolvedEvent>,
+) {
+    let now = time.elapsed_seconds_f64();
+    if board_loaded_event_reader.iter().last().is_some() {
+        feed.entries.clear();
+    }
+
+    let name = |source: ChangeSource| match source {
+        ChangeSource::Player => options.get("player_name").unwrap_or("You"),
+        ChangeSource::Remote => options.get("teammate_name").unwrap_or("Teammate"),
+        ChangeSource::Handicap => "Handicap",
+    };
+
+    // a remote sync can change the whole board at once, that is one entry
+    let mut remote_changes = 0;
+    let mut entries = Vec::new();
+    for event in cell_changed_event_reader.iter() {
+        // pre-crossed cells aren't anyone's move
+        if event.source == ChangeSource::Handicap {
+            continue;
+        }
+        if event.source == ChangeSource::Remote {
+            remote_changes += 1;
+        } else {
+            let verb = match event.new {
+                Cell::Filled => "filled",
+                Cell::Crossed => "crossed",
+                Cell::Empty => "cleared",
+            };
+            let label = cell_label(&board, event.x, event.y);
+            entries.push(format!("{} {} {}", name(event.source), verb, label));
+        }
+
+        // only a change to a filled cell can alter a line's runs
+        if event.old != Cell::Filled && event.new != Cell::Filled {
+            continue;
+        }
+        if rules.0.row_satisfied(&board.p, event.y) {
+            let row = row_label(&board, event.y);
+            entries.push(format!("{} completed row {}", name(event.source), row));
+        }
+        if rules.0.column_satisfied(&board.p, event.x) {
+            let column = column_label(event.x);
+            entries.push(format!(
+                "{} completed column {}",
+                name(event.source),
+                column
+            ));
+        }
+    }
+    if remote_changes > 1 {
+        entries.insert(
+            0,
+            format!(
+                "{} changed {} cells",
+                name(ChangeSource::Remote),
+                remote_changes
+            ),
+        );
+    } else if remote_changes == 1 {
+        entries.insert(0, format!("{} changed a cell", name(ChangeSource::Remote)));
+    }
+    for _ in hint_used_event_reader.iter() {
+        entries.push(String::from("Hint used"));
+    }
+    for _ in puzzle_solved_event_reader.iter() {
+        entries.push(String::from("Puzzle solved"));
+    }
+
+    for entry in entries {
+        feed.push(entry, now);
+    }
+    let max = options
+        .get("feed_entries")
+        .and_then(|max| max.parse().ok())
+        .unwrap_or(DEFAULT_FEED_ENTRIES);
+    if feed.entries.len() > max {
+        feed.entries.truncate(max);
+    }
+    if feed
+        .entries
+        .back()
+        .map_or(false, |(_, t)| now - t > FEED_SECONDS)
+    {
+        feed.entries.retain(|(_, t)| now - t <= FEED_SECONDS);
+    }
+}
+
+fn feed_draw_system(
+    mut commands: Commands,
+    view: Res<BoardView>,
+    win_size: Res<WinSize>,
+    feed: Res<ActivityFeed>,
+    settings: Res<Settings>,
+    speaking: Res<Speaking>,
+    hud_scale: Res<HudScale>,
+    game_textures: Res<GameTextures>,
+    text_query: Query<Entity, With<FeedText>>,
+) {
+    let resized = view.is_changed() || win_size.is_changed() || hud_scale.is_changed();
+    if !(feed.is_changed() || resized || settings.is_changed() || speaking.is_changed()) {
+        return;
+    }
+    for entity in text_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    if !feed.visible || feed.entries.is_empty() || settings.do_not_disturb {
+        return;
+    }
+
+    let style = TextStyle {
+        font: game_textures.font.clone(),
+        font_size: FEED_FONT_SIZE * hud_scale.world(&view),
+        color: Color::WHITE,
+    };
+    let lines: Vec<String> = feed
+        .entries
+        .iter()
+        .map(|(entry, _)| speaking.label_line(entry))
+        .collect();
+    let margin = FEED_MARGIN * hud_scale.0;
+    let corner = view.screen_to_world(Vec2::new(win_size.w - margin, win_size.h - margin));
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(lines.join("\n"), style)
+                .with_alignment(TextAlignment::TOP_RIGHT),
+            transform: Transform::from_xyz(corner.x, corner.y, HUD_Z),
+            ..Default::default()
+        })
+        .insert(FeedText);
+}
@@ -0,0 +1,221 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+
+use crate::camera::BoardView;
+use crate::campaign::thumbnail_image;
+use crate::hud_scale::HudScale;
+use crate::layers::{HUD_PANEL_Z, HUD_Z};
+use crate::screens::Screen;
+use crate::timer::SolveTimer;
+use crate::{GameTextures, WinSize};
+
+// endregion
+
+// region:      CONSTANTS
+
+const PAGE_SECONDS: f32 = 0.5;
+const MAX_FLIP_BOOK_SECONDS: f32 = 6.; // long packs flip faster instead
+const PICTURE_SIZE: f32 = 0.6; // fraction of the window's short side
+const LABEL_FONT_SIZE: f32 = 22.;
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct FlipBookEntity;
+
+#[derive(Component)]
+pub struct FlipBookPage;
+
+#[derive(Component)]
+pub struct FlipBookLabel;
+
+// endregion
+
+// region:      RESOURCES
+
+/// Pictures the room already solved from its pack, from the `art=` sections
+/// of the join payload. They are flipped through over the new board before
+/// it can be played, any key, click or touch skips to the board.
+#[derive(Resource, Default)]
+pub struct FlipBook {
+    pages: Vec<String>,
+    /// Each picture with its size, the longer side being 1.
+    images: Vec<(Handle<Image>, Vec2)>,
+    page: Option<usize>,
+    shown: f32,
+}
+
+impl FlipBook {
+    /// Queues the pages for the board that was just joined.
+    pub fn start(&mut self, pages: Vec<String>) {
+        self.pages = pages;
+        self.images.clear();
+        self.page = None;
+        self.shown = 0.;
+    }
+
+    fn page_seconds(&self) -> f32 {
+        PAGE_SECONDS.min(MAX_FLIP_BOOK_SECONDS / self.pages.len().max(1) as f32)
+    }
+}
+
+// endregion
+
+pub struct FlipBookPlugin;
+
+impl Plugin for FlipBookPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FlipBook>()
+            .add_system(flip_book_open_system)
+            .add_system_set(
+                SystemSet::on_enter(Screen::FlipBook).with_system(flip_book_enter_system),
+            )
+            .add_system_set(
+                SystemSet::on_update(Screen::FlipBook).with_system(flip_book_update_system),
+            )
+            .add_system_set(
+                SystemSet::on_exit(Screen::FlipBook).with_system(flip_book_exit_system),
+            );
+    }
+}
+
+fn flip_book_open_system(mut flip_book: ResMut<FlipBook>, mut screen: ResMut<State<Screen>>) {
+    if !flip_book.is_changed() || flip_book.pages.is_empty() {
+        return;
+    }
+    match screen.current() {
+        Screen::Board => {
+            screen.set(Screen::FlipBook).ok();
+        }
+        // a second join mid flip book has cleared the pictures, it just closes
+        Screen::FlipBook => {}
+        // joined from another screen, the pictures are not worth leaving it
+        _ => flip_book.pages.clear(),
+    }
+}
+
+fn flip_book_enter_system(
+    mut commands: Commands,
+    view: Res<BoardView>,
+    win_size: Res<WinSize>,
+    hud_scale: Res<HudScale>,
+    game_textures: Res<GameTextures>,
+    mut flip_book: ResMut<FlipBook>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    flip_book.images = flip_book
+        .pages
+        .iter()
+        .filter_map(|page| thumbnail_image(page))
+        .map(|image| {
+            let size = image.size();
+            (images.add(image), size / size.max_element())
+        })
+        .collect();
+
+    // backdrop hiding the board underneath
+    let center = view.screen_to_world(Vec2::new(win_size.w, win_size.h) / 2.);
+    commands
+        .spawn(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgb(0.12, 0.12, 0.14),
+                custom_size: Some(Vec2::new(win_size.w, win_size.h) * view.scale),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(center.x, center.y, HUD_Z),
+            ..Default::default()
+        })
+        .insert(FlipBookEntity);
+
+    let side = win_size.w.min(win_size.h) * PICTURE_SIZE * view.scale;
+    commands
+        .spawn(SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(Vec2::splat(side)),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(center.x, center.y, HUD_PANEL_Z),
+            visibility: Visibility { is_visible: false },
+            ..Default::default()
+        })
+        .insert(FlipBookEntity)
+        .insert(FlipBookPage);
+
+    let font_size = LABEL_FONT_SIZE * hud_scale.world(&view);
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: game_textures.font.clone(),
+                    font_size,
+                    color: Color::WHITE,
+                },
+            )
+            .with_alignment(TextAlignment::CENTER),
+            transform: Transform::from_xyz(center.x, center.y - side / 2. - font_size, HUD_PANEL_Z),
+            ..Default::default()
+        })
+        .insert(FlipBookEntity)
+        .insert(FlipBookLabel);
+}
+
+/// Turns the pages, then goes back to the board with a fresh solve clock.
+fn flip_book_update_system(
+    time: Res<Time>,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<MouseButton>>,
+    touches: Res<Touches>,
+    mut flip_book: ResMut<FlipBook>,
+    mut screen: ResMut<State<Screen>>,
+    mut page_query: Query<(&mut Handle<Image>, &mut Sprite, &mut Visibility), With<FlipBookPage>>,
+    mut label_query: Query<&mut Text, With<FlipBookLabel>>,
+) {
+    let page = (flip_book.shown / flip_book.page_seconds()) as usize;
+    flip_book.shown += time.delta_seconds();
+    let skipped = keys.get_just_pressed().next().is_some()
+        || buttons.get_just_pressed().next().is_some()
+        || touches.iter_just_pressed().next().is_some();
+    if skipped || page >= flip_book.images.len() {
+        flip_book.pages.clear();
+        screen.set(Screen::Board).ok();
+        return;
+    }
+    if flip_book.page == Some(page) {
+        return;
+    }
+    flip_book.page = Some(page);
+
+    let (image, size) = &flip_book.images[page];
+    if let Some((mut texture, mut sprite, mut visibility)) = page_query.iter_mut().next() {
+        *texture = image.clone();
+        // the longer side fills the slot, the picture keeps its aspect ratio
+        let side = sprite.custom_size.unwrap_or_default().max_element();
+        sprite.custom_size = Some(*size * side);
+        visibility.is_visible = true;
+    }
+    for mut text in label_query.iter_mut() {
+        let count = flip_book.images.len();
+        text.sections[0].value = format!("Solved so far  {} / {}", page + 1, count);
+    }
+}
+
+fn flip_book_exit_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut flip_book: ResMut<FlipBook>,
+    mut solve_timer: ResMut<SolveTimer>,
+    flip_book_query: Query<Entity, With<FlipBookEntity>>,
+) {
+    for entity in flip_book_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    flip_book.images.clear();
+    // nobody could play while it was showing
+    if solve_timer.finished.is_none() {
+        solve_timer.started = time.elapsed_seconds_f64();
+    }
+}
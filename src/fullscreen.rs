@@ -0,0 +1,63 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+
+use crate::WASMSendChannel;
+
+// endregion
+
+// region:      RESOURCES
+
+/// Last fullscreen state reported to the host.
+#[derive(Resource, Default)]
+pub struct Fullscreen(pub bool);
+
+// endregion
+
+pub struct FullscreenPlugin;
+
+impl Plugin for FullscreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Fullscreen>()
+            .add_system(fullscreen_report_system);
+    }
+}
+
+fn document() -> Option<web_sys::Document> {
+    web_sys::window()?.document()
+}
+
+/// Puts the canvas in fullscreen. Browsers only allow it from a user
+/// gesture, so hosts call this from their button's click handler.
+pub fn request() -> bool {
+    document()
+        .and_then(|document| document.get_element_by_id("bevy-canvas"))
+        .map_or(false, |canvas| canvas.request_fullscreen().is_ok())
+}
+
+pub fn exit() -> bool {
+    match document() {
+        Some(document) if document.fullscreen_element().is_some() => {
+            document.exit_fullscreen();
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Replies `("fullscreen", "1" | "0")` whenever the state changes, including
+/// when the browser leaves fullscreen on its own (Esc). The canvas size
+/// change itself goes through the usual resize check.
+fn fullscreen_report_system(
+    send_channel: Res<WASMSendChannel>,
+    mut fullscreen: ResMut<Fullscreen>,
+) {
+    let active = document().map_or(false, |document| document.fullscreen_element().is_some());
+    if fullscreen.0 != active {
+        fullscreen.0 = active;
+        send_channel.tx.send((
+            String::from("fullscreen"),
+            String::from(if active { "1" } else { "0" }),
+        ));
+    }
+}
@@ -0,0 +1,237 @@
+// region:      IMPORTS
+
+use bevy::{prelude::*, sprite::Anchor, utils::HashMap};
+use picross_handler::Cell;
+
+use crate::board::{cell_to_char, char_to_cell, Board, BoardLoadedEvent};
+use crate::history::History;
+use crate::layers::GHOST_Z;
+use crate::options::BoardOptions;
+use crate::storage;
+use crate::timer::SolveTimer;
+use crate::win::PuzzleSolvedEvent;
+use crate::{GameTextures, TILE_SIZE};
+
+// endregion
+
+// region:      CONSTANTS
+
+const GHOST_ALPHA: f32 = 0.35;
+const GHOST_KEY_PREFIX: &str = "ghost.";
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct GhostTile {
+    pub x: usize,
+    pub y: usize,
+}
+
+// endregion
+
+// region:      RESOURCES
+
+struct GhostStep {
+    t: f64,
+    x: usize,
+    y: usize,
+    cell: Cell,
+}
+
+/// Playback of the best recorded solve of the current puzzle, enabled with
+/// the `ghost` option.
+#[derive(Resource, Default)]
+pub struct GhostRun {
+    steps: Vec<GhostStep>,
+    next: usize,
+    tiles: HashMap<(usize, usize), Entity>,
+    layout: (f32, f32, f32),
+}
+
+// endregion
+
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GhostRun>()
+            .add_system(ghost_record_system)
+            .add_system(ghost_load_system)
+            .add_system(ghost_playback_system)
+            .add_system(ghost_layout_system);
+    }
+}
+
+fn ghost_record_system(
+    time: Res<Time>,
+    board: Res<Board>,
+    history: Res<History>,
+    solve_timer: Res<SolveTimer>,
+    mut puzzle_solved_event_reader: EventReader<PuzzleSolvedEvent>,
+) {
+    for _ in puzzle_solved_event_reader.iter() {
+        let key = format!("{}{}", GHOST_KEY_PREFIX, board.id);
+        let total_ms = solve_timer.elapsed_ms(&time);
+        let best_ms =
+            storage::load(&key).and_then(|saved| saved.split('|').next()?.parse::<u64>().ok());
+        if best_ms.map_or(false, |best_ms| best_ms <= total_ms) {
+            continue;
+        }
+
+        // "total|t,x,y,c;t,x,y,c" with times in milliseconds, cells in the
+        // puzzle's own layout so a randomized replay still lines up
+        let (w, h) = (board.p.get_width(), board.p.get_height());
+        let steps: Vec<String> = history
+            .player_entries()
+            .filter(|entry| entry.x < w && entry.y < h)
+            .map(|entry| {
+                let (x, y) = board.orientation.to_original(entry.x, entry.y, w, h);
+                format!(
+                    "{},{},{},{}",
+                    (entry.t * 1000.) as u64,
+                    x,
+                    y,
+                    cell_to_char(entry.cell)
+                )
+            })
+            .collect();
+        storage::save(&key, &format!("{}|{}", total_ms, steps.join(";")));
+    }
+}
+
+fn parse_ghost(saved: &str) -> Vec<GhostStep> {
+    let steps = match saved.split('|').nth(1) {
+        Some(steps) => steps,
+        None => return Vec::new(),
+    };
+
+    steps
+        .split(';')
+        .filter_map(|step| {
+            let mut step_iter = step.split(',');
+            Some(GhostStep {
+                t: step_iter.next()?.parse::<f64>().ok()? / 1000.,
+                x: step_iter.next()?.parse().ok()?,
+                y: step_iter.next()?.parse().ok()?,
+                cell: char_to_cell(step_iter.next()?.chars().next()?)?,
+            })
+        })
+        .collect()
+}
+
+fn ghost_load_system(
+    mut commands: Commands,
+    board: Res<Board>,
+    options: Res<BoardOptions>,
+    mut ghost_run: ResMut<GhostRun>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+) {
+    for _ in board_loaded_event_reader.iter() {
+        for (_, entity) in ghost_run.tiles.drain() {
+            commands.entity(entity).despawn();
+        }
+        ghost_run.next = 0;
+        ghost_run.steps.clear();
+
+        if options.get_bool("ghost", false) {
+            if let Some(saved) = storage::load(&format!("{}{}", GHOST_KEY_PREFIX, board.id)) {
+                let (w, h) = (board.p.get_width(), board.p.get_height());
+                let (ow, oh) = board.orientation.original_size(w, h);
+                ghost_run.steps = parse_ghost(&saved)
+                    .into_iter()
+                    .filter(|step| step.x < ow && step.y < oh)
+                    .map(|step| {
+                        let (x, y) = board.orientation.to_transformed(step.x, step.y, ow, oh);
+                        GhostStep { x, y, ..step }
+                    })
+                    .collect();
+            }
+        }
+    }
+}
+
+fn ghost_playback_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    board: Res<Board>,
+    solve_timer: Res<SolveTimer>,
+    game_textures: Res<GameTextures>,
+    mut ghost_run: ResMut<GhostRun>,
+    mut ghost_query: Query<&mut Handle<Image>, With<GhostTile>>,
+) {
+    let elapsed = solve_timer.elapsed(&time);
+    let ghost_run = ghost_run.as_mut();
+
+    while let Some(step) = ghost_run.steps.get(ghost_run.next) {
+        if step.t > elapsed {
+            break;
+        }
+        ghost_run.next += 1;
+
+        if step.x >= board.p.get_width() || step.y >= board.p.get_height() {
+            continue;
+        }
+
+        let texture = match step.cell {
+            Cell::Filled => game_textures.tile_filled.clone(),
+            Cell::Crossed => game_textures.tile_crossed.clone(),
+            Cell::Empty => {
+                if let Some(entity) = ghost_run.tiles.remove(&(step.x, step.y)) {
+                    commands.entity(entity).despawn();
+                }
+                continue;
+            }
+        };
+
+        if let Some(entity) = ghost_run.tiles.get(&(step.x, step.y)) {
+            if let Ok(mut ghost_texture) = ghost_query.get_mut(*entity) {
+                *ghost_texture = texture;
+            }
+            continue;
+        }
+
+        let (x, y) = board.cell_to_tile(step.x, step.y);
+        let entity = commands
+            .spawn(SpriteBundle {
+                texture,
+                sprite: Sprite {
+                    color: Color::rgba(1., 1., 1., GHOST_ALPHA),
+                    custom_size: Some(Vec2::splat(TILE_SIZE.0)),
+                    anchor: Anchor::BottomLeft,
+                    ..Default::default()
+                },
+                transform: Transform {
+                    translation: board.tile_translation(x, y, GHOST_Z),
+                    scale: Vec3::new(board.tile_scale, board.tile_scale, 1.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(GhostTile {
+                x: step.x,
+                y: step.y,
+            })
+            .id();
+        ghost_run.tiles.insert((step.x, step.y), entity);
+    }
+}
+
+fn ghost_layout_system(
+    board: Res<Board>,
+    mut ghost_run: ResMut<GhostRun>,
+    mut ghost_query: Query<(&mut Transform, &GhostTile)>,
+) {
+    let layout = (board.origin.0, board.origin.1, board.pixels_per_tile);
+    if !board.is_changed() || ghost_run.layout == layout {
+        return;
+    }
+    ghost_run.layout = layout;
+
+    for (mut transform, ghost_tile) in ghost_query.iter_mut() {
+        let (x, y) = board.cell_to_tile(ghost_tile.x, ghost_tile.y);
+        transform.translation = board.tile_translation(x, y, GHOST_Z);
+        transform.scale = Vec3::new(board.tile_scale, board.tile_scale, 1.);
+    }
+}
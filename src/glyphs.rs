@@ -0,0 +1,393 @@
+// region:      IMPORTS
+
+use ab_glyph::{point, Font as _, ScaleFont};
+use bevy::{
+    asset::LoadState,
+    prelude::*,
+    render::{
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+    },
+    sprite::Anchor,
+    text::Text2dSize,
+};
+
+use crate::board::Clue;
+use crate::layers::GLYPH_Z_OFFSET;
+use crate::{GameTextures, WASMSendChannel};
+
+// endregion
+
+// region:      CONSTANTS
+
+const FONT_TIMEOUT_SECONDS: f64 = 8.;
+
+pub const GLYPH_WIDTH: usize = 3;
+pub const GLYPH_HEIGHT: usize = 5;
+pub const GLYPH_PADDING: usize = 1;
+
+// clue numbers rendered once with the real font, 10 per atlas row
+const ATLAS_NUMBERS: usize = 100;
+const ATLAS_COLUMNS: usize = 10;
+const ATLAS_FONT_PX: f32 = 48.;
+const ATLAS_CELL_PADDING: f32 = 2.;
+
+// 3x5 bitmaps for 0-9, rows top to bottom, also printed by export.rs
+pub const DIGIT_BITMAPS: [[u8; GLYPH_HEIGHT]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+// endregion
+
+// region:      COMPONENTS
+
+/// Marks a clue whose digits are drawn from the fallback atlas.
+#[derive(Component)]
+pub struct FallbackDigits;
+
+#[derive(Component)]
+pub struct FallbackDigit;
+
+/// Marks a clue drawn from the number atlas instead of laying out its text.
+#[derive(Component)]
+pub struct AtlasNumber;
+
+#[derive(Component)]
+pub struct AtlasNumberSprite;
+
+// endregion
+
+// region:      RESOURCES
+
+#[derive(Resource, Default)]
+pub struct FontFallback {
+    pub atlas: Option<Handle<TextureAtlas>>,
+    reported: bool,
+}
+
+/// Clue values 0-99 pre-rendered with the clue font. Hundreds of clues share
+/// one texture instead of each going through text layout, and the numbers
+/// are downscaled from a large rendering rather than blurred up.
+#[derive(Resource, Default)]
+pub struct NumberAtlas {
+    pub atlas: Option<Handle<TextureAtlas>>,
+    /// Cell size over the font's pixel size.
+    cell: Vec2,
+}
+
+// endregion
+
+pub struct GlyphPlugin;
+
+impl Plugin for GlyphPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FontFallback>()
+            .init_resource::<NumberAtlas>()
+            .add_system(font_fallback_system)
+            .add_system(spawn_fallback_digits_system)
+            .add_system(fallback_digit_color_system)
+            .add_system(number_atlas_system)
+            .add_system(spawn_atlas_numbers_system.after(number_atlas_system))
+            .add_system(atlas_number_color_system);
+    }
+}
+
+fn font_fallback_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    game_textures: Res<GameTextures>,
+    send_channel: Res<WASMSendChannel>,
+    mut images: ResMut<Assets<Image>>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut fallback: ResMut<FontFallback>,
+    fallback_query: Query<Entity, With<FallbackDigits>>,
+    digit_query: Query<Entity, With<FallbackDigit>>,
+) {
+    let load_state = asset_server.get_load_state(&game_textures.font);
+
+    // the real font arrived late, drop the fallback digits
+    if load_state == LoadState::Loaded {
+        if fallback.atlas.is_some() {
+            fallback.atlas = None;
+            // clues can have other children, only remove the digits
+            for entity in digit_query.iter() {
+                commands.entity(entity).despawn_recursive();
+            }
+            for entity in fallback_query.iter() {
+                commands.entity(entity).remove::<FallbackDigits>();
+            }
+        }
+        return;
+    }
+
+    let timed_out = time.elapsed_seconds_f64() > FONT_TIMEOUT_SECONDS;
+    if fallback.atlas.is_none() && (load_state == LoadState::Failed || timed_out) {
+        let texture = images.add(digit_atlas_image());
+        let atlas = TextureAtlas::from_grid(
+            texture,
+            Vec2::new(GLYPH_WIDTH as f32, GLYPH_HEIGHT as f32),
+            10,
+            1,
+            Some(Vec2::new(GLYPH_PADDING as f32, 0.)),
+            None,
+        );
+        fallback.atlas = Some(texture_atlases.add(atlas));
+
+        if !fallback.reported {
+            fallback.reported = true;
+            warn!(
+                "Clue font unavailable ({:?}), using fallback digits",
+                load_state
+            );
+            send_channel.tx.send((
+                String::from("error"),
+                format!("font failed to load: {:?}", load_state),
+            ));
+        }
+    }
+}
+
+fn digit_atlas_image() -> Image {
+    let width = 10 * GLYPH_WIDTH + 9 * GLYPH_PADDING;
+    let mut data = vec![0u8; width * GLYPH_HEIGHT * 4];
+    for (digit, bitmap) in DIGIT_BITMAPS.iter().enumerate() {
+        let left = digit * (GLYPH_WIDTH + GLYPH_PADDING);
+        for (row, bits) in bitmap.iter().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - column)) != 0 {
+                    let i = (row * width + left + column) * 4;
+                    // white so the sprite color tints it like text
+                    data[i..i + 4].copy_from_slice(&[255, 255, 255, 255]);
+                }
+            }
+        }
+    }
+
+    let mut image = Image::new(
+        Extent3d {
+            width: width as u32,
+            height: GLYPH_HEIGHT as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    // keep the pixel digits crisp when scaled up
+    image.sampler_descriptor = ImageSampler::nearest();
+    image
+}
+
+fn spawn_fallback_digits_system(
+    mut commands: Commands,
+    fallback: Res<FontFallback>,
+    clue_query: Query<(Entity, &Text), (With<Clue>, Without<FallbackDigits>)>,
+) {
+    let atlas = match &fallback.atlas {
+        Some(atlas) => atlas,
+        None => return,
+    };
+
+    for (entity, text) in clue_query.iter() {
+        let section = &text.sections[0];
+        let digits: Vec<usize> = section
+            .value
+            .chars()
+            .filter_map(|c| c.to_digit(10))
+            .map(|d| d as usize)
+            .collect();
+
+        // font_size matches the text height, digits keep the bitmap aspect ratio
+        let height = section.style.font_size;
+        let width = height * GLYPH_WIDTH as f32 / GLYPH_HEIGHT as f32;
+        let advance = width * (GLYPH_WIDTH + GLYPH_PADDING) as f32 / GLYPH_WIDTH as f32;
+        let first = -advance * (digits.len() as f32 - 1.) / 2.;
+
+        commands
+            .entity(entity)
+            .insert(FallbackDigits)
+            .with_children(|parent| {
+                for (i, digit) in digits.iter().enumerate() {
+                    parent
+                        .spawn(SpriteSheetBundle {
+                            sprite: TextureAtlasSprite {
+                                index: *digit,
+                                color: section.style.color,
+                                custom_size: Some(Vec2::new(width, height)),
+                                anchor: Anchor::Center,
+                                ..Default::default()
+                            },
+                            texture_atlas: atlas.clone(),
+                            transform: Transform::from_xyz(
+                                first + i as f32 * advance,
+                                0.,
+                                GLYPH_Z_OFFSET,
+                            ),
+                            ..Default::default()
+                        })
+                        .insert(FallbackDigit);
+                }
+            });
+    }
+}
+
+fn fallback_digit_color_system(
+    clue_query: Query<(&Text, &Children), (With<FallbackDigits>, Changed<Text>)>,
+    mut digit_query: Query<&mut TextureAtlasSprite, With<FallbackDigit>>,
+) {
+    for (text, children) in clue_query.iter() {
+        for child in children.iter() {
+            if let Ok(mut sprite) = digit_query.get_mut(*child) {
+                sprite.color = text.sections[0].style.color;
+            }
+        }
+    }
+}
+
+fn number_atlas_system(
+    fonts: Res<Assets<Font>>,
+    game_textures: Res<GameTextures>,
+    mut images: ResMut<Assets<Image>>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut number_atlas: ResMut<NumberAtlas>,
+) {
+    if number_atlas.atlas.is_some() {
+        return;
+    }
+    let font = match fonts.get(&game_textures.font) {
+        Some(font) => &font.font,
+        None => return,
+    };
+
+    let scaled = font.as_scaled(ATLAS_FONT_PX);
+    let widest_digit = ('0'..='9')
+        .map(|c| scaled.h_advance(font.glyph_id(c)))
+        .fold(0., f32::max);
+    let cell = Vec2::new(
+        (widest_digit * 2. + ATLAS_CELL_PADDING * 2.).ceil(),
+        (scaled.ascent() - scaled.descent() + ATLAS_CELL_PADDING * 2.).ceil(),
+    );
+    let rows = ATLAS_NUMBERS / ATLAS_COLUMNS;
+    let width = cell.x as usize * ATLAS_COLUMNS;
+    let height = cell.y as usize * rows;
+    let mut data = vec![0u8; width * height * 4];
+
+    for number in 0..ATLAS_NUMBERS {
+        let label = number.to_string();
+        let label_width: f32 = label
+            .chars()
+            .map(|c| scaled.h_advance(font.glyph_id(c)))
+            .sum();
+        let left = (number % ATLAS_COLUMNS) as f32 * cell.x;
+        let top = (number / ATLAS_COLUMNS) as f32 * cell.y;
+        let mut x = left + (cell.x - label_width) / 2.;
+        let baseline = top + ATLAS_CELL_PADDING + scaled.ascent();
+
+        for c in label.chars() {
+            let id = font.glyph_id(c);
+            let glyph = id.with_scale_and_position(ATLAS_FONT_PX, point(x, baseline));
+            x += scaled.h_advance(id);
+            let outlined = match font.outline_glyph(glyph) {
+                Some(outlined) => outlined,
+                None => continue,
+            };
+            let bounds = outlined.px_bounds();
+            outlined.draw(|gx, gy, coverage| {
+                let px = bounds.min.x as i32 + gx as i32;
+                let py = bounds.min.y as i32 + gy as i32;
+                // stay inside this number's cell
+                if px < left as i32
+                    || py < top as i32
+                    || px >= (left + cell.x) as i32
+                    || py >= (top + cell.y) as i32
+                {
+                    return;
+                }
+                let i = (py as usize * width + px as usize) * 4;
+                let alpha = (coverage.clamp(0., 1.) * 255.) as u8;
+                // white so the sprite color tints it like text
+                data[i..i + 4].copy_from_slice(&[255, 255, 255, alpha.max(data[i + 3])]);
+            });
+        }
+    }
+
+    let texture = images.add(Image::new(
+        Extent3d {
+            width: width as u32,
+            height: height as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    ));
+    let atlas = TextureAtlas::from_grid(texture, cell, ATLAS_COLUMNS, rows, None, None);
+    number_atlas.atlas = Some(texture_atlases.add(atlas));
+    number_atlas.cell = cell / ATLAS_FONT_PX;
+}
+
+/// Swaps a clue's text for a sprite from the atlas. The text stays the
+/// source of truth for value and color, it just isn't laid out or drawn.
+fn spawn_atlas_numbers_system(
+    mut commands: Commands,
+    number_atlas: Res<NumberAtlas>,
+    clue_query: Query<(Entity, &Text), (With<Clue>, Without<AtlasNumber>, Without<FallbackDigits>)>,
+) {
+    let atlas = match &number_atlas.atlas {
+        Some(atlas) => atlas,
+        None => return,
+    };
+
+    for (entity, text) in clue_query.iter() {
+        let section = &text.sections[0];
+        let number = match section.value.parse::<usize>() {
+            Ok(number) if number < ATLAS_NUMBERS => number,
+            // anything else keeps its text
+            _ => continue,
+        };
+
+        commands
+            .entity(entity)
+            .insert(AtlasNumber)
+            .remove::<Text2dSize>()
+            .with_children(|parent| {
+                parent
+                    .spawn(SpriteSheetBundle {
+                        sprite: TextureAtlasSprite {
+                            index: number,
+                            color: section.style.color,
+                            custom_size: Some(number_atlas.cell * section.style.font_size),
+                            anchor: Anchor::Center,
+                            ..Default::default()
+                        },
+                        texture_atlas: atlas.clone(),
+                        transform: Transform::from_xyz(0., 0., GLYPH_Z_OFFSET),
+                        ..Default::default()
+                    })
+                    .insert(AtlasNumberSprite);
+            });
+    }
+}
+
+fn atlas_number_color_system(
+    clue_query: Query<(&Text, &Children), (With<AtlasNumber>, Changed<Text>)>,
+    mut sprite_query: Query<&mut TextureAtlasSprite, With<AtlasNumberSprite>>,
+) {
+    for (text, children) in clue_query.iter() {
+        for child in children.iter() {
+            if let Ok(mut sprite) = sprite_query.get_mut(*child) {
+                sprite.color = text.sections[0].style.color;
+            }
+        }
+    }
+}
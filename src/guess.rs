@@ -0,0 +1,321 @@
+// region:      IMPORTS
+
+use bevy::{prelude::*, utils::HashSet};
+use picross_handler::Cell;
+
+use crate::board::{Board, BoardLoadedEvent, CellChangedEvent, ChangeSource, SpawnTilesEvent};
+use crate::board_commands::BoardCommand;
+use crate::camera::BoardView;
+use crate::control_profile::ControlProfile;
+use crate::hud_scale::HudScale;
+use crate::layers::{CHILD_Z_OFFSET, HIGHLIGHT_Z, HUD_Z};
+use crate::options::BoardOptions;
+use crate::screens::Screen;
+use crate::GameTextures;
+
+// endregion
+
+// region:      CONSTANTS
+
+// tint over guessed cells, overridable with the guess_color option
+const GUESS_COLOR: Color = Color::rgba(0.6, 0.2, 0.9, 0.35);
+
+// toolbar in the bottom left corner, in HUD pixels
+const BUTTON_SIZE: Vec2 = Vec2::new(96., 36.);
+const BUTTON_MARGIN: f32 = 8.;
+const BUTTON_FONT_SIZE: f32 = 18.;
+const BUTTON_COLOR: Color = Color::rgba(0.2, 0.2, 0.24, 0.9);
+const ACTIVE_BUTTON_COLOR: Color = Color::rgba(0.45, 0.2, 0.6, 0.9);
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct GuessTint;
+
+#[derive(Component)]
+pub struct GuessToolbar;
+
+// endregion
+
+// region:      RESOURCES
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ToolbarButton {
+    Guess,
+    Commit,
+    Revert,
+    /// Handled by the consistency plugin, shown here with the others.
+    Check,
+    /// Handled by the hint plugin.
+    Hint,
+}
+
+/// Guess mode, entered with G or the toolbar. The board is snapshotted on
+/// entry and the cells the player decides afterwards are tinted, until the
+/// guess is committed (G again, keeping them) or reverted (Backspace,
+/// putting the snapshot back).
+#[derive(Resource, Default)]
+pub struct Guess {
+    snapshot: Option<Vec<Cell>>,
+    pub cells: HashSet<(usize, usize)>,
+}
+
+impl Guess {
+    pub fn active(&self) -> bool {
+        self.snapshot.is_some()
+    }
+}
+
+// endregion
+
+pub struct GuessPlugin;
+
+impl Plugin for GuessPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Guess>()
+            .add_system(guess_toolbar_system)
+            .add_system(guess_cells_system.after(guess_toolbar_system))
+            .add_system(guess_tint_system.after(guess_cells_system))
+            .add_system(guess_toolbar_draw_system.after(guess_toolbar_system));
+    }
+}
+
+fn toolbar_buttons(active: bool) -> &'static [ToolbarButton] {
+    match active {
+        true => &[
+            ToolbarButton::Commit,
+            ToolbarButton::Revert,
+            ToolbarButton::Check,
+            ToolbarButton::Hint,
+        ],
+        false => &[
+            ToolbarButton::Guess,
+            ToolbarButton::Check,
+            ToolbarButton::Hint,
+        ],
+    }
+}
+
+/// Center of toolbar button `i` in screen units, left to right or, in a
+/// column, bottom up. `scale` is the HUD's times the profile's.
+fn button_center(i: usize, scale: f32, column: bool) -> Vec2 {
+    let (size, margin) = (BUTTON_SIZE * scale, BUTTON_MARGIN * scale);
+    let first = margin + size / 2.;
+    match column {
+        true => Vec2::new(first.x, first.y + i as f32 * (size.y + margin)),
+        false => Vec2::new(first.x + i as f32 * (size.x + margin), first.y),
+    }
+}
+
+/// The toolbar button under a screen position, the board ignores presses
+/// that land on one.
+pub fn toolbar_button_at(
+    screen_pos: Vec2,
+    active: bool,
+    hud_scale: f32,
+    profile: &ControlProfile,
+) -> Option<ToolbarButton> {
+    let scale = hud_scale * profile.toolbar_scale;
+    let half = BUTTON_SIZE * scale / 2.;
+    toolbar_buttons(active)
+        .iter()
+        .enumerate()
+        .find(|(i, _)| {
+            let center = button_center(*i, scale, profile.toolbar_column);
+            ((screen_pos - center).abs() - half).max_element() <= 0.
+        })
+        .map(|(_, button)| *button)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn guess_toolbar_system(
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    board: Res<Board>,
+    screen: Res<State<Screen>>,
+    hud_scale: Res<HudScale>,
+    profile: Res<ControlProfile>,
+    mut guess: ResMut<Guess>,
+    mut board_command_event_writer: EventWriter<BoardCommand>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+) {
+    // a guess doesn't outlive its board
+    if board_loaded_event_reader.iter().last().is_some() && guess.active() {
+        guess.snapshot = None;
+        guess.cells.clear();
+    }
+    if *screen.current() != Screen::Board {
+        return;
+    }
+
+    let mut pressed = None;
+    if keys.just_pressed(KeyCode::G) {
+        pressed = Some(match guess.active() {
+            true => ToolbarButton::Commit,
+            false => ToolbarButton::Guess,
+        });
+    }
+    if keys.just_pressed(KeyCode::Back) && guess.active() {
+        pressed = Some(ToolbarButton::Revert);
+    }
+    let cursor = windows
+        .get_primary()
+        .and_then(|window| window.cursor_position());
+    if let (true, Some(cursor)) = (buttons.just_pressed(MouseButton::Left), cursor) {
+        pressed = toolbar_button_at(cursor, guess.active(), hud_scale.0, &profile).or(pressed);
+    }
+
+    let (width, height) = (board.p.get_width(), board.p.get_height());
+    match pressed {
+        Some(ToolbarButton::Guess) => {
+            guess.snapshot = Some(
+                (0..height)
+                    .flat_map(|y| (0..width).map(move |x| (x, y)))
+                    .map(|(x, y)| board.p.get_cell(x, y))
+                    .collect(),
+            );
+            guess.cells.clear();
+        }
+        Some(ToolbarButton::Commit) => {
+            guess.snapshot = None;
+            guess.cells.clear();
+        }
+        Some(ToolbarButton::Revert) => {
+            let snapshot = guess.snapshot.take().unwrap_or_default();
+            // the server hears about the revert like any other move
+            for (i, cell) in snapshot.into_iter().enumerate() {
+                let (x, y) = (i % width, i / width);
+                if board.p.get_cell(x, y) != cell {
+                    board_command_event_writer.send(BoardCommand {
+                        x,
+                        y,
+                        cell,
+                        source: ChangeSource::Player,
+                    });
+                }
+            }
+            guess.cells.clear();
+        }
+        Some(ToolbarButton::Check) | Some(ToolbarButton::Hint) | None => {}
+    }
+}
+
+fn guess_cells_system(
+    mut guess: ResMut<Guess>,
+    mut cell_changed_event_reader: EventReader<CellChangedEvent>,
+) {
+    for event in cell_changed_event_reader.iter() {
+        if !guess.active() || event.source != ChangeSource::Player {
+            continue;
+        }
+        if event.new == Cell::Empty {
+            guess.cells.remove(&(event.x, event.y));
+        } else {
+            guess.cells.insert((event.x, event.y));
+        }
+    }
+}
+
+fn guess_tint_system(
+    mut commands: Commands,
+    board: Res<Board>,
+    guess: Res<Guess>,
+    options: Res<BoardOptions>,
+    mut spawn_tiles_event_reader: EventReader<SpawnTilesEvent>,
+    tint_query: Query<Entity, With<GuessTint>>,
+) {
+    let respawned = spawn_tiles_event_reader.iter().last().is_some();
+    if !(guess.is_changed() || respawned) {
+        return;
+    }
+    for entity in tint_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let color = options.get_color("guess_color", GUESS_COLOR);
+    for (x, y) in guess.cells.iter() {
+        let (tile_x, tile_y) = board.cell_to_tile(*x, *y);
+        commands
+            .spawn(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::splat(board.pixels_per_tile)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(board.tile_translation(
+                    tile_x + 0.5,
+                    tile_y + 0.5,
+                    HIGHLIGHT_Z,
+                )),
+                ..Default::default()
+            })
+            .insert(GuessTint);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn guess_toolbar_draw_system(
+    mut commands: Commands,
+    view: Res<BoardView>,
+    guess: Res<Guess>,
+    screen: Res<State<Screen>>,
+    hud_scale: Res<HudScale>,
+    profile: Res<ControlProfile>,
+    game_textures: Res<GameTextures>,
+    toolbar_query: Query<Entity, With<GuessToolbar>>,
+) {
+    let visible = *screen.current() == Screen::Board;
+    let drawn = !toolbar_query.is_empty();
+    let resized = view.is_changed() || hud_scale.is_changed() || profile.is_changed();
+    if !(resized || guess.is_changed() || screen.is_changed() || visible != drawn) {
+        return;
+    }
+    for entity in toolbar_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if !visible {
+        return;
+    }
+
+    let scale = hud_scale.0 * profile.toolbar_scale;
+    let world_scale = hud_scale.world(&view) * profile.toolbar_scale;
+    for (i, button) in toolbar_buttons(guess.active()).iter().enumerate() {
+        let (label, color) = match button {
+            ToolbarButton::Guess => ("Guess", BUTTON_COLOR),
+            ToolbarButton::Commit => ("Commit", ACTIVE_BUTTON_COLOR),
+            ToolbarButton::Revert => ("Revert", ACTIVE_BUTTON_COLOR),
+            ToolbarButton::Check => ("Check", BUTTON_COLOR),
+            ToolbarButton::Hint => ("Hint", BUTTON_COLOR),
+        };
+        let center = view.screen_to_world(button_center(i, scale, profile.toolbar_column));
+        commands
+            .spawn(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(BUTTON_SIZE * world_scale),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(center.extend(HUD_Z)),
+                ..Default::default()
+            })
+            .insert(GuessToolbar)
+            .with_children(|parent| {
+                parent.spawn(Text2dBundle {
+                    text: Text::from_section(
+                        label,
+                        TextStyle {
+                            font: game_textures.font.clone(),
+                            font_size: BUTTON_FONT_SIZE * world_scale,
+                            color: Color::WHITE,
+                        },
+                    )
+                    .with_alignment(TextAlignment::CENTER),
+                    transform: Transform::from_xyz(0., 0., CHILD_Z_OFFSET),
+                    ..Default::default()
+                });
+            });
+    }
+}
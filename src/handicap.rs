@@ -0,0 +1,188 @@
+// region:      IMPORTS
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use picross_handler::Cell;
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use wasm_bindgen::JsValue;
+
+use crate::board::{Board, BoardLoadedEvent, ChangeSource, InputEvent};
+use crate::board_commands::BoardCommand;
+use crate::bridge::reply_error;
+use crate::options::BoardOptions;
+use crate::start::StartLock;
+use crate::stats::Stats;
+use crate::{BridgeCommandEvent, WASMSendChannel};
+
+// endregion
+
+// region:      RESOURCES
+
+/// Per player handicap sent by the server as `("handicap", json)` with any
+/// of `delay_ms`, `hint_budget` and `cross_percent`. Replaced whole by every
+/// message, `{}` clears it.
+#[derive(Resource, Default)]
+pub struct Handicap {
+    /// Player input waits this long before it reaches the board.
+    pub delay_ms: f64,
+    /// Hints the player may take on a puzzle, unlimited when `None`.
+    pub hint_budget: Option<u32>,
+    /// Share of the solution's empty cells crossed for the player on load,
+    /// needs the `solution` option.
+    pub cross_percent: f64,
+    pending: VecDeque<(f64, InputEvent)>,
+    crossed_board: Option<String>,
+}
+
+impl Handicap {
+    fn from_json(data: &str) -> Result<Self, String> {
+        let json = js_sys::JSON::parse(data)
+            .ok()
+            .filter(|json| json.is_object())
+            .ok_or_else(|| format!("handicap: invalid json {}", data))?;
+        let field = |key: &str| {
+            js_sys::Reflect::get(&json, &JsValue::from_str(key))
+                .ok()
+                .and_then(|value| value.as_f64())
+        };
+        Ok(Self {
+            delay_ms: field("delay_ms").unwrap_or(0.).max(0.),
+            hint_budget: field("hint_budget").map(|budget| budget.max(0.) as u32),
+            cross_percent: field("cross_percent").unwrap_or(0.).clamp(0., 100.),
+            ..Default::default()
+        })
+    }
+
+    /// Whether the hint system may hand out another hint.
+    pub fn hint_allowed(&self, stats: &Stats) -> bool {
+        self.hint_budget
+            .map_or(true, |budget| stats.hints_used < budget)
+    }
+}
+
+// endregion
+
+// region:      SYSTEM PARAMS
+
+/// Everything between a player's input and the board: the race start lock
+/// and the input delay.
+#[derive(SystemParam)]
+pub struct InputGate<'w, 's> {
+    time: Res<'w, Time>,
+    start_lock: Res<'w, StartLock>,
+    handicap: ResMut<'w, Handicap>,
+    #[system_param(ignore)]
+    marker: PhantomData<&'s ()>,
+}
+
+impl<'w, 's> InputGate<'w, 's> {
+    /// Input due this frame, delayed player input from earlier frames first.
+    pub fn admit<'a>(&mut self, events: impl Iterator<Item = &'a InputEvent>) -> Vec<InputEvent> {
+        let now = self.time.elapsed_seconds_f64();
+        let mut due = Vec::new();
+        while self
+            .handicap
+            .pending
+            .front()
+            .map_or(false, |(at, _)| *at <= now)
+        {
+            if let Some((_, event)) = self.handicap.pending.pop_front() {
+                due.push(event);
+            }
+        }
+
+        for event in events {
+            if !event.from_player {
+                due.push(event.clone());
+            } else if self.start_lock.locked() {
+                // nothing the player does counts before a race starts
+            } else if self.handicap.delay_ms > 0. {
+                let at = now + self.handicap.delay_ms / 1000.;
+                self.handicap.pending.push_back((at, event.clone()));
+            } else {
+                due.push(event.clone());
+            }
+        }
+        due
+    }
+}
+
+// endregion
+
+pub struct HandicapPlugin;
+
+impl Plugin for HandicapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Handicap>()
+            .add_system(handicap_command_system)
+            .add_system(pre_cross_system.after(handicap_command_system));
+    }
+}
+
+fn handicap_command_system(
+    send_channel: Res<WASMSendChannel>,
+    mut handicap: ResMut<Handicap>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+) {
+    for event in bridge_command_event_reader.iter() {
+        if event.command != "handicap" {
+            continue;
+        }
+        match Handicap::from_json(&event.data) {
+            Ok(new) => *handicap = new,
+            Err(err) => reply_error(&send_channel, err),
+        }
+    }
+}
+
+/// Crosses `cross_percent` of the cells the solution leaves empty, once per
+/// board. These changes stay local, they are never sent to the server.
+fn pre_cross_system(
+    board: Res<Board>,
+    options: Res<BoardOptions>,
+    mut handicap: ResMut<Handicap>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut board_command_event_writer: EventWriter<BoardCommand>,
+) {
+    if board_loaded_event_reader.iter().last().is_some() {
+        handicap.crossed_board = None;
+        // input meant for the last board
+        handicap.pending.clear();
+    }
+    if handicap.cross_percent <= 0. || handicap.crossed_board.as_ref() == Some(&board.id) {
+        return;
+    }
+
+    let (width, height) = (board.p.get_width(), board.p.get_height());
+    let solution = match options
+        .get("solution")
+        .filter(|solution| solution.len() == width * height)
+    {
+        Some(solution) => solution.as_bytes(),
+        None => return,
+    };
+    handicap.crossed_board = Some(board.id.clone());
+
+    let mut candidates: Vec<(usize, usize)> = (0..width * height)
+        .map(|i| (i % width, i / width))
+        .filter(|(x, y)| {
+            // the solution is in the puzzle's own layout, not the randomized one
+            let (sx, sy) = board.orientation.to_original(*x, *y, width, height);
+            solution[sy * width + sx] != b'1' && board.p.get_cell(*x, *y) == Cell::Empty
+        })
+        .collect();
+    let count = (candidates.len() as f64 * handicap.cross_percent / 100.).round() as usize;
+
+    // partial shuffle, the first `count` candidates get crossed
+    for i in 0..count {
+        let j = i + (js_sys::Math::random() * (candidates.len() - i) as f64) as usize;
+        candidates.swap(i, j.min(candidates.len() - 1));
+        let (x, y) = candidates[i];
+        board_command_event_writer.send(BoardCommand {
+            x,
+            y,
+            cell: Cell::Crossed,
+            source: ChangeSource::Handicap,
+        });
+    }
+}
@@ -0,0 +1,379 @@
+// region:      IMPORTS
+
+use bevy::{
+    core::CorePlugin,
+    ecs::event::{Events, ManualEventReader},
+    input::touch::Touches,
+    prelude::*,
+    utils::Duration,
+};
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use picross_handler::Cell;
+
+use crate::archive::ArchiveView;
+use crate::auto_clues::AutoCluesPlugin;
+use crate::board::{
+    cell_to_char, Board, BoardAction, BoardLoadedEvent, BoardPlugin, Clue, InputEvent, Stroke, Tile,
+};
+use crate::camera::CameraPlugin;
+use crate::clean_view::CleanView;
+use crate::context_loss::ContextLoss;
+use crate::control_profile::ControlProfile;
+use crate::flip_book::FlipBook;
+use crate::guess::Guess;
+use crate::handicap::Handicap;
+use crate::hint::HintPlugin;
+use crate::history::HistoryPlugin;
+use crate::hud_scale::HudScale;
+use crate::keyboard::KeyboardPlugin;
+use crate::limits::BoardLimits;
+use crate::options::BoardOptions;
+use crate::pending::PendingPlugin;
+use crate::photo_import::PhotoImportPlugin;
+use crate::playlist::Playlist;
+use crate::rules::RulesPlugin;
+use crate::screens::Screen;
+use crate::settings::Settings;
+use crate::solve_demo::SolveDemoPlugin;
+use crate::speaking::SpeakingPlugin;
+use crate::start::StartLock;
+use crate::stats::Stats;
+use crate::telemetry::HintUsedEvent;
+use crate::timer::TimerPlugin;
+use crate::updates::UpdatePlugin;
+use crate::view_state::ViewStatePlugin;
+use crate::win::{Solved, WinPlugin};
+use crate::win_goal::WinGoalPlugin;
+use crate::{
+    receive_channel_system, BoardUpdateEvent, BridgeCommandEvent, GameTextures, NewBoardEvent,
+    PrintExportEvent, WASMReceiveChannel, WASMSendChannel, WinSize,
+};
+
+// endregion
+
+// region:      CONSTANTS
+
+const TICK: Duration = Duration::from_millis(16);
+const WIN_SIZE: (f32, f32) = (800., 600.);
+const LOAD_TICKS: usize = 500; // parsing runs on the task pool, give it real time
+pub const SETTLE_TICKS: usize = 5; // redraw, delete and spawn each take a frame
+
+// endregion
+
+/// Headless app running the board, update and win pipelines on a fixed clock.
+/// Bridge messages go in through the same channel the host uses, replies come
+/// back out of `WASMSendChannel`.
+pub struct Harness {
+    pub app: App,
+    bridge: Sender<(String, String)>,
+    replies: Receiver<(String, String)>,
+    loaded_reader: ManualEventReader<BoardLoadedEvent>,
+    loaded: usize,
+}
+
+impl Harness {
+    pub fn new() -> Self {
+        let (bridge, rx) = unbounded();
+        let (tx, replies) = unbounded();
+
+        let mut app = App::new();
+        app.add_plugin(CorePlugin::default())
+            .init_resource::<Time>()
+            .insert_resource(WinSize {
+                w: WIN_SIZE.0,
+                h: WIN_SIZE.1,
+            })
+            .insert_resource(stub_textures())
+            // the default limits read the browser navigator
+            .insert_resource(BoardLimits {
+                max_cells: 40000,
+                max_width: None,
+                max_height: None,
+            })
+            .insert_resource(WASMReceiveChannel { rx })
+            .insert_resource(WASMSendChannel { tx })
+            .init_resource::<BoardOptions>()
+            .init_resource::<Playlist>()
+            .init_resource::<FlipBook>()
+            .init_resource::<StartLock>()
+            .init_resource::<Handicap>()
+            .init_resource::<ContextLoss>()
+            .init_resource::<ArchiveView>()
+            .init_resource::<Settings>()
+            .init_resource::<ControlProfile>()
+            .init_resource::<Input<KeyCode>>()
+            .init_resource::<Input<MouseButton>>()
+            .init_resource::<Touches>()
+            .init_resource::<Windows>()
+            .init_resource::<Guess>()
+            .init_resource::<Stats>()
+            .init_resource::<HudScale>()
+            .init_resource::<CleanView>()
+            .add_state(Screen::Board)
+            .add_event::<NewBoardEvent>()
+            .add_event::<BoardUpdateEvent>()
+            .add_event::<PrintExportEvent>()
+            .add_event::<BridgeCommandEvent>()
+            .add_event::<HintUsedEvent>()
+            .add_plugin(BoardPlugin)
+            .add_plugin(RulesPlugin)
+            .add_plugin(WinPlugin)
+            .add_plugin(WinGoalPlugin)
+            .add_plugin(TimerPlugin)
+            .add_plugin(HistoryPlugin)
+            .add_plugin(UpdatePlugin)
+            .add_plugin(PhotoImportPlugin)
+            .add_plugin(SolveDemoPlugin)
+            .add_plugin(PendingPlugin)
+            .add_plugin(KeyboardPlugin)
+            .add_plugin(AutoCluesPlugin)
+            .add_plugin(HintPlugin)
+            .add_plugin(CameraPlugin)
+            .add_plugin(ViewStatePlugin)
+            .add_plugin(SpeakingPlugin)
+            .add_system(receive_channel_system);
+
+        let mut harness = Self {
+            app,
+            bridge,
+            replies,
+            loaded_reader: Default::default(),
+            loaded: 0,
+        };
+        harness.ticks(SETTLE_TICKS);
+        harness
+    }
+
+    /// Advances the clock by one fixed step and runs a frame.
+    pub fn tick(&mut self) {
+        {
+            let mut time = self.app.world.resource_mut::<Time>();
+            let last = time.last_update().unwrap_or_else(|| time.startup());
+            time.update_with_instant(last + TICK);
+        }
+        self.app.update();
+        let events = self.app.world.resource::<Events<BoardLoadedEvent>>();
+        self.loaded += self.loaded_reader.iter(events).count();
+    }
+
+    pub fn ticks(&mut self, n: usize) {
+        for _ in 0..n {
+            self.tick();
+        }
+    }
+
+    /// Queues a message as if the host called `send_wasm`. The bridge handles
+    /// one message per frame, so each message needs a tick.
+    pub fn send(&mut self, command: &str, data: &str) {
+        self.bridge
+            .send((command.to_string(), data.to_string()))
+            .unwrap();
+    }
+
+    /// Joins a puzzle and runs until it is loaded and its tiles are spawned.
+    /// Returns false if the board never loaded.
+    pub fn join(&mut self, clues: &str, cells: &str) -> bool {
+        let loaded = self.loaded;
+        self.send("j", format!("{}SPLIT{}", clues, cells).as_str());
+        for _ in 0..LOAD_TICKS {
+            self.tick();
+            if self.loaded > loaded {
+                self.ticks(SETTLE_TICKS);
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        false
+    }
+
+    /// Sends a player input on a puzzle cell and runs it through the pipeline,
+    /// a click of its own.
+    pub fn input_cell(&mut self, x: usize, y: usize, action: BoardAction) {
+        self.drag(&[(x, y)], action);
+    }
+
+    /// Presses once and paints the cells in order, a frame each, the way the
+    /// input system reports a drag.
+    pub fn drag(&mut self, cells: &[(usize, usize)], action: BoardAction) {
+        self.app.world.resource_mut::<Stroke>().0 += 1;
+        for (x, y) in cells {
+            let (x, y) = self.board().cell_to_tile(*x, *y);
+            self.app
+                .world
+                .resource_mut::<Events<InputEvent>>()
+                .send(InputEvent {
+                    x,
+                    y,
+                    action,
+                    from_player: true,
+                });
+            self.tick();
+        }
+        self.ticks(SETTLE_TICKS);
+    }
+
+    /// Holds keys down together for one frame, then lets go of them.
+    pub fn chord(&mut self, keys: &[KeyCode]) {
+        let mut input = self.app.world.resource_mut::<Input<KeyCode>>();
+        for key in keys {
+            input.press(*key);
+        }
+        self.tick();
+        let mut input = self.app.world.resource_mut::<Input<KeyCode>>();
+        for key in keys {
+            input.release(*key);
+        }
+        input.clear();
+        self.ticks(SETTLE_TICKS);
+    }
+
+    pub fn board(&self) -> &Board {
+        self.app.world.resource::<Board>()
+    }
+
+    /// The board's cells as a `"u"` payload.
+    pub fn cells(&self) -> String {
+        let p = &self.board().p;
+        let mut cells = String::new();
+        for y in 0..p.get_height() {
+            for x in 0..p.get_width() {
+                cells.push(cell_to_char(p.get_cell(x, y)));
+            }
+        }
+        cells
+    }
+
+    pub fn solved(&self) -> bool {
+        self.app.world.resource::<Solved>().0
+    }
+
+    pub fn count<T: Component>(&mut self) -> usize {
+        self.app
+            .world
+            .query_filtered::<(), With<T>>()
+            .iter(&self.app.world)
+            .count()
+    }
+
+    /// Every reply sent to the host since the last call.
+    pub fn replies(&mut self) -> Vec<(String, String)> {
+        self.replies.try_iter().collect()
+    }
+}
+
+fn stub_textures() -> GameTextures {
+    GameTextures {
+        tile_empty: Handle::default(),
+        tile_filled: Handle::default(),
+        tile_crossed: Handle::default(),
+        tile_clue: Handle::default(),
+        font: Handle::default(),
+    }
+}
+
+/// Clue string in picross_handler's format, one line per row then per column.
+pub fn clue_string(rows: &[&[u32]], columns: &[&[u32]]) -> String {
+    let line = |clues: &&[u32]| {
+        clues
+            .iter()
+            .map(|clue| clue.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    let mut lines: Vec<String> = rows.iter().map(line).collect();
+    lines.push(String::from("break"));
+    lines.extend(columns.iter().map(line));
+    lines.join("\n")
+}
+
+/// X.X
+/// XXX
+/// X.X
+pub fn plus_puzzle() -> String {
+    clue_string(&[&[1, 1], &[3], &[1, 1]], &[&[3], &[1], &[3]])
+}
+
+// region:      TESTS
+
+// round trips through the bridge and several plugins, tests of one plugin
+// live next to it
+
+const SOLUTION: &str = "101111101";
+
+#[test]
+fn join_spawns_tiles_and_clues() {
+    let mut harness = Harness::new();
+    assert!(harness.join(plus_puzzle().as_str(), "000000000"));
+
+    assert_eq!(harness.board().p.get_width(), 3);
+    assert_eq!(harness.board().p.get_height(), 3);
+    assert_eq!(harness.cells(), "000000000");
+    assert_eq!(harness.count::<Tile>(), 9);
+    assert!(harness.count::<Clue>() > 0);
+}
+
+#[test]
+fn join_applies_initial_cells() {
+    let mut harness = Harness::new();
+    assert!(harness.join(plus_puzzle().as_str(), "1X0000000"));
+
+    assert_eq!(harness.cells(), "1X0000000");
+}
+
+#[test]
+fn player_input_sets_cell_and_reports_it() {
+    let mut harness = Harness::new();
+    assert!(harness.join(plus_puzzle().as_str(), "000000000"));
+    harness.replies();
+
+    harness.input_cell(1, 1, BoardAction::Fill);
+
+    assert!(harness.board().p.get_cell(1, 1) == Cell::Filled);
+    let expected = format!(
+        "{},{}",
+        harness.board().p.get_pos(1, 1),
+        cell_to_char(Cell::Filled)
+    );
+    assert!(harness.replies().contains(&(String::from("c"), expected)));
+}
+
+#[test]
+fn repeated_input_does_not_report_again() {
+    let mut harness = Harness::new();
+    assert!(harness.join(plus_puzzle().as_str(), "000000000"));
+    harness.input_cell(0, 0, BoardAction::Cross);
+    harness.replies();
+
+    harness.input_cell(0, 0, BoardAction::Cross);
+
+    assert!(harness.replies().iter().all(|(command, _)| command != "c"));
+}
+
+#[test]
+fn remote_update_applies_cells_without_reporting() {
+    let mut harness = Harness::new();
+    assert!(harness.join(plus_puzzle().as_str(), "000000000"));
+    harness.replies();
+
+    harness.send("u", "X10000001");
+    harness.ticks(SETTLE_TICKS);
+
+    assert_eq!(harness.cells(), "X10000001");
+    assert!(harness.replies().iter().all(|(command, _)| command != "c"));
+}
+
+#[test]
+fn solving_the_board_marks_it_solved() {
+    let mut harness = Harness::new();
+    assert!(harness.join(plus_puzzle().as_str(), "000000000"));
+    assert!(!harness.solved());
+
+    harness.replies();
+    harness.send("u", SOLUTION);
+    harness.ticks(SETTLE_TICKS);
+
+    assert!(harness.solved());
+    assert!(harness.replies().iter().any(|(command, _)| command == "w"));
+}
+
+// endregion
@@ -0,0 +1,48 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+
+use crate::board::{Board, Loading};
+use crate::bridge::cells_checksum;
+use crate::options::BoardOptions;
+use crate::WASMSendChannel;
+
+// endregion
+
+// region:      CONSTANTS
+
+const DEFAULT_HEARTBEAT_SECONDS: f32 = 5.;
+
+// endregion
+
+pub struct HeartbeatPlugin;
+
+impl Plugin for HeartbeatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(heartbeat_system);
+    }
+}
+
+/// Sends `("hb", checksum)` every `heartbeat` seconds (0 turns it off), the
+/// checksum of the cells as a `"u"` payload would carry them, in the
+/// puzzle's own layout. A server whose own checksum differs pushes the full
+/// state back.
+fn heartbeat_system(
+    time: Res<Time>,
+    board: Res<Board>,
+    loading: Res<Loading>,
+    options: Res<BoardOptions>,
+    send_channel: Res<WASMSendChannel>,
+    mut last: Local<f64>,
+) {
+    let interval = options.get_f32("heartbeat", DEFAULT_HEARTBEAT_SECONDS) as f64;
+    let now = time.elapsed_seconds_f64();
+    if interval <= 0. || now - *last < interval || loading.0 || board.id.is_empty() {
+        return;
+    }
+    *last = now;
+
+    send_channel
+        .tx
+        .send((String::from("hb"), cells_checksum(&board.original_cells())));
+}
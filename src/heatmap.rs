@@ -0,0 +1,202 @@
+// region:      IMPORTS
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+    },
+    sprite::Anchor,
+};
+
+use crate::board::{Board, BoardLoadedEvent, CellChangedEvent};
+use crate::layers::HIGHLIGHT_Z;
+use crate::options::BoardOptions;
+
+// endregion
+
+// region:      CONSTANTS
+
+// ramp ends, overridable with the heatmap_cold and heatmap_hot options
+const HEATMAP_COLD: Color = Color::rgba(0.2, 0.4, 1., 0.25);
+const HEATMAP_HOT: Color = Color::rgba(1., 0.15, 0.1, 0.65);
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct HeatmapOverlay;
+
+// endregion
+
+// region:      RESOURCES
+
+/// How many times each cell changed this session, local and remote.
+#[derive(Resource, Default)]
+pub struct EditCounts {
+    width: usize,
+    height: usize,
+    counts: Vec<u32>,
+}
+
+impl EditCounts {
+    pub fn get(&self, x: usize, y: usize) -> u32 {
+        self.counts.get(y * self.width + x).copied().unwrap_or(0)
+    }
+}
+
+/// Toggled with H, starts on when the `heatmap` option is set.
+#[derive(Resource, Default)]
+pub struct Heatmap {
+    pub visible: bool,
+    image: Option<Handle<Image>>,
+}
+
+// endregion
+
+pub struct HeatmapPlugin;
+
+impl Plugin for HeatmapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditCounts>()
+            .init_resource::<Heatmap>()
+            .add_system(edit_count_system)
+            .add_system(heatmap_toggle_system)
+            .add_system(
+                heatmap_overlay_system
+                    .after(edit_count_system)
+                    .after(heatmap_toggle_system),
+            );
+    }
+}
+
+fn edit_count_system(
+    board: Res<Board>,
+    mut edit_counts: ResMut<EditCounts>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut cell_changed_event_reader: EventReader<CellChangedEvent>,
+) {
+    if board_loaded_event_reader.iter().last().is_some() {
+        edit_counts.width = board.p.get_width();
+        edit_counts.height = board.p.get_height();
+        edit_counts.counts = vec![0; edit_counts.width * edit_counts.height];
+    }
+
+    for event in cell_changed_event_reader.iter() {
+        let i = event.y * edit_counts.width + event.x;
+        if let Some(count) = edit_counts.counts.get_mut(i) {
+            *count += 1;
+        }
+    }
+}
+
+fn heatmap_toggle_system(
+    keys: Res<Input<KeyCode>>,
+    options: Res<BoardOptions>,
+    mut heatmap: ResMut<Heatmap>,
+) {
+    if options.is_changed() {
+        heatmap.visible = options.get_bool("heatmap", false);
+    }
+    if keys.just_pressed(KeyCode::H) {
+        heatmap.visible = !heatmap.visible;
+    }
+}
+
+fn heatmap_overlay_system(
+    mut commands: Commands,
+    board: Res<Board>,
+    options: Res<BoardOptions>,
+    edit_counts: Res<EditCounts>,
+    mut heatmap: ResMut<Heatmap>,
+    mut images: ResMut<Assets<Image>>,
+    mut overlay_query: Query<
+        (Entity, &mut Sprite, &mut Transform, &mut Visibility),
+        With<HeatmapOverlay>,
+    >,
+) {
+    if !heatmap.visible {
+        for (_, _, _, mut visibility) in overlay_query.iter_mut() {
+            visibility.is_visible = false;
+        }
+        return;
+    }
+
+    let position = board.tile_translation(board.grid_left() as f32, 0., HIGHLIGHT_Z);
+    let size =
+        Vec2::new(board.p.get_width() as f32, board.p.get_height() as f32) * board.pixels_per_tile;
+
+    // one texel per cell, stretched over the grid
+    if edit_counts.is_changed() || heatmap.is_changed() || options.is_changed() {
+        let image = heatmap_image(&edit_counts, &options);
+        match &heatmap.image {
+            Some(handle) => {
+                let _ = images.set(handle, image);
+            }
+            None => heatmap.image = Some(images.add(image)),
+        }
+    }
+
+    if let Some((_, mut sprite, mut transform, mut visibility)) = overlay_query.iter_mut().next() {
+        sprite.custom_size = Some(size);
+        transform.translation = position;
+        visibility.is_visible = true;
+        return;
+    }
+
+    if let Some(handle) = &heatmap.image {
+        commands
+            .spawn(SpriteBundle {
+                texture: handle.clone(),
+                sprite: Sprite {
+                    custom_size: Some(size),
+                    anchor: Anchor::BottomLeft,
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(position),
+                ..Default::default()
+            })
+            .insert(HeatmapOverlay);
+    }
+}
+
+fn heatmap_image(edit_counts: &EditCounts, options: &BoardOptions) -> Image {
+    let cold = options.get_color("heatmap_cold", HEATMAP_COLD);
+    let hot = options.get_color("heatmap_hot", HEATMAP_HOT);
+    let max = edit_counts.counts.iter().copied().max().unwrap_or(0).max(1);
+    let (width, height) = (edit_counts.width.max(1), edit_counts.height.max(1));
+
+    let mut data = vec![0u8; width * height * 4];
+    for y in 0..edit_counts.height {
+        for x in 0..edit_counts.width {
+            let count = edit_counts.get(x, y);
+            if count == 0 {
+                continue;
+            }
+            // texel rows run top down, board rows bottom up
+            let i = ((height - 1 - y) * width + x) * 4;
+            let color = lerp_color(cold, hot, (count - 1) as f32 / (max - 1).max(1) as f32);
+            data[i..i + 4].copy_from_slice(&color.as_rgba_u8());
+        }
+    }
+
+    let mut image = Image::new(
+        Extent3d {
+            width: width as u32,
+            height: height as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    image.sampler_descriptor = ImageSampler::nearest();
+    image
+}
+
+pub fn lerp_color(a: Color, b: Color, t: f32) -> Color {
+    let (a, b) = (a.as_rgba_f32(), b.as_rgba_f32());
+    let mix = |i: usize| a[i] + (b[i] - a[i]) * t;
+    Color::rgba(mix(0), mix(1), mix(2), mix(3))
+}
@@ -0,0 +1,239 @@
+// region:      IMPORTS
+
+use bevy::{prelude::*, sprite::Anchor};
+use picross_handler::Cell;
+
+use crate::archive::ArchiveView;
+use crate::board::{cell_to_char, Board, BoardLoadedEvent, ChangeSource, SpawnTilesEvent};
+use crate::board_commands::{player_moves_allowed, BoardCommand};
+use crate::bridge::reply_error;
+use crate::context_loss::ContextLoss;
+use crate::control_profile::ControlProfile;
+use crate::guess::{toolbar_button_at, Guess, ToolbarButton};
+use crate::handicap::Handicap;
+use crate::hud_scale::HudScale;
+use crate::layers::HIGHLIGHT_Z;
+use crate::options::BoardOptions;
+use crate::screens::Screen;
+use crate::solver::forced;
+use crate::start::StartLock;
+use crate::stats::Stats;
+use crate::telemetry::HintUsedEvent;
+use crate::win::Solved;
+use crate::{BridgeCommandEvent, WASMSendChannel};
+
+// endregion
+
+// region:      CONSTANTS
+
+const HIGHLIGHT_SECONDS: f64 = 1.5;
+const HIGHLIGHT_COLOR: Color = Color::rgba(0.3, 0.9, 0.4, 0.6);
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct HintHighlight;
+
+// endregion
+
+// region:      RESOURCES
+
+/// The cell the last hint decided, highlighted until `until`.
+#[derive(Resource, Default)]
+pub struct Hint {
+    cell: Option<(usize, usize)>,
+    until: f64,
+}
+
+// endregion
+
+pub struct HintPlugin;
+
+impl Plugin for HintPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Hint>()
+            .add_system(hint_system)
+            .add_system(hint_highlight_system.after(hint_system));
+    }
+}
+
+/// The toolbar's Hint button or `("h", "")` decides one cell the board's
+/// lines force as it stands, a fill when there is one, as the player's own
+/// move. Replies `("h", "pos,cell")` like a `c` move, or `("h", "none")`
+/// when line logic finds nothing, which doesn't use up a hint. Hints are
+/// limited by the handicap's `hint_budget`, and refused with an error
+/// whenever the player couldn't make the move themselves.
+#[allow(clippy::too_many_arguments)]
+fn hint_system(
+    time: Res<Time>,
+    buttons: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    board: Res<Board>,
+    guess: Res<Guess>,
+    solved: Res<Solved>,
+    stats: Res<Stats>,
+    handicap: Res<Handicap>,
+    archive_view: Res<ArchiveView>,
+    start_lock: Res<StartLock>,
+    context_loss: Res<ContextLoss>,
+    options: Res<BoardOptions>,
+    screen: Res<State<Screen>>,
+    hud_scale: Res<HudScale>,
+    profile: Res<ControlProfile>,
+    send_channel: Res<WASMSendChannel>,
+    mut hint: ResMut<Hint>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+    mut board_command_event_writer: EventWriter<BoardCommand>,
+    mut hint_used_event_writer: EventWriter<HintUsedEvent>,
+) {
+    if board_loaded_event_reader.iter().last().is_some() && hint.cell.is_some() {
+        hint.cell = None;
+    }
+    let now = time.elapsed_seconds_f64();
+    if hint.cell.is_some() && now >= hint.until {
+        hint.cell = None;
+    }
+
+    let mut requested = bridge_command_event_reader
+        .iter()
+        .filter(|event| event.command == "h")
+        .count()
+        > 0;
+    if *screen.current() == Screen::Board {
+        let cursor = windows
+            .get_primary()
+            .and_then(|window| window.cursor_position());
+        if let (true, Some(cursor)) = (buttons.just_pressed(MouseButton::Left), cursor) {
+            requested |= toolbar_button_at(cursor, guess.active(), hud_scale.0, &profile)
+                == Some(ToolbarButton::Hint);
+        }
+    }
+    if !requested {
+        return;
+    }
+
+    if solved.0 {
+        reply_error(&send_channel, String::from("h: nothing left to hint"));
+        return;
+    }
+    // the hint is the player's move, refused where theirs would be
+    if let Err(reason) = player_moves_allowed(&start_lock, &context_loss, &options, &archive_view) {
+        reply_error(&send_channel, format!("h: {}", reason));
+        return;
+    }
+    if !handicap.hint_allowed(&stats) {
+        reply_error(&send_channel, String::from("h: no hints left"));
+        return;
+    }
+    // a broken line would hint from a board that can't be finished
+    let cells = match forced(&board.p) {
+        Ok(cells) => cells,
+        Err(line) => {
+            reply_error(&send_channel, format!("h: {} can't match its clues", line));
+            return;
+        }
+    };
+    let pick = cells
+        .iter()
+        .find(|(_, _, cell)| *cell == Cell::Filled)
+        .or_else(|| cells.first());
+    let (x, y, cell) = match pick {
+        Some(pick) => *pick,
+        None => {
+            send_channel
+                .tx
+                .send((String::from("h"), String::from("none")));
+            return;
+        }
+    };
+    board_command_event_writer.send(BoardCommand {
+        x,
+        y,
+        cell,
+        source: ChangeSource::Player,
+    });
+    hint_used_event_writer.send(HintUsedEvent);
+    send_channel.tx.send((
+        String::from("h"),
+        format!("{},{}", board.original_pos(x, y), cell_to_char(cell)),
+    ));
+    hint.cell = Some((x, y));
+    hint.until = now + HIGHLIGHT_SECONDS;
+}
+
+/// The hinted cell, fading out over `HIGHLIGHT_SECONDS`.
+fn hint_highlight_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    board: Res<Board>,
+    hint: Res<Hint>,
+    mut spawn_tiles_event_reader: EventReader<SpawnTilesEvent>,
+    mut highlight_query: Query<(Entity, &mut Sprite), With<HintHighlight>>,
+) {
+    let respawned = spawn_tiles_event_reader.iter().count() > 0;
+    if hint.is_changed() || respawned {
+        for (entity, _) in highlight_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        if let Some((x, y)) = hint.cell {
+            let (x, y) = board.cell_to_tile(x, y);
+            commands
+                .spawn(SpriteBundle {
+                    sprite: Sprite {
+                        color: HIGHLIGHT_COLOR,
+                        custom_size: Some(Vec2::splat(board.pixels_per_tile)),
+                        anchor: Anchor::BottomLeft,
+                        ..Default::default()
+                    },
+                    transform: Transform::from_translation(board.tile_translation(
+                        x,
+                        y,
+                        HIGHLIGHT_Z,
+                    )),
+                    ..Default::default()
+                })
+                .insert(HintHighlight);
+        }
+        return;
+    }
+
+    let left = ((hint.until - time.elapsed_seconds_f64()) / HIGHLIGHT_SECONDS).clamp(0., 1.);
+    for (_, mut sprite) in highlight_query.iter_mut() {
+        sprite.color.set_a(HIGHLIGHT_COLOR.a() * left as f32);
+    }
+}
+
+// region:      TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::harness::{plus_puzzle, Harness, SETTLE_TICKS};
+
+    #[test]
+    fn hint_decides_one_forced_cell() {
+        let mut harness = Harness::new();
+        assert!(harness.join(plus_puzzle().as_str(), "000000000"));
+
+        // the bottom row's 1,1 in three cells forces a fill first
+        harness.send("h", "");
+        harness.ticks(SETTLE_TICKS);
+        assert_eq!(harness.cells(), "100000000");
+        assert!(harness.replies().iter().any(|(command, _)| command == "h"));
+
+        // a spectator's hint would be dropped by the board, so it's refused
+        harness.send("o", "spectate=1");
+        harness.tick();
+        harness.send("h", "");
+        harness.ticks(SETTLE_TICKS);
+        assert_eq!(harness.cells(), "100000000");
+        let replies = harness.replies();
+        assert!(replies.iter().any(|(command, _)| command == "error"));
+        assert!(replies.iter().all(|(command, _)| command != "h"));
+    }
+}
+
+// endregion
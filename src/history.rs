@@ -0,0 +1,364 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+use picross_handler::Cell;
+
+use crate::board::{Board, BoardLoadedEvent, CellChangedEvent, ChangeSource, Stroke};
+use crate::board_commands::BoardCommand;
+use crate::screens::Screen;
+use crate::telemetry::HintUsedEvent;
+use crate::timer::SolveTimer;
+
+// endregion
+
+// region:      CONSTANTS
+
+/// Entries between snapshots, the most a projection ever replays.
+const SNAPSHOT_INTERVAL: usize = 64;
+
+const UNDO_KEY: KeyCode = KeyCode::Z;
+const REDO_KEY: KeyCode = KeyCode::Y;
+
+// endregion
+
+// region:      RESOURCES
+
+#[derive(Clone)]
+pub struct HistoryEntry {
+    /// Seconds since the puzzle loaded.
+    pub t: f64,
+    pub x: usize,
+    pub y: usize,
+    /// The cell before and after the change.
+    pub old: Cell,
+    pub cell: Cell,
+    pub from_player: bool,
+}
+
+/// Player moves undone together: the cells of one stroke, or of one batch
+/// of commands applied in the same frame.
+struct UndoStep {
+    entries: Vec<usize>,
+    stroke: u64,
+    /// Frame of the latest entry.
+    frame: u64,
+}
+
+impl UndoStep {
+    /// Each changed cell once, with what it was before the step and what
+    /// the step left it as, in the order the step first changed them.
+    fn cells(&self, entries: &[HistoryEntry]) -> Vec<(usize, usize, Cell, Cell)> {
+        let mut cells: Vec<(usize, usize, Cell, Cell)> = Vec::new();
+        for entry in self.entries.iter().map(|index| &entries[*index]) {
+            match cells
+                .iter_mut()
+                .find(|(x, y, _, _)| (*x, *y) == (entry.x, entry.y))
+            {
+                Some((_, _, _, cell)) => *cell = entry.cell,
+                None => cells.push((entry.x, entry.y, entry.old, entry.cell)),
+            }
+        }
+        cells
+    }
+}
+
+/// The log of the current puzzle: the board as it was loaded and every
+/// applied `CellChangedEvent` after it, in order, plus when hints were taken.
+/// The log is the record of the solve, `Board::p` is just its latest state
+/// kept up to date by `board_command_system`. Undo and redo, the timelapse,
+/// the audit, ghosts and crash recovery all read the board at some point of
+/// the log through `cells_at`.
+#[derive(Resource, Default)]
+pub struct History {
+    pub entries: Vec<HistoryEntry>,
+    /// Seconds since the puzzle loaded.
+    pub hints: Vec<f64>,
+    width: usize,
+    /// The board after every `SNAPSHOT_INTERVAL` entries, the first one the
+    /// board as loaded.
+    snapshots: Vec<Vec<Cell>>,
+    /// Steps of player moves that can be taken back, latest last.
+    undo: Vec<UndoStep>,
+    /// Steps taken back that can be made again, latest last.
+    redo: Vec<UndoStep>,
+    /// Undo and redo moves sent but not logged yet, they leave both stacks
+    /// alone when they come back as changes.
+    replaying: Vec<(usize, usize, Cell)>,
+}
+
+impl History {
+    pub fn player_entries(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.entries.iter().filter(|entry| entry.from_player)
+    }
+
+    /// Starts a new log from the board as it was loaded.
+    fn start(&mut self, board: &Board) {
+        let (width, height) = (board.p.get_width(), board.p.get_height());
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| board.p.get_cell(x, y))
+            .collect();
+        *self = Self {
+            width,
+            snapshots: vec![cells],
+            ..Default::default()
+        };
+    }
+
+    /// Width and height of the logged board.
+    pub fn size(&self) -> (usize, usize) {
+        let cells = self.snapshots.first().map_or(0, Vec::len);
+        (self.width, cells.checked_div(self.width).unwrap_or(0))
+    }
+
+    /// The board after the first `count` entries, row by row from the bottom,
+    /// replayed from the closest snapshot before it.
+    pub fn cells_at(&self, count: usize) -> Vec<Cell> {
+        let count = count.min(self.entries.len());
+        let snapshot = (count / SNAPSHOT_INTERVAL).min(self.snapshots.len().saturating_sub(1));
+        let mut cells = match self.snapshots.get(snapshot) {
+            Some(cells) => cells.clone(),
+            None => return Vec::new(),
+        };
+        for entry in &self.entries[snapshot * SNAPSHOT_INTERVAL..count] {
+            // entries of a board replaced in the same frame fall outside
+            if entry.x < self.width {
+                if let Some(cell) = cells.get_mut(entry.y * self.width + entry.x) {
+                    *cell = entry.cell;
+                }
+            }
+        }
+        cells
+    }
+
+    /// Logs a change. A player move joins the latest undo step when it is
+    /// part of the same stroke or the same frame's batch.
+    fn push(&mut self, entry: HistoryEntry, stroke: u64, frame: u64) {
+        let index = self.entries.len();
+        if entry.from_player {
+            let change = (entry.x, entry.y, entry.cell);
+            match self.replaying.iter().position(|pending| *pending == change) {
+                Some(pending) => {
+                    self.replaying.remove(pending);
+                }
+                None => {
+                    match self.undo.last_mut() {
+                        Some(step) if step.stroke == stroke || step.frame == frame => {
+                            step.entries.push(index);
+                            step.frame = frame;
+                        }
+                        _ => self.undo.push(UndoStep {
+                            entries: vec![index],
+                            stroke,
+                            frame,
+                        }),
+                    }
+                    self.redo.clear();
+                    self.replaying.clear();
+                }
+            }
+        }
+        self.entries.push(entry);
+        if self.entries.len() % SNAPSHOT_INTERVAL == 0 && !self.snapshots.is_empty() {
+            let snapshot = self.cells_at(self.entries.len());
+            self.snapshots.push(snapshot);
+        }
+    }
+
+    /// The commands taking back the latest player step still on the board.
+    /// Cells another player has since overwritten are left alone, a step
+    /// they overwrote entirely is dropped.
+    fn undo(&mut self, board: &Board) -> Vec<BoardCommand> {
+        while let Some(step) = self.undo.pop() {
+            let changes: Vec<(usize, usize, Cell)> = step
+                .cells(&self.entries)
+                .into_iter()
+                .filter(|(x, y, old, cell)| old != cell && board.p.get_cell(*x, *y) == *cell)
+                .map(|(x, y, old, _)| (x, y, old))
+                .collect();
+            if changes.is_empty() {
+                continue;
+            }
+            self.redo.push(step);
+            return self.replay(changes);
+        }
+        Vec::new()
+    }
+
+    /// The commands making the latest undone step again, on the cells still
+    /// as the undo left them.
+    fn redo(&mut self, board: &Board) -> Vec<BoardCommand> {
+        while let Some(step) = self.redo.pop() {
+            let changes: Vec<(usize, usize, Cell)> = step
+                .cells(&self.entries)
+                .into_iter()
+                .filter(|(x, y, old, cell)| old != cell && board.p.get_cell(*x, *y) == *old)
+                .map(|(x, y, _, cell)| (x, y, cell))
+                .collect();
+            if changes.is_empty() {
+                continue;
+            }
+            self.undo.push(step);
+            return self.replay(changes);
+        }
+        Vec::new()
+    }
+
+    fn replay(&mut self, changes: Vec<(usize, usize, Cell)>) -> Vec<BoardCommand> {
+        self.replaying.extend(changes.iter().copied());
+        changes
+            .into_iter()
+            .map(|(x, y, cell)| BoardCommand {
+                x,
+                y,
+                cell,
+                source: ChangeSource::Player,
+            })
+            .collect()
+    }
+}
+
+// endregion
+
+pub struct HistoryPlugin;
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<History>()
+            .add_system(reset_history_system)
+            .add_system(record_history_system.after(reset_history_system))
+            .add_system(undo_system.after(record_history_system));
+    }
+}
+
+fn reset_history_system(
+    board: Res<Board>,
+    mut history: ResMut<History>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+) {
+    if board_loaded_event_reader.iter().last().is_some() {
+        history.start(&board);
+    }
+}
+
+fn record_history_system(
+    time: Res<Time>,
+    stroke: Res<Stroke>,
+    solve_timer: Res<SolveTimer>,
+    mut history: ResMut<History>,
+    mut cell_changed_event_reader: EventReader<CellChangedEvent>,
+    mut hint_used_event_reader: EventReader<HintUsedEvent>,
+    mut frame: Local<u64>,
+) {
+    *frame += 1;
+    let t = solve_timer.elapsed(&time);
+    for _ in hint_used_event_reader.iter() {
+        history.hints.push(t);
+    }
+    for event in cell_changed_event_reader.iter() {
+        let entry = HistoryEntry {
+            t,
+            x: event.x,
+            y: event.y,
+            old: event.old,
+            cell: event.new,
+            from_player: event.source == ChangeSource::Player,
+        };
+        history.push(entry, stroke.0, *frame);
+    }
+}
+
+/// Ctrl+Z takes back the player's latest stroke, Ctrl+Y or Ctrl+Shift+Z
+/// makes it again. Both are ordinary player moves on the log and go to the
+/// server like any other.
+fn undo_system(
+    keys: Res<Input<KeyCode>>,
+    board: Res<Board>,
+    screen: Res<State<Screen>>,
+    mut history: ResMut<History>,
+    mut board_command_event_writer: EventWriter<BoardCommand>,
+) {
+    let ctrl = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    let shift = keys.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+    if !ctrl || *screen.current() != Screen::Board {
+        return;
+    }
+    let commands = if keys.just_pressed(REDO_KEY) || (shift && keys.just_pressed(UNDO_KEY)) {
+        history.redo(&board)
+    } else if keys.just_pressed(UNDO_KEY) {
+        history.undo(&board)
+    } else {
+        return;
+    };
+    board_command_event_writer.send_batch(commands);
+}
+
+// region:      TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{cell_to_char, BoardAction};
+    use crate::harness::{plus_puzzle, Harness, SETTLE_TICKS};
+
+    #[test]
+    fn history_projects_the_board_at_any_point_of_the_log() {
+        let mut harness = Harness::new();
+        assert!(harness.join(plus_puzzle().as_str(), "1X0000000"));
+        harness.input_cell(1, 1, BoardAction::Fill);
+        harness.send("u", "1X0010001");
+        harness.ticks(SETTLE_TICKS);
+
+        let history = harness.app.world.resource::<History>();
+        let at = |count: usize| -> String {
+            history
+                .cells_at(count)
+                .into_iter()
+                .map(cell_to_char)
+                .collect()
+        };
+        assert_eq!(history.entries.len(), 2);
+        assert_eq!(at(0), "1X0000000");
+        assert_eq!(at(1), "1X0010000");
+        assert_eq!(at(2), harness.cells());
+    }
+
+    #[test]
+    fn undo_takes_back_the_latest_move_and_redo_makes_it_again() {
+        let mut harness = Harness::new();
+        assert!(harness.join(plus_puzzle().as_str(), "000000000"));
+        harness.input_cell(0, 0, BoardAction::Fill);
+        harness.input_cell(1, 0, BoardAction::Cross);
+
+        harness.chord(&[KeyCode::LControl, KeyCode::Z]);
+        assert_eq!(harness.cells(), "100000000");
+        harness.chord(&[KeyCode::LControl, KeyCode::Z]);
+        assert_eq!(harness.cells(), "000000000");
+
+        harness.chord(&[KeyCode::LControl, KeyCode::Y]);
+        assert_eq!(harness.cells(), "100000000");
+        // a new move drops what was left to redo
+        harness.input_cell(2, 2, BoardAction::Fill);
+        harness.chord(&[KeyCode::LControl, KeyCode::Y]);
+        assert_eq!(harness.cells(), "100000001");
+    }
+
+    #[test]
+    fn undo_takes_back_a_whole_stroke_at_once() {
+        let mut harness = Harness::new();
+        assert!(harness.join(plus_puzzle().as_str(), "000000000"));
+        harness.drag(&[(0, 0), (0, 1), (0, 2)], BoardAction::Fill);
+        harness.input_cell(2, 0, BoardAction::Fill);
+        assert_eq!(harness.cells(), "101100100");
+
+        harness.chord(&[KeyCode::LControl, KeyCode::Z]);
+        assert_eq!(harness.cells(), "100100100");
+        harness.chord(&[KeyCode::LControl, KeyCode::Z]);
+        assert_eq!(harness.cells(), "000000000");
+
+        harness.chord(&[KeyCode::LControl, KeyCode::Y]);
+        assert_eq!(harness.cells(), "100100100");
+    }
+}
+
+// endregion
@@ -0,0 +1,66 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+
+use crate::camera::BoardView;
+use crate::settings::Settings;
+use crate::WinSize;
+
+// endregion
+
+// region:      CONSTANTS
+
+/// Window short side the HUD's pixel sizes were picked for.
+const REFERENCE_SHORT_SIDE: f32 = 720.;
+const MIN_WINDOW_SCALE: f32 = 0.8;
+const MAX_WINDOW_SCALE: f32 = 2.5;
+
+// endregion
+
+// region:      RESOURCES
+
+/// Size of the HUD (toolbar, timers, banners, overlay text) relative to its
+/// nominal pixel sizes. It follows the window, not the board, so a huge
+/// puzzle doesn't shrink the HUD and a phone doesn't blow it up, times the
+/// per device `ui_scale` setting.
+#[derive(Resource)]
+pub struct HudScale(pub f32);
+
+impl Default for HudScale {
+    fn default() -> Self {
+        Self(1.)
+    }
+}
+
+impl HudScale {
+    /// World units per HUD pixel in the current view.
+    pub fn world(&self, view: &BoardView) -> f32 {
+        self.0 * view.scale
+    }
+}
+
+// endregion
+
+pub struct HudScalePlugin;
+
+impl Plugin for HudScalePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HudScale>().add_system(hud_scale_system);
+    }
+}
+
+fn hud_scale_system(
+    win_size: Res<WinSize>,
+    settings: Res<Settings>,
+    mut hud_scale: ResMut<HudScale>,
+) {
+    if !(win_size.is_changed() || settings.is_changed()) {
+        return;
+    }
+    let window = (win_size.w.min(win_size.h) / REFERENCE_SHORT_SIDE)
+        .clamp(MIN_WINDOW_SCALE, MAX_WINDOW_SCALE);
+    let scale = window * settings.ui_scale;
+    if hud_scale.0 != scale {
+        hud_scale.0 = scale;
+    }
+}
@@ -0,0 +1,169 @@
+// region:      IMPORTS
+
+use bevy::{prelude::*, sprite::Anchor};
+
+use crate::board::{Board, BoardLoadedEvent, CellChangedEvent};
+use crate::layers::EFFECT_Z;
+use crate::lines::{column_cells, column_clues, forced_cells, row_cells, row_clues};
+use crate::marks::ClueLine;
+use crate::screens::Screen;
+use crate::settings::Settings;
+use crate::win::Solved;
+
+// endregion
+
+// region:      CONSTANTS
+
+const SHIMMER_SPEED: f32 = 0.4; // clue bands per second, slower than the update shimmer
+const SHIMMER_LENGTH: f32 = 0.35; // fraction of the clue band
+const SHIMMER_ALPHA: f32 = 0.3;
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct IdleShimmer;
+
+// endregion
+
+// region:      RESOURCES
+
+/// When the board last changed and, once it has sat still long enough, the
+/// line being nudged. Only the line's clues shimmer, never its cells.
+#[derive(Resource, Default)]
+pub struct IdleHint {
+    last_change: f64,
+    pub line: Option<ClueLine>,
+}
+
+// endregion
+
+pub struct IdleHintPlugin;
+
+impl Plugin for IdleHintPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<IdleHint>()
+            .add_system(idle_hint_system)
+            .add_system(idle_shimmer_system.after(idle_hint_system));
+    }
+}
+
+/// The line with the most cells it forces on its own, the easiest place to
+/// pick the puzzle back up.
+fn stuck_line(board: &Board) -> Option<ClueLine> {
+    let p = &board.p;
+    let rows = (0..p.get_height()).map(|y| {
+        let forced = forced_cells(&row_clues(p, y), &row_cells(p, y)).len();
+        (ClueLine::Row(y), forced)
+    });
+    let columns = (0..p.get_width()).map(|x| {
+        let forced = forced_cells(&column_clues(p, x), &column_cells(p, x)).len();
+        (ClueLine::Column(x), forced)
+    });
+    rows.chain(columns)
+        .filter(|(_, forced)| *forced > 0)
+        .max_by_key(|(_, forced)| *forced)
+        .map(|(line, _)| line)
+}
+
+fn idle_hint_system(
+    time: Res<Time>,
+    board: Res<Board>,
+    solved: Res<Solved>,
+    settings: Res<Settings>,
+    mut idle_hint: ResMut<IdleHint>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut cell_changed_event_reader: EventReader<CellChangedEvent>,
+) {
+    let now = time.elapsed_seconds_f64();
+    let loaded = board_loaded_event_reader.iter().count() > 0;
+    let changed = cell_changed_event_reader.iter().count() > 0;
+    if loaded || changed || settings.is_changed() {
+        idle_hint.last_change = now;
+        if idle_hint.line.is_some() {
+            idle_hint.line = None;
+        }
+        return;
+    }
+
+    let idle = now - idle_hint.last_change >= settings.idle_hint_seconds as f64;
+    if !settings.idle_hints || solved.0 || !idle || idle_hint.line.is_some() {
+        return;
+    }
+    match stuck_line(&board) {
+        Some(line) => idle_hint.line = Some(line),
+        // nothing a single line gives away, look again after another wait
+        None => idle_hint.last_change = now,
+    }
+}
+
+/// A soft band sweeping along the clues of the nudged line, or a steady tint
+/// with reduced motion.
+fn idle_shimmer_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    board: Res<Board>,
+    settings: Res<Settings>,
+    idle_hint: Res<IdleHint>,
+    screen: Res<State<Screen>>,
+    mut shimmer_query: Query<(Entity, &mut Transform, &mut Sprite), With<IdleShimmer>>,
+) {
+    let line = match idle_hint.line {
+        Some(line) if *screen.current() == Screen::Board => line,
+        _ => {
+            for (entity, _, _) in shimmer_query.iter() {
+                commands.entity(entity).despawn();
+            }
+            return;
+        }
+    };
+
+    // the clue band in tiles, from its corner along the line
+    let height = board.p.get_height() as f32;
+    let (corner, along) = match line {
+        ClueLine::Row(y) => (
+            Vec2::new(board.row_band_left() as f32, y as f32),
+            Vec2::new(board.row_band as f32, 1.),
+        ),
+        ClueLine::Column(x) => (
+            Vec2::new((board.grid_left() + x) as f32, height),
+            Vec2::new(1., board.h as f32 - height),
+        ),
+    };
+    let progress = (time.elapsed_seconds() * SHIMMER_SPEED).fract();
+    let (offset, size) = match (settings.reduced_motion, line) {
+        (true, _) => (Vec2::ZERO, along),
+        (false, ClueLine::Row(_)) => {
+            let length = along.x * SHIMMER_LENGTH;
+            let offset = Vec2::new(progress * (along.x - length), 0.);
+            (offset, Vec2::new(length, along.y))
+        }
+        (false, ClueLine::Column(_)) => {
+            let length = along.y * SHIMMER_LENGTH;
+            let offset = Vec2::new(0., progress * (along.y - length));
+            (offset, Vec2::new(along.x, length))
+        }
+    };
+    let translation = board.tile_translation(corner.x + offset.x, corner.y + offset.y, EFFECT_Z);
+    let size = size * board.pixels_per_tile;
+
+    if let Some((_, mut transform, mut sprite)) = shimmer_query.iter_mut().next() {
+        transform.translation = translation;
+        sprite.custom_size = Some(size);
+        return;
+    }
+
+    commands
+        .spawn(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(1., 0.95, 0.6, SHIMMER_ALPHA),
+                custom_size: Some(size),
+                anchor: Anchor::BottomLeft,
+                ..Default::default()
+            },
+            transform: Transform::from_translation(translation),
+            ..Default::default()
+        })
+        .insert(IdleShimmer);
+}
@@ -0,0 +1,209 @@
+// region:      IMPORTS
+
+use bevy::{
+    prelude::*,
+    reflect::TypeUuid,
+    render::{
+        render_resource::{AsBindGroup, Extent3d, ShaderRef, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+    },
+    sprite::{Material2d, Material2dPlugin, MaterialMesh2dBundle},
+};
+use picross_handler::Cell;
+
+use crate::board::{Board, SpawnTilesEvent};
+use crate::layers::TILE_Z;
+use crate::options::BoardOptions;
+use crate::GameTextures;
+
+// endregion
+
+// region:      CONSTANTS
+
+const DEFAULT_INSTANCED_THRESHOLD: f32 = 2500.; // cells
+const REGION_CELLS: usize = 128; // per side, keeps state textures well under WebGL2 limits
+
+// encoded in the red channel of the state texture
+const STATE_EMPTY: u8 = 0;
+const STATE_FILLED: u8 = 128;
+const STATE_CROSSED: u8 = 255;
+
+// endregion
+
+// region:      COMPONENTS
+
+/// One quad covering up to `REGION_CELLS` x `REGION_CELLS` puzzle cells.
+#[derive(Component)]
+pub struct BoardRegion {
+    x: usize,
+    y: usize,
+    w: usize,
+    h: usize,
+    state: Handle<Image>,
+}
+
+// endregion
+
+// region:      MATERIAL
+
+/// Draws a whole region of tiles, picking the tile texture per cell from `state`.
+#[derive(AsBindGroup, TypeUuid, Debug, Clone)]
+#[uuid = "5b0a4c7e-2f3d-4e8a-9c61-3d7f0b2a9e14"]
+pub struct BoardMaterial {
+    #[texture(0)]
+    state: Handle<Image>,
+    #[texture(1)]
+    #[sampler(2)]
+    empty: Handle<Image>,
+    #[texture(3)]
+    #[sampler(4)]
+    filled: Handle<Image>,
+    #[texture(5)]
+    #[sampler(6)]
+    crossed: Handle<Image>,
+}
+
+impl Material2d for BoardMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/board_material.wgsl".into()
+    }
+}
+
+// endregion
+
+pub struct InstancedPlugin;
+
+impl Plugin for InstancedPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(Material2dPlugin::<BoardMaterial>::default())
+            .add_system(spawn_regions_system)
+            .add_system(sync_regions_system.after(spawn_regions_system));
+    }
+}
+
+/// Large boards are drawn as a few textured quads instead of a sprite per cell.
+/// The `instanced_threshold` option sets the cell count where this kicks in.
+pub fn use_instanced(board: &Board, options: &BoardOptions) -> bool {
+    let threshold = options.get_f32("instanced_threshold", DEFAULT_INSTANCED_THRESHOLD);
+    (board.p.get_width() * board.p.get_height()) as f32 >= threshold
+}
+
+fn spawn_regions_system(
+    mut commands: Commands,
+    board: Res<Board>,
+    options: Res<BoardOptions>,
+    game_textures: Res<GameTextures>,
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<BoardMaterial>>,
+    mut spawn_tiles_event_reader: EventReader<SpawnTilesEvent>,
+    region_query: Query<Entity, With<BoardRegion>>,
+) {
+    if spawn_tiles_event_reader.iter().last().is_none() {
+        return;
+    }
+
+    // regions follow the tiles, a respawn always starts from scratch
+    for entity in region_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    if !use_instanced(&board, &options) {
+        return;
+    }
+
+    let mesh = meshes.add(Mesh::from(shape::Quad::default()));
+    let grid_left = board.grid_left() as f32;
+    for y in (0..board.p.get_height()).step_by(REGION_CELLS) {
+        for x in (0..board.p.get_width()).step_by(REGION_CELLS) {
+            let w = REGION_CELLS.min(board.p.get_width() - x);
+            let h = REGION_CELLS.min(board.p.get_height() - y);
+            let mut region = BoardRegion {
+                x,
+                y,
+                w,
+                h,
+                state: Handle::default(),
+            };
+            region.state = images.add(state_image(&board, &region));
+
+            let material = materials.add(BoardMaterial {
+                state: region.state.clone(),
+                empty: game_textures.tile_empty.clone(),
+                filled: game_textures.tile_filled.clone(),
+                crossed: game_textures.tile_crossed.clone(),
+            });
+            // the quad is centered on its transform
+            let translation = board.tile_translation(
+                grid_left + x as f32 + w as f32 / 2.,
+                y as f32 + h as f32 / 2.,
+                TILE_Z,
+            );
+            commands
+                .spawn(MaterialMesh2dBundle {
+                    mesh: mesh.clone().into(),
+                    material,
+                    transform: Transform {
+                        translation,
+                        scale: Vec3::new(
+                            w as f32 * board.pixels_per_tile,
+                            h as f32 * board.pixels_per_tile,
+                            1.,
+                        ),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .insert(region);
+        }
+    }
+}
+
+fn sync_regions_system(
+    board: Res<Board>,
+    mut images: ResMut<Assets<Image>>,
+    region_query: Query<&BoardRegion>,
+) {
+    if !board.is_changed() {
+        return;
+    }
+
+    for region in region_query.iter() {
+        if let Some(image) = images.get_mut(&region.state) {
+            write_state(&board, region, &mut image.data);
+        }
+    }
+}
+
+fn state_image(board: &Board, region: &BoardRegion) -> Image {
+    let mut data = vec![0u8; region.w * region.h * 4];
+    write_state(board, region, &mut data);
+
+    let mut image = Image::new(
+        Extent3d {
+            width: region.w as u32,
+            height: region.h as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8Unorm,
+    );
+    // cells are read with textureLoad, but keep it unfiltered anyway
+    image.sampler_descriptor = ImageSampler::nearest();
+    image
+}
+
+/// One texel per cell, texel rows match board rows (bottom up).
+fn write_state(board: &Board, region: &BoardRegion, data: &mut [u8]) {
+    for y in 0..region.h {
+        for x in 0..region.w {
+            let state = match board.p.get_cell(region.x + x, region.y + y) {
+                Cell::Empty => STATE_EMPTY,
+                Cell::Filled => STATE_FILLED,
+                Cell::Crossed => STATE_CROSSED,
+            };
+            let i = (y * region.w + x) * 4;
+            data[i..i + 4].copy_from_slice(&[state, 0, 0, 255]);
+        }
+    }
+}
@@ -0,0 +1,264 @@
+// region:      IMPORTS
+
+use bevy::{input::touch::Touches, prelude::*, sprite::Anchor};
+
+use crate::board::{Board, BoardAction, BoardLoadedEvent, InputEvent, SpawnTilesEvent, Stroke};
+use crate::layers::CURSOR_Z;
+use crate::rules::Rules;
+use crate::screens::Screen;
+
+// endregion
+
+// region:      CONSTANTS
+
+const FILL_KEY: KeyCode = KeyCode::Z;
+const CROSS_KEY: KeyCode = KeyCode::X;
+const CLEAR_KEY: KeyCode = KeyCode::C;
+const MOVE_LETTERS: [KeyCode; 4] = [KeyCode::W, KeyCode::A, KeyCode::S, KeyCode::D];
+
+const CURSOR_COLOR: Color = Color::rgba(0.2, 0.6, 1., 0.45);
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct KeyboardCursorSprite;
+
+// endregion
+
+// region:      RESOURCES
+
+/// The cell keyboard play acts on. Shown from the first arrow or WAD press
+/// until the mouse or a finger is used again, S being the save slots' key
+/// until then. While it is shown S and C belong to it, not to the save slots
+/// and the check, the WASD presses it takes are taken off the keys.
+#[derive(Resource, Default)]
+pub struct KeyboardCursor {
+    pub active: bool,
+    pub x: usize,
+    pub y: usize,
+    /// Key held since it acted and the action it took, repeated on every
+    /// cell the cursor moves to until the key is let go.
+    painting: Option<(KeyCode, BoardAction)>,
+}
+
+// endregion
+
+pub struct KeyboardPlugin;
+
+impl Plugin for KeyboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KeyboardCursor>()
+            .add_system(keyboard_cursor_system)
+            .add_system(keyboard_cursor_draw_system.after(keyboard_cursor_system));
+    }
+}
+
+/// One cell's step for the arrow or WASD key just pressed, up being up the
+/// screen.
+fn step(keys: &Input<KeyCode>) -> Option<(i32, i32)> {
+    let pressed = |a, b| keys.just_pressed(a) || keys.just_pressed(b);
+    if pressed(KeyCode::Up, KeyCode::W) {
+        Some((0, 1))
+    } else if pressed(KeyCode::Down, KeyCode::S) {
+        Some((0, -1))
+    } else if pressed(KeyCode::Left, KeyCode::A) {
+        Some((-1, 0))
+    } else if pressed(KeyCode::Right, KeyCode::D) {
+        Some((1, 0))
+    } else {
+        None
+    }
+}
+
+/// Arrows or WASD move the cursor, Z fills, X crosses and C clears the cell
+/// under it. Z and X on a cell already in their state clear it, like a
+/// click. Holding one of them while moving paints the cells passed over as
+/// one stroke. Everything goes through `InputEvent`s like pointer input.
+#[allow(clippy::too_many_arguments)]
+pub fn keyboard_cursor_system(
+    mut keys: ResMut<Input<KeyCode>>,
+    buttons: Res<Input<MouseButton>>,
+    touches: Res<Touches>,
+    board: Res<Board>,
+    rules: Res<Rules>,
+    screen: Res<State<Screen>>,
+    mut cursor: ResMut<KeyboardCursor>,
+    mut stroke: ResMut<Stroke>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut input_event_writer: EventWriter<InputEvent>,
+) {
+    let (width, height) = (board.p.get_width(), board.p.get_height());
+    if board_loaded_event_reader.iter().last().is_some() {
+        // top left, where reading the board starts
+        (cursor.x, cursor.y) = (0, height.saturating_sub(1));
+    }
+    if buttons.get_just_pressed().next().is_some() || touches.iter_just_pressed().next().is_some() {
+        if cursor.active {
+            cursor.active = false;
+            cursor.painting = None;
+        }
+        return;
+    }
+    // Ctrl+Z is undo
+    let ctrl = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    if *screen.current() != Screen::Board || ctrl || width == 0 || height == 0 {
+        return;
+    }
+
+    if let Some((key, _)) = cursor.painting {
+        if !keys.pressed(key) {
+            cursor.painting = None;
+        }
+    }
+
+    let mut moved = false;
+    // S opens the save slots while the cursor is hidden, it only moves it
+    let slots_key = !cursor.active && keys.just_pressed(KeyCode::S);
+    if let Some((dx, dy)) = step(&keys).filter(|_| !slots_key) {
+        // taken, systems ordered after this one don't see the press
+        for key in MOVE_LETTERS {
+            keys.clear_just_pressed(key);
+        }
+        if cursor.active {
+            let x = (cursor.x as i32 + dx).clamp(0, width as i32 - 1) as usize;
+            let y = (cursor.y as i32 + dy).clamp(0, height as i32 - 1) as usize;
+            moved = (x, y) != (cursor.x, cursor.y);
+            (cursor.x, cursor.y) = (x, y);
+        } else {
+            // the first press only shows where the cursor is
+            cursor.active = true;
+        }
+    }
+    if !cursor.active {
+        return;
+    }
+    // a board smaller than the last one may have left it outside
+    if cursor.x >= width || cursor.y >= height {
+        (cursor.x, cursor.y) = (cursor.x.min(width - 1), cursor.y.min(height - 1));
+    }
+    let cell = board.p.get_cell(cursor.x, cursor.y);
+    let pressed = [
+        (FILL_KEY, BoardAction::Fill),
+        (CROSS_KEY, BoardAction::Cross),
+        (CLEAR_KEY, BoardAction::Empty),
+    ]
+    .into_iter()
+    .find(|(key, _)| keys.just_pressed(*key));
+    let action = match pressed {
+        Some((key, action)) => {
+            stroke.0 += 1;
+            let action = match action {
+                BoardAction::Empty => action,
+                _ => rules.0.drag_action(action, cell),
+            };
+            cursor.painting = Some((key, action));
+            action
+        }
+        None => match cursor.painting.filter(|_| moved) {
+            Some((_, action)) => action,
+            None => return,
+        },
+    };
+    let (x, y) = board.cell_to_tile(cursor.x, cursor.y);
+    input_event_writer.send(InputEvent {
+        x,
+        y,
+        action,
+        from_player: true,
+    });
+}
+
+fn keyboard_cursor_draw_system(
+    mut commands: Commands,
+    board: Res<Board>,
+    cursor: Res<KeyboardCursor>,
+    mut spawn_tiles_event_reader: EventReader<SpawnTilesEvent>,
+    sprite_query: Query<Entity, With<KeyboardCursorSprite>>,
+) {
+    let respawned = spawn_tiles_event_reader.iter().count() > 0;
+    if !(cursor.is_changed() || respawned) {
+        return;
+    }
+    for entity in sprite_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    if !cursor.active || board.p.get_width() == 0 {
+        return;
+    }
+
+    let (x, y) = board.cell_to_tile(cursor.x, cursor.y);
+    commands
+        .spawn(SpriteBundle {
+            sprite: Sprite {
+                color: CURSOR_COLOR,
+                custom_size: Some(Vec2::splat(board.pixels_per_tile)),
+                anchor: Anchor::BottomLeft,
+                ..Default::default()
+            },
+            transform: Transform::from_translation(board.tile_translation(x, y, CURSOR_Z)),
+            ..Default::default()
+        })
+        .insert(KeyboardCursorSprite);
+}
+
+// region:      TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::harness::{plus_puzzle, Harness};
+
+    #[test]
+    fn keyboard_cursor_moves_and_fills() {
+        let mut harness = Harness::new();
+        assert!(harness.join(plus_puzzle().as_str(), "000000000"));
+
+        // the first arrow only shows the cursor, on the top left cell
+        harness.chord(&[KeyCode::Right]);
+        harness.chord(&[KeyCode::Right]);
+        harness.chord(&[KeyCode::Z]);
+        assert_eq!(harness.cells(), "000000010");
+        // Z on a filled cell clears it, like a click
+        harness.chord(&[KeyCode::Z]);
+        assert_eq!(harness.cells(), "000000000");
+        harness.chord(&[KeyCode::S]);
+        harness.chord(&[KeyCode::X]);
+        assert_eq!(harness.cells(), "0000X0000");
+    }
+
+    #[test]
+    fn s_is_the_save_slots_key_until_the_cursor_shows() {
+        let mut harness = Harness::new();
+        assert!(harness.join(plus_puzzle().as_str(), "000000000"));
+        let press_s = |harness: &mut Harness| {
+            harness
+                .app
+                .world
+                .resource_mut::<Input<KeyCode>>()
+                .press(KeyCode::S);
+            harness.tick();
+            let s_left = harness
+                .app
+                .world
+                .resource::<Input<KeyCode>>()
+                .just_pressed(KeyCode::S);
+            let mut input = harness.app.world.resource_mut::<Input<KeyCode>>();
+            input.release(KeyCode::S);
+            input.clear();
+            s_left
+        };
+
+        // hidden, the cursor leaves S to the save slots
+        assert!(press_s(&mut harness));
+        assert!(!harness.app.world.resource::<KeyboardCursor>().active);
+
+        // shown, it takes S for itself
+        harness.chord(&[KeyCode::Right]);
+        assert!(!press_s(&mut harness));
+        assert!(harness.app.world.resource::<KeyboardCursor>().active);
+    }
+}
+
+// endregion
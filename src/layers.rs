@@ -0,0 +1,85 @@
+// region:      IMPORTS
+
+use bevy::render::view::RenderLayers;
+
+// endregion
+
+// region:      Z ORDER
+
+// every world-space sprite and text takes its z from here, back to front
+
+pub const BACKGROUND_Z: f32 = 0.;
+pub const FRAME_Z: f32 = 0.25;
+pub const LENS_BACKGROUND_Z: f32 = 0.5;
+pub const TILE_Z: f32 = 1.;
+pub const GHOST_Z: f32 = 1.5;
+pub const CLUE_Z: f32 = 2.;
+pub const HIGHLIGHT_Z: f32 = 3.;
+pub const EFFECT_Z: f32 = 4.;
+pub const CURSOR_Z: f32 = 5.;
+pub const HUD_Z: f32 = 6.;
+// panels spread over a HUD backdrop, their pictures and buttons above them
+pub const HUD_PANEL_Z: f32 = HUD_Z + 0.1;
+pub const HUD_LABEL_Z: f32 = HUD_Z + 0.2;
+// star and suggestion banners, over every panel
+pub const HUD_BANNER_Z: f32 = HUD_Z + 0.5;
+// over the star banner and anything else on the HUD
+pub const WIN_Z: f32 = 7.;
+// win screen stats and buttons, and the button labels
+pub const WIN_PANEL_Z: f32 = WIN_Z + 0.1;
+pub const WIN_LABEL_Z: f32 = WIN_Z + 0.2;
+pub const LENS_MASK_Z: f32 = 10.;
+
+// children of a clue's text, relative to it
+pub const GLYPH_Z_OFFSET: f32 = 0.01;
+// effect layers sit just under the clue text but above the clue tile
+pub const EFFECT_Z_OFFSET: f32 = -0.01;
+
+// children of a HUD sprite, relative to it: a picture or label on it, and a
+// label over such a picture
+pub const CHILD_Z_OFFSET: f32 = 0.1;
+pub const CHILD_LABEL_Z_OFFSET: f32 = 0.2;
+
+// fail the build if someone reorders the layers above
+const _: () = assert!(
+    BACKGROUND_Z < FRAME_Z
+        && FRAME_Z < LENS_BACKGROUND_Z
+        && LENS_BACKGROUND_Z < TILE_Z
+        && TILE_Z < GHOST_Z
+        && GHOST_Z < CLUE_Z
+        && CLUE_Z < HIGHLIGHT_Z
+        && HIGHLIGHT_Z < EFFECT_Z
+        && EFFECT_Z < CURSOR_Z
+        && CURSOR_Z < HUD_Z
+        && HUD_Z < HUD_PANEL_Z
+        && HUD_PANEL_Z < HUD_LABEL_Z
+        && HUD_LABEL_Z + CHILD_Z_OFFSET < HUD_BANNER_Z
+        && HUD_PANEL_Z + CHILD_LABEL_Z_OFFSET < HUD_BANNER_Z
+        && HUD_BANNER_Z < WIN_Z
+        && WIN_Z < WIN_PANEL_Z
+        && WIN_PANEL_Z < WIN_LABEL_Z
+        && WIN_LABEL_Z < LENS_MASK_Z
+        && 0. < CHILD_Z_OFFSET
+        && CHILD_Z_OFFSET < CHILD_LABEL_Z_OFFSET
+        && GHOST_Z < CLUE_Z + EFFECT_Z_OFFSET
+        && CLUE_Z + GLYPH_Z_OFFSET < HIGHLIGHT_Z
+);
+
+// endregion
+
+// region:      RENDER LAYERS
+
+/// Seen by every camera, the default for entities without `RenderLayers`.
+const BOARD_LAYER: u8 = 0;
+/// Only seen by the magnifier camera.
+pub const MAGNIFIER_LAYER: u8 = 1;
+
+pub fn magnifier_only_layers() -> RenderLayers {
+    RenderLayers::layer(MAGNIFIER_LAYER)
+}
+
+pub fn magnifier_camera_layers() -> RenderLayers {
+    RenderLayers::from_layers(&[BOARD_LAYER, MAGNIFIER_LAYER])
+}
+
+// endregion
@@ -0,0 +1,109 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+use picross_handler::Puzzle;
+use wasm_bindgen::JsValue;
+
+use crate::options::BoardOptions;
+
+// endregion
+
+// region:      CONSTANTS
+
+/// Cells allowed per GB of `navigator.deviceMemory`.
+const CELLS_PER_GB: f64 = 5000.;
+const MIN_CELLS: usize = 2500;
+pub const MAX_CELLS: usize = 40000;
+/// Used when the browser doesn't report device memory.
+const DEFAULT_CELLS: usize = 20000;
+
+// endregion
+
+// region:      RESOURCES
+
+/// Largest board a join may load. Derived from device memory unless the host
+/// sets the `max_cells`, `max_width` or `max_height` options.
+#[derive(Resource)]
+pub struct BoardLimits {
+    pub max_cells: usize,
+    pub max_width: Option<usize>,
+    pub max_height: Option<usize>,
+}
+
+impl Default for BoardLimits {
+    fn default() -> Self {
+        Self {
+            max_cells: device_max_cells(),
+            max_width: None,
+            max_height: None,
+        }
+    }
+}
+
+impl BoardLimits {
+    /// Counts clue cells too, since every one of them becomes an entity.
+    pub fn check(&self, p: &Puzzle) -> Result<(), String> {
+        let total_w = p.get_width() + p.get_longest_row_clue_len();
+        let total_h = p.get_height() + p.get_longest_column_clue_len();
+        if self.max_width.map_or(false, |max| p.get_width() > max) {
+            return Err(format!(
+                "board too wide: {} columns, limit is {}",
+                p.get_width(),
+                self.max_width.unwrap()
+            ));
+        }
+        if self.max_height.map_or(false, |max| p.get_height() > max) {
+            return Err(format!(
+                "board too tall: {} rows, limit is {}",
+                p.get_height(),
+                self.max_height.unwrap()
+            ));
+        }
+        if total_w * total_h > self.max_cells {
+            return Err(format!(
+                "board too large: {}x{} with clues is {} cells, limit is {}",
+                p.get_width(),
+                p.get_height(),
+                total_w * total_h,
+                self.max_cells
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn device_max_cells() -> usize {
+    let memory = web_sys::window()
+        .and_then(|window| {
+            js_sys::Reflect::get(&window.navigator(), &JsValue::from_str("deviceMemory")).ok()
+        })
+        .and_then(|memory| memory.as_f64());
+    match memory {
+        Some(gb) => ((gb * CELLS_PER_GB) as usize).clamp(MIN_CELLS, MAX_CELLS),
+        None => DEFAULT_CELLS,
+    }
+}
+
+// endregion
+
+pub struct LimitsPlugin;
+
+impl Plugin for LimitsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BoardLimits>()
+            .add_system(limits_options_system);
+    }
+}
+
+fn limits_options_system(options: Res<BoardOptions>, mut limits: ResMut<BoardLimits>) {
+    if !options.is_changed() {
+        return;
+    }
+
+    limits.max_cells = match options.get("max_cells").and_then(|v| v.parse().ok()) {
+        Some(max_cells) => max_cells,
+        None => device_max_cells(),
+    };
+    limits.max_width = options.get("max_width").and_then(|v| v.parse().ok());
+    limits.max_height = options.get("max_height").and_then(|v| v.parse().ok());
+}
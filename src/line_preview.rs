@@ -0,0 +1,249 @@
+// region:      IMPORTS
+
+use bevy::{prelude::*, sprite::Anchor};
+use picross_handler::Cell;
+
+use crate::board::{Board, BoardLoadedEvent, CellChangedEvent, SpawnTilesEvent, LINE_HEADER_TILES};
+use crate::camera::BoardView;
+use crate::feed::{column_label, row_label};
+use crate::hud_scale::HudScale;
+use crate::layers::{HIGHLIGHT_Z, HUD_Z};
+use crate::lines::{column_cells, column_clues, row_cells, row_clues};
+use crate::marks::ClueLine;
+use crate::screens::Screen;
+use crate::{GameTextures, WinSize};
+
+// endregion
+
+// region:      CONSTANTS
+
+const HEADER_COLOR: Color = Color::rgba(0.3, 0.5, 0.9, 0.35);
+const PREVIEW_COLOR: Color = Color::rgba(0.3, 0.5, 0.9, 0.2);
+const PREVIEW_FONT_SIZE: f32 = 20.;
+const PREVIEW_MARGIN: f32 = 8.;
+
+// endregion
+
+// region:      COMPONENTS
+
+/// The strips marking where line headers can be clicked.
+#[derive(Component)]
+pub struct LineHeaderStrip;
+
+#[derive(Component)]
+pub struct LinePreviewOverlay;
+
+// endregion
+
+// region:      RESOURCES
+
+/// Line picked by clicking its header, highlighted and summed up at the top
+/// of the window. Clicking the header again or anywhere else on the board
+/// clears it.
+#[derive(Resource, Default)]
+pub struct LinePreview(pub Option<ClueLine>);
+
+// endregion
+
+pub struct LinePreviewPlugin;
+
+impl Plugin for LinePreviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LinePreview>()
+            .add_system(line_header_strip_system)
+            .add_system(line_header_click_system)
+            .add_system(line_preview_draw_system.after(line_header_click_system));
+    }
+}
+
+fn line_header_strip_system(
+    mut commands: Commands,
+    board: Res<Board>,
+    mut spawn_tiles_event_reader: EventReader<SpawnTilesEvent>,
+    strip_query: Query<Entity, With<LineHeaderStrip>>,
+) {
+    if spawn_tiles_event_reader.iter().last().is_none() {
+        return;
+    }
+    for entity in strip_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let (width, height) = (board.p.get_width() as f32, board.p.get_height() as f32);
+    let band = board.grid_left() as f32;
+    let mut strips = Vec::new();
+    if board.row_band > 0 {
+        strips.push((
+            Vec2::new(board.row_header_left(), 0.),
+            Vec2::new(LINE_HEADER_TILES, height),
+        ));
+    }
+    if board.h > board.p.get_height() {
+        let top = board.h as f32 - LINE_HEADER_TILES;
+        strips.push((Vec2::new(band, top), Vec2::new(width, LINE_HEADER_TILES)));
+    }
+    for (min, size) in strips {
+        commands
+            .spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: HEADER_COLOR,
+                    custom_size: Some(size * board.pixels_per_tile),
+                    anchor: Anchor::BottomLeft,
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(board.tile_translation(
+                    min.x,
+                    min.y,
+                    HIGHLIGHT_Z,
+                )),
+                ..Default::default()
+            })
+            .insert(LineHeaderStrip);
+    }
+}
+
+fn line_header_click_system(
+    buttons: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    board: Res<Board>,
+    view: Res<BoardView>,
+    screen: Res<State<Screen>>,
+    mut preview: ResMut<LinePreview>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+) {
+    if board_loaded_event_reader.iter().last().is_some() {
+        preview.0 = None;
+    }
+    if !buttons.just_pressed(MouseButton::Left) || *screen.current() != Screen::Board {
+        return;
+    }
+    let screen_pos = match windows.get_primary().and_then(|w| w.cursor_position()) {
+        Some(screen_pos) => screen_pos,
+        None => return,
+    };
+
+    let pos = (view.screen_to_world(screen_pos) - Vec2::new(board.origin.0, board.origin.1))
+        / board.pixels_per_tile;
+    let picked = board.line_header_at(pos.x, pos.y);
+    let selected = match picked {
+        Some(line) if preview.0 != Some(line) => Some(line),
+        _ => None,
+    };
+    if preview.0 != selected {
+        preview.0 = selected;
+    }
+}
+
+/// `Row 3: 2 1 3 (sum 6 + gaps 2 = 8 of 10, slack 2) - 4 filled, 2 to go`,
+/// the arithmetic every solver starts a line with.
+fn summary(board: &Board, line: ClueLine) -> String {
+    let (name, clues, cells) = match line {
+        ClueLine::Row(y) => (
+            format!("Row {}", row_label(board, y)),
+            row_clues(&board.p, y),
+            row_cells(&board.p, y),
+        ),
+        ClueLine::Column(x) => (
+            format!("Column {}", column_label(x)),
+            column_clues(&board.p, x),
+            column_cells(&board.p, x),
+        ),
+    };
+    let runs: Vec<usize> = clues.into_iter().filter(|clue| *clue > 0).collect();
+    let total: usize = runs.iter().sum();
+    let gaps = runs.len().saturating_sub(1);
+    let needed = total + gaps;
+    let slack = cells.len().saturating_sub(needed);
+    let filled = cells.iter().filter(|cell| **cell == Cell::Filled).count();
+    let labels: Vec<String> = runs.iter().map(|run| run.to_string()).collect();
+    let labels = match labels.is_empty() {
+        true => String::from("0"),
+        false => labels.join(" "),
+    };
+    let to_go = match filled <= total {
+        true => format!("{} to go", total - filled),
+        false => format!("{} too many", filled - total),
+    };
+    format!(
+        "{}: {} (sum {} + gaps {} = {} of {}, slack {}) - {} filled, {}",
+        name,
+        labels,
+        total,
+        gaps,
+        needed,
+        cells.len(),
+        slack,
+        filled,
+        to_go
+    )
+}
+
+fn line_preview_draw_system(
+    mut commands: Commands,
+    board: Res<Board>,
+    view: Res<BoardView>,
+    win_size: Res<WinSize>,
+    preview: Res<LinePreview>,
+    hud_scale: Res<HudScale>,
+    game_textures: Res<GameTextures>,
+    mut cell_changed_event_reader: EventReader<CellChangedEvent>,
+    mut spawn_tiles_event_reader: EventReader<SpawnTilesEvent>,
+    overlay_query: Query<Entity, With<LinePreviewOverlay>>,
+) {
+    let changed = cell_changed_event_reader.iter().count() > 0;
+    let respawned = spawn_tiles_event_reader.iter().count() > 0;
+    let resized = view.is_changed() || hud_scale.is_changed();
+    if !(preview.is_changed() || resized || changed || respawned) {
+        return;
+    }
+    for entity in overlay_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    let line = match preview.0 {
+        Some(line) => line,
+        None => return,
+    };
+
+    // the whole line, clue band included
+    let (min, size) = match line {
+        ClueLine::Row(y) => (Vec2::new(0., y as f32), Vec2::new(board.w as f32, 1.)),
+        ClueLine::Column(x) => (
+            Vec2::new((board.grid_left() + x) as f32, 0.),
+            Vec2::new(1., board.h as f32),
+        ),
+    };
+    commands
+        .spawn(SpriteBundle {
+            sprite: Sprite {
+                color: PREVIEW_COLOR,
+                custom_size: Some(size * board.pixels_per_tile),
+                anchor: Anchor::BottomLeft,
+                ..Default::default()
+            },
+            transform: Transform::from_translation(board.tile_translation(
+                min.x,
+                min.y,
+                HIGHLIGHT_Z,
+            )),
+            ..Default::default()
+        })
+        .insert(LinePreviewOverlay);
+
+    let margin = PREVIEW_MARGIN * hud_scale.0;
+    let position = view.screen_to_world(Vec2::new(win_size.w / 2., win_size.h - margin));
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                summary(&board, line),
+                TextStyle {
+                    font: game_textures.font.clone(),
+                    font_size: PREVIEW_FONT_SIZE * hud_scale.world(&view),
+                    color: Color::WHITE,
+                },
+            )
+            .with_alignment(TextAlignment::TOP_CENTER),
+            transform: Transform::from_translation(position.extend(HUD_Z)),
+            ..Default::default()
+        })
+        .insert(LinePreviewOverlay);
+}
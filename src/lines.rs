@@ -0,0 +1,285 @@
+// region:      IMPORTS
+
+use picross_handler::{Cell, Puzzle};
+
+// endregion
+
+// helpers for checking rows and columns against their clues
+
+pub fn row_clues(p: &Puzzle, y: usize) -> Vec<usize> {
+    p.row_clues[y].iter().map(|clue| *clue as usize).collect()
+}
+
+pub fn column_clues(p: &Puzzle, x: usize) -> Vec<usize> {
+    p.column_clues[x]
+        .iter()
+        .map(|clue| *clue as usize)
+        .collect()
+}
+
+pub fn row_cells(p: &Puzzle, y: usize) -> Vec<Cell> {
+    (0..p.get_width()).map(|x| p.get_cell(x, y)).collect()
+}
+
+pub fn column_cells(p: &Puzzle, x: usize) -> Vec<Cell> {
+    (0..p.get_height()).map(|y| p.get_cell(x, y)).collect()
+}
+
+/// Lengths of each run of filled cells, in order.
+pub fn filled_runs(cells: &[Cell]) -> Vec<usize> {
+    let mut runs = Vec::new();
+    let mut run = 0;
+    for cell in cells {
+        if *cell == Cell::Filled {
+            run += 1;
+        } else if run > 0 {
+            runs.push(run);
+            run = 0;
+        }
+    }
+    if run > 0 {
+        runs.push(run);
+    }
+    runs
+}
+
+/// A line is satisfied when its filled runs match the clues exactly,
+/// a lone `0` clue meaning the line is empty.
+pub fn line_satisfied(clues: &[usize], cells: &[Cell]) -> bool {
+    let clues: Vec<usize> = clues.iter().copied().filter(|clue| *clue > 0).collect();
+    filled_runs(cells) == clues
+}
+
+/// Whether the line can still be completed to match its clues, empty cells
+/// being undecided and crossed cells known gaps.
+pub fn line_consistent(clues: &[usize], cells: &[Cell]) -> bool {
+    let clues: Vec<usize> = clues.iter().copied().filter(|clue| *clue > 0).collect();
+    let n = cells.len();
+
+    // fits[k][i]: clues k.. can still be placed in cells i..
+    let mut fits = vec![vec![false; n + 1]; clues.len() + 1];
+    fits[clues.len()][n] = true;
+    for i in (0..n).rev() {
+        fits[clues.len()][i] = fits[clues.len()][i + 1] && cells[i] != Cell::Filled;
+    }
+    for k in (0..clues.len()).rev() {
+        for i in (0..n).rev() {
+            // leave cell i as a gap
+            if cells[i] != Cell::Filled && fits[k][i + 1] {
+                fits[k][i] = true;
+                continue;
+            }
+            // or start the run here, ended by a gap or the edge
+            let end = i + clues[k];
+            if end > n || cells[i..end].contains(&Cell::Crossed) {
+                continue;
+            }
+            fits[k][i] = if end == n {
+                fits[k + 1][n]
+            } else {
+                cells[end] != Cell::Filled && fits[k + 1][end + 1]
+            };
+        }
+    }
+    fits[0][0]
+}
+
+/// placements[k][i]: ways to place clues k.. in cells i.., counted like
+/// `line_consistent` checks them. Counts get large, hence f64.
+fn placements(clues: &[usize], cells: &[Cell]) -> Vec<Vec<f64>> {
+    let n = cells.len();
+    let mut ways = vec![vec![0.; n + 1]; clues.len() + 1];
+    ways[clues.len()][n] = 1.;
+    for i in (0..n).rev() {
+        if cells[i] != Cell::Filled {
+            ways[clues.len()][i] = ways[clues.len()][i + 1];
+        }
+    }
+    for k in (0..clues.len()).rev() {
+        for i in (0..n).rev() {
+            let mut count = 0.;
+            if cells[i] != Cell::Filled {
+                count += ways[k][i + 1];
+            }
+            let end = i + clues[k];
+            if end <= n && !cells[i..end].contains(&Cell::Crossed) {
+                count += if end == n {
+                    ways[k + 1][n]
+                } else if cells[end] != Cell::Filled {
+                    ways[k + 1][end + 1]
+                } else {
+                    0.
+                };
+            }
+            ways[k][i] = count;
+        }
+    }
+    ways
+}
+
+/// For each cell, the share of the line's consistent placements that fill
+/// it. `None` when no placement fits.
+pub fn fill_probabilities(clues: &[usize], cells: &[Cell]) -> Option<Vec<f64>> {
+    let clues: Vec<usize> = clues.iter().copied().filter(|clue| *clue > 0).collect();
+    let n = cells.len();
+    let after = placements(&clues, cells);
+    let total = after[0][0];
+    if total <= 0. {
+        return None;
+    }
+    // the same count over the reversed line gives the clues before a run
+    let reversed_clues: Vec<usize> = clues.iter().rev().copied().collect();
+    let reversed_cells: Vec<Cell> = cells.iter().rev().copied().collect();
+    let before = placements(&reversed_clues, &reversed_cells);
+
+    let mut covered = vec![0.; n];
+    for (k, clue) in clues.iter().enumerate() {
+        for start in 0..=n.saturating_sub(*clue) {
+            let end = start + clue;
+            if end > n || cells[start..end].contains(&Cell::Crossed) {
+                continue;
+            }
+            // clues ..k in the cells before the gap at start - 1
+            let ways_before = match start {
+                0 => (k == 0) as u8 as f64,
+                _ if cells[start - 1] == Cell::Filled => 0.,
+                _ => before[clues.len() - k][n - start + 1],
+            };
+            // clues k + 1.. in the cells after the gap at end
+            let ways_after = match end == n {
+                true => (k + 1 == clues.len()) as u8 as f64,
+                false if cells[end] == Cell::Filled => 0.,
+                false => after[k + 1][end + 1],
+            };
+            let ways = ways_before * ways_after;
+            if ways > 0. {
+                for share in &mut covered[start..end] {
+                    *share += ways;
+                }
+            }
+        }
+    }
+    Some(covered.into_iter().map(|ways| ways / total).collect())
+}
+
+/// Undecided cells the line forces on its own, by index, with what they must
+/// be. Empty when nothing follows or no placement fits.
+pub fn forced_cells(clues: &[usize], cells: &[Cell]) -> Vec<(usize, Cell)> {
+    // shares are sums of large counts, a hair off 1 still means every placement
+    const SURE: f64 = 1e-9;
+    let shares = match fill_probabilities(clues, cells) {
+        Some(shares) => shares,
+        None => return Vec::new(),
+    };
+    cells
+        .iter()
+        .zip(shares)
+        .enumerate()
+        .filter(|(_, (cell, _))| **cell == Cell::Empty)
+        .filter_map(|(i, (_, share))| match share {
+            _ if share <= 0. => Some((i, Cell::Crossed)),
+            _ if share >= 1. - SURE => Some((i, Cell::Filled)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Lengths of the runs from the start of `cells` that can't change any more,
+/// up to the first undecided cell.
+fn closed_runs<'a>(cells: impl Iterator<Item = &'a Cell>) -> Vec<usize> {
+    let mut runs = Vec::new();
+    let mut run = 0;
+    for cell in cells {
+        match cell {
+            Cell::Filled => run += 1,
+            Cell::Crossed if run > 0 => {
+                runs.push(run);
+                run = 0;
+            }
+            Cell::Crossed => {}
+            // a run touching an undecided cell may still grow
+            Cell::Empty => return runs,
+        }
+    }
+    if run > 0 {
+        runs.push(run);
+    }
+    runs
+}
+
+/// Which clues are done for certain. A run closed off by crosses or the
+/// edge, with nothing undecided between it and that end of the line, is the
+/// clue counted from the same end when their lengths agree. Every clue is
+/// done once the line is satisfied.
+pub fn finished_clues(clues: &[usize], cells: &[Cell]) -> Vec<bool> {
+    if line_satisfied(clues, cells) {
+        return vec![true; clues.len()];
+    }
+    let mut finished = vec![false; clues.len()];
+    // a lone 0 only finishes with the line
+    let runs: Vec<usize> = (0..clues.len()).filter(|k| clues[*k] > 0).collect();
+    for (k, run) in runs.iter().zip(closed_runs(cells.iter())) {
+        if clues[*k] != run {
+            break;
+        }
+        finished[*k] = true;
+    }
+    for (k, run) in runs.iter().rev().zip(closed_runs(cells.iter().rev())) {
+        if clues[*k] != run {
+            break;
+        }
+        finished[*k] = true;
+    }
+    finished
+}
+
+// region:      TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use picross_handler::Cell::{Crossed as X, Empty as E, Filled as F};
+
+    #[test]
+    fn overlapping_runs_force_their_shared_cells() {
+        assert_eq!(forced_cells(&[3], &[E; 5]), vec![(2, F)]);
+        let shares = fill_probabilities(&[3], &[E; 5]).unwrap();
+        let thirds: Vec<f64> = shares.iter().map(|share| share * 3.).collect();
+        for (share, expected) in thirds.iter().zip([1., 2., 3., 2., 1.]) {
+            assert!((share - expected).abs() < 1e-9);
+        }
+        // the clues fill the line with one gap each between them
+        assert_eq!(forced_cells(&[1, 1], &[E; 3]), vec![(0, F), (1, X), (2, F)]);
+    }
+
+    #[test]
+    fn crossed_cells_split_the_gaps() {
+        let cells = [E, E, X, E, E, E];
+        assert!(line_consistent(&[3], &cells));
+        assert_eq!(
+            forced_cells(&[3], &cells),
+            vec![(0, X), (1, X), (3, F), (4, F), (5, F)]
+        );
+        assert!(!line_consistent(&[3], &[E, E, X, E, E]));
+    }
+
+    #[test]
+    fn empty_clue_lines_cross_everything() {
+        for clues in [&[0][..], &[][..]] {
+            assert!(line_consistent(clues, &[E; 3]));
+            assert_eq!(forced_cells(clues, &[E; 3]), vec![(0, X), (1, X), (2, X)]);
+            assert!(!line_consistent(clues, &[E, F, E]));
+            assert!(line_satisfied(clues, &[X, E, X]));
+        }
+    }
+
+    #[test]
+    fn contradictory_lines_have_no_placements() {
+        let cells = [F, X, F];
+        assert!(!line_consistent(&[2], &cells));
+        assert!(fill_probabilities(&[2], &cells).is_none());
+        assert!(forced_cells(&[2], &cells).is_empty());
+    }
+}
+
+// endregion
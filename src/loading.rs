@@ -0,0 +1,78 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+
+use crate::board::Loading;
+use crate::layers::{CHILD_Z_OFFSET, HUD_Z};
+use crate::{GameTextures, WinSize};
+
+// endregion
+
+// region:      CONSTANTS
+
+const LOADING_TEXT: &str = "Loading...";
+const LOADING_FONT_SIZE: f32 = 32.;
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct LoadingOverlay;
+
+// endregion
+
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(loading_overlay_system);
+    }
+}
+
+fn loading_overlay_system(
+    mut commands: Commands,
+    loading: Res<Loading>,
+    win_size: Res<WinSize>,
+    game_textures: Res<GameTextures>,
+    overlay_query: Query<Entity, With<LoadingOverlay>>,
+) {
+    if !loading.is_changed() {
+        return;
+    }
+
+    for entity in overlay_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if !loading.0 {
+        return;
+    }
+
+    // dim the old board with the text on top
+    commands
+        .spawn(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(0., 0., 0., 0.4),
+                custom_size: Some(Vec2::new(win_size.w, win_size.h)),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(win_size.w / 2., win_size.h / 2., HUD_Z),
+            ..Default::default()
+        })
+        .insert(LoadingOverlay)
+        .with_children(|parent| {
+            parent.spawn(Text2dBundle {
+                text: Text::from_section(
+                    LOADING_TEXT,
+                    TextStyle {
+                        font: game_textures.font.clone(),
+                        font_size: LOADING_FONT_SIZE,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_alignment(TextAlignment::CENTER),
+                transform: Transform::from_xyz(0., 0., CHILD_Z_OFFSET),
+                ..Default::default()
+            });
+        });
+}
@@ -0,0 +1,254 @@
+// region:      IMPORTS
+
+use bevy::{
+    core_pipeline::clear_color::ClearColorConfig,
+    input::touch::Touches,
+    prelude::*,
+    render::{
+        camera::{Viewport, WindowOrigin},
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+
+use crate::camera::BoardView;
+use crate::control_profile::ControlProfile;
+use crate::layers::{
+    magnifier_camera_layers, magnifier_only_layers, LENS_BACKGROUND_Z, LENS_MASK_Z,
+};
+
+// endregion
+
+// region:      CONSTANTS
+
+const MAGNIFIER_ZOOM: f32 = 3.;
+const LENS_SIZE: f32 = 180.; // logical pixels
+const LENS_OFFSET: f32 = 110.; // lens sits above the cursor so a finger doesn't cover it
+const LENS_RIM: f32 = 0.06; // fraction of the lens radius used by the rim
+const LENS_TEXTURE_SIZE: u32 = 128;
+const LONG_PRESS_SECONDS: f32 = 0.5;
+const LONG_PRESS_SLOP: f32 = 12.;
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct MagnifierCamera;
+
+#[derive(Component)]
+pub struct LensBackground;
+
+#[derive(Component)]
+pub struct LensMask;
+
+// endregion
+
+// region:      RESOURCES
+
+#[derive(Resource, Default)]
+pub struct Magnifier {
+    pub active: bool,
+    touch_held: f32,
+}
+
+// endregion
+
+pub struct MagnifierPlugin;
+
+impl Plugin for MagnifierPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Magnifier>()
+            .add_startup_system(magnifier_setup_system)
+            .add_system(magnifier_activation_system)
+            .add_system(magnifier_follow_system);
+    }
+}
+
+fn magnifier_setup_system(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    clear_color: Res<ClearColor>,
+) {
+    // second camera drawing the board zoomed in, only over the lens viewport
+    commands
+        .spawn(Camera2dBundle {
+            camera: Camera {
+                priority: 1,
+                is_active: false,
+                ..Default::default()
+            },
+            camera_2d: Camera2d {
+                clear_color: ClearColorConfig::None,
+            },
+            projection: OrthographicProjection {
+                window_origin: WindowOrigin::Center,
+                scale: 1. / MAGNIFIER_ZOOM,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(magnifier_camera_layers())
+        .insert(MagnifierCamera);
+
+    let world_size = Vec2::splat(LENS_SIZE / MAGNIFIER_ZOOM);
+
+    // opaque backdrop so the main view doesn't bleed through gaps between tiles
+    commands
+        .spawn(SpriteBundle {
+            sprite: Sprite {
+                color: clear_color.0,
+                custom_size: Some(world_size),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(0., 0., LENS_BACKGROUND_Z),
+            ..Default::default()
+        })
+        .insert(magnifier_only_layers())
+        .insert(LensBackground);
+
+    // rim and corner mask that turns the square viewport into a circular lens
+    commands
+        .spawn(SpriteBundle {
+            texture: images.add(lens_mask_image(clear_color.0)),
+            sprite: Sprite {
+                custom_size: Some(world_size),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(0., 0., LENS_MASK_Z),
+            ..Default::default()
+        })
+        .insert(magnifier_only_layers())
+        .insert(LensMask);
+}
+
+fn lens_mask_image(background: Color) -> Image {
+    let size = LENS_TEXTURE_SIZE;
+    let center = (size as f32 - 1.) / 2.;
+    let outer = size as f32 / 2.;
+    let inner = outer * (1. - LENS_RIM);
+    let rim = Color::rgb(0.2, 0.2, 0.2).as_rgba_u8();
+    let background = background.as_rgba_u8();
+
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let distance = Vec2::new(x as f32 - center, y as f32 - center).length();
+            if distance < inner {
+                data.extend_from_slice(&[0, 0, 0, 0]);
+            } else if distance < outer {
+                data.extend_from_slice(&rim);
+            } else {
+                data.extend_from_slice(&background);
+            }
+        }
+    }
+
+    Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+fn magnifier_activation_system(
+    time: Res<Time>,
+    keys: Res<Input<KeyCode>>,
+    touches: Res<Touches>,
+    profile: Res<ControlProfile>,
+    mut magnifier: ResMut<Magnifier>,
+) {
+    // a touch held still counts as a long press, where the profile has it
+    let mut long_press = false;
+    if let Some(touch) = touches
+        .iter()
+        .next()
+        .filter(|_| profile.long_press_magnifier)
+    {
+        if touch.position().distance(touch.start_position()) < LONG_PRESS_SLOP {
+            magnifier.touch_held += time.delta_seconds();
+        } else {
+            magnifier.touch_held = 0.;
+        }
+        long_press = magnifier.touch_held >= LONG_PRESS_SECONDS;
+    } else {
+        magnifier.touch_held = 0.;
+    }
+
+    let active = keys.pressed(KeyCode::M) || long_press;
+    if magnifier.active != active {
+        magnifier.active = active;
+    }
+}
+
+fn magnifier_follow_system(
+    magnifier: Res<Magnifier>,
+    windows: Res<Windows>,
+    view: Res<BoardView>,
+    mut camera_query: Query<
+        (&mut Camera, &mut Transform, &mut OrthographicProjection),
+        With<MagnifierCamera>,
+    >,
+    mut lens_query: Query<
+        &mut Transform,
+        (
+            Or<(With<LensBackground>, With<LensMask>)>,
+            Without<MagnifierCamera>,
+        ),
+    >,
+) {
+    let window = windows.get_primary().unwrap();
+    let cursor = window.cursor_position().filter(|_| magnifier.active);
+
+    for (mut camera, mut transform, mut projection) in camera_query.iter_mut() {
+        let (cursor, viewport) = match cursor.and_then(|c| Some((c, lens_viewport(window, c)?))) {
+            Some(found) => found,
+            None => {
+                camera.is_active = false;
+                continue;
+            }
+        };
+
+        camera.is_active = true;
+        camera.viewport = Some(viewport);
+        // the lens magnifies whatever the main camera is zoomed to
+        let world = view.screen_to_world(cursor);
+        projection.scale = view.scale / MAGNIFIER_ZOOM;
+        transform.translation.x = world.x;
+        transform.translation.y = world.y;
+
+        for mut lens_transform in lens_query.iter_mut() {
+            lens_transform.translation.x = world.x;
+            lens_transform.translation.y = world.y;
+            lens_transform.scale = Vec3::new(view.scale, view.scale, 1.);
+        }
+    }
+}
+
+/// Places the lens above the cursor, clamped inside the window.
+/// Returns `None` when the window is too small to fit the lens.
+fn lens_viewport(window: &Window, cursor: Vec2) -> Option<Viewport> {
+    let scale_factor = window.scale_factor() as f32;
+    let size = (LENS_SIZE * scale_factor).round() as u32;
+    let physical_width = window.physical_width();
+    let physical_height = window.physical_height();
+    if size == 0 || size > physical_width || size > physical_height {
+        return None;
+    }
+
+    // viewports are positioned from the top left, the cursor from the bottom left
+    let left = (cursor.x - LENS_SIZE / 2.) * scale_factor;
+    let top = (window.height() - cursor.y - LENS_OFFSET - LENS_SIZE / 2.) * scale_factor;
+    let left = (left.max(0.) as u32).min(physical_width - size);
+    let top = (top.max(0.) as u32).min(physical_height - size);
+
+    Some(Viewport {
+        physical_position: UVec2::new(left, top),
+        physical_size: UVec2::new(size, size),
+        ..Default::default()
+    })
+}
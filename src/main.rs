@@ -2,19 +2,213 @@
 
 #![allow(unused)]
 
+use archive::ArchivePlugin;
+use arrows::ArrowPlugin;
+use artwork::ArtworkPlugin;
+use audit::AuditPlugin;
+use auto_clues::AutoCluesPlugin;
+use bests::BestsPlugin;
 use bevy::input::mouse::MouseButtonInput;
 use bevy::input::touch::TouchPhase;
 use bevy::input::ButtonState;
 use bevy::{prelude::*, render::camera::WindowOrigin};
 use board::{BoardAction, BoardPlugin, CurrentAction, InputEvent};
+use brackets::BracketPlugin;
+use bridge::{
+    check_len, parse_join, parse_query, parse_update, reply_error, MAX_MESSAGE_LEN, MAX_OPTIONS_LEN,
+};
+use camera::{CameraPlugin, MainCamera};
+use campaign::CampaignPlugin;
+use celebration::CelebrationPlugin;
+use clean_view::CleanViewPlugin;
+use clue_effects::ClueEffectPlugin;
+use clue_hiding::ClueHidingPlugin;
+use conflicts::ConflictPlugin;
+use consistency::ConsistencyPlugin;
+use context_loss::ContextLossPlugin;
+use control_hint::ControlHintPlugin;
+use control_profile::ControlProfilePlugin;
 use crossbeam_channel::{unbounded, Receiver, Sender};
+use diff::DiffPlugin;
+use export::ExportPlugin;
+use feed::FeedPlugin;
+use flip_book::{FlipBook, FlipBookPlugin};
+use fullscreen::FullscreenPlugin;
+#[cfg(feature = "replays")]
+use ghost::GhostPlugin;
+use glyphs::GlyphPlugin;
+use guess::GuessPlugin;
+use handicap::HandicapPlugin;
+use heartbeat::HeartbeatPlugin;
+use heatmap::HeatmapPlugin;
+use hint::HintPlugin;
+use history::HistoryPlugin;
+use hud_scale::HudScalePlugin;
+#[cfg(feature = "hints")]
+use idle_hint::IdleHintPlugin;
+use instanced::InstancedPlugin;
+use keyboard::KeyboardPlugin;
+use limits::LimitsPlugin;
+use line_preview::LinePreviewPlugin;
+use loading::LoadingPlugin;
+use magnifier::MagnifierPlugin;
+use marks::MarksPlugin;
+use music::MusicPlugin;
+#[cfg(feature = "multiplayer")]
+use net_sim::NetSimPlugin;
+#[cfg(feature = "multiplayer")]
+use notes::NotesPlugin;
+use options::BoardOptions;
+use penalty::PenaltyPlugin;
+use pencil::PencilPlugin;
+use pending::PendingPlugin;
+use photo_import::PhotoImportPlugin;
 use picross_handler::Cell;
 use picross_handler::Puzzle;
+use playlist::{Playlist, PlaylistPlugin};
+use pointer_lock::PointerLockPlugin;
+use pressure::PressurePlugin;
+use preview::PreviewPlugin;
+#[cfg(feature = "hints")]
+use probability::ProbabilityPlugin;
+use recovery::RecoveryPlugin;
+#[cfg(feature = "multiplayer")]
+use remote_effects::RemoteEffectPlugin;
+use reveal::RevealPlugin;
+use rules::RulesPlugin;
+use save_slots::SaveSlotsPlugin;
+use screens::ScreenPlugin;
+use settings::SettingsPlugin;
+use solve_demo::SolveDemoPlugin;
+use speaking::SpeakingPlugin;
+use stars::StarsPlugin;
+use start::StartPlugin;
+use stats::StatsPlugin;
 use std::sync::*;
+use subscriptions::SubscriptionPlugin;
+use suggest::SuggestPlugin;
+#[cfg(feature = "multiplayer")]
+use tab_sync::TabSyncPlugin;
+#[cfg(feature = "multiplayer")]
+use team_stats::TeamStatsPlugin;
+use telemetry::TelemetryPlugin;
+use textures::{generate_tile_textures, TexturePlugin};
+#[cfg(feature = "replays")]
+use timelapse::TimelapsePlugin;
+use timer::TimerPlugin;
+use touch_prediction::TouchPredictionPlugin;
+use trace::TracePlugin;
+use updates::UpdatePlugin;
+use validity::ValidityPlugin;
+use view_state::ViewStatePlugin;
 use wasm_bindgen::prelude::*;
 use web_sys::HtmlCanvasElement;
-
+use weekly::WeeklyPlugin;
+use win::WinPlugin;
+use win_goal::{WinGoal, WinGoalPlugin};
+use win_screen::WinScreenPlugin;
+
+mod archive;
+mod arrows;
+mod artwork;
+mod audit;
+mod auto_clues;
+mod bests;
 mod board;
+mod board_commands;
+mod brackets;
+mod bridge;
+mod camera;
+mod campaign;
+mod celebration;
+mod clean_view;
+mod clue_effects;
+mod clue_hiding;
+mod conflicts;
+mod consistency;
+mod context_loss;
+mod control_hint;
+mod control_profile;
+mod diff;
+mod export;
+mod feed;
+mod flip_book;
+mod fullscreen;
+#[cfg(feature = "replays")]
+mod ghost;
+mod glyphs;
+mod guess;
+mod handicap;
+#[cfg(test)]
+mod harness;
+mod heartbeat;
+mod heatmap;
+mod hint;
+mod history;
+mod hud_scale;
+#[cfg(feature = "hints")]
+mod idle_hint;
+mod instanced;
+mod keyboard;
+mod layers;
+mod limits;
+mod line_preview;
+mod lines;
+mod loading;
+mod magnifier;
+mod marks;
+mod music;
+#[cfg(feature = "multiplayer")]
+mod net_sim;
+#[cfg(feature = "multiplayer")]
+mod notes;
+mod options;
+mod penalty;
+mod pencil;
+mod pending;
+mod photo_import;
+mod playlist;
+mod pointer_lock;
+mod pressure;
+mod preview;
+#[cfg(feature = "hints")]
+mod probability;
+mod randomizer;
+mod recovery;
+#[cfg(feature = "multiplayer")]
+mod remote_effects;
+mod reveal;
+mod rules;
+mod save_slots;
+mod screens;
+mod settings;
+mod solve_demo;
+mod solver;
+mod speaking;
+mod stars;
+mod start;
+mod stats;
+mod storage;
+mod subscriptions;
+mod suggest;
+#[cfg(feature = "multiplayer")]
+mod tab_sync;
+#[cfg(feature = "multiplayer")]
+mod team_stats;
+mod telemetry;
+mod textures;
+#[cfg(feature = "replays")]
+mod timelapse;
+mod timer;
+mod touch_prediction;
+mod trace;
+mod updates;
+mod validity;
+mod view_state;
+mod weekly;
+mod win;
+mod win_goal;
+mod win_screen;
 
 // endregion
 
@@ -69,6 +263,17 @@ struct NewBoardEvent {
 
 struct BoardUpdateEvent(String);
 
+struct PrintExportEvent {
+    cell_size: Option<f32>,
+    png: bool,
+}
+
+/// Commands owned by a plugin, forwarded as is for the plugin to handle.
+struct BridgeCommandEvent {
+    command: String,
+    data: String,
+}
+
 // endregion
 
 fn main() {
@@ -93,6 +298,24 @@ fn main() {
         canvas_height = (4096. / device_pixel_ratio);
     }
 
+    // options in the page URL apply from the first frame, static pages need
+    // no script to configure the game
+    let mut options = BoardOptions::default();
+    let query = window_elm
+        .location()
+        .search()
+        .map_err(|err| format!("query: {:?}", err))
+        .and_then(|search| parse_query(&search));
+    if let Ok(pairs) = &query {
+        for (key, value) in pairs {
+            options.merge(key, value);
+        }
+    }
+
+    trace::init();
+    subscriptions::init();
+    view_state::init();
+
     // construct global sender
     let (tx, rx) = unbounded();
     unsafe {
@@ -109,35 +332,134 @@ fn main() {
 
     let send_channel = WASMSendChannel { tx };
 
-    App::new()
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            window: WindowDescriptor {
-                width: canvas_width,
-                height: canvas_height,
-                canvas: Some("#bevy-canvas".to_string()),
-                fit_canvas_to_parent: true,
-                ..Default::default()
-            },
-            ..Default::default()
-        }))
-        .add_plugin(BoardPlugin)
-        .add_startup_system(setup_system)
-        .add_event::<NewBoardEvent>()
-        .add_event::<BoardUpdateEvent>()
-        .insert_resource(receive_channel)
-        .insert_resource(send_channel)
-        .add_system(receive_channel_system)
-        .run();
-}
-
-fn setup_system(mut commands: Commands, asset_server: Res<AssetServer>, windows: Res<Windows>) {
-    commands.spawn(Camera2dBundle {
-        projection: OrthographicProjection {
-            window_origin: WindowOrigin::BottomLeft,
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins.set(WindowPlugin {
+        window: WindowDescriptor {
+            width: canvas_width,
+            height: canvas_height,
+            canvas: Some("#bevy-canvas".to_string()),
+            fit_canvas_to_parent: true,
             ..Default::default()
         },
         ..Default::default()
-    });
+    }))
+    .add_plugin(BoardPlugin)
+    .add_plugin(MagnifierPlugin)
+    .add_plugin(GlyphPlugin)
+    .add_plugin(ClueEffectPlugin)
+    .add_plugin(ExportPlugin)
+    .add_plugin(WinPlugin)
+    .add_plugin(WinGoalPlugin)
+    .add_plugin(WeeklyPlugin)
+    .add_plugin(TimerPlugin)
+    .add_plugin(TelemetryPlugin)
+    .add_plugin(HistoryPlugin)
+    .add_plugin(ArtworkPlugin)
+    .add_plugin(SettingsPlugin)
+    .add_plugin(ControlHintPlugin)
+    .add_plugin(ControlProfilePlugin)
+    .add_plugin(CleanViewPlugin)
+    .add_plugin(ViewStatePlugin)
+    .add_plugin(KeyboardPlugin)
+    .add_plugin(StatsPlugin)
+    .add_plugin(ArchivePlugin)
+    .add_plugin(AutoCluesPlugin)
+    .add_plugin(ConflictPlugin)
+    .add_plugin(UpdatePlugin)
+    .add_plugin(LoadingPlugin)
+    .add_plugin(LimitsPlugin)
+    .add_plugin(InstancedPlugin)
+    .add_plugin(RulesPlugin)
+    .add_plugin(CameraPlugin)
+    .add_plugin(PenaltyPlugin)
+    .add_plugin(MarksPlugin)
+    .add_plugin(HeatmapPlugin)
+    .add_plugin(HintPlugin)
+    .add_plugin(PlaylistPlugin)
+    .add_plugin(ScreenPlugin)
+    .add_plugin(CampaignPlugin)
+    .add_plugin(StarsPlugin)
+    .add_plugin(BestsPlugin)
+    .add_plugin(SuggestPlugin)
+    .add_plugin(WinScreenPlugin)
+    .add_plugin(MusicPlugin)
+    .add_plugin(PointerLockPlugin)
+    .add_plugin(PhotoImportPlugin)
+    .add_plugin(SolveDemoPlugin)
+    .add_plugin(PendingPlugin)
+    .add_plugin(ValidityPlugin)
+    .add_plugin(RevealPlugin)
+    .add_plugin(CelebrationPlugin)
+    .add_plugin(DiffPlugin)
+    .add_plugin(FeedPlugin)
+    .add_plugin(SpeakingPlugin)
+    .add_plugin(StartPlugin)
+    .add_plugin(HandicapPlugin)
+    .add_plugin(ClueHidingPlugin)
+    .add_plugin(TexturePlugin)
+    .add_plugin(TracePlugin)
+    .add_plugin(SubscriptionPlugin)
+    .add_plugin(LinePreviewPlugin)
+    .add_plugin(TouchPredictionPlugin)
+    .add_plugin(FullscreenPlugin)
+    .add_plugin(PencilPlugin)
+    .add_plugin(PressurePlugin)
+    .add_plugin(PreviewPlugin)
+    .add_plugin(ContextLossPlugin)
+    .add_plugin(HeartbeatPlugin)
+    .add_plugin(SaveSlotsPlugin)
+    .add_plugin(GuessPlugin)
+    .add_plugin(ConsistencyPlugin)
+    .add_plugin(AuditPlugin)
+    .add_plugin(HudScalePlugin)
+    .add_plugin(FlipBookPlugin)
+    .add_plugin(BracketPlugin)
+    .add_plugin(RecoveryPlugin)
+    .add_plugin(ArrowPlugin)
+    .add_startup_system(setup_system)
+    .add_event::<NewBoardEvent>()
+    .add_event::<BoardUpdateEvent>()
+    .add_event::<PrintExportEvent>()
+    .add_event::<BridgeCommandEvent>()
+    .insert_resource(receive_channel)
+    .insert_resource(send_channel)
+    .insert_resource(options)
+    .add_system(receive_channel_system);
+    // logging is only set up now
+    if let Err(err) = query {
+        warn!("Ignoring the URL options, {}", err);
+    }
+
+    // optional subsystems, see the features in Cargo.toml
+    #[cfg(feature = "hints")]
+    app.add_plugin(ProbabilityPlugin).add_plugin(IdleHintPlugin);
+    #[cfg(feature = "multiplayer")]
+    app.add_plugin(RemoteEffectPlugin)
+        .add_plugin(TabSyncPlugin)
+        .add_plugin(NetSimPlugin)
+        .add_plugin(TeamStatsPlugin)
+        .add_plugin(NotesPlugin);
+    #[cfg(feature = "replays")]
+    app.add_plugin(GhostPlugin).add_plugin(TimelapsePlugin);
+
+    app.run();
+}
+
+fn setup_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    windows: Res<Windows>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    commands
+        .spawn(Camera2dBundle {
+            projection: OrthographicProjection {
+                window_origin: WindowOrigin::BottomLeft,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(MainCamera);
 
     let window = windows.get_primary().unwrap();
 
@@ -148,11 +470,13 @@ fn setup_system(mut commands: Commands, asset_server: Res<AssetServer>, windows:
 
     commands.insert_resource(win_size);
 
+    // generated rather than fetched, the PNGs are opt in through the options
+    let [tile_empty, tile_filled, tile_crossed, tile_clue] = generate_tile_textures(&mut images);
     let game_textures = GameTextures {
-        tile_empty: asset_server.load("tiles/tile_empty.png"),
-        tile_filled: asset_server.load("tiles/tile_filled.png"),
-        tile_crossed: asset_server.load("tiles/tile_crossed.png"),
-        tile_clue: asset_server.load("tiles/tile_clue.png"),
+        tile_empty,
+        tile_filled,
+        tile_crossed,
+        tile_clue,
         font: asset_server.load("fonts/FOT-NewRodin-Pro-DB.otf"),
     };
     commands.insert_resource(game_textures)
@@ -160,29 +484,77 @@ fn setup_system(mut commands: Commands, asset_server: Res<AssetServer>, windows:
 
 fn receive_channel_system(
     receive_channel: Res<WASMReceiveChannel>,
+    send_channel: Res<WASMSendChannel>,
+    mut options: ResMut<BoardOptions>,
+    mut playlist: ResMut<Playlist>,
+    mut flip_book: ResMut<FlipBook>,
+    mut win_goal: ResMut<WinGoal>,
     mut new_board_event_writer: EventWriter<NewBoardEvent>,
     mut board_update_event_writer: EventWriter<BoardUpdateEvent>,
+    mut print_export_event_writer: EventWriter<PrintExportEvent>,
+    mut bridge_command_event_writer: EventWriter<BridgeCommandEvent>,
 ) {
     if let Ok(string) = receive_channel.rx.try_recv() {
         let command: &str = string.0.as_str();
         let data = string.1;
+        // oversized messages are dropped before anything walks them
+        if let Err(err) = check_len(command, data.as_str(), MAX_MESSAGE_LEN) {
+            reply_error(&send_channel, err);
+            return;
+        }
         match command {
             // joined room, new board and cells
-            "j" => {
-                let mut data_iter = data.split("SPLIT");
-                let mut clues = String::from("");
-                let mut cells = String::from("");
-                if let Some(line) = data_iter.next() {
-                    clues = String::from(line);
+            "j" => match parse_join(data.as_str()) {
+                Ok(join) => {
+                    // a themed cell shape belongs to the puzzle it came with
+                    if options.get("cell_shape").is_some() {
+                        options.merge("cell_shape", "");
+                    }
+                    // optional options section, applied before the board is built
+                    if let Some(line) = join.options {
+                        options.merge_from_str(line.as_str());
+                    }
+                    // a plain join ends whatever playlist was running
+                    playlist.start(join.clues.clone(), join.playlist);
+                    flip_book.start(join.artwork);
+                    win_goal.start(join.win);
+                    new_board_event_writer.send(NewBoardEvent {
+                        clues: join.clues,
+                        cells: join.cells,
+                    })
                 }
-                if let Some(line) = data_iter.next() {
-                    cells = String::from(line);
-                }
-                new_board_event_writer.send(NewBoardEvent { clues, cells })
-            }
+                Err(err) => reply_error(&send_channel, err),
+            },
             // board update
-            "u" => {
-                board_update_event_writer.send(BoardUpdateEvent(data));
+            "u" => match parse_update(data.as_str()) {
+                Ok(cells) => board_update_event_writer.send(BoardUpdateEvent(cells)),
+                Err(err) => reply_error(&send_channel, err),
+            },
+            // options update
+            "o" => match check_len("options", data.as_str(), MAX_OPTIONS_LEN) {
+                Ok(()) => options.merge_from_str(data.as_str()),
+                Err(err) => reply_error(&send_channel, err),
+            },
+            // blank puzzle export for printing, replies with ("print", svg) or
+            // ("print_png", base64 png) for "cell_size,png"
+            "print" => {
+                let (cell_size, format) = data.split_once(',').unwrap_or((data.as_str(), ""));
+                print_export_event_writer.send(PrintExportEvent {
+                    cell_size: cell_size.parse::<f32>().ok(),
+                    png: format == "png",
+                });
+            }
+
+            // commands handled by plugins
+            "telemetry" | "s" | "stats" | "m" | "map" | "diff" | "start_at" | "handicap"
+            | "replay" | "preview" | "timelapse" | "slots" | "slot_name" | "audit" | "arrow"
+            | "team" | "audio" | "photo" | "solve_demo" | "note" | "flush" | "check" | "bests"
+            | "profile" | "clean" | "round_end" | "archive_view" | "view" | "weekly"
+            | "speaking" | "weekly_summary" | "h" => {
+                bridge_command_event_writer.send(BridgeCommandEvent {
+                    command: String::from(command),
+                    data,
+                });
             }
             // unknown command
             c => {
@@ -194,10 +566,20 @@ fn receive_channel_system(
 
 #[wasm_bindgen]
 pub fn send_wasm(command: &str, data: &str) {
+    #[cfg(feature = "multiplayer")]
+    if net_sim::hold_incoming(command, data) {
+        return;
+    }
+    deliver_wasm(command, data);
+}
+
+/// Hands a host message to `receive_channel_system`.
+fn deliver_wasm(command: &str, data: &str) {
     let tx: Sender<(String, String)>;
     unsafe {
         tx = GLOBAL_SENDER.as_ref().unwrap().lock().unwrap().clone();
     }
+    trace::record(true, command, data);
     tx.send((command.to_string(), data.to_string()));
 }
 
@@ -209,7 +591,12 @@ pub fn recv_wasm() -> String {
         rx = GLOBAL_RECEIVER.as_ref().unwrap().lock().unwrap().clone();
     }
 
-    if let Ok(string) = rx.try_recv() {
+    // whatever subscriptions left behind came out of the channel first
+    let message = subscriptions::unclaimed().or_else(|| rx.try_recv().ok());
+    #[cfg(feature = "multiplayer")]
+    let message = net_sim::outgoing(message);
+    if let Some(string) = message {
+        trace::record(false, string.0.as_str(), string.1.as_str());
         result.push_str(string.0.as_str());
         result.push_str("SPLIT");
         result.push_str(string.1.as_str());
@@ -217,3 +604,74 @@ pub fn recv_wasm() -> String {
 
     result
 }
+
+/// Whether the player would lose anything by leaving now, moves the server
+/// hasn't confirmed or messages not yet taken from `recv_wasm`. Meant for a
+/// `beforeunload` handler, `("pending", n)` tells the same as it changes.
+#[wasm_bindgen]
+pub fn has_pending_wasm() -> bool {
+    pending::has_pending()
+}
+
+/// Calls `callback(command, data)` with the outgoing messages `filter_json`
+/// asks for, `{"classes":["solve","progress","error"],"commands":["hb"]}`,
+/// instead of handing them out through `recv_wasm`. Returns the
+/// subscription's id, 0 when the filter is invalid.
+#[wasm_bindgen]
+pub fn subscribe_wasm(filter_json: &str, callback: js_sys::Function) -> u32 {
+    match subscriptions::subscribe(filter_json, callback) {
+        Ok(id) => id,
+        Err(err) => {
+            warn!("{}", err);
+            0
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn unsubscribe_wasm(id: u32) -> bool {
+    subscriptions::unsubscribe(id)
+}
+
+/// Recent bridge messages both ways as json, send it back with `"replay"`
+/// to play a session again on a fresh board.
+#[wasm_bindgen]
+pub fn dump_trace_wasm() -> String {
+    trace::to_json()
+}
+
+/// Fullscreens the canvas, false when the browser refused. Changes are
+/// reported back as `("fullscreen", "1" | "0")`.
+#[wasm_bindgen]
+pub fn request_fullscreen_wasm() -> bool {
+    fullscreen::request()
+}
+
+#[wasm_bindgen]
+pub fn exit_fullscreen_wasm() -> bool {
+    fullscreen::exit()
+}
+
+/// The camera's zoom and the cell in the middle of the view, with the
+/// compact modes, as json for `set_view_wasm` to bring back after a reload
+/// or on another player's board. Empty before a board is shown.
+#[wasm_bindgen]
+pub fn get_view_wasm() -> String {
+    view_state::get()
+}
+
+/// Restores a view from `get_view_wasm`, once the board is laid out. False
+/// when the json is invalid.
+#[wasm_bindgen]
+pub fn set_view_wasm(view_json: &str) -> bool {
+    match view_state::parse(view_json) {
+        Ok(_) => {
+            deliver_wasm("view", view_json);
+            true
+        }
+        Err(err) => {
+            warn!("{}", err);
+            false
+        }
+    }
+}
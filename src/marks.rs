@@ -0,0 +1,131 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+
+use crate::board::{Board, BoardAction, ClueMarkedEvent, InputEvent};
+use crate::bridge::reply_error;
+use crate::options::BoardOptions;
+use crate::{BridgeCommandEvent, WASMSendChannel};
+
+// endregion
+
+// region:      CLUE ADDRESSES
+
+/// A clue by the line it belongs to and its position in that line's clue
+/// list, which stays the same on every client whatever the layout.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClueLine {
+    Row(usize),
+    Column(usize),
+}
+
+impl std::fmt::Display for ClueLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ClueLine::Row(y) => write!(f, "r{}", y),
+            ClueLine::Column(x) => write!(f, "c{}", x),
+        }
+    }
+}
+
+impl ClueLine {
+    /// Inverse of `Display`, `r3` or `c0`.
+    pub fn parse(line: &str) -> Option<Self> {
+        let index = line.get(1..)?.parse().ok()?;
+        match line.chars().next()? {
+            'r' => Some(ClueLine::Row(index)),
+            'c' => Some(ClueLine::Column(index)),
+            _ => None,
+        }
+    }
+}
+
+fn action_to_char(action: BoardAction) -> char {
+    match action {
+        BoardAction::Fill => '1',
+        BoardAction::Cross => 'X',
+        BoardAction::Empty => '0',
+    }
+}
+
+fn char_to_action(c: &str) -> Option<BoardAction> {
+    match c {
+        "1" => Some(BoardAction::Fill),
+        "X" => Some(BoardAction::Cross),
+        "0" => Some(BoardAction::Empty),
+        _ => None,
+    }
+}
+
+// endregion
+
+pub struct MarksPlugin;
+
+impl Plugin for MarksPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(send_marks_system)
+            .add_system(receive_marks_system);
+    }
+}
+
+/// Shares the player's clue markings as `("m", "line,idx,state")` when the
+/// `share_marks` option is on. Off by default, markings are private notes.
+fn send_marks_system(
+    options: Res<BoardOptions>,
+    send_channel: Res<WASMSendChannel>,
+    mut clue_marked_event_reader: EventReader<ClueMarkedEvent>,
+) {
+    let share = options.get_bool("share_marks", false);
+    for event in clue_marked_event_reader.iter() {
+        if !share || !event.from_player {
+            continue;
+        }
+        send_channel.tx.send((
+            String::from("m"),
+            format!(
+                "{},{},{}",
+                event.line,
+                event.index,
+                action_to_char(event.action)
+            ),
+        ));
+    }
+}
+
+fn receive_marks_system(
+    board: Res<Board>,
+    options: Res<BoardOptions>,
+    send_channel: Res<WASMSendChannel>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+    mut input_event_writer: EventWriter<InputEvent>,
+) {
+    let share = options.get_bool("share_marks", false);
+    for event in bridge_command_event_reader.iter() {
+        if event.command != "m" || !share {
+            continue;
+        }
+
+        let mut fields = event.data.split(',');
+        let mark = match (fields.next(), fields.next(), fields.next()) {
+            (Some(line), Some(index), Some(state)) => ClueLine::parse(line)
+                .zip(index.parse().ok())
+                .zip(char_to_action(state)),
+            _ => None,
+        };
+        let tile = mark.and_then(|((line, index), action)| {
+            board
+                .clue_tile(line, index)
+                .map(|(x, y, _)| ((x, y), action))
+        });
+        match tile {
+            // goes through the same path as a local mark, without being sent back
+            Some(((x, y), action)) => input_event_writer.send(InputEvent {
+                x,
+                y,
+                action,
+                from_player: false,
+            }),
+            None => reply_error(&send_channel, format!("m: invalid mark {}", event.data)),
+        }
+    }
+}
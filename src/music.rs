@@ -0,0 +1,169 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+
+use crate::board::{Board, BoardLoadedEvent, CellChangedEvent};
+use crate::bridge::reply_error;
+use crate::lines::{column_cells, column_clues, line_satisfied, row_cells, row_clues};
+use crate::options::BoardOptions;
+use crate::settings::Settings;
+use crate::{BridgeCommandEvent, WASMSendChannel};
+
+// endregion
+
+// region:      CONSTANTS
+
+const MAX_STEMS: usize = 4;
+/// Seconds a stem takes to fade all the way in or out.
+const FADE_SECONDS: f32 = 2.;
+
+// endregion
+
+// region:      RESOURCES
+
+/// Looping stems from the `music` option, comma separated asset paths
+/// started together. The first one always plays, the others join in turn
+/// as more of the puzzle's lines are done, so the music builds towards the
+/// solve. Switched and turned up or down with the `"audio"` command.
+#[derive(Resource, Default)]
+pub struct Music {
+    /// The option the stems were started from.
+    playing: String,
+    sinks: Vec<Handle<AudioSink>>,
+    /// Loudness of each stem before the volume setting, eased towards its
+    /// target.
+    levels: Vec<f32>,
+    /// Share of the rows and columns matching their clues.
+    progress: f32,
+}
+
+impl Music {
+    /// Stem `i` of `count` fades in over its share of the progress after
+    /// the first.
+    fn target(&self, i: usize, count: usize) -> f32 {
+        match i {
+            0 => 1.,
+            _ => (self.progress * (count - 1) as f32 - (i - 1) as f32).clamp(0., 1.),
+        }
+    }
+}
+
+// endregion
+
+pub struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Music>()
+            .add_system(music_command_system)
+            .add_system(music_progress_system)
+            .add_system(music_stems_system.after(music_command_system))
+            .add_system(
+                music_mix_system
+                    .after(music_stems_system)
+                    .after(music_progress_system),
+            );
+    }
+}
+
+/// `("audio", "on" | "off")` turns the music on or off, `("audio", "0.4")`
+/// sets its volume. Both are kept with the other settings.
+fn music_command_system(
+    send_channel: Res<WASMSendChannel>,
+    mut settings: ResMut<Settings>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+) {
+    for event in bridge_command_event_reader.iter() {
+        if event.command != "audio" {
+            continue;
+        }
+        match event.data.as_str() {
+            "on" => settings.music = true,
+            "off" => settings.music = false,
+            data => match data.parse::<f32>() {
+                Ok(volume) if volume.is_finite() => settings.music_volume = volume.clamp(0., 1.),
+                _ => reply_error(&send_channel, format!("audio: invalid value {}", data)),
+            },
+        }
+    }
+}
+
+fn music_progress_system(
+    board: Res<Board>,
+    mut music: ResMut<Music>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut cell_changed_event_reader: EventReader<CellChangedEvent>,
+) {
+    let loaded = board_loaded_event_reader.iter().last().is_some();
+    let changed = cell_changed_event_reader.iter().last().is_some();
+    if !(loaded || changed) {
+        return;
+    }
+    let p = &board.p;
+    let rows = (0..p.get_height()).filter(|y| line_satisfied(&row_clues(p, *y), &row_cells(p, *y)));
+    let columns =
+        (0..p.get_width()).filter(|x| line_satisfied(&column_clues(p, *x), &column_cells(p, *x)));
+    let lines = (p.get_width() + p.get_height()).max(1);
+    music.progress = (rows.count() + columns.count()) as f32 / lines as f32;
+}
+
+/// Starts the stems when the option or the setting asks for them, stopping
+/// whatever played before.
+fn music_stems_system(
+    audio: Res<Audio>,
+    settings: Res<Settings>,
+    options: Res<BoardOptions>,
+    asset_server: Res<AssetServer>,
+    mut music: ResMut<Music>,
+    audio_sinks: Res<Assets<AudioSink>>,
+) {
+    if !(options.is_changed() || settings.is_changed()) {
+        return;
+    }
+    let wanted = match settings.music {
+        true => options.get("music").unwrap_or_default(),
+        false => "",
+    };
+    if wanted == music.playing {
+        return;
+    }
+
+    for sink in music.sinks.drain(..) {
+        if let Some(sink) = audio_sinks.get(&sink) {
+            sink.stop();
+        }
+    }
+    music.levels.clear();
+    music.playing = wanted.to_string();
+    for path in wanted
+        .split(',')
+        .filter(|path| !path.is_empty())
+        .take(MAX_STEMS)
+    {
+        let settings = PlaybackSettings::LOOP.with_volume(0.);
+        let sink = audio.play_with_settings(asset_server.load(path.trim()), settings);
+        music.sinks.push(audio_sinks.get_handle(sink));
+        music.levels.push(0.);
+    }
+}
+
+/// Eases every stem towards its share of the current progress.
+fn music_mix_system(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    audio_sinks: Res<Assets<AudioSink>>,
+    mut music: ResMut<Music>,
+) {
+    let count = music.sinks.len();
+    let step = time.delta_seconds() / FADE_SECONDS;
+    for i in 0..count {
+        let target = music.target(i, count);
+        let level = &mut music.levels[i];
+        *level += (target - *level).clamp(-step, step);
+        let volume = *level * settings.music_volume;
+        // sinks only exist once their stem has loaded
+        if let Some(sink) = audio_sinks.get(&music.sinks[i]) {
+            sink.set_volume(volume);
+        }
+    }
+}
@@ -0,0 +1,193 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use crate::options::BoardOptions;
+use crate::start::epoch_ms;
+
+// endregion
+
+// region:      GLOBAL
+
+/// Delay settings and held messages, used from the wasm exports outside of
+/// any system.
+static NET_SIM: Mutex<NetSim> = Mutex::new(NetSim::new());
+
+// endregion
+
+// region:      CONSTANTS
+
+const MAX_SIM_MS: f64 = 30_000.;
+
+// endregion
+
+// region:      RESOURCES
+
+struct Link {
+    queue: VecDeque<(f64, String, String)>,
+    /// Due time of the last queued message, later ones never overtake it.
+    last_due: f64,
+}
+
+impl Link {
+    const fn new() -> Self {
+        Self {
+            queue: VecDeque::new(),
+            last_due: 0.,
+        }
+    }
+
+    fn push(&mut self, due: f64, command: String, data: String) {
+        self.last_due = due.max(self.last_due);
+        self.queue.push_back((self.last_due, command, data));
+    }
+
+    fn pop_due(&mut self, now: f64) -> Option<(String, String)> {
+        match self.queue.front() {
+            Some((due, _, _)) if *due <= now => self
+                .queue
+                .pop_front()
+                .map(|(_, command, data)| (command, data)),
+            _ => None,
+        }
+    }
+}
+
+/// A bad connection for testing embeds, set with the debug options
+/// `net_delay_ms` and `net_jitter_ms`. Every message from the host and to it
+/// is held for the delay plus up to the jitter, in order like over a socket,
+/// so remote updates arrive late and conflicts actually happen.
+struct NetSim {
+    delay_ms: f64,
+    jitter_ms: f64,
+    incoming: Link,
+    outgoing: Link,
+}
+
+impl NetSim {
+    const fn new() -> Self {
+        Self {
+            delay_ms: 0.,
+            jitter_ms: 0.,
+            incoming: Link::new(),
+            outgoing: Link::new(),
+        }
+    }
+
+    fn active(&self) -> bool {
+        self.delay_ms > 0. || self.jitter_ms > 0.
+    }
+
+    fn due(&self) -> f64 {
+        epoch_ms() + self.delay_ms + js_sys::Math::random() * self.jitter_ms
+    }
+}
+
+// endregion
+
+pub struct NetSimPlugin;
+
+impl Plugin for NetSimPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(net_sim_options_system)
+            .add_system(net_sim_release_system);
+    }
+}
+
+/// Holds a message from the host when the simulation is on, true when it
+/// was held and `net_sim_release_system` delivers it later.
+pub fn hold_incoming(command: &str, data: &str) -> bool {
+    let mut sim = match NET_SIM.lock() {
+        Ok(sim) => sim,
+        Err(_) => return false,
+    };
+    // the options themselves would otherwise be stuck behind the delay
+    if (!sim.active() && sim.incoming.queue.is_empty()) || command == "o" {
+        return false;
+    }
+    let due = sim.due();
+    sim.incoming
+        .push(due, command.to_string(), data.to_string());
+    true
+}
+
+/// Passes a message for the host through the delay, returning the next one
+/// that is due, if any.
+pub fn outgoing(message: Option<(String, String)>) -> Option<(String, String)> {
+    let mut sim = match NET_SIM.lock() {
+        Ok(sim) => sim,
+        Err(_) => return message,
+    };
+    if !sim.active() && sim.outgoing.queue.is_empty() {
+        return message;
+    }
+    if let Some((command, data)) = message {
+        let due = sim.due();
+        sim.outgoing.push(due, command, data);
+    }
+    sim.outgoing.pop_due(epoch_ms())
+}
+
+/// Messages for the host still held back.
+pub fn held_outgoing() -> usize {
+    NET_SIM.lock().map_or(0, |sim| sim.outgoing.queue.len())
+}
+
+/// Makes every held message for the host due at once, for a page about to
+/// unload.
+pub fn release_outgoing() {
+    if let Ok(mut sim) = NET_SIM.lock() {
+        for (due, _, _) in sim.outgoing.queue.iter_mut() {
+            *due = 0.;
+        }
+    }
+}
+
+fn parse_ms(options: &BoardOptions, key: &str) -> f64 {
+    options
+        .get(key)
+        .and_then(|value| value.parse::<f64>().ok())
+        .filter(|ms| ms.is_finite())
+        .map_or(0., |ms| ms.clamp(0., MAX_SIM_MS))
+}
+
+fn net_sim_options_system(options: Res<BoardOptions>) {
+    if !options.is_changed() {
+        return;
+    }
+    if let Ok(mut sim) = NET_SIM.lock() {
+        sim.delay_ms = parse_ms(&options, "net_delay_ms");
+        sim.jitter_ms = parse_ms(&options, "net_jitter_ms");
+        if sim.active() {
+            warn!(
+                "Simulating a bad connection: {}ms delay, {}ms jitter",
+                sim.delay_ms, sim.jitter_ms
+            );
+        }
+    }
+}
+
+/// Hands held host messages to the game once they are due. Turning the
+/// simulation off lets whatever is held through in order.
+fn net_sim_release_system() {
+    let now = epoch_ms();
+    loop {
+        let message = match NET_SIM.lock() {
+            Ok(mut sim) => {
+                let now = match sim.active() {
+                    true => now,
+                    false => f64::INFINITY,
+                };
+                sim.incoming.pop_due(now)
+            }
+            Err(_) => return,
+        };
+        match message {
+            // outside the lock, the trace and the channel take their own
+            Some((command, data)) => crate::deliver_wasm(&command, &data),
+            None => return,
+        }
+    }
+}
@@ -0,0 +1,276 @@
+// region:      IMPORTS
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::board::{Board, BoardLoadedEvent, SpawnTilesEvent};
+use crate::bridge::reply_error;
+use crate::camera::BoardView;
+use crate::hud_scale::HudScale;
+use crate::layers::{EFFECT_Z_OFFSET, HIGHLIGHT_Z, HUD_Z};
+use crate::options::BoardOptions;
+use crate::pointer_lock::PointerLock;
+use crate::screens::Screen;
+use crate::settings::Settings;
+use crate::speaking::Speaking;
+use crate::{BridgeCommandEvent, GameTextures, WASMSendChannel};
+
+// endregion
+
+// region:      CONSTANTS
+
+const MAX_NOTE_LEN: usize = 140;
+
+const NOTE_DOT_COLOR: Color = Color::rgb(0.95, 0.8, 0.2);
+const NOTE_DOT_SIZE: f32 = 0.18; // of a tile
+const NOTE_DOT_INSET: f32 = 0.2; // from the tile's top right corner
+const SPEAKING_RING_COLOR: Color = Color::rgba(0.95, 0.8, 0.2, 0.4);
+const SPEAKING_RING_SIZE: f32 = 0.36; // of a tile
+const SPEAKING_PULSE_SPEED: f32 = 6.; // radians per second
+const SPEAKING_PULSE_AMOUNT: f32 = 0.2;
+const NOTE_FONT_SIZE: f32 = 16.;
+const NOTE_OFFSET: f32 = 6.; // above the cell
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct NoteDot;
+
+#[derive(Component)]
+pub struct NoteHoverText;
+
+/// Behind the dots of notes whose author is speaking.
+#[derive(Component)]
+pub struct SpeakingRing;
+
+// endregion
+
+// region:      RESOURCES
+
+pub struct Note {
+    pub author: String,
+    pub text: String,
+}
+
+/// Short notes players leave on cells for each other during long co-op
+/// puzzles, at most one per cell. Only a note's author can change or clear
+/// it, they go away with the board.
+#[derive(Resource, Default)]
+pub struct Notes(pub HashMap<(usize, usize), Note>);
+
+// endregion
+
+pub struct NotesPlugin;
+
+impl Plugin for NotesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Notes>()
+            .add_system(note_command_system)
+            .add_system(note_dot_draw_system.after(note_command_system))
+            .add_system(note_hover_system.after(note_command_system))
+            .add_system(speaking_pulse_system.after(note_dot_draw_system));
+    }
+}
+
+/// `("note", "x,y,player,text")` leaves `player`'s note on cell `(x, y)`,
+/// an empty text clears it. Notes by the local player, the `player_name`
+/// option, are sent back out the same way for the server to pass on.
+fn note_command_system(
+    options: Res<BoardOptions>,
+    board: Res<Board>,
+    send_channel: Res<WASMSendChannel>,
+    mut notes: ResMut<Notes>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+) {
+    if board_loaded_event_reader.iter().last().is_some() && !notes.0.is_empty() {
+        notes.0.clear();
+    }
+
+    for event in bridge_command_event_reader.iter() {
+        if event.command != "note" {
+            continue;
+        }
+        let mut fields = event.data.splitn(4, ',');
+        let note = match (fields.next(), fields.next(), fields.next(), fields.next()) {
+            (Some(x), Some(y), Some(author), Some(text)) => x
+                .parse::<usize>()
+                .ok()
+                .zip(y.parse::<usize>().ok())
+                .filter(|(x, y)| *x < board.p.get_width() && *y < board.p.get_height())
+                .filter(|_| !author.is_empty() && text.chars().count() <= MAX_NOTE_LEN)
+                .map(|cell| (cell, author, text)),
+            _ => None,
+        };
+        let (cell, author, text) = match note {
+            Some(note) => note,
+            None => {
+                reply_error(&send_channel, format!("note: invalid note {}", event.data));
+                continue;
+            }
+        };
+
+        match notes.0.get(&cell) {
+            Some(note) if note.author != author => {
+                reply_error(
+                    &send_channel,
+                    format!(
+                        "note: cell {},{} has a note by {}",
+                        cell.0, cell.1, note.author
+                    ),
+                );
+                continue;
+            }
+            None if text.is_empty() => continue,
+            _ => (),
+        }
+        match text.is_empty() {
+            true => notes.0.remove(&cell),
+            false => notes.0.insert(
+                cell,
+                Note {
+                    author: author.to_string(),
+                    text: text.to_string(),
+                },
+            ),
+        };
+        if options.get("player_name") == Some(author) {
+            send_channel
+                .tx
+                .send((String::from("note"), event.data.clone()));
+        }
+    }
+}
+
+/// Draws a dot on every cell with a note, ringed while its author speaks.
+fn note_dot_draw_system(
+    mut commands: Commands,
+    board: Res<Board>,
+    notes: Res<Notes>,
+    speaking: Res<Speaking>,
+    mut spawn_tiles_event_reader: EventReader<SpawnTilesEvent>,
+    dot_query: Query<Entity, Or<(With<NoteDot>, With<SpeakingRing>)>>,
+) {
+    let respawned = spawn_tiles_event_reader.iter().last().is_some();
+    if !(notes.is_changed() || speaking.is_changed() || respawned) {
+        return;
+    }
+    for entity in dot_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    for ((x, y), note) in notes.0.iter() {
+        let (tile_x, tile_y) = board.cell_to_tile(*x, *y);
+        let center = (tile_x + 1. - NOTE_DOT_INSET, tile_y + 1. - NOTE_DOT_INSET);
+        commands
+            .spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: NOTE_DOT_COLOR,
+                    custom_size: Some(Vec2::splat(NOTE_DOT_SIZE * board.pixels_per_tile)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(board.tile_translation(
+                    center.0,
+                    center.1,
+                    HIGHLIGHT_Z,
+                )),
+                ..Default::default()
+            })
+            .insert(NoteDot);
+        if !speaking.is_speaking(&note.author) {
+            continue;
+        }
+        commands
+            .spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: SPEAKING_RING_COLOR,
+                    custom_size: Some(Vec2::splat(SPEAKING_RING_SIZE * board.pixels_per_tile)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(board.tile_translation(
+                    center.0,
+                    center.1,
+                    HIGHLIGHT_Z + EFFECT_Z_OFFSET,
+                )),
+                ..Default::default()
+            })
+            .insert(SpeakingRing);
+    }
+}
+
+fn speaking_pulse_system(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut ring_query: Query<&mut Transform, With<SpeakingRing>>,
+) {
+    let pulse = match settings.reduced_motion {
+        true => 1.,
+        false => 1. + SPEAKING_PULSE_AMOUNT * (time.elapsed_seconds() * SPEAKING_PULSE_SPEED).sin(),
+    };
+    for mut transform in ring_query.iter_mut() {
+        transform.scale = Vec3::new(pulse, pulse, 1.);
+    }
+}
+
+/// Shows the note under the cursor above its cell, with who left it and
+/// whether they are speaking.
+#[allow(clippy::too_many_arguments)]
+fn note_hover_system(
+    mut commands: Commands,
+    windows: Res<Windows>,
+    board: Res<Board>,
+    view: Res<BoardView>,
+    notes: Res<Notes>,
+    speaking: Res<Speaking>,
+    hud_scale: Res<HudScale>,
+    screen: Res<State<Screen>>,
+    pointer_lock: Res<PointerLock>,
+    game_textures: Res<GameTextures>,
+    mut hovered: Local<Option<(usize, usize)>>,
+    hover_query: Query<Entity, With<NoteHoverText>>,
+) {
+    let cursor = pointer_lock
+        .cursor
+        .or_else(|| windows.get_primary().and_then(|w| w.cursor_position()))
+        .filter(|_| *screen.current() == Screen::Board);
+    let cell = cursor
+        .and_then(|cursor| {
+            let pos = (view.screen_to_world(cursor) - Vec2::new(board.origin.0, board.origin.1))
+                / board.pixels_per_tile;
+            board.tile_to_cell(pos.x, pos.y)
+        })
+        .filter(|cell| notes.0.contains_key(cell));
+    let redraw =
+        notes.is_changed() || speaking.is_changed() || view.is_changed() || hud_scale.is_changed();
+    if cell == *hovered && !redraw {
+        return;
+    }
+    *hovered = cell;
+    for entity in hover_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    let (cell, note) = match cell.and_then(|cell| notes.0.get(&cell).map(|note| (cell, note))) {
+        Some(hovered) => hovered,
+        None => return,
+    };
+
+    let (tile_x, tile_y) = board.cell_to_tile(cell.0, cell.1);
+    let position = board.tile_translation(tile_x + 0.5, tile_y + 1., HUD_Z);
+    let offset = NOTE_OFFSET * hud_scale.world(&view);
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                format!("{}: {}", speaking.label(&note.author), note.text),
+                TextStyle {
+                    font: game_textures.font.clone(),
+                    font_size: NOTE_FONT_SIZE * hud_scale.world(&view),
+                    color: Color::WHITE,
+                },
+            )
+            .with_alignment(TextAlignment::BOTTOM_CENTER),
+            transform: Transform::from_translation(position + Vec3::new(0., offset, 0.)),
+            ..Default::default()
+        })
+        .insert(NoteHoverText);
+}
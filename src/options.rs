@@ -0,0 +1,119 @@
+// region:      IMPORTS
+
+use bevy::{prelude::*, utils::HashMap};
+
+// endregion
+
+// region:      CONSTANTS
+
+/// Presets for the `theme` option, each one a set of tile color options.
+/// `light` puts the default colors back.
+const THEMES: [(&str, &str); 3] = [
+    (
+        "light",
+        "tile_border_color=;tile_empty_color=;tile_filled_color=;tile_cross_color=;tile_clue_color=",
+    ),
+    (
+        "dark",
+        "tile_border_color=0c0c10;tile_empty_color=2b2b33;tile_filled_color=e6e6ea;tile_cross_color=5a5a66;tile_clue_color=1c1c22",
+    ),
+    (
+        "contrast",
+        "tile_border_color=000000;tile_empty_color=ffffff;tile_filled_color=000000;tile_cross_color=c00000;tile_clue_color=ffe600",
+    ),
+];
+
+// endregion
+
+// region:      RESOURCES
+
+/// Embedder supplied options, given as `key=value;key=value` either through
+/// the `"o"` command or as the optional third section of a join payload, or
+/// as `?key=value&key=value` in the page URL before either arrives.
+#[derive(Resource, Default)]
+pub struct BoardOptions(pub HashMap<String, String>);
+
+/// Splits `key=value;key=value` into trimmed pairs, skipping empty keys.
+pub fn parse_pairs(pairs: &str) -> impl Iterator<Item = (&str, &str)> {
+    pairs.split(';').filter_map(|pair| {
+        let mut pair_iter = pair.splitn(2, '=');
+        let key = pair_iter.next().unwrap_or("").trim();
+        let value = pair_iter.next().unwrap_or("").trim();
+        if key.is_empty() {
+            None
+        } else {
+            Some((key, value))
+        }
+    })
+}
+
+impl BoardOptions {
+    pub fn merge_from_str(&mut self, options: &str) {
+        for (key, value) in parse_pairs(options) {
+            self.merge(key, value);
+        }
+    }
+
+    /// Sets one option, an empty value removes it. A `theme` sets its colors
+    /// as if they were given right there, later colors still override them.
+    pub fn merge(&mut self, key: &str, value: &str) {
+        if key == "theme" {
+            match THEMES.iter().find(|(name, _)| *name == value) {
+                Some((_, preset)) => self.merge_from_str(preset),
+                None if !value.is_empty() => warn!("Unknown theme {}", value),
+                None => {}
+            }
+        }
+        if value.is_empty() {
+            self.0.remove(key);
+        } else {
+            self.0.insert(key.to_string(), value.to_string());
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(|value| value.as_str())
+    }
+
+    pub fn get_bool(&self, key: &str, default: bool) -> bool {
+        match self.get(key) {
+            Some("1") | Some("true") | Some("on") => true,
+            Some("0") | Some("false") | Some("off") => false,
+            _ => default,
+        }
+    }
+
+    pub fn get_f32(&self, key: &str, default: f32) -> f32 {
+        self.get(key)
+            .and_then(|value| value.parse::<f32>().ok())
+            .unwrap_or(default)
+    }
+
+    /// The `locale` option for `Intl` formatting, `default` when unset or
+    /// not shaped like a language tag, which `Intl` would throw on.
+    pub fn locale(&self) -> &str {
+        let valid = |tag: &str| {
+            let mut subtags = tag.split('-');
+            let language = subtags.next().unwrap_or_default();
+            (2..=8).contains(&language.len())
+                && language.chars().all(|c| c.is_ascii_alphabetic())
+                && subtags.all(|subtag| {
+                    (2..=8).contains(&subtag.len())
+                        && subtag.chars().all(|c| c.is_ascii_alphanumeric())
+                })
+        };
+        match self.get("locale") {
+            Some(locale) if valid(locale) => locale,
+            _ => "default",
+        }
+    }
+
+    /// Accepts `rrggbb` or `rrggbbaa`, with or without a leading `#`.
+    pub fn get_color(&self, key: &str, default: Color) -> Color {
+        self.get(key)
+            .and_then(|value| Color::hex(value.trim_start_matches('#')).ok())
+            .unwrap_or(default)
+    }
+}
+
+// endregion
@@ -0,0 +1,125 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+use picross_handler::Cell;
+
+use crate::board::{Board, CellChangedEvent, ChangeSource};
+use crate::layers::EFFECT_Z;
+use crate::options::BoardOptions;
+use crate::stats::Stats;
+use crate::timer::SolveTimer;
+use crate::GameTextures;
+
+// endregion
+
+// region:      CONSTANTS
+
+const DEFAULT_PENALTY_SECONDS: f32 = 30.;
+const FLASH_SECONDS: f32 = 1.2;
+const FLASH_RISE: f32 = 1.5; // tiles over the whole flash
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct PenaltyFlash {
+    remaining: f32,
+}
+
+// endregion
+
+pub struct PenaltyPlugin;
+
+impl Plugin for PenaltyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(penalty_system)
+            .add_system(penalty_flash_system);
+    }
+}
+
+/// Race mode with a known solution, the host sends `race=1` and the
+/// `solution` cells in the join options. Every wrong fill costs
+/// `penalty_seconds` on the clock.
+fn penalty_system(
+    mut commands: Commands,
+    board: Res<Board>,
+    options: Res<BoardOptions>,
+    game_textures: Res<GameTextures>,
+    mut solve_timer: ResMut<SolveTimer>,
+    mut stats: ResMut<Stats>,
+    mut cell_changed_event_reader: EventReader<CellChangedEvent>,
+) {
+    let width = board.p.get_width();
+    let solution = options
+        .get("solution")
+        .filter(|solution| solution.len() == width * board.p.get_height());
+    let seconds = options.get_f32("penalty_seconds", DEFAULT_PENALTY_SECONDS);
+
+    for event in cell_changed_event_reader.iter() {
+        let solution = match solution {
+            Some(solution) if options.get_bool("race", false) => solution.as_bytes(),
+            _ => continue,
+        };
+        // the solution is in the puzzle's own layout, not the randomized one
+        let (x, y) = board
+            .orientation
+            .to_original(event.x, event.y, width, board.p.get_height());
+        let wrong = event.source == ChangeSource::Player
+            && event.new == Cell::Filled
+            && solution[y * width + x] != b'1';
+        if !wrong || solve_timer.finished.is_some() {
+            continue;
+        }
+
+        solve_timer.penalty += seconds as f64;
+        stats.penalties += 1;
+        stats.penalty_seconds += seconds as f64;
+
+        let (x, y) = board.cell_to_tile(event.x, event.y);
+        commands
+            .spawn(Text2dBundle {
+                text: Text::from_section(
+                    format!("+{}s", seconds),
+                    TextStyle {
+                        font: game_textures.font.clone(),
+                        font_size: board.pixels_per_tile * 0.6,
+                        color: Color::RED,
+                    },
+                )
+                .with_alignment(TextAlignment::CENTER),
+                transform: Transform::from_translation(board.tile_translation(
+                    x + 0.5,
+                    y + 0.5,
+                    EFFECT_Z,
+                )),
+                ..Default::default()
+            })
+            .insert(PenaltyFlash {
+                remaining: FLASH_SECONDS,
+            });
+    }
+}
+
+fn penalty_flash_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    board: Res<Board>,
+    mut flash_query: Query<(Entity, &mut PenaltyFlash, &mut Text, &mut Transform)>,
+) {
+    for (entity, mut flash, mut text, mut transform) in flash_query.iter_mut() {
+        flash.remaining -= time.delta_seconds();
+        if flash.remaining <= 0. {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        // drift up and fade out
+        transform.translation.y +=
+            FLASH_RISE * board.pixels_per_tile * time.delta_seconds() / FLASH_SECONDS;
+        text.sections[0]
+            .style
+            .color
+            .set_a(flash.remaining / FLASH_SECONDS);
+    }
+}
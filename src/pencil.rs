@@ -0,0 +1,117 @@
+// region:      IMPORTS
+
+use bevy::{prelude::*, utils::HashSet};
+use picross_handler::Cell;
+
+use crate::board::{Board, BoardLoadedEvent, CellChangedEvent, SpawnTilesEvent};
+use crate::layers::HIGHLIGHT_Z;
+
+// endregion
+
+// region:      CONSTANTS
+
+const PENCIL_COLOR: Color = Color::rgba(0.95, 0.55, 0.1, 0.9);
+const PENCIL_SIZE: f32 = 0.3; // of a tile
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct PencilMark {
+    pub x: usize,
+    pub y: usize,
+}
+
+// endregion
+
+// region:      RESOURCES
+
+/// Cells the player flagged with a button bound to `flag`. They are only a
+/// note to self, never sent to the server, and go away once the cell is
+/// filled or crossed.
+#[derive(Resource, Default)]
+pub struct PencilMarks(pub HashSet<(usize, usize)>);
+
+// endregion
+
+// region:      EVENTS
+
+pub struct PencilToggledEvent {
+    pub x: usize,
+    pub y: usize,
+}
+
+// endregion
+
+pub struct PencilPlugin;
+
+impl Plugin for PencilPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PencilMarks>()
+            .add_event::<PencilToggledEvent>()
+            .add_system(pencil_marks_system)
+            .add_system(pencil_draw_system.after(pencil_marks_system));
+    }
+}
+
+fn pencil_marks_system(
+    board: Res<Board>,
+    mut marks: ResMut<PencilMarks>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut cell_changed_event_reader: EventReader<CellChangedEvent>,
+    mut pencil_toggled_event_reader: EventReader<PencilToggledEvent>,
+) {
+    if board_loaded_event_reader.iter().last().is_some() {
+        marks.0.clear();
+    }
+    for event in pencil_toggled_event_reader.iter() {
+        // a decided cell needs no pencil
+        if board.p.get_cell(event.x, event.y) != Cell::Empty {
+            continue;
+        }
+        if !marks.0.remove(&(event.x, event.y)) {
+            marks.0.insert((event.x, event.y));
+        }
+    }
+    for event in cell_changed_event_reader.iter() {
+        if event.new != Cell::Empty && marks.0.contains(&(event.x, event.y)) {
+            marks.0.remove(&(event.x, event.y));
+        }
+    }
+}
+
+fn pencil_draw_system(
+    mut commands: Commands,
+    board: Res<Board>,
+    marks: Res<PencilMarks>,
+    mut spawn_tiles_event_reader: EventReader<SpawnTilesEvent>,
+    mark_query: Query<Entity, With<PencilMark>>,
+) {
+    let respawned = spawn_tiles_event_reader.iter().last().is_some();
+    if !(marks.is_changed() || respawned) {
+        return;
+    }
+    for entity in mark_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    for (x, y) in marks.0.iter() {
+        let (tile_x, tile_y) = board.cell_to_tile(*x, *y);
+        commands
+            .spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: PENCIL_COLOR,
+                    custom_size: Some(Vec2::splat(PENCIL_SIZE * board.pixels_per_tile)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(board.tile_translation(
+                    tile_x + 0.5,
+                    tile_y + 0.5,
+                    HIGHLIGHT_Z,
+                )),
+                ..Default::default()
+            })
+            .insert(PencilMark { x: *x, y: *y });
+    }
+}
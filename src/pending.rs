@@ -0,0 +1,205 @@
+// region:      IMPORTS
+
+use bevy::{prelude::*, utils::HashMap};
+use picross_handler::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::board::{
+    cell_to_char, char_to_cell, Board, BoardLoadedEvent, CellChangedEvent, ChangeSource,
+};
+use crate::timer::SolveTimer;
+use crate::{BoardUpdateEvent, BridgeCommandEvent, WASMSendChannel, GLOBAL_RECEIVER};
+
+// endregion
+
+// region:      GLOBAL
+
+/// Unconfirmed moves as of the last frame, for `has_pending_wasm` which
+/// runs outside of any system.
+static PENDING_MOVES: AtomicUsize = AtomicUsize::new(0);
+
+// endregion
+
+// region:      RESOURCES
+
+/// The local player's moves nobody has echoed back yet, with when they were
+/// made on the solve clock. A move is settled when a `"u"` update carries
+/// it, or when someone else's change replaces it, which `conflicts.rs`
+/// checks against. They only count as unconfirmed once an update shows a
+/// server is syncing the board, alone there is nobody to confirm anything.
+#[derive(Resource, Default)]
+pub struct PendingMoves {
+    cells: HashMap<(usize, usize), (Cell, f64)>,
+    synced: bool,
+}
+
+impl PendingMoves {
+    /// The unsettled local move on a cell and when it was made.
+    pub fn get(&self, x: usize, y: usize) -> Option<(Cell, f64)> {
+        self.cells.get(&(x, y)).copied()
+    }
+
+    /// Moves waiting on the server, none without one.
+    pub fn unconfirmed(&self) -> impl Iterator<Item = ((usize, usize), Cell)> + '_ {
+        self.cells
+            .iter()
+            .filter(|_| self.synced)
+            .map(|(position, (cell, _))| (*position, *cell))
+    }
+}
+
+// endregion
+
+pub struct PendingPlugin;
+
+impl Plugin for PendingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingMoves>()
+            .add_system(pending_moves_system)
+            .add_system(flush_command_system.after(pending_moves_system));
+    }
+}
+
+/// Whether leaving now could lose something: unconfirmed moves, or
+/// messages the host hasn't taken from `recv_wasm` yet.
+pub fn has_pending() -> bool {
+    let unsent = unsafe { GLOBAL_RECEIVER.as_ref() }
+        .and_then(|rx| rx.lock().ok())
+        .map_or(0, |rx| rx.len());
+    #[cfg(feature = "multiplayer")]
+    let unsent = unsent + crate::net_sim::held_outgoing();
+    PENDING_MOVES.load(Ordering::Relaxed) > 0 || unsent + crate::subscriptions::unclaimed_len() > 0
+}
+
+/// Sends `("pending", n)` whenever the number of unconfirmed moves changes,
+/// for host pages warning before they unload.
+pub fn pending_moves_system(
+    time: Res<Time>,
+    board: Res<Board>,
+    solve_timer: Res<SolveTimer>,
+    send_channel: Res<WASMSendChannel>,
+    mut pending: ResMut<PendingMoves>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut board_update_event_reader: EventReader<BoardUpdateEvent>,
+    mut cell_changed_event_reader: EventReader<CellChangedEvent>,
+) {
+    let before = pending.unconfirmed().count();
+    if board_loaded_event_reader.iter().last().is_some() {
+        pending.cells.clear();
+        pending.synced = false;
+    }
+
+    let width = board.p.get_width();
+    for event in board_update_event_reader.iter() {
+        pending.synced = true;
+        let update: Vec<Option<Cell>> = event.0.chars().map(char_to_cell).collect();
+        pending
+            .cells
+            .retain(|(x, y), (cell, _)| update.get(y * width + x) != Some(&Some(*cell)));
+    }
+    let now = solve_timer.elapsed(&time);
+    for event in cell_changed_event_reader.iter() {
+        let cell = (event.x, event.y);
+        match event.source {
+            ChangeSource::Player => {
+                pending.cells.insert(cell, (event.new, now));
+            }
+            ChangeSource::Remote => {
+                pending.cells.remove(&cell);
+            }
+            _ => (),
+        }
+    }
+
+    let count = pending.unconfirmed().count();
+    PENDING_MOVES.store(count, Ordering::Relaxed);
+    if count != before {
+        send_channel
+            .tx
+            .send((String::from("pending"), count.to_string()));
+    }
+}
+
+/// `("flush", "")` before navigating away: sends every unconfirmed move
+/// again, lets go of anything the connection simulator holds, then replies
+/// `("flush", n)` with the moves resent. Everything is in `recv_wasm` by then.
+fn flush_command_system(
+    board: Res<Board>,
+    pending: Res<PendingMoves>,
+    send_channel: Res<WASMSendChannel>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+) {
+    for event in bridge_command_event_reader.iter() {
+        if event.command != "flush" {
+            continue;
+        }
+        for ((x, y), cell) in pending.unconfirmed() {
+            send_channel.tx.send((
+                String::from("c"),
+                format!("{},{}", board.original_pos(x, y), cell_to_char(cell)),
+            ));
+        }
+        #[cfg(feature = "multiplayer")]
+        crate::net_sim::release_outgoing();
+        send_channel.tx.send((
+            String::from("flush"),
+            pending.unconfirmed().count().to_string(),
+        ));
+    }
+}
+
+// region:      TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::BoardAction;
+    use crate::harness::{plus_puzzle, Harness, SETTLE_TICKS};
+
+    #[test]
+    fn moves_stay_pending_until_an_update_carries_them() {
+        let mut harness = Harness::new();
+        assert!(harness.join(plus_puzzle().as_str(), "000000000"));
+        harness.send("u", "000000000");
+        harness.ticks(SETTLE_TICKS);
+        harness.replies();
+
+        harness.input_cell(0, 0, BoardAction::Fill);
+        let pending = (String::from("pending"), String::from("1"));
+        assert!(harness.replies().contains(&pending));
+
+        harness.send("u", "100000000");
+        harness.ticks(SETTLE_TICKS);
+        let settled = (String::from("pending"), String::from("0"));
+        assert!(harness.replies().contains(&settled));
+    }
+
+    #[test]
+    fn moves_are_kept_for_conflicts_but_not_pending_without_a_server() {
+        let mut harness = Harness::new();
+        assert!(harness.join(plus_puzzle().as_str(), "000000000"));
+        harness.replies();
+
+        harness.input_cell(0, 0, BoardAction::Fill);
+        assert!(harness
+            .replies()
+            .iter()
+            .all(|(command, _)| command != "pending"));
+        let pending = harness.app.world.resource::<PendingMoves>();
+        assert!(pending.get(0, 0).map(|(cell, _)| cell) == Some(Cell::Filled));
+        assert_eq!(pending.unconfirmed().count(), 0);
+
+        // someone else's change settles the move
+        harness.send("u", "000000000");
+        harness.ticks(SETTLE_TICKS);
+        assert_eq!(harness.cells(), "000000000");
+        assert!(harness
+            .app
+            .world
+            .resource::<PendingMoves>()
+            .get(0, 0)
+            .is_none());
+    }
+}
+
+// endregion
@@ -0,0 +1,474 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+use image::{imageops, imageops::FilterType, GrayImage};
+use picross_handler::Cell;
+
+use crate::board::{Board, ChangeSource};
+use crate::board_commands::BoardCommand;
+use crate::bridge::reply_error;
+use crate::{BridgeCommandEvent, WASMSendChannel};
+
+// endregion
+
+// region:      CONSTANTS
+
+// photos are shrunk to this before anything else, plenty for a paper grid
+const MAX_SIDE: u32 = 512;
+
+// of a cell, sampled away from its gridlines
+const INSET: f32 = 0.2;
+const FILL_SAMPLES: usize = 6; // per side
+const DIAGONAL_SAMPLES: usize = 10;
+const FILL_SHARE: f32 = 0.6;
+const CROSS_SHARE: f32 = 0.7;
+
+// a gridline is dark across this share of the other lines' cells
+const LINE_SHARE: f32 = 0.75;
+const LINE_TOLERANCE: f32 = 0.3; // of a cell, from where the line belongs
+
+// endregion
+
+pub struct PhotoImportPlugin;
+
+impl Plugin for PhotoImportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(photo_import_system);
+    }
+}
+
+/// `("photo", base64 png)` loads a photographed paper copy of the current
+/// puzzle as the board's progress: shaded cells fill, cells with an X drawn
+/// through them cross, everything else empties. Experimental, the grid is
+/// found as the largest dark shape clear of the photo's edges, so a photo
+/// should show the whole puzzle on plain paper. When that guess is wrong the
+/// host can send the grid's corners first, `tlx,tly,trx,try,brx,bry,blx,bly;`
+/// as fractions of the photo's width and height. The photo must be a PNG,
+/// browsers re-encode any other through a canvas. Replies `("photo",
+/// {"filled":n,"crossed":n})`, or an error when the photo's grid doesn't
+/// match the board.
+fn photo_import_system(
+    board: Res<Board>,
+    send_channel: Res<WASMSendChannel>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+    mut board_command_event_writer: EventWriter<BoardCommand>,
+) {
+    for event in bridge_command_event_reader.iter() {
+        if event.command != "photo" {
+            continue;
+        }
+        // paper copies are in the puzzle's own layout, not the randomized one
+        let p = &board.p;
+        let (width, height) = match board.orientation.transpose {
+            true => (p.get_height(), p.get_width()),
+            false => (p.get_width(), p.get_height()),
+        };
+        let cells = match read_photo(&event.data, width, height) {
+            Ok(cells) => cells,
+            Err(err) => {
+                reply_error(&send_channel, format!("photo: {}", err));
+                continue;
+            }
+        };
+
+        let (mut filled, mut crossed) = (0, 0);
+        for (i, cell) in cells.into_iter().enumerate() {
+            // photo rows run top down, board rows bottom up
+            let (x, y) = (i % width, height - 1 - i / width);
+            let (x, y) = board.orientation.to_transformed(x, y, width, height);
+            match cell {
+                Cell::Filled => filled += 1,
+                Cell::Crossed => crossed += 1,
+                _ => (),
+            }
+            if p.get_cell(x, y) != cell {
+                board_command_event_writer.send(BoardCommand {
+                    x,
+                    y,
+                    cell,
+                    source: ChangeSource::Player,
+                });
+            }
+        }
+        send_channel.tx.send((
+            String::from("photo"),
+            format!("{{\"filled\":{},\"crossed\":{}}}", filled, crossed),
+        ));
+    }
+}
+
+/// The `width` by `height` cells of the photographed grid, rows top down.
+fn read_photo(data: &str, width: usize, height: usize) -> Result<Vec<Cell>, String> {
+    let (corners, encoded) = match data.split_once(';') {
+        Some((corners, encoded)) => (Some(parse_corners(corners)?), encoded),
+        None => (None, data),
+    };
+    let bytes = base64::decode(encoded.trim()).map_err(|err| err.to_string())?;
+    let image = image::load_from_memory(&bytes).map_err(|err| err.to_string())?;
+    let mut gray = image.to_luma8();
+    let (w, h) = gray.dimensions();
+    if w.max(h) > MAX_SIDE {
+        let scale = MAX_SIDE as f32 / w.max(h) as f32;
+        let (w, h) = ((w as f32 * scale) as u32, (h as f32 * scale) as u32);
+        gray = imageops::resize(&gray, w.max(1), h.max(1), FilterType::Triangle);
+    }
+
+    let mask = Mask::new(&gray);
+    let corners = match corners {
+        Some(corners) => corners.map(|c| c * Vec2::new(mask.width as f32, mask.height as f32)),
+        None => mask.find_grid().ok_or("no grid found")?,
+    };
+    let quad = Quad::new(corners);
+
+    let columns = |u: f32| quad.line_share(&mask, u, height, true);
+    let rows = |v: f32| quad.line_share(&mask, v, width, false);
+    let steps = (quad.span() * 2.) as usize;
+    if !lines_match(columns, width, steps) || !lines_match(rows, height, steps) {
+        return Err(format!("grid doesn't look {}x{}", width, height));
+    }
+
+    Ok((0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| quad.classify(&mask, x, y, width, height))
+        .collect())
+}
+
+/// Eight fractions of the photo's size, the grid's corners clockwise from
+/// its top left.
+fn parse_corners(s: &str) -> Result<[Vec2; 4], String> {
+    let values: Vec<f32> = s
+        .split(',')
+        .map(|value| value.trim().parse::<f32>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("invalid corners {}", s))?;
+    if values.len() != 8 || values.iter().any(|v| !(0. ..=1.).contains(v)) {
+        return Err(format!("invalid corners {}", s));
+    }
+    Ok([0, 2, 4, 6].map(|i| Vec2::new(values[i], values[i + 1])))
+}
+
+/// Whether the line shares along a profile show exactly the `count` + 1
+/// gridlines of `count` cells. Fully shaded lines can hide gridlines between
+/// them, so fewer runs pass but more never do.
+fn lines_match(share: impl Fn(f32) -> f32, count: usize, steps: usize) -> bool {
+    let steps = steps.max(count * 4);
+    let dark: Vec<bool> = (0..=steps)
+        .map(|i| share(i as f32 / steps as f32) >= LINE_SHARE)
+        .collect();
+    let runs = dark
+        .iter()
+        .zip(std::iter::once(&false).chain(dark.iter()))
+        .filter(|(now, before)| **now && !**before)
+        .count();
+    let found = |k: usize| {
+        let at = k as f32 / count as f32;
+        (0..=steps).any(|i| {
+            let t = i as f32 / steps as f32;
+            dark[i] && (t - at).abs() <= LINE_TOLERANCE / count as f32
+        })
+    };
+    runs <= count + 1 && (0..=count).all(found)
+}
+
+/// Ink and paper told apart by Otsu's threshold, pixel rows top down.
+struct Mask {
+    width: usize,
+    height: usize,
+    dark: Vec<bool>,
+    /// Dark or next to dark, so thin pen lines survive a slightly off sample.
+    thick: Vec<bool>,
+}
+
+impl Mask {
+    fn new(gray: &GrayImage) -> Self {
+        let threshold = otsu_threshold(gray);
+        let (width, height) = (gray.width() as usize, gray.height() as usize);
+        let dark: Vec<bool> = gray.pixels().map(|pixel| pixel.0[0] <= threshold).collect();
+        let thick = (0..width * height)
+            .map(|i| {
+                let (x, y) = (i % width, i / width);
+                let (x0, x1) = (x.saturating_sub(1), (x + 1).min(width - 1));
+                let (y0, y1) = (y.saturating_sub(1), (y + 1).min(height - 1));
+                (y0..=y1).any(|y| (x0..=x1).any(|x| dark[y * width + x]))
+            })
+            .collect();
+        Self {
+            width,
+            height,
+            dark,
+            thick,
+        }
+    }
+
+    fn sample(&self, cells: &[bool], point: Vec2) -> bool {
+        let (x, y) = (point.x.floor(), point.y.floor());
+        if x < 0. || y < 0. || x >= self.width as f32 || y >= self.height as f32 {
+            return false;
+        }
+        cells[y as usize * self.width + x as usize]
+    }
+
+    /// Corners of the largest dark shape not touching the photo's edges,
+    /// which on a photo of a puzzle is its connected gridlines. Clockwise
+    /// from the top left, the points furthest along each diagonal.
+    fn find_grid(&self) -> Option<[Vec2; 4]> {
+        let (width, height) = (self.width, self.height);
+        let mut seen = vec![false; width * height];
+        let mut best: Option<(usize, [Vec2; 4])> = None;
+        for start in 0..width * height {
+            if !self.dark[start] || seen[start] {
+                continue;
+            }
+            seen[start] = true;
+            let mut stack = vec![start];
+            let (mut size, mut edge) = (0, false);
+            // lowest and highest x + y, then x - y
+            let mut extremes = [(f32::MAX, Vec2::ZERO), (f32::MIN, Vec2::ZERO)];
+            let mut diagonals = [(f32::MAX, Vec2::ZERO), (f32::MIN, Vec2::ZERO)];
+            while let Some(i) = stack.pop() {
+                let (x, y) = (i % width, i / width);
+                size += 1;
+                edge |= x == 0 || y == 0 || x == width - 1 || y == height - 1;
+                let point = Vec2::new(x as f32, y as f32);
+                let (sum, difference) = (point.x + point.y, point.x - point.y);
+                if sum < extremes[0].0 {
+                    extremes[0] = (sum, point);
+                }
+                if sum > extremes[1].0 {
+                    extremes[1] = (sum, point);
+                }
+                if difference < diagonals[0].0 {
+                    diagonals[0] = (difference, point);
+                }
+                if difference > diagonals[1].0 {
+                    diagonals[1] = (difference, point);
+                }
+                for ny in y.saturating_sub(1)..=(y + 1).min(height - 1) {
+                    for nx in x.saturating_sub(1)..=(x + 1).min(width - 1) {
+                        let n = ny * width + nx;
+                        if self.dark[n] && !seen[n] {
+                            seen[n] = true;
+                            stack.push(n);
+                        }
+                    }
+                }
+            }
+            if !edge && best.map_or(true, |(best, _)| size > best) {
+                let corners = [extremes[0].1, diagonals[1].1, extremes[1].1, diagonals[0].1];
+                best = Some((size, corners));
+            }
+        }
+        best.map(|(_, corners)| corners)
+    }
+}
+
+/// Otsu's method, the gray level splitting the histogram into two classes
+/// with the most variance between them.
+fn otsu_threshold(gray: &GrayImage) -> u8 {
+    let mut histogram = [0u64; 256];
+    for pixel in gray.pixels() {
+        histogram[pixel.0[0] as usize] += 1;
+    }
+    let total: u64 = histogram.iter().sum();
+    let sum: f64 = histogram
+        .iter()
+        .enumerate()
+        .map(|(level, count)| (level as u64 * count) as f64)
+        .sum();
+
+    let (mut below, mut below_sum) = (0u64, 0f64);
+    let (mut best, mut threshold) = (0f64, 0u8);
+    for (level, count) in histogram.iter().enumerate() {
+        below += count;
+        if below == 0 {
+            continue;
+        }
+        let above = total - below;
+        if above == 0 {
+            break;
+        }
+        below_sum += (level as u64 * count) as f64;
+        let mean_below = below_sum / below as f64;
+        let mean_above = (sum - below_sum) / above as f64;
+        let between = below as f64 * above as f64 * (mean_below - mean_above).powi(2);
+        if between > best {
+            best = between;
+            threshold = level as u8;
+        }
+    }
+    threshold
+}
+
+/// Perspective mapping of the unit square onto the grid's four corners, so
+/// cells are sampled where they are on a photo taken at an angle.
+struct Quad {
+    corners: [Vec2; 4],
+    a: f32,
+    b: f32,
+    c: f32,
+    d: f32,
+    e: f32,
+    f: f32,
+    g: f32,
+    h: f32,
+}
+
+impl Quad {
+    /// Heckbert's square to quadrilateral projection, `corners` clockwise
+    /// from the one at (0, 0).
+    fn new(corners: [Vec2; 4]) -> Self {
+        let [p0, p1, p2, p3] = corners;
+        let d1 = p1 - p2;
+        let d2 = p3 - p2;
+        let d3 = p0 - p1 + p2 - p3;
+        let (g, h) = match d3.abs().max_element() < f32::EPSILON {
+            // a parallelogram, no foreshortening
+            true => (0., 0.),
+            false => {
+                let den = d1.perp_dot(d2);
+                (d3.perp_dot(d2) / den, d1.perp_dot(d3) / den)
+            }
+        };
+        Self {
+            corners,
+            a: p1.x - p0.x + g * p1.x,
+            b: p3.x - p0.x + h * p3.x,
+            c: p0.x,
+            d: p1.y - p0.y + g * p1.y,
+            e: p3.y - p0.y + h * p3.y,
+            f: p0.y,
+            g,
+            h,
+        }
+    }
+
+    /// The photo's pixel at `(u, v)` of the grid, each from 0 to 1.
+    fn map(&self, u: f32, v: f32) -> Vec2 {
+        let w = self.g * u + self.h * v + 1.;
+        Vec2::new(
+            (self.a * u + self.b * v + self.c) / w,
+            (self.d * u + self.e * v + self.f) / w,
+        )
+    }
+
+    /// The longest side in pixels.
+    fn span(&self) -> f32 {
+        (0..4)
+            .map(|i| self.corners[i].distance(self.corners[(i + 1) % 4]))
+            .fold(0., f32::max)
+    }
+
+    /// Share of the `across` cells' centers dark at `t` along the grid,
+    /// down each column's line when `vertical`, else along each row's.
+    fn line_share(&self, mask: &Mask, t: f32, across: usize, vertical: bool) -> f32 {
+        let dark = (0..across)
+            .map(|k| (k as f32 + 0.5) / across as f32)
+            .filter(|s| match vertical {
+                true => mask.sample(&mask.thick, self.map(t, *s)),
+                false => mask.sample(&mask.thick, self.map(*s, t)),
+            })
+            .count();
+        dark as f32 / across.max(1) as f32
+    }
+
+    /// Mostly ink fills, ink down both diagonals crosses.
+    fn classify(&self, mask: &Mask, x: usize, y: usize, width: usize, height: usize) -> Cell {
+        let at = |s: f32, t: f32| {
+            let inside = |i: f32| INSET + (1. - 2. * INSET) * i;
+            self.map(
+                (x as f32 + inside(s)) / width as f32,
+                (y as f32 + inside(t)) / height as f32,
+            )
+        };
+
+        let fill_samples = FILL_SAMPLES * FILL_SAMPLES;
+        let filled = (0..fill_samples)
+            .map(|i| {
+                let (s, t) = (i % FILL_SAMPLES, i / FILL_SAMPLES);
+                let (s, t) = (
+                    (s as f32 + 0.5) / FILL_SAMPLES as f32,
+                    (t as f32 + 0.5) / FILL_SAMPLES as f32,
+                );
+                at(s, t)
+            })
+            .filter(|point| mask.sample(&mask.dark, *point))
+            .count();
+        if filled as f32 / fill_samples as f32 >= FILL_SHARE {
+            return Cell::Filled;
+        }
+
+        let diagonal = |flip: bool| {
+            let dark = (0..DIAGONAL_SAMPLES)
+                .map(|i| i as f32 / (DIAGONAL_SAMPLES - 1) as f32)
+                .filter(|s| {
+                    let t = if flip { 1. - s } else { *s };
+                    mask.sample(&mask.thick, at(*s, t))
+                })
+                .count();
+            dark as f32 / DIAGONAL_SAMPLES as f32
+        };
+        match diagonal(false) >= CROSS_SHARE && diagonal(true) >= CROSS_SHARE {
+            true => Cell::Crossed,
+            false => Cell::Empty,
+        }
+    }
+}
+
+// region:      TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::harness::{plus_puzzle, Harness, SETTLE_TICKS};
+
+    /// A 3x3 paper grid as a base64 PNG, `cells` rows top down with `1` shaded
+    /// and `X` crossed.
+    fn paper_photo(cells: &str) -> String {
+        const MARGIN: i32 = 30;
+        const CELL: i32 = 40;
+        const LINE: i32 = 2;
+        let cells = cells.as_bytes();
+        let side = (MARGIN * 2 + CELL * 3 + LINE) as u32;
+        let image = image::GrayImage::from_fn(side, side, |px, py| {
+            let (gx, gy) = (px as i32 - MARGIN, py as i32 - MARGIN);
+            if !(0..CELL * 3 + LINE).contains(&gx) || !(0..CELL * 3 + LINE).contains(&gy) {
+                return image::Luma([255]);
+            }
+            let (lx, ly) = (gx % CELL, gy % CELL);
+            let cell = cells[((gy / CELL).min(2) * 3 + (gx / CELL).min(2)) as usize];
+            let ink = lx < LINE
+                || ly < LINE
+                || cell == b'1'
+                || (cell == b'X' && ((lx - ly).abs() <= LINE || (lx + ly - CELL).abs() <= LINE));
+            image::Luma([if ink { 0 } else { 255 }])
+        });
+        let mut png = Vec::new();
+        image
+            .write_to(
+                &mut std::io::Cursor::new(&mut png),
+                image::ImageOutputFormat::Png,
+            )
+            .unwrap();
+        base64::encode(png)
+    }
+
+    #[test]
+    fn photo_of_a_paper_grid_loads_as_progress() {
+        let mut harness = Harness::new();
+        assert!(harness.join(plus_puzzle().as_str(), "000000000"));
+        harness.replies();
+
+        harness.send("photo", &paper_photo("1X0000001"));
+        harness.ticks(SETTLE_TICKS);
+
+        // board rows run bottom up
+        assert_eq!(harness.cells(), "0010001X0");
+        let reply = (
+            String::from("photo"),
+            String::from("{\"filled\":2,\"crossed\":1}"),
+        );
+        assert!(harness.replies().contains(&reply));
+    }
+}
+
+// endregion
@@ -0,0 +1,211 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+
+use crate::camera::BoardView;
+use crate::hud_scale::HudScale;
+use crate::layers::{CHILD_Z_OFFSET, HUD_Z};
+use crate::options::BoardOptions;
+use crate::stats::Stats;
+use crate::timer::SolveTimer;
+use crate::win::PuzzleSolvedEvent;
+use crate::{GameTextures, NewBoardEvent, WASMSendChannel, WinSize};
+
+// endregion
+
+// region:      CONSTANTS
+
+const DEFAULT_COUNTDOWN_SECONDS: f32 = 5.;
+const INTERSTITIAL_FONT_SIZE: f32 = 28.;
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct PlaylistInterstitial;
+
+#[derive(Component)]
+pub struct InterstitialText;
+
+// endregion
+
+// region:      RESOURCES
+
+/// Clues of the puzzles from a join, played in order. `current` is the index
+/// of the puzzle on the board, the joined one being 0.
+#[derive(Resource, Default)]
+pub struct Playlist {
+    pub puzzles: Vec<String>,
+    pub current: usize,
+    countdown: Option<f32>,
+    summary: String,
+}
+
+impl Playlist {
+    /// Replaces the queue, `queued` being the puzzles after the joined one.
+    pub fn start(&mut self, joined: String, queued: Vec<String>) {
+        let mut puzzles = vec![joined];
+        puzzles.extend(queued);
+        *self = Playlist {
+            puzzles,
+            ..Default::default()
+        };
+    }
+
+    pub fn len(&self) -> usize {
+        self.puzzles.len()
+    }
+
+    /// Switches to another puzzle in the list, returning its load event.
+    pub fn jump(&mut self, index: usize) -> Option<NewBoardEvent> {
+        let clues = self.puzzles.get(index)?.clone();
+        self.current = index;
+        self.countdown = None;
+        Some(NewBoardEvent {
+            clues,
+            cells: String::new(),
+        })
+    }
+
+    fn progress_json(&self, event: &str, elapsed_ms: u64) -> String {
+        format!(
+            "{{\"event\":\"{}\",\"index\":{},\"count\":{},\"elapsed_ms\":{}}}",
+            event,
+            self.current,
+            self.len(),
+            elapsed_ms
+        )
+    }
+}
+
+// endregion
+
+pub struct PlaylistPlugin;
+
+impl Plugin for PlaylistPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Playlist>()
+            .add_system(playlist_solved_system)
+            .add_system(playlist_countdown_system.after(playlist_solved_system));
+    }
+}
+
+/// Replies `("playlist", json)` with the solved puzzle's index, then counts
+/// down to the next one.
+fn playlist_solved_system(
+    time: Res<Time>,
+    stats: Res<Stats>,
+    options: Res<BoardOptions>,
+    solve_timer: Res<SolveTimer>,
+    send_channel: Res<WASMSendChannel>,
+    mut playlist: ResMut<Playlist>,
+    mut puzzle_solved_event_reader: EventReader<PuzzleSolvedEvent>,
+) {
+    for _ in puzzle_solved_event_reader.iter() {
+        // a single puzzle join isn't a playlist
+        if playlist.len() <= 1 {
+            continue;
+        }
+
+        let elapsed_ms = solve_timer.elapsed_ms(&time);
+        let last = playlist.current + 1 >= playlist.len();
+        let event = if last { "complete" } else { "solved" };
+        send_channel.tx.send((
+            String::from("playlist"),
+            playlist.progress_json(event, elapsed_ms),
+        ));
+        if last {
+            continue;
+        }
+
+        playlist.summary = format!(
+            "Puzzle {} of {} solved in {}:{:02}\nmistakes: {}  conflicts: {}",
+            playlist.current + 1,
+            playlist.len(),
+            elapsed_ms / 60000,
+            elapsed_ms / 1000 % 60,
+            stats.penalties,
+            stats.conflicts.len()
+        );
+        playlist.countdown = Some(options.get_f32("playlist_countdown", DEFAULT_COUNTDOWN_SECONDS));
+    }
+}
+
+fn playlist_countdown_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    view: Res<BoardView>,
+    hud_scale: Res<HudScale>,
+    win_size: Res<WinSize>,
+    game_textures: Res<GameTextures>,
+    send_channel: Res<WASMSendChannel>,
+    mut playlist: ResMut<Playlist>,
+    mut new_board_event_writer: EventWriter<NewBoardEvent>,
+    interstitial_query: Query<Entity, With<PlaylistInterstitial>>,
+    mut text_query: Query<&mut Text, With<InterstitialText>>,
+) {
+    let remaining = match playlist.countdown {
+        Some(remaining) => remaining - time.delta_seconds(),
+        None => {
+            for entity in interstitial_query.iter() {
+                commands.entity(entity).despawn_recursive();
+            }
+            return;
+        }
+    };
+
+    if remaining <= 0. {
+        let next = playlist.current + 1;
+        if let Some(event) = playlist.jump(next) {
+            new_board_event_writer.send(event);
+        }
+        send_channel.tx.send((
+            String::from("playlist"),
+            playlist.progress_json("started", 0),
+        ));
+        return;
+    }
+    playlist.countdown = Some(remaining);
+
+    let message = format!(
+        "{}\nnext puzzle in {}",
+        playlist.summary,
+        remaining.ceil() as u32
+    );
+    if let Some(mut text) = text_query.iter_mut().next() {
+        text.sections[0].value = message;
+        return;
+    }
+
+    // centered on whatever part of the board is in view
+    let center = view.screen_to_world(Vec2::new(win_size.w, win_size.h) / 2.);
+    commands
+        .spawn(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(0., 0., 0., 0.6),
+                custom_size: Some(Vec2::new(win_size.w, win_size.h) * view.scale),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(center.x, center.y, HUD_Z),
+            ..Default::default()
+        })
+        .insert(PlaylistInterstitial)
+        .with_children(|parent| {
+            parent
+                .spawn(Text2dBundle {
+                    text: Text::from_section(
+                        message,
+                        TextStyle {
+                            font: game_textures.font.clone(),
+                            font_size: INTERSTITIAL_FONT_SIZE * hud_scale.world(&view),
+                            color: Color::WHITE,
+                        },
+                    )
+                    .with_alignment(TextAlignment::CENTER),
+                    transform: Transform::from_xyz(0., 0., CHILD_Z_OFFSET),
+                    ..Default::default()
+                })
+                .insert(InterstitialText);
+        });
+}
@@ -0,0 +1,167 @@
+// region:      IMPORTS
+
+use bevy::{input::mouse::MouseMotion, prelude::*, window::CursorGrabMode};
+
+use crate::board::Board;
+use crate::camera::BoardView;
+use crate::hud_scale::HudScale;
+use crate::layers::CURSOR_Z;
+use crate::screens::Screen;
+use crate::settings::Settings;
+
+// endregion
+
+// region:      CONSTANTS
+
+const CROSSHAIR_LENGTH: f32 = 14.;
+const CROSSHAIR_WIDTH: f32 = 2.;
+const CROSSHAIR_COLOR: Color = Color::rgb(0.9, 0.2, 0.2);
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct VirtualCursor;
+
+// endregion
+
+// region:      RESOURCES
+
+/// Speed solving with the pointer captured, on with the `pointer_lock`
+/// setting. The first click on the board captures it, from then on mouse
+/// motion times `pointer_sensitivity` moves a cursor of our own that stops
+/// at the edges of the board, so a fast flick can't click outside the
+/// canvas mid race. Escape, leaving the window or another screen let go.
+#[derive(Resource, Default)]
+pub struct PointerLock {
+    /// The virtual cursor in screen units while captured, board input uses
+    /// it in place of the window's.
+    pub cursor: Option<Vec2>,
+}
+
+// endregion
+
+pub struct PointerLockPlugin;
+
+impl Plugin for PointerLockPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PointerLock>()
+            .add_system(pointer_lock_system)
+            .add_system(virtual_cursor_draw_system.after(pointer_lock_system));
+    }
+}
+
+/// The board including its clues, as min and max in screen units.
+fn board_rect(board: &Board, view: &BoardView) -> (Vec2, Vec2) {
+    let origin = Vec2::new(board.origin.0, board.origin.1);
+    let size = Vec2::new(board.w as f32, board.h as f32) * board.pixels_per_tile;
+    (
+        view.world_to_screen(origin),
+        view.world_to_screen(origin + size),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn pointer_lock_system(
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<MouseButton>>,
+    board: Res<Board>,
+    view: Res<BoardView>,
+    settings: Res<Settings>,
+    screen: Res<State<Screen>>,
+    mut windows: ResMut<Windows>,
+    mut pointer_lock: ResMut<PointerLock>,
+    mut mouse_motion_event_reader: EventReader<MouseMotion>,
+) {
+    let motion: Vec2 = mouse_motion_event_reader
+        .iter()
+        .map(|event| event.delta)
+        .sum();
+    let window = match windows.get_primary_mut() {
+        Some(window) => window,
+        None => return,
+    };
+    let (min, max) = board_rect(&board, &view);
+
+    let release = !settings.pointer_lock
+        || *screen.current() != Screen::Board
+        || keys.just_pressed(KeyCode::Escape)
+        || !window.is_focused();
+    if release {
+        if pointer_lock.cursor.is_some() {
+            window.set_cursor_grab_mode(CursorGrabMode::None);
+            window.set_cursor_visibility(true);
+            pointer_lock.cursor = None;
+        }
+        return;
+    }
+
+    match pointer_lock.cursor {
+        Some(cursor) => {
+            // motion counts down the screen, cursor positions up
+            let moved = cursor + Vec2::new(motion.x, -motion.y) * settings.pointer_sensitivity;
+            let clamped = moved.clamp(min, max);
+            if clamped != cursor {
+                pointer_lock.cursor = Some(clamped);
+            }
+        }
+        None => {
+            // browsers only grant the lock from a click
+            let start = window
+                .cursor_position()
+                .filter(|_| buttons.just_pressed(MouseButton::Left))
+                .filter(|pos| pos.cmpge(min).all() && pos.cmple(max).all());
+            if let Some(start) = start {
+                window.set_cursor_grab_mode(CursorGrabMode::Locked);
+                window.set_cursor_visibility(false);
+                pointer_lock.cursor = Some(start);
+            }
+        }
+    }
+}
+
+fn virtual_cursor_draw_system(
+    mut commands: Commands,
+    view: Res<BoardView>,
+    hud_scale: Res<HudScale>,
+    pointer_lock: Res<PointerLock>,
+    mut cursor_query: Query<(Entity, &mut Transform), With<VirtualCursor>>,
+) {
+    let cursor = match pointer_lock.cursor {
+        Some(cursor) => view.screen_to_world(cursor),
+        None => {
+            for (entity, _) in cursor_query.iter() {
+                commands.entity(entity).despawn();
+            }
+            return;
+        }
+    };
+    if !cursor_query.is_empty() && !view.is_changed() && !hud_scale.is_changed() {
+        for (_, mut transform) in cursor_query.iter_mut() {
+            transform.translation = cursor.extend(CURSOR_Z);
+        }
+        return;
+    }
+
+    for (entity, _) in cursor_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    let (long, thin) = (
+        CROSSHAIR_LENGTH * hud_scale.world(&view),
+        CROSSHAIR_WIDTH * hud_scale.world(&view),
+    );
+    for size in [Vec2::new(long, thin), Vec2::new(thin, long)] {
+        commands
+            .spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: CROSSHAIR_COLOR,
+                    custom_size: Some(size),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(cursor.extend(CURSOR_Z)),
+                ..Default::default()
+            })
+            .insert(VirtualCursor);
+    }
+}
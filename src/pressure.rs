@@ -0,0 +1,96 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use wasm_bindgen::{closure::Closure, JsCast};
+
+use crate::settings::Settings;
+
+// endregion
+
+// region:      GLOBAL
+
+/// Set by the canvas' pointer listeners, outside of any system: whether the
+/// latest pointer is a pen touching the screen, and how hard, as f32 bits.
+static PEN_DOWN: AtomicBool = AtomicBool::new(false);
+static PEN_PRESSURE: AtomicU32 = AtomicU32::new(0);
+
+// endregion
+
+// region:      RESOURCES
+
+/// How hard a pen is pressed, against the `pen_light_pressure` and
+/// `pen_firm_pressure` settings.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PenWeight {
+    /// Pencil marks instead of fills.
+    Light,
+    /// Between the thresholds, nothing, so a stroke wavering around one
+    /// doesn't flip between the two.
+    Unsure,
+    Firm,
+}
+
+/// The cell a light pen stroke last pencilled, so it isn't toggled back off
+/// before the mark is in `PencilMarks`.
+#[derive(Resource, Default)]
+pub struct PenInput {
+    pub pencilled: Option<(usize, usize)>,
+}
+
+// endregion
+
+pub struct PressurePlugin;
+
+impl Plugin for PressurePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PenInput>()
+            .add_startup_system(pressure_listener_system);
+    }
+}
+
+fn pressure_listener_system() {
+    let canvas = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.get_element_by_id("bevy-canvas"));
+    let canvas = match canvas {
+        Some(canvas) => canvas,
+        None => {
+            warn!("No canvas to read pen pressure from");
+            return;
+        }
+    };
+
+    let listener =
+        Closure::<dyn FnMut(web_sys::PointerEvent)>::new(|event: web_sys::PointerEvent| {
+            // a pen hovering reports pressure 0, mice and fingers don't count
+            let pen = event.pointer_type() == "pen" && event.buttons() != 0;
+            PEN_DOWN.store(pen, Ordering::SeqCst);
+            PEN_PRESSURE.store(event.pressure().to_bits(), Ordering::SeqCst);
+        });
+    for name in ["pointerdown", "pointermove", "pointerup", "pointercancel"] {
+        if let Err(err) =
+            canvas.add_event_listener_with_callback(name, listener.as_ref().unchecked_ref())
+        {
+            warn!("Unable to listen for {}: {:?}", name, err);
+        }
+    }
+    // the canvas lives as long as the page
+    listener.forget();
+}
+
+/// How hard the pen painting right now is pressed, None for any other
+/// pointer or with the `pen_pressure` setting off.
+pub fn pen_weight(settings: &Settings) -> Option<PenWeight> {
+    if !settings.pen_pressure || !PEN_DOWN.load(Ordering::SeqCst) {
+        return None;
+    }
+    let pressure = f32::from_bits(PEN_PRESSURE.load(Ordering::SeqCst));
+    Some(if pressure < settings.pen_light_pressure {
+        PenWeight::Light
+    } else if pressure < settings.pen_firm_pressure {
+        PenWeight::Unsure
+    } else {
+        PenWeight::Firm
+    })
+}
@@ -0,0 +1,234 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+use wasm_bindgen::JsValue;
+
+use crate::bridge::{check_cells, reply_error};
+use crate::camera::BoardView;
+use crate::campaign::{slot, thumbnail_image};
+use crate::layers::{CHILD_Z_OFFSET, HUD_PANEL_Z, HUD_Z};
+use crate::screens::Screen;
+use crate::{BridgeCommandEvent, GameTextures, WASMSendChannel, WinSize};
+
+// endregion
+
+// region:      CONSTANTS
+
+const MAX_PREVIEW_ROOMS: usize = 64;
+const MAX_PREVIEW_CELLS: usize = 50 * 50; // per room, previews are meant to be tiny
+const PREVIEW_PADDING: f32 = 0.2; // fraction of a slot, leaves room for the id
+const PREVIEW_FONT_SIZE: f32 = 0.12; // fraction of a slot
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct PreviewEntity;
+
+// endregion
+
+// region:      RESOURCES
+
+pub struct RoomPreview {
+    pub id: String,
+    pub width: usize,
+    pub height: usize,
+    pub cells: String,
+}
+
+/// Room states from `("preview", json)`, drawn as a grid of miniature boards
+/// so a lobby page can reuse this module for its previews. The json is a
+/// list of `{"id", "width", "height", "cells"}`, an empty list goes back to
+/// the board.
+#[derive(Resource, Default)]
+pub struct LobbyPreview {
+    pub rooms: Vec<RoomPreview>,
+}
+
+// endregion
+
+pub struct PreviewPlugin;
+
+impl Plugin for PreviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LobbyPreview>()
+            .add_system(preview_command_system)
+            .add_system_set(SystemSet::on_update(Screen::Preview).with_system(preview_draw_system))
+            .add_system_set(SystemSet::on_exit(Screen::Preview).with_system(preview_exit_system));
+    }
+}
+
+fn parse_rooms(data: &str) -> Result<Vec<RoomPreview>, String> {
+    let json = js_sys::JSON::parse(data)
+        .ok()
+        .filter(|json| js_sys::Array::is_array(json))
+        .ok_or_else(|| String::from("preview: expected a json list"))?;
+    let rooms = js_sys::Array::from(&json);
+    if rooms.length() as usize > MAX_PREVIEW_ROOMS {
+        return Err(format!(
+            "preview: {} rooms, limit is {}",
+            rooms.length(),
+            MAX_PREVIEW_ROOMS
+        ));
+    }
+
+    let mut previews = Vec::new();
+    for (i, room) in rooms.iter().enumerate() {
+        let field = |key: &str| js_sys::Reflect::get(&room, &JsValue::from_str(key)).ok();
+        let size = |key: &str| {
+            field(key)
+                .and_then(|value| value.as_f64())
+                .map(|n| n as usize)
+        };
+        let (id, width, height, cells) = match (
+            field("id").and_then(|value| value.as_string()),
+            size("width"),
+            size("height"),
+            field("cells").and_then(|value| value.as_string()),
+        ) {
+            (Some(id), Some(width), Some(height), Some(cells)) => (id, width, height, cells),
+            _ => {
+                return Err(format!(
+                    "preview: room {} needs id, width, height and cells",
+                    i
+                ))
+            }
+        };
+        if width == 0 || height == 0 || width * height > MAX_PREVIEW_CELLS {
+            return Err(format!("preview: room {} is {}x{}", id, width, height));
+        }
+        if cells.chars().count() != width * height {
+            return Err(format!(
+                "preview: room {} has {} cells, expected {}",
+                id,
+                cells.chars().count(),
+                width * height
+            ));
+        }
+        check_cells(&cells)?;
+        previews.push(RoomPreview {
+            id,
+            width,
+            height,
+            cells,
+        });
+    }
+    Ok(previews)
+}
+
+/// Replaces the previews whole, replies `("preview", count)`.
+fn preview_command_system(
+    send_channel: Res<WASMSendChannel>,
+    mut preview: ResMut<LobbyPreview>,
+    mut screen: ResMut<State<Screen>>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+) {
+    for event in bridge_command_event_reader.iter() {
+        if event.command != "preview" {
+            continue;
+        }
+        let rooms = match parse_rooms(&event.data) {
+            Ok(rooms) => rooms,
+            Err(err) => {
+                reply_error(&send_channel, err);
+                continue;
+            }
+        };
+        let count = rooms.len();
+        preview.rooms = rooms;
+        let next = match count {
+            0 => Screen::Board,
+            _ => Screen::Preview,
+        };
+        if *screen.current() != next {
+            screen.set(next).ok();
+        }
+        send_channel
+            .tx
+            .send((String::from("preview"), count.to_string()));
+    }
+}
+
+fn preview_exit_system(mut commands: Commands, preview_query: Query<Entity, With<PreviewEntity>>) {
+    for entity in preview_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Rebuilt whole whenever the rooms or the window change, there is no input.
+fn preview_draw_system(
+    mut commands: Commands,
+    view: Res<BoardView>,
+    win_size: Res<WinSize>,
+    preview: Res<LobbyPreview>,
+    game_textures: Res<GameTextures>,
+    mut images: ResMut<Assets<Image>>,
+    preview_query: Query<Entity, With<PreviewEntity>>,
+) {
+    if !(preview.is_changed() || win_size.is_changed() || preview_query.is_empty()) {
+        return;
+    }
+    for entity in preview_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    // backdrop hiding the board underneath
+    let center = view.screen_to_world(Vec2::new(win_size.w, win_size.h) / 2.);
+    commands
+        .spawn(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgb(0.12, 0.12, 0.14),
+                custom_size: Some(Vec2::new(win_size.w, win_size.h) * view.scale),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(center.x, center.y, HUD_Z),
+            ..Default::default()
+        })
+        .insert(PreviewEntity);
+
+    let count = preview.rooms.len();
+    for (i, room) in preview.rooms.iter().enumerate() {
+        let (center, side) = slot(i, count, &win_size, &view);
+        let inner = side * (1. - PREVIEW_PADDING);
+        // the longer side fills the slot, the board keeps its aspect ratio
+        let longest = room.width.max(room.height) as f32;
+        let picture = Vec2::new(room.width as f32, room.height as f32) / longest * inner;
+        let record = format!("{};{};{}", room.width, room.height, room.cells);
+
+        let mut entity = commands.spawn(SpatialBundle::from_transform(Transform::from_xyz(
+            center.x,
+            center.y + side * PREVIEW_FONT_SIZE / 2.,
+            HUD_PANEL_Z,
+        )));
+        entity.insert(PreviewEntity).with_children(|parent| {
+            if let Some(image) = thumbnail_image(&record) {
+                parent.spawn(SpriteBundle {
+                    texture: images.add(image),
+                    sprite: Sprite {
+                        custom_size: Some(picture),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                });
+            }
+            parent.spawn(Text2dBundle {
+                text: Text::from_section(
+                    room.id.clone(),
+                    TextStyle {
+                        font: game_textures.font.clone(),
+                        font_size: side * PREVIEW_FONT_SIZE,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_alignment(TextAlignment::CENTER),
+                transform: Transform::from_xyz(
+                    0.,
+                    -(inner + side * PREVIEW_FONT_SIZE) / 2.,
+                    CHILD_Z_OFFSET,
+                ),
+                ..Default::default()
+            });
+        });
+    }
+}
@@ -0,0 +1,183 @@
+// region:      IMPORTS
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+    },
+    sprite::Anchor,
+};
+use picross_handler::{Cell, Puzzle};
+
+use crate::board::{Board, BoardLoadedEvent, CellChangedEvent};
+use crate::heatmap::lerp_color;
+use crate::layers::HIGHLIGHT_Z;
+use crate::lines::{column_cells, column_clues, fill_probabilities, row_cells, row_clues};
+use crate::options::BoardOptions;
+use crate::stats::Stats;
+
+// endregion
+
+// region:      CONSTANTS
+
+// ramp from surely empty to surely filled
+const LIKELY_EMPTY: Color = Color::rgba(0.2, 0.4, 1., 0.35);
+const LIKELY_FILLED: Color = Color::rgba(1., 0.5, 0., 0.6);
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct ProbabilityOverlay;
+
+// endregion
+
+// region:      RESOURCES
+
+/// Assist toggled with P, or on from the start with the `probabilities`
+/// option. Shading each undecided cell by how likely it is to be filled
+/// gives a lot away, so using it is recorded in the stats.
+#[derive(Resource, Default)]
+pub struct Probabilities {
+    pub visible: bool,
+    image: Option<Handle<Image>>,
+}
+
+// endregion
+
+pub struct ProbabilityPlugin;
+
+impl Plugin for ProbabilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Probabilities>()
+            .add_system(probability_toggle_system)
+            .add_system(probability_overlay_system.after(probability_toggle_system));
+    }
+}
+
+fn probability_toggle_system(
+    keys: Res<Input<KeyCode>>,
+    options: Res<BoardOptions>,
+    mut probabilities: ResMut<Probabilities>,
+) {
+    if options.is_changed() {
+        probabilities.visible = options.get_bool("probabilities", false);
+    }
+    if keys.just_pressed(KeyCode::P) {
+        probabilities.visible = !probabilities.visible;
+    }
+}
+
+fn probability_overlay_system(
+    mut commands: Commands,
+    board: Res<Board>,
+    mut stats: ResMut<Stats>,
+    mut probabilities: ResMut<Probabilities>,
+    mut images: ResMut<Assets<Image>>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut cell_changed_event_reader: EventReader<CellChangedEvent>,
+    mut overlay_query: Query<
+        (&mut Sprite, &mut Transform, &mut Visibility),
+        With<ProbabilityOverlay>,
+    >,
+) {
+    let loaded = board_loaded_event_reader.iter().count() > 0;
+    let changed = cell_changed_event_reader.iter().count() > 0;
+    if !probabilities.visible {
+        for (_, _, mut visibility) in overlay_query.iter_mut() {
+            visibility.is_visible = false;
+        }
+        return;
+    }
+    if !stats.probability_overlay {
+        stats.probability_overlay = true;
+    }
+
+    // one texel per cell, stretched over the grid
+    if loaded || changed || probabilities.is_changed() {
+        let image = probability_image(&board.p);
+        match &probabilities.image {
+            Some(handle) => {
+                let _ = images.set(handle, image);
+            }
+            None => probabilities.image = Some(images.add(image)),
+        }
+    }
+
+    let position = board.tile_translation(board.grid_left() as f32, 0., HIGHLIGHT_Z);
+    let size =
+        Vec2::new(board.p.get_width() as f32, board.p.get_height() as f32) * board.pixels_per_tile;
+    if let Some((mut sprite, mut transform, mut visibility)) = overlay_query.iter_mut().next() {
+        sprite.custom_size = Some(size);
+        transform.translation = position;
+        visibility.is_visible = true;
+        return;
+    }
+
+    if let Some(handle) = &probabilities.image {
+        commands
+            .spawn(SpriteBundle {
+                texture: handle.clone(),
+                sprite: Sprite {
+                    custom_size: Some(size),
+                    anchor: Anchor::BottomLeft,
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(position),
+                ..Default::default()
+            })
+            .insert(ProbabilityOverlay);
+    }
+}
+
+/// Row and column estimates are treated as independent, so a line that is
+/// sure about a cell outweighs one that isn't. Lines that can't be completed
+/// any more leave their cells unshaded.
+fn probability_image(p: &Puzzle) -> Image {
+    let (width, height) = (p.get_width(), p.get_height());
+    let rows: Vec<Option<Vec<f64>>> = (0..height)
+        .map(|y| fill_probabilities(&row_clues(p, y), &row_cells(p, y)))
+        .collect();
+    let columns: Vec<Option<Vec<f64>>> = (0..width)
+        .map(|x| fill_probabilities(&column_clues(p, x), &column_cells(p, x)))
+        .collect();
+
+    let (texels_w, texels_h) = (width.max(1), height.max(1));
+    let mut data = vec![0u8; texels_w * texels_h * 4];
+    for y in 0..height {
+        for x in 0..width {
+            if p.get_cell(x, y) != Cell::Empty {
+                continue;
+            }
+            let (row, column) = match (&rows[y], &columns[x]) {
+                (Some(row), Some(column)) => (row[x], column[y]),
+                _ => continue,
+            };
+            let filled = row * column;
+            let empty = (1. - row) * (1. - column);
+            let chance = match filled + empty {
+                sum if sum > 0. => filled / sum,
+                _ => continue,
+            };
+            // texel rows run top down, board rows bottom up
+            let i = ((texels_h - 1 - y) * texels_w + x) * 4;
+            let color = lerp_color(LIKELY_EMPTY, LIKELY_FILLED, chance as f32);
+            data[i..i + 4].copy_from_slice(&color.as_rgba_u8());
+        }
+    }
+
+    let mut image = Image::new(
+        Extent3d {
+            width: texels_w as u32,
+            height: texels_h as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    image.sampler_descriptor = ImageSampler::nearest();
+    image
+}
@@ -0,0 +1,159 @@
+// region:      IMPORTS
+
+use picross_handler::{Cell, Puzzle};
+
+// endregion
+
+// region:      ORIENTATION
+
+/// Rotation or mirror applied to a puzzle as it loads when the `randomize`
+/// option is set: a transpose first, then the mirrors. The board works in the
+/// transformed layout, everything crossing the bridge (`j` and `u` cells,
+/// `c` and `h` moves) stays in the puzzle's own so other players agree. The
+/// orientation is reported in the stats.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct Orientation {
+    pub transpose: bool,
+    pub flip_x: bool,
+    pub flip_y: bool,
+}
+
+impl Orientation {
+    /// Any of the eight orientations, `fit` drops the transpose for
+    /// puzzles that aren't square.
+    pub fn random() -> Self {
+        let coin = || js_sys::Math::random() < 0.5;
+        Self {
+            transpose: coin(),
+            flip_x: coin(),
+            flip_y: coin(),
+        }
+    }
+
+    /// Transposing swaps width and height, which only a square board survives.
+    pub fn fit(self, p: &Puzzle) -> Self {
+        Self {
+            transpose: self.transpose && p.get_width() == p.get_height(),
+            ..self
+        }
+    }
+
+    /// Where the cell at `(x, y)` of the original `w` by `h` puzzle ends up.
+    pub fn to_transformed(&self, x: usize, y: usize, w: usize, h: usize) -> (usize, usize) {
+        let (mut x, mut y, w, h) = match self.transpose {
+            true => (y, x, h, w),
+            false => (x, y, w, h),
+        };
+        if self.flip_x {
+            x = w - 1 - x;
+        }
+        if self.flip_y {
+            y = h - 1 - y;
+        }
+        (x, y)
+    }
+
+    /// Where the cell at `(x, y)` of the transformed `w` by `h` board came from.
+    pub fn to_original(&self, x: usize, y: usize, w: usize, h: usize) -> (usize, usize) {
+        let x = if self.flip_x { w - 1 - x } else { x };
+        let y = if self.flip_y { h - 1 - y } else { y };
+        match self.transpose {
+            true => (y, x),
+            false => (x, y),
+        }
+    }
+
+    /// Original `w` by `h` of the transformed `w` by `h` board.
+    pub fn original_size(&self, w: usize, h: usize) -> (usize, usize) {
+        match self.transpose {
+            true => (h, w),
+            false => (w, h),
+        }
+    }
+
+    /// Index into the original layout's cells, the way `c` moves count, of
+    /// the cell at `(x, y)` of the transformed `w` by `h` board.
+    pub fn to_original_pos(&self, x: usize, y: usize, w: usize, h: usize) -> usize {
+        let (ow, _) = self.original_size(w, h);
+        let (x, y) = self.to_original(x, y, w, h);
+        y * ow + x
+    }
+
+    /// Where the cell at index `i` of the original layout's cells ends up on
+    /// the transformed `w` by `h` board.
+    pub fn from_original_pos(&self, i: usize, w: usize, h: usize) -> (usize, usize) {
+        let (ow, oh) = self.original_size(w, h);
+        self.to_transformed(i % ow, i / ow, ow, oh)
+    }
+
+    /// Moves clues and cells together so the puzzle stays solvable.
+    pub fn apply(&self, p: &mut Puzzle) {
+        if *self == Self::default() {
+            return;
+        }
+
+        let (w, h) = (p.get_width(), p.get_height());
+        let cells: Vec<Cell> = (0..w * h).map(|i| p.get_cell(i % w, i / w)).collect();
+
+        if self.transpose {
+            std::mem::swap(&mut p.row_clues, &mut p.column_clues);
+        }
+        // clues read along their line, so mirroring across a line reverses them
+        if self.flip_x {
+            p.row_clues.iter_mut().for_each(|clues| clues.reverse());
+            p.column_clues.reverse();
+        }
+        if self.flip_y {
+            p.column_clues.iter_mut().for_each(|clues| clues.reverse());
+            p.row_clues.reverse();
+        }
+
+        for (i, cell) in cells.into_iter().enumerate() {
+            let (x, y) = self.to_transformed(i % w, i / w, w, h);
+            p.set_cell(x, y, cell);
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"transpose\":{},\"flip_x\":{},\"flip_y\":{}}}",
+            self.transpose, self.flip_x, self.flip_y
+        )
+    }
+}
+
+// endregion
+
+// region:      TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Board, BoardAction};
+    use crate::harness::{plus_puzzle, Harness, SETTLE_TICKS};
+
+    #[test]
+    fn randomized_board_talks_in_the_puzzle_layout() {
+        let mut harness = Harness::new();
+        assert!(harness.join(plus_puzzle().as_str(), "000000000"));
+        harness.app.world.resource_mut::<Board>().orientation = Orientation {
+            transpose: true,
+            flip_x: true,
+            flip_y: false,
+        };
+
+        // the puzzle's (0, 0) is (2, 0) once transposed and mirrored
+        harness.send("u", "100000000");
+        harness.ticks(SETTLE_TICKS);
+        assert_eq!(harness.cells(), "001000000");
+
+        // and the board's (2, 2) is the puzzle's (2, 0)
+        harness.replies();
+        harness.input_cell(2, 2, BoardAction::Fill);
+        assert!(harness
+            .replies()
+            .contains(&(String::from("c"), String::from("2,1"))));
+    }
+}
+
+// endregion
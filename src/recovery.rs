@@ -0,0 +1,145 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+use picross_handler::Cell;
+
+use crate::board::{cell_to_char, char_to_cell, Board, BoardLoadedEvent, ChangeSource};
+use crate::board_commands::BoardCommand;
+use crate::history::History;
+use crate::storage;
+use crate::win::PuzzleSolvedEvent;
+
+// endregion
+
+// region:      CONSTANTS
+
+const RECOVERY_KEY_PREFIX: &str = "recovery.";
+const SAVE_SECONDS: f64 = 5.;
+
+// endregion
+
+// region:      RESOURCES
+
+/// How much of the log is in storage, and since when.
+#[derive(Resource, Default)]
+pub struct Recovery {
+    saved_entries: usize,
+    saved_at: f64,
+}
+
+// endregion
+
+pub struct RecoveryPlugin;
+
+impl Plugin for RecoveryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Recovery>()
+            .add_system(recovery_restore_system)
+            .add_system(recovery_save_system.after(recovery_restore_system));
+    }
+}
+
+fn key(board: &Board) -> String {
+    format!("{}{}", RECOVERY_KEY_PREFIX, board.id)
+}
+
+fn cells_string(cells: &[Cell]) -> String {
+    cells.iter().map(|cell| cell_to_char(*cell)).collect()
+}
+
+/// Saves the log to `recovery.<id>` as `width;height;cells|x,y,c;x,y,c`, the
+/// board as loaded followed by the player's moves on it. Moves from other
+/// players are left out, the server has those.
+pub fn save(board: &Board, history: &History) {
+    if board.id.is_empty() {
+        return;
+    }
+    let moves: Vec<String> = history
+        .player_entries()
+        .map(|entry| format!("{},{},{}", entry.x, entry.y, cell_to_char(entry.cell)))
+        .collect();
+    if moves.is_empty() {
+        storage::remove(&key(board));
+        return;
+    }
+    let (width, height) = history.size();
+    let base = cells_string(&history.cells_at(0));
+    storage::save(
+        &key(board),
+        &format!("{};{};{}|{}", width, height, base, moves.join(";")),
+    );
+}
+
+/// The moves of a saved log, if it was saved over the board as it is now.
+fn saved_moves(saved: &str, board: &Board) -> Option<Vec<(usize, usize, Cell)>> {
+    let (header, moves) = saved.split_once('|')?;
+    let (width, height) = (board.p.get_width(), board.p.get_height());
+    let cells: String = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| cell_to_char(board.p.get_cell(x, y)))
+        .collect();
+    if header != format!("{};{};{}", width, height, cells) {
+        return None;
+    }
+    let moves = moves.split(';').filter_map(|step| {
+        let mut fields = step.split(',');
+        let x = fields.next()?.parse::<usize>().ok()?;
+        let y = fields.next()?.parse::<usize>().ok()?;
+        let cell = char_to_cell(fields.next()?.chars().next()?)?;
+        (x < width && y < height).then_some((x, y, cell))
+    });
+    Some(moves.collect())
+}
+
+/// A board that loads exactly as it did when its log was last saved lost
+/// the moves made on it, a crashed or closed tab on a puzzle the server
+/// doesn't keep. They are played again as the player's own. A board that
+/// loads with progress on it is left alone, whoever kept that progress
+/// knows better.
+fn recovery_restore_system(
+    board: Res<Board>,
+    mut recovery: ResMut<Recovery>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut board_command_event_writer: EventWriter<BoardCommand>,
+) {
+    if board_loaded_event_reader.iter().last().is_none() || board.id.is_empty() {
+        return;
+    }
+    recovery.saved_entries = 0;
+    let moves = match storage::load(&key(&board)).and_then(|saved| saved_moves(&saved, &board)) {
+        Some(moves) => moves,
+        None => return,
+    };
+    info!("Recovering {} moves on {}", moves.len(), board.id);
+    for (x, y, cell) in moves {
+        board_command_event_writer.send(BoardCommand {
+            x,
+            y,
+            cell,
+            source: ChangeSource::Player,
+        });
+    }
+}
+
+/// Keeps the stored log a few seconds behind at most, and drops it once the
+/// puzzle is solved.
+fn recovery_save_system(
+    time: Res<Time>,
+    board: Res<Board>,
+    history: Res<History>,
+    mut recovery: ResMut<Recovery>,
+    mut puzzle_solved_event_reader: EventReader<PuzzleSolvedEvent>,
+) {
+    if puzzle_solved_event_reader.iter().last().is_some() {
+        storage::remove(&key(&board));
+        recovery.saved_entries = history.entries.len();
+        return;
+    }
+    let now = time.elapsed_seconds_f64();
+    if history.entries.len() == recovery.saved_entries || now - recovery.saved_at < SAVE_SECONDS {
+        return;
+    }
+    save(&board, &history);
+    recovery.saved_entries = history.entries.len();
+    recovery.saved_at = now;
+}
@@ -0,0 +1,190 @@
+// region:      IMPORTS
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+use crate::board::{Board, CellChangedEvent, ChangeSource};
+use crate::layers::EFFECT_Z;
+use crate::options::BoardOptions;
+use crate::settings::Settings;
+
+// endregion
+
+// region:      CONSTANTS
+
+const FLASH_SECONDS: f32 = 0.6;
+const RIPPLE_SECONDS: f32 = 0.5;
+const RIPPLE_TILES: f32 = 2.5; // final diameter
+const RING_TEXTURE_SIZE: u32 = 64;
+const RING_WIDTH: f32 = 4.;
+/// Past this many remote changes in one frame it is a sync, not a teammate.
+const MAX_EFFECTS_PER_FRAME: usize = 32;
+const DEFAULT_REMOTE_COLOR: Color = Color::rgba(0.1, 0.7, 0.9, 0.6);
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct RemoteFlash {
+    pub x: usize,
+    pub y: usize,
+    pub elapsed: f32,
+}
+
+#[derive(Component)]
+pub struct RemoteRipple {
+    pub x: usize,
+    pub y: usize,
+    pub elapsed: f32,
+}
+
+// endregion
+
+// region:      RESOURCES
+
+#[derive(Resource)]
+pub struct RingTexture(pub Handle<Image>);
+
+// endregion
+
+pub struct RemoteEffectPlugin;
+
+impl Plugin for RemoteEffectPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(remote_effect_setup_system)
+            .add_system(spawn_remote_effects_system)
+            .add_system(remote_effect_system.after(spawn_remote_effects_system));
+    }
+}
+
+fn remote_effect_setup_system(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    commands.insert_resource(RingTexture(images.add(ring_image())));
+}
+
+fn ring_image() -> Image {
+    let size = RING_TEXTURE_SIZE;
+    let radius = size as f32 / 2.;
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let dx = x as f32 + 0.5 - radius;
+            let dy = y as f32 + 0.5 - radius;
+            let distance = (dx * dx + dy * dy).sqrt();
+            // white so the sprite color tints it, soft on both edges
+            let alpha = (1. - ((radius - RING_WIDTH - distance).abs() / RING_WIDTH)).clamp(0., 1.);
+            data.extend_from_slice(&[255, 255, 255, (alpha * 255.) as u8]);
+        }
+    }
+
+    Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+/// Remote changes flash in the `remote_color` option, with a ripple
+/// spreading from the cell unless reduced motion is on. Nothing shows in do
+/// not disturb mode.
+fn spawn_remote_effects_system(
+    mut commands: Commands,
+    board: Res<Board>,
+    settings: Res<Settings>,
+    options: Res<BoardOptions>,
+    ring_texture: Res<RingTexture>,
+    mut cell_changed_event_reader: EventReader<CellChangedEvent>,
+) {
+    let remote: Vec<(usize, usize)> = cell_changed_event_reader
+        .iter()
+        .filter(|event| event.source == ChangeSource::Remote)
+        .map(|event| (event.x, event.y))
+        .collect();
+    if remote.is_empty() || remote.len() > MAX_EFFECTS_PER_FRAME || settings.do_not_disturb {
+        return;
+    }
+
+    let color = options.get_color("remote_color", DEFAULT_REMOTE_COLOR);
+    let mut ring_color = color;
+    ring_color.set_a(1.);
+    for (x, y) in remote {
+        let center = cell_center(&board, x, y, EFFECT_Z);
+        commands
+            .spawn(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::splat(board.pixels_per_tile)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(center),
+                ..Default::default()
+            })
+            .insert(RemoteFlash { x, y, elapsed: 0. });
+
+        if settings.reduced_motion {
+            continue;
+        }
+        commands
+            .spawn(SpriteBundle {
+                texture: ring_texture.0.clone(),
+                sprite: Sprite {
+                    color: ring_color,
+                    custom_size: Some(Vec2::ZERO),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(center),
+                ..Default::default()
+            })
+            .insert(RemoteRipple { x, y, elapsed: 0. });
+    }
+}
+
+fn cell_center(board: &Board, x: usize, y: usize, z: f32) -> Vec3 {
+    let (x, y) = board.cell_to_tile(x, y);
+    board.tile_translation(x + 0.5, y + 0.5, z)
+}
+
+fn remote_effect_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    board: Res<Board>,
+    mut flash_query: Query<(Entity, &mut RemoteFlash, &mut Sprite, &mut Transform)>,
+    mut ripple_query: Query<
+        (Entity, &mut RemoteRipple, &mut Sprite, &mut Transform),
+        Without<RemoteFlash>,
+    >,
+) {
+    for (entity, mut flash, mut sprite, mut transform) in flash_query.iter_mut() {
+        flash.elapsed += time.delta_seconds();
+        if flash.elapsed >= FLASH_SECONDS {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        // fade from the color's own alpha, and follow the board when it is resized
+        let alpha = sprite.color.a();
+        sprite
+            .color
+            .set_a(alpha.min(1. - flash.elapsed / FLASH_SECONDS));
+        transform.translation = cell_center(&board, flash.x, flash.y, EFFECT_Z);
+        sprite.custom_size = Some(Vec2::splat(board.pixels_per_tile));
+    }
+
+    for (entity, mut ripple, mut sprite, mut transform) in ripple_query.iter_mut() {
+        ripple.elapsed += time.delta_seconds();
+        if ripple.elapsed >= RIPPLE_SECONDS {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let t = ripple.elapsed / RIPPLE_SECONDS;
+        sprite.color.set_a(1. - t);
+        sprite.custom_size = Some(Vec2::splat(board.pixels_per_tile * RIPPLE_TILES * t));
+        transform.translation = cell_center(&board, ripple.x, ripple.y, EFFECT_Z);
+    }
+}
@@ -0,0 +1,110 @@
+// region:      IMPORTS
+
+use bevy::{prelude::*, transform::TransformSystem};
+
+use crate::board::{Board, BoardLoadedEvent, Clue, Tile};
+use crate::layers::TILE_Z;
+use crate::options::BoardOptions;
+use crate::settings::Settings;
+
+// endregion
+
+// region:      CONSTANTS
+
+const DEFAULT_REVEAL_SECONDS: f32 = 0.8;
+/// Share of the reveal each tile spends growing in, the rest is the stagger.
+const TILE_SHARE: f32 = 0.35;
+
+// endregion
+
+// region:      RESOURCES
+
+/// Cascade of tiles growing in by diagonal from the top left when a puzzle
+/// loads. Lasts `reveal_seconds`, off when that is 0 or with reduced motion.
+#[derive(Resource, Default)]
+pub struct Reveal {
+    started: Option<f64>,
+    duration: f32,
+}
+
+// endregion
+
+pub struct RevealPlugin;
+
+impl Plugin for RevealPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Reveal>()
+            .add_system(reveal_start_system)
+            // after this frame's spawn commands are applied, before anything is drawn
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                reveal_system.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+fn reveal_start_system(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    options: Res<BoardOptions>,
+    mut reveal: ResMut<Reveal>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+) {
+    if board_loaded_event_reader.iter().last().is_none() {
+        return;
+    }
+    let duration = options.get_f32("reveal_seconds", DEFAULT_REVEAL_SECONDS);
+    if settings.reduced_motion || duration <= 0. {
+        reveal.started = None;
+        return;
+    }
+    reveal.started = Some(time.elapsed_seconds_f64());
+    reveal.duration = duration;
+}
+
+fn reveal_system(
+    time: Res<Time>,
+    board: Res<Board>,
+    mut reveal: ResMut<Reveal>,
+    mut tile_query: Query<(&Tile, &mut Transform, &mut Sprite)>,
+    mut clue_query: Query<(&Clue, &mut Text)>,
+) {
+    let started = match reveal.started {
+        Some(started) => started,
+        None => return,
+    };
+    let elapsed = (time.elapsed_seconds_f64() - started) as f32 / reveal.duration;
+    let done = elapsed >= 1.;
+    let diagonals = (board.w + board.h).saturating_sub(2).max(1) as f32;
+
+    // 0 to 1 for a tile, top left first
+    let progress = |x: f32, y: f32| {
+        if done {
+            return 1.;
+        }
+        let diagonal = (x + (board.h as f32 - 1. - y)) / diagonals;
+        let t = (elapsed - diagonal * (1. - TILE_SHARE)) / TILE_SHARE;
+        t.clamp(0., 1.)
+    };
+
+    for (tile, mut transform, mut sprite) in tile_query.iter_mut() {
+        let t = progress(tile.x, tile.y);
+        // ease out, grown from the middle of the tile
+        let scale = 1. - (1. - t) * (1. - t);
+        let inset = (1. - scale) * board.pixels_per_tile / 2.;
+        transform.translation =
+            board.tile_translation(tile.x, tile.y, TILE_Z) + Vec3::new(inset, inset, 0.);
+        transform.scale = Vec3::new(board.tile_scale * scale, board.tile_scale * scale, 1.);
+        sprite.color.set_a(t);
+    }
+    for (clue, mut text) in clue_query.iter_mut() {
+        let t = progress(clue.x, clue.y);
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(t);
+        }
+    }
+
+    if done {
+        reveal.started = None;
+    }
+}
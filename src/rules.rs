@@ -0,0 +1,167 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+use picross_handler::{Cell, Puzzle};
+
+use crate::board::BoardAction;
+use crate::bridge::reply_error;
+use crate::lines::{
+    column_cells, column_clues, line_consistent, line_satisfied, row_cells, row_clues,
+};
+use crate::options::BoardOptions;
+use crate::WASMSendChannel;
+
+// endregion
+
+// region:      RULES
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LineState {
+    Satisfied,
+    Incomplete,
+    Contradictory,
+}
+/// How a puzzle variant reads its clues, decides it is solved and turns
+/// player actions into cells. Selected by the `rules` option.
+pub trait RuleSet: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn line_satisfied(&self, clues: &[usize], cells: &[Cell]) -> bool;
+
+    /// Whether undecided cells could still make the line satisfied.
+    fn line_consistent(&self, clues: &[usize], cells: &[Cell]) -> bool;
+
+    fn line_state(&self, clues: &[usize], cells: &[Cell]) -> LineState {
+        if self.line_satisfied(clues, cells) {
+            LineState::Satisfied
+        } else if self.line_consistent(clues, cells) {
+            LineState::Incomplete
+        } else {
+            LineState::Contradictory
+        }
+    }
+
+    fn row_state(&self, p: &Puzzle, y: usize) -> LineState {
+        self.line_state(&row_clues(p, y), &row_cells(p, y))
+    }
+
+    fn column_state(&self, p: &Puzzle, x: usize) -> LineState {
+        self.line_state(&column_clues(p, x), &column_cells(p, x))
+    }
+
+    fn row_satisfied(&self, p: &Puzzle, y: usize) -> bool {
+        self.line_satisfied(&row_clues(p, y), &row_cells(p, y))
+    }
+
+    fn column_satisfied(&self, p: &Puzzle, x: usize) -> bool {
+        self.line_satisfied(&column_clues(p, x), &column_cells(p, x))
+    }
+
+    fn solved(&self, p: &Puzzle) -> bool {
+        p.get_width() > 0
+            && p.get_height() > 0
+            && (0..p.get_height()).all(|y| self.row_satisfied(p, y))
+            && (0..p.get_width()).all(|x| self.column_satisfied(p, x))
+    }
+
+    /// Action a press starting on `cell` performs for the rest of the drag.
+    fn drag_action(&self, action: BoardAction, cell: Cell) -> BoardAction;
+
+    /// Cell an action leaves behind.
+    fn apply(&self, action: BoardAction) -> Cell;
+}
+
+/// Classic picross, runs of filled cells separated by at least one gap.
+pub struct Standard;
+
+impl RuleSet for Standard {
+    fn name(&self) -> &'static str {
+        "standard"
+    }
+
+    fn line_satisfied(&self, clues: &[usize], cells: &[Cell]) -> bool {
+        line_satisfied(clues, cells)
+    }
+
+    fn line_consistent(&self, clues: &[usize], cells: &[Cell]) -> bool {
+        line_consistent(clues, cells)
+    }
+
+    fn drag_action(&self, action: BoardAction, cell: Cell) -> BoardAction {
+        // pressing on a cell that already matches clears instead
+        match (action, cell) {
+            (BoardAction::Fill, Cell::Filled) | (BoardAction::Cross, Cell::Crossed) => {
+                BoardAction::Empty
+            }
+            _ => action,
+        }
+    }
+
+    fn apply(&self, action: BoardAction) -> Cell {
+        match action {
+            BoardAction::Fill => Cell::Filled,
+            BoardAction::Cross => Cell::Crossed,
+            BoardAction::Empty => Cell::Empty,
+        }
+    }
+}
+
+/// Looks up a rule set by its `rules` option value.
+pub fn rule_set(name: &str) -> Result<Box<dyn RuleSet>, String> {
+    match name {
+        "standard" => Ok(Box::new(Standard)),
+        // these need colored, merged or split cells, picross_handler only has one fill
+        "color" | "mega" | "triangle" => Err(format!(
+            "rules: {} puzzles need cell states picross_handler doesn't have",
+            name
+        )),
+        name => Err(format!("rules: unknown variant {}", name)),
+    }
+}
+
+// endregion
+
+// region:      RESOURCES
+
+#[derive(Resource)]
+pub struct Rules(pub Box<dyn RuleSet>);
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self(Box::new(Standard))
+    }
+}
+
+// endregion
+
+pub struct RulesPlugin;
+
+impl Plugin for RulesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rules>()
+            .add_system(rules_options_system);
+    }
+}
+
+fn rules_options_system(
+    options: Res<BoardOptions>,
+    send_channel: Res<WASMSendChannel>,
+    mut rules: ResMut<Rules>,
+) {
+    if !options.is_changed() {
+        return;
+    }
+
+    let name = options.get("rules").unwrap_or("standard");
+    if rules.0.name() == name {
+        return;
+    }
+    match rule_set(name) {
+        Ok(rule_set) => rules.0 = rule_set,
+        Err(err) => {
+            // keep playing by the standard rules rather than refusing the board
+            reply_error(&send_channel, err);
+            rules.0 = Box::new(Standard);
+        }
+    }
+}
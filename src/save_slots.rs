@@ -0,0 +1,453 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+use wasm_bindgen::JsValue;
+
+use crate::board::{cell_to_char, char_to_cell, Board, ChangeSource};
+use crate::board_commands::BoardCommand;
+use crate::bridge::reply_error;
+use crate::camera::BoardView;
+use crate::campaign::thumbnail_image;
+use crate::keyboard::keyboard_cursor_system;
+use crate::layers::{CHILD_Z_OFFSET, HUD_LABEL_Z, HUD_PANEL_Z, HUD_Z};
+use crate::options::BoardOptions;
+use crate::screens::Screen;
+use crate::start::epoch_ms;
+use crate::storage;
+use crate::{BridgeCommandEvent, GameTextures, WASMSendChannel, WinSize};
+
+// endregion
+
+// region:      CONSTANTS
+
+pub const MAX_SAVE_SLOTS: usize = 5;
+const MAX_SLOT_NAME_LEN: usize = 32;
+
+const PANEL_WIDTH: f32 = 0.45; // fraction of the window
+const PANEL_MARGIN: f32 = 12.;
+const ROW_PADDING: f32 = 0.1; // fraction of a row
+const BUTTON_WIDTH: f32 = 1.3; // in row heights
+const SLOT_FONT_SIZE: f32 = 0.22; // fraction of a row
+
+const PANEL_COLOR: Color = Color::rgba(0.12, 0.12, 0.14, 0.92);
+const ROW_COLOR: Color = Color::rgb(0.22, 0.22, 0.25);
+const SELECTED_COLOR: Color = Color::rgb(0.35, 0.35, 0.2);
+const BUTTON_COLOR: Color = Color::rgb(0.3, 0.45, 0.7);
+const DISABLED_COLOR: Color = Color::rgb(0.3, 0.3, 0.3);
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct SaveSlotsEntity;
+
+// endregion
+
+// region:      RESOURCES
+
+pub struct SavedSlot {
+    pub name: String,
+    /// Epoch ms of the save.
+    pub t: f64,
+    /// `width;height;cells`, like campaign thumbnails.
+    pub record: String,
+}
+
+impl SavedSlot {
+    fn to_storage(&self) -> String {
+        format!("{}|{}|{}", self.name, self.t, self.record)
+    }
+
+    fn from_storage(saved: &str) -> Option<Self> {
+        let mut fields = saved.splitn(3, '|');
+        Some(Self {
+            name: fields.next()?.to_string(),
+            t: fields.next()?.parse().ok()?,
+            record: fields.next()?.to_string(),
+        })
+    }
+}
+
+/// Named snapshots of the current puzzle, kept in storage per puzzle id so
+/// a player can branch off before a guess and come back to it. The panel is
+/// toggled with S or the host's `("slots", "")`, `("slot_name", "i;name")`
+/// renames a slot.
+#[derive(Resource, Default)]
+pub struct SaveSlots {
+    pub selected: usize,
+    pub slots: Vec<Option<SavedSlot>>,
+}
+
+impl SaveSlots {
+    fn key(board: &Board, i: usize) -> String {
+        format!("slots.{}.{}", board.id, i)
+    }
+
+    fn load(&mut self, board: &Board) {
+        self.slots = (0..MAX_SAVE_SLOTS)
+            .map(|i| storage::load(&Self::key(board, i)).and_then(|s| SavedSlot::from_storage(&s)))
+            .collect();
+    }
+
+    fn store(&self, board: &Board, i: usize) {
+        if let Some(Some(slot)) = self.slots.get(i) {
+            storage::save(&Self::key(board, i), &slot.to_storage());
+        }
+    }
+}
+
+// endregion
+
+pub struct SaveSlotsPlugin;
+
+impl Plugin for SaveSlotsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SaveSlots>()
+            .add_system(open_slots_system.after(keyboard_cursor_system))
+            .add_system(slot_name_system)
+            .add_system_set(SystemSet::on_enter(Screen::Slots).with_system(slots_enter_system))
+            .add_system_set(
+                SystemSet::on_update(Screen::Slots)
+                    .with_system(slots_input_system)
+                    .with_system(slots_draw_system.after(slots_input_system)),
+            )
+            .add_system_set(SystemSet::on_exit(Screen::Slots).with_system(slots_exit_system));
+    }
+}
+
+/// S, or the host's `("slots", "")`, opens and closes the panel. An S the
+/// keyboard cursor took to move or show itself doesn't count.
+fn open_slots_system(
+    keys: Res<Input<KeyCode>>,
+    board: Res<Board>,
+    mut screen: ResMut<State<Screen>>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+) {
+    let requested = bridge_command_event_reader
+        .iter()
+        .any(|event| event.command == "slots");
+    let pressed = keys.just_pressed(KeyCode::S);
+    if !(requested || pressed) || board.id.is_empty() {
+        return;
+    }
+
+    let next = match screen.current() {
+        Screen::Board => Screen::Slots,
+        Screen::Slots => Screen::Board,
+        Screen::Map | Screen::Preview | Screen::FlipBook | Screen::Win => return,
+    };
+    screen.set(next).ok();
+}
+
+fn slot_name_system(
+    board: Res<Board>,
+    send_channel: Res<WASMSendChannel>,
+    mut save_slots: ResMut<SaveSlots>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+) {
+    for event in bridge_command_event_reader.iter() {
+        if event.command != "slot_name" {
+            continue;
+        }
+        let (i, name) = match event.data.split_once(';') {
+            Some((i, name)) => (i.parse::<usize>().ok(), name),
+            None => (None, ""),
+        };
+        let i = match i.filter(|i| *i < MAX_SAVE_SLOTS) {
+            Some(i) => i,
+            None => {
+                reply_error(
+                    &send_channel,
+                    format!("slot_name: invalid slot in {}", event.data),
+                );
+                continue;
+            }
+        };
+        if save_slots.slots.is_empty() {
+            save_slots.load(&board);
+        }
+        // the name shares the storage record, so no separators
+        let name: String = name
+            .chars()
+            .filter(|c| *c != '|' && !c.is_control())
+            .take(MAX_SLOT_NAME_LEN)
+            .collect();
+        match save_slots.slots.get_mut(i) {
+            Some(Some(slot)) => slot.name = name,
+            _ => {
+                reply_error(&send_channel, format!("slot_name: slot {} is empty", i));
+                continue;
+            }
+        }
+        save_slots.store(&board, i);
+    }
+}
+
+fn slots_enter_system(board: Res<Board>, mut save_slots: ResMut<SaveSlots>) {
+    save_slots.load(&board);
+}
+
+fn slots_exit_system(mut commands: Commands, slots_query: Query<Entity, With<SaveSlotsEntity>>) {
+    for entity in slots_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Row `i` of the panel and its save and load buttons, as (center, size) in
+/// screen units. The panel hangs from the top right corner.
+fn row_layout(i: usize, win_size: &WinSize) -> [(Vec2, Vec2); 3] {
+    let width = win_size.w * PANEL_WIDTH;
+    let height = (win_size.h - 2. * PANEL_MARGIN) / MAX_SAVE_SLOTS as f32;
+    let left = win_size.w - PANEL_MARGIN - width;
+    let top = win_size.h - PANEL_MARGIN - i as f32 * height;
+    let row = (
+        Vec2::new(left + width / 2., top - height / 2.),
+        Vec2::new(width, height * (1. - ROW_PADDING)),
+    );
+    let button = Vec2::new(height * BUTTON_WIDTH, height * 0.6);
+    let load_x = left + width - button.x / 2. - height * ROW_PADDING;
+    let save_x = load_x - button.x - height * ROW_PADDING;
+    [
+        row,
+        (Vec2::new(save_x, row.0.y), button),
+        (Vec2::new(load_x, row.0.y), button),
+    ]
+}
+
+fn contains((center, size): (Vec2, Vec2), point: Vec2) -> bool {
+    ((point - center).abs() - size / 2.).max_element() <= 0.
+}
+
+fn board_record(board: &Board) -> String {
+    let p = &board.p;
+    let cells: String = (0..p.get_height())
+        .flat_map(|y| (0..p.get_width()).map(move |x| cell_to_char(p.get_cell(x, y))))
+        .collect();
+    format!("{};{};{}", p.get_width(), p.get_height(), cells)
+}
+
+/// Up and Down pick a slot, Space saves to it and Return loads it, or the
+/// buttons do the same with the mouse. Loading closes the panel.
+fn slots_input_system(
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    win_size: Res<WinSize>,
+    board: Res<Board>,
+    mut save_slots: ResMut<SaveSlots>,
+    mut screen: ResMut<State<Screen>>,
+    mut board_command_event_writer: EventWriter<BoardCommand>,
+) {
+    let mut selected = save_slots.selected;
+    if keys.just_pressed(KeyCode::Up) {
+        selected = selected.saturating_sub(1);
+    }
+    if keys.just_pressed(KeyCode::Down) {
+        selected = (selected + 1).min(MAX_SAVE_SLOTS - 1);
+    }
+    let mut save = keys.just_pressed(KeyCode::Space);
+    let mut load = keys.just_pressed(KeyCode::Return);
+
+    let cursor = windows
+        .get_primary()
+        .and_then(|window| window.cursor_position());
+    if let (true, Some(cursor)) = (buttons.just_pressed(MouseButton::Left), cursor) {
+        for i in 0..MAX_SAVE_SLOTS {
+            let [row, save_button, load_button] = row_layout(i, &win_size);
+            if contains(row, cursor) {
+                selected = i;
+                save = contains(save_button, cursor);
+                load = contains(load_button, cursor);
+            }
+        }
+    }
+    if selected != save_slots.selected {
+        save_slots.selected = selected;
+    }
+
+    if save {
+        let name = match save_slots.slots.get(selected) {
+            Some(Some(slot)) => slot.name.clone(),
+            _ => format!("Slot {}", selected + 1),
+        };
+        if let Some(slot) = save_slots.slots.get_mut(selected) {
+            *slot = Some(SavedSlot {
+                name,
+                t: epoch_ms(),
+                record: board_record(&board),
+            });
+        }
+        save_slots.store(&board, selected);
+    } else if load {
+        let cells = match save_slots.slots.get(selected) {
+            Some(Some(slot)) => slot.record.rsplit(';').next().unwrap_or_default(),
+            _ => return,
+        };
+        let width = board.p.get_width();
+        // only records of this board's size, ids are not meant to collide
+        if cells.chars().count() != width * board.p.get_height() {
+            warn!("Save slot {} doesn't fit the board", selected);
+            return;
+        }
+        for (i, cell) in cells.chars().filter_map(char_to_cell).enumerate() {
+            let (x, y) = (i % width, i / width);
+            if board.p.get_cell(x, y) != cell {
+                board_command_event_writer.send(BoardCommand {
+                    x,
+                    y,
+                    cell,
+                    source: ChangeSource::Player,
+                });
+            }
+        }
+        screen.set(Screen::Board).ok();
+    }
+}
+
+fn slot_time(t: f64, locale: &str) -> String {
+    js_sys::Date::new(&JsValue::from_f64(t))
+        .to_locale_string(locale, &JsValue::UNDEFINED)
+        .into()
+}
+
+fn slots_draw_system(
+    mut commands: Commands,
+    view: Res<BoardView>,
+    win_size: Res<WinSize>,
+    save_slots: Res<SaveSlots>,
+    options: Res<BoardOptions>,
+    game_textures: Res<GameTextures>,
+    mut images: ResMut<Assets<Image>>,
+    slots_query: Query<Entity, With<SaveSlotsEntity>>,
+) {
+    // rebuilt whole, the panel only changes on a save or a new selection
+    if !(save_slots.is_changed()
+        || win_size.is_changed()
+        || options.is_changed()
+        || slots_query.is_empty())
+    {
+        return;
+    }
+    for entity in slots_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let world = |(center, size): (Vec2, Vec2), z: f32| {
+        (view.screen_to_world(center).extend(z), size * view.scale)
+    };
+    let panel = (
+        Vec2::new(
+            win_size.w - PANEL_MARGIN - win_size.w * PANEL_WIDTH / 2.,
+            win_size.h / 2.,
+        ),
+        Vec2::new(win_size.w * PANEL_WIDTH, win_size.h - 2. * PANEL_MARGIN) + PANEL_MARGIN,
+    );
+    let (translation, size) = world(panel, HUD_Z);
+    commands
+        .spawn(SpriteBundle {
+            sprite: Sprite {
+                color: PANEL_COLOR,
+                custom_size: Some(size),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(translation),
+            ..Default::default()
+        })
+        .insert(SaveSlotsEntity);
+
+    let text_style = |size: f32| TextStyle {
+        font: game_textures.font.clone(),
+        font_size: size,
+        color: Color::WHITE,
+    };
+    for i in 0..MAX_SAVE_SLOTS {
+        let [row, save_button, load_button] = row_layout(i, &win_size);
+        let slot = save_slots.slots.get(i).and_then(|slot| slot.as_ref());
+        let font_size = row.1.y * SLOT_FONT_SIZE * view.scale;
+
+        let (translation, size) = world(row, HUD_PANEL_Z);
+        let color = match i == save_slots.selected {
+            true => SELECTED_COLOR,
+            false => ROW_COLOR,
+        };
+        commands
+            .spawn(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(size),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(translation),
+                ..Default::default()
+            })
+            .insert(SaveSlotsEntity);
+
+        // mini preview at the left, then name and time
+        let side = row.1.y * (1. - ROW_PADDING);
+        let left = row.0.x - row.1.x / 2.;
+        let picture = (
+            Vec2::new(left + side / 2. + PANEL_MARGIN, row.0.y),
+            Vec2::splat(side),
+        );
+        if let Some(image) = slot.and_then(|slot| thumbnail_image(&slot.record)) {
+            let (translation, size) = world(picture, HUD_LABEL_Z);
+            commands
+                .spawn(SpriteBundle {
+                    texture: images.add(image),
+                    sprite: Sprite {
+                        custom_size: Some(size),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_translation(translation),
+                    ..Default::default()
+                })
+                .insert(SaveSlotsEntity);
+        }
+        let label = match slot {
+            Some(slot) => format!("{}\n{}", slot.name, slot_time(slot.t, options.locale())),
+            None => format!("Slot {}\nempty", i + 1),
+        };
+        let text_at = Vec2::new(left + side + 2. * PANEL_MARGIN, row.0.y);
+        commands
+            .spawn(Text2dBundle {
+                text: Text::from_section(label, text_style(font_size))
+                    .with_alignment(TextAlignment::CENTER_LEFT),
+                transform: Transform::from_translation(
+                    view.screen_to_world(text_at).extend(HUD_LABEL_Z),
+                ),
+                ..Default::default()
+            })
+            .insert(SaveSlotsEntity);
+
+        for (button, label, enabled) in [
+            (save_button, "Save", true),
+            (load_button, "Load", slot.is_some()),
+        ] {
+            let (translation, size) = world(button, HUD_LABEL_Z);
+            commands
+                .spawn(SpriteBundle {
+                    sprite: Sprite {
+                        color: if enabled {
+                            BUTTON_COLOR
+                        } else {
+                            DISABLED_COLOR
+                        },
+                        custom_size: Some(size),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_translation(translation),
+                    ..Default::default()
+                })
+                .insert(SaveSlotsEntity)
+                .with_children(|parent| {
+                    parent.spawn(Text2dBundle {
+                        text: Text::from_section(label, text_style(font_size))
+                            .with_alignment(TextAlignment::CENTER),
+                        transform: Transform::from_xyz(0., 0., CHILD_Z_OFFSET),
+                        ..Default::default()
+                    });
+                });
+        }
+    }
+}
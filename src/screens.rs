@@ -0,0 +1,32 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+
+// endregion
+
+// region:      STATES
+
+/// Which screen is showing. Board input only runs on `Screen::Board`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Screen {
+    Board,
+    Map,
+    /// Lobby room previews, see `preview.rs`.
+    Preview,
+    /// Named save slots panel, see `save_slots.rs`.
+    Slots,
+    /// Solved pictures of the room's pack shown on join, see `flip_book.rs`.
+    FlipBook,
+    /// The solved picture and how the solve went, see `win_screen.rs`.
+    Win,
+}
+
+// endregion
+
+pub struct ScreenPlugin;
+
+impl Plugin for ScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state(Screen::Board);
+    }
+}
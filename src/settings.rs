@@ -0,0 +1,390 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+
+use crate::board::BoardAction;
+use crate::options::parse_pairs;
+use crate::storage;
+use crate::BridgeCommandEvent;
+
+// endregion
+
+// region:      CONSTANTS
+
+const SETTINGS_KEY: &str = "settings";
+/// Past this the painted cells run visibly ahead of the finger.
+const MAX_TOUCH_PREDICTION_MS: f32 = 100.;
+const MIN_UI_SCALE: f32 = 0.5;
+const MAX_UI_SCALE: f32 = 3.;
+const MIN_IDLE_HINT_SECONDS: f32 = 5.;
+const MAX_IDLE_HINT_SECONDS: f32 = 600.;
+const MIN_POINTER_SENSITIVITY: f32 = 0.1;
+const MAX_POINTER_SENSITIVITY: f32 = 5.;
+
+// endregion
+
+// region:      RESOURCES
+
+/// What pressing a mouse button does. The left button always paints with the
+/// control tile's action, right and middle are set per device with the
+/// `right_click` and `middle_click` settings.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ClickBinding {
+    Action(BoardAction),
+    /// Toggles a local pencil mark on the cell.
+    Flag,
+    /// Leaves the button to the browser, e.g. for its context menu.
+    Disabled,
+}
+
+impl ClickBinding {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "fill" => Some(ClickBinding::Action(BoardAction::Fill)),
+            "cross" => Some(ClickBinding::Action(BoardAction::Cross)),
+            "empty" => Some(ClickBinding::Action(BoardAction::Empty)),
+            "flag" => Some(ClickBinding::Flag),
+            "none" => Some(ClickBinding::Disabled),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            ClickBinding::Action(BoardAction::Fill) => "fill",
+            ClickBinding::Action(BoardAction::Cross) => "cross",
+            ClickBinding::Action(BoardAction::Empty) => "empty",
+            ClickBinding::Flag => "flag",
+            ClickBinding::Disabled => "none",
+        }
+    }
+}
+
+/// Whether row clues go on two lines to leave the tiles more room. `auto`
+/// leaves it to the window's shape and the `stack_clues_fraction` option.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum CompactClues {
+    Auto,
+    Always,
+    Never,
+}
+
+impl CompactClues {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "auto" => Some(CompactClues::Auto),
+            "always" => Some(CompactClues::Always),
+            "never" => Some(CompactClues::Never),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CompactClues::Auto => "auto",
+            CompactClues::Always => "always",
+            CompactClues::Never => "never",
+        }
+    }
+}
+
+/// Where the control tile goes. `auto` leaves it to the control profile,
+/// `corner` keeps it in the corner between the clues on any device and `off`
+/// leaves the corner empty, the left button filling and the right crossing.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ControlTilePlacement {
+    Auto,
+    Corner,
+    Off,
+}
+
+impl ControlTilePlacement {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "auto" => Some(ControlTilePlacement::Auto),
+            "corner" => Some(ControlTilePlacement::Corner),
+            "off" => Some(ControlTilePlacement::Off),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            ControlTilePlacement::Auto => "auto",
+            ControlTilePlacement::Corner => "corner",
+            ControlTilePlacement::Off => "off",
+        }
+    }
+}
+
+/// Per device preferences, persisted to localStorage as `key=value;key=value`
+/// and updatable by the host with the `"s"` command.
+#[derive(Resource)]
+pub struct Settings {
+    pub control_toggle_discovered: bool,
+    /// Skips decorative animations, hosts mirror `prefers-reduced-motion`.
+    pub reduced_motion: bool,
+    /// How far ahead touch strokes are painted, calibrated per device by the
+    /// host. 0 turns prediction off.
+    pub touch_prediction_ms: f32,
+    pub right_click: ClickBinding,
+    pub middle_click: ClickBinding,
+    /// Hides what other players are doing (remote change effects, the
+    /// activity feed, the update shimmer), their changes still apply. Also
+    /// toggled with N.
+    pub do_not_disturb: bool,
+    /// Player's factor on top of the window based HUD scale.
+    pub ui_scale: f32,
+    /// Shimmers the clues of a line with something left to deduce once the
+    /// board has sat still for `idle_hint_seconds`.
+    pub idle_hints: bool,
+    pub idle_hint_seconds: f32,
+    /// Row clue layout, applied on every board load and right away when
+    /// changed.
+    pub compact_clues: CompactClues,
+    /// Row clue layout of a restored view, for this session only and never
+    /// saved. Cleared when the player picks `compact_clues`.
+    pub view_compact_clues: Option<CompactClues>,
+    /// Applied to the control tile of every board as it spawns and right
+    /// away when changed.
+    pub control_tile: ControlTilePlacement,
+    /// Row clues on the right of the grid and the control tile in the top
+    /// right corner, for left handed play. Applied like `compact_clues`.
+    pub mirror_clues: bool,
+    /// Plays the stems of the `music` option, see `music.rs`.
+    pub music: bool,
+    pub music_volume: f32,
+    /// Captures the pointer on the first click on the board, see
+    /// `pointer_lock.rs`.
+    pub pointer_lock: bool,
+    /// Virtual cursor movement per unit of mouse motion while captured.
+    pub pointer_sensitivity: f32,
+    /// Holds drags to the row or column they start along, see `LineLock`.
+    pub line_lock: bool,
+    /// A pen pressed lighter than `pen_light_pressure` pencils, one pressed
+    /// firmer than `pen_firm_pressure` paints, see `pressure.rs`.
+    pub pen_pressure: bool,
+    pub pen_light_pressure: f32,
+    pub pen_firm_pressure: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            control_toggle_discovered: false,
+            reduced_motion: false,
+            touch_prediction_ms: 0.,
+            right_click: ClickBinding::Action(BoardAction::Cross),
+            middle_click: ClickBinding::Action(BoardAction::Empty),
+            do_not_disturb: false,
+            ui_scale: 1.,
+            idle_hints: true,
+            idle_hint_seconds: 60.,
+            compact_clues: CompactClues::Auto,
+            view_compact_clues: None,
+            control_tile: ControlTilePlacement::Auto,
+            mirror_clues: false,
+            music: true,
+            music_volume: 0.5,
+            pointer_lock: false,
+            pointer_sensitivity: 1.,
+            line_lock: true,
+            pen_pressure: true,
+            pen_light_pressure: 0.25,
+            pen_firm_pressure: 0.4,
+        }
+    }
+}
+
+impl Settings {
+    /// The row clue layout in effect, a restored view's over the player's.
+    pub fn compact(&self) -> CompactClues {
+        self.view_compact_clues.unwrap_or(self.compact_clues)
+    }
+
+    pub fn load() -> Self {
+        let mut settings = Settings::default();
+        if let Some(saved) = storage::load(SETTINGS_KEY) {
+            settings.merge_from_str(&saved);
+        }
+        settings
+    }
+
+    pub fn merge_from_str(&mut self, pairs: &str) {
+        for (key, value) in parse_pairs(pairs) {
+            match key {
+                "control_toggle_discovered" => {
+                    self.control_toggle_discovered = value == "1";
+                }
+                "reduced_motion" => {
+                    self.reduced_motion = value == "1";
+                }
+                "touch_prediction_ms" => match value.parse::<f32>() {
+                    Ok(ms) if ms.is_finite() => {
+                        self.touch_prediction_ms = ms.clamp(0., MAX_TOUCH_PREDICTION_MS);
+                    }
+                    _ => warn!("Invalid touch_prediction_ms: {}", value),
+                },
+                "right_click" | "middle_click" => match ClickBinding::parse(value) {
+                    Some(binding) if key == "right_click" => self.right_click = binding,
+                    Some(binding) => self.middle_click = binding,
+                    None => warn!("Invalid {}: {}", key, value),
+                },
+                "do_not_disturb" => {
+                    self.do_not_disturb = value == "1";
+                }
+                "ui_scale" => match value.parse::<f32>() {
+                    Ok(scale) if scale.is_finite() => {
+                        self.ui_scale = scale.clamp(MIN_UI_SCALE, MAX_UI_SCALE);
+                    }
+                    _ => warn!("Invalid ui_scale: {}", value),
+                },
+                "idle_hints" => {
+                    self.idle_hints = value == "1";
+                }
+                "idle_hint_seconds" => match value.parse::<f32>() {
+                    Ok(seconds) if seconds.is_finite() => {
+                        self.idle_hint_seconds =
+                            seconds.clamp(MIN_IDLE_HINT_SECONDS, MAX_IDLE_HINT_SECONDS);
+                    }
+                    _ => warn!("Invalid idle_hint_seconds: {}", value),
+                },
+                "compact_clues" => match CompactClues::parse(value) {
+                    Some(compact_clues) => {
+                        self.compact_clues = compact_clues;
+                        self.view_compact_clues = None;
+                    }
+                    None => warn!("Invalid compact_clues: {}", value),
+                },
+                "control_tile" => match ControlTilePlacement::parse(value) {
+                    Some(control_tile) => self.control_tile = control_tile,
+                    None => warn!("Invalid control_tile: {}", value),
+                },
+                "mirror_clues" => {
+                    self.mirror_clues = value == "1";
+                }
+                "music" => {
+                    self.music = value == "1";
+                }
+                "music_volume" => match value.parse::<f32>() {
+                    Ok(volume) if volume.is_finite() => self.music_volume = volume.clamp(0., 1.),
+                    _ => warn!("Invalid music_volume: {}", value),
+                },
+                "pointer_lock" => {
+                    self.pointer_lock = value == "1";
+                }
+                "pointer_sensitivity" => match value.parse::<f32>() {
+                    Ok(sensitivity) if sensitivity.is_finite() => {
+                        self.pointer_sensitivity =
+                            sensitivity.clamp(MIN_POINTER_SENSITIVITY, MAX_POINTER_SENSITIVITY);
+                    }
+                    _ => warn!("Invalid pointer_sensitivity: {}", value),
+                },
+                "line_lock" => {
+                    self.line_lock = value == "1";
+                }
+                "pen_pressure" => {
+                    self.pen_pressure = value == "1";
+                }
+                "pen_light_pressure" | "pen_firm_pressure" => match value.parse::<f32>() {
+                    Ok(pressure) if pressure.is_finite() && key == "pen_light_pressure" => {
+                        self.pen_light_pressure = pressure.clamp(0., 1.);
+                    }
+                    Ok(pressure) if pressure.is_finite() => {
+                        self.pen_firm_pressure = pressure.clamp(0., 1.);
+                    }
+                    _ => warn!("Invalid {}: {}", key, value),
+                },
+                k => warn!("Unknown setting: {}", k),
+            }
+        }
+    }
+
+    pub fn to_pairs(&self) -> String {
+        format!(
+            "control_toggle_discovered={};reduced_motion={};touch_prediction_ms={};right_click={};middle_click={};do_not_disturb={};ui_scale={};idle_hints={};idle_hint_seconds={};compact_clues={};control_tile={};mirror_clues={};music={};music_volume={};pointer_lock={};pointer_sensitivity={};line_lock={};pen_pressure={};pen_light_pressure={};pen_firm_pressure={}",
+            self.control_toggle_discovered as u8,
+            self.reduced_motion as u8,
+            self.touch_prediction_ms,
+            self.right_click.as_str(),
+            self.middle_click.as_str(),
+            self.do_not_disturb as u8,
+            self.ui_scale,
+            self.idle_hints as u8,
+            self.idle_hint_seconds,
+            self.compact_clues.as_str(),
+            self.control_tile.as_str(),
+            self.mirror_clues as u8,
+            self.music as u8,
+            self.music_volume,
+            self.pointer_lock as u8,
+            self.pointer_sensitivity,
+            self.line_lock as u8,
+            self.pen_pressure as u8,
+            self.pen_light_pressure,
+            self.pen_firm_pressure
+        )
+    }
+}
+
+// endregion
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Settings::load())
+            .add_system(settings_command_system)
+            .add_system(do_not_disturb_toggle_system)
+            .add_system(
+                settings_save_system
+                    .after(settings_command_system)
+                    .after(do_not_disturb_toggle_system),
+            );
+    }
+}
+
+fn settings_command_system(
+    mut settings: ResMut<Settings>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+) {
+    for event in bridge_command_event_reader.iter() {
+        if event.command == "s" {
+            settings.merge_from_str(&event.data);
+        }
+    }
+}
+
+fn do_not_disturb_toggle_system(keys: Res<Input<KeyCode>>, mut settings: ResMut<Settings>) {
+    if keys.just_pressed(KeyCode::N) {
+        settings.do_not_disturb = !settings.do_not_disturb;
+    }
+}
+
+fn settings_save_system(settings: Res<Settings>) {
+    if settings.is_changed() && !settings.is_added() {
+        storage::save(SETTINGS_KEY, &settings.to_pairs());
+    }
+}
+
+// region:      TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout_settings_survive_their_saved_pairs() {
+        let mut settings = Settings::default();
+        settings.merge_from_str("compact_clues=always;control_tile=off;mirror_clues=1");
+        let mut restored = Settings::default();
+        restored.merge_from_str(&settings.to_pairs());
+
+        assert!(restored.compact_clues == CompactClues::Always);
+        assert!(restored.control_tile == ControlTilePlacement::Off);
+        assert!(restored.mirror_clues);
+        assert_eq!(restored.to_pairs(), settings.to_pairs());
+    }
+}
+
+// endregion
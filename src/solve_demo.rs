@@ -0,0 +1,174 @@
+// region:      IMPORTS
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::board::{Board, BoardLoadedEvent, ChangeSource};
+use crate::board_commands::BoardCommand;
+use crate::bridge::reply_error;
+use crate::options::BoardOptions;
+use crate::solver::{line_steps, solve, LineStep};
+use crate::{BridgeCommandEvent, WASMSendChannel};
+
+// endregion
+
+// region:      CONSTANTS
+
+const DEFAULT_LINES_PER_SECOND: f32 = 4.;
+const MAX_LINES_PER_SECOND: f32 = 60.;
+
+// endregion
+
+// region:      RESOURCES
+
+/// Lines of the solution still to be written by a running demo.
+#[derive(Resource, Default)]
+pub struct SolveDemo {
+    steps: VecDeque<LineStep>,
+    seconds_per_line: f64,
+    next_at: f64,
+}
+
+// endregion
+
+pub struct SolveDemoPlugin;
+
+impl Plugin for SolveDemoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SolveDemo>()
+            .add_system(solve_demo_command_system)
+            .add_system(solve_demo_step_system.after(solve_demo_command_system));
+    }
+}
+
+/// `("solve_demo", lines per second)` solves the puzzle and writes the
+/// solution in, a row or column at a time in the order line logic finds
+/// them, for recording solution videos and eyeballing new puzzles. Only with
+/// the `debug` option on, `("solve_demo", "stop")` ends it early. Replies
+/// `("solve_demo", {"lines":n,"unique":bool})` or the reason it can't solve.
+fn solve_demo_command_system(
+    time: Res<Time>,
+    board: Res<Board>,
+    options: Res<BoardOptions>,
+    send_channel: Res<WASMSendChannel>,
+    mut demo: ResMut<SolveDemo>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+) {
+    if board_loaded_event_reader.iter().last().is_some() {
+        demo.steps.clear();
+    }
+    for event in bridge_command_event_reader.iter() {
+        if event.command != "solve_demo" {
+            continue;
+        }
+        if !options.get_bool("debug", false) {
+            reply_error(
+                &send_channel,
+                String::from("solve_demo: needs the debug option"),
+            );
+            continue;
+        }
+        if event.data == "stop" {
+            demo.steps.clear();
+            continue;
+        }
+        let lines_per_second = match event.data.as_str() {
+            "" => DEFAULT_LINES_PER_SECOND,
+            data => match data.parse::<f32>() {
+                Ok(speed) if speed > 0. => speed.min(MAX_LINES_PER_SECOND),
+                _ => {
+                    reply_error(&send_channel, format!("solve_demo: invalid speed {}", data));
+                    continue;
+                }
+            },
+        };
+
+        let solution = match solve(&board.p) {
+            Ok(solution) => solution,
+            Err(err) => {
+                reply_error(&send_channel, format!("solve_demo: {}", err));
+                continue;
+            }
+        };
+        demo.steps = line_steps(&board.p, &solution.cells).into();
+        demo.seconds_per_line = 1. / lines_per_second as f64;
+        demo.next_at = time.elapsed_seconds_f64();
+        send_channel.tx.send((
+            String::from("solve_demo"),
+            format!(
+                "{{\"lines\":{},\"unique\":{}}}",
+                demo.steps.len(),
+                solution.unique
+            ),
+        ));
+    }
+}
+
+fn solve_demo_step_system(
+    time: Res<Time>,
+    board: Res<Board>,
+    mut demo: ResMut<SolveDemo>,
+    mut board_command_event_writer: EventWriter<BoardCommand>,
+) {
+    let now = time.elapsed_seconds_f64();
+    if demo.steps.is_empty() || now < demo.next_at {
+        return;
+    }
+    demo.next_at = now + demo.seconds_per_line;
+    let step = match demo.steps.pop_front() {
+        Some(step) => step,
+        None => return,
+    };
+    // local only, the server never hears about a demo's moves
+    board_command_event_writer.send_batch(
+        step.into_iter()
+            .filter(|(x, y, cell)| board.p.get_cell(*x, *y) != *cell)
+            .map(|(x, y, cell)| BoardCommand {
+                x,
+                y,
+                cell,
+                source: ChangeSource::Handicap,
+            }),
+    );
+}
+
+// region:      TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::harness::{plus_puzzle, Harness, SETTLE_TICKS};
+
+    #[test]
+    fn solve_demo_writes_the_solution_in_with_the_debug_option() {
+        let mut harness = Harness::new();
+        assert!(harness.join(plus_puzzle().as_str(), "000000000"));
+        harness.replies();
+
+        harness.send("solve_demo", "60");
+        harness.ticks(SETTLE_TICKS);
+        assert!(harness
+            .replies()
+            .iter()
+            .any(|(command, _)| command == "error"));
+
+        harness
+            .app
+            .world
+            .resource_mut::<BoardOptions>()
+            .merge("debug", "1");
+        harness.send("solve_demo", "60");
+        harness.ticks(60);
+
+        assert!(harness.solved());
+        let reply = (
+            String::from("solve_demo"),
+            String::from("{\"lines\":3,\"unique\":true}"),
+        );
+        assert!(harness.replies().contains(&reply));
+    }
+}
+
+// endregion
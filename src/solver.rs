@@ -0,0 +1,259 @@
+// region:      IMPORTS
+
+use bevy::utils::{Duration, Instant};
+use picross_handler::{Cell, Puzzle};
+
+use crate::lines::{
+    column_cells, column_clues, forced_cells, line_consistent, row_cells, row_clues,
+};
+use crate::marks::ClueLine;
+
+// endregion
+
+// region:      CONSTANTS
+
+// boards line logic leaves open are guessed at, this many boards deep at most
+const MAX_SEARCH_NODES: usize = 5000;
+
+// endregion
+
+// helpers for solving a whole puzzle from its clues, cells indexed by
+// `y * width + x` with rows bottom up like the board's
+
+/// Cells one line decided together, `(x, y, cell)`.
+pub type LineStep = Vec<(usize, usize, Cell)>;
+
+/// Why line logic stopped short.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    /// The first line found that can no longer match its clues.
+    Contradiction(ClueLine),
+    OutOfTime,
+}
+
+pub struct Solution {
+    pub cells: Vec<Cell>,
+    /// False when a second solution turned up, or the search gave up before
+    /// ruling one out.
+    pub unique: bool,
+}
+
+/// Line logic first, then a guess at the first undecided cell whenever it
+/// stalls, backing out of guesses that break a line.
+pub fn solve(p: &Puzzle) -> Result<Solution, &'static str> {
+    let (width, height) = (p.get_width(), p.get_height());
+    let mut solutions: Vec<Vec<Cell>> = Vec::new();
+    let mut stack = vec![vec![Cell::Empty; width * height]];
+    let mut nodes = 0;
+    let mut gave_up = false;
+    while let Some(mut cells) = stack.pop() {
+        nodes += 1;
+        if nodes > MAX_SEARCH_NODES {
+            gave_up = true;
+            break;
+        }
+        if propagate(p, &mut cells, None, None).is_err() {
+            continue;
+        }
+        match cells.iter().position(|cell| *cell == Cell::Empty) {
+            Some(i) => {
+                let mut crossed = cells.clone();
+                crossed[i] = Cell::Crossed;
+                cells[i] = Cell::Filled;
+                stack.push(crossed);
+                stack.push(cells);
+            }
+            None => {
+                solutions.push(cells);
+                if solutions.len() > 1 {
+                    break;
+                }
+            }
+        }
+    }
+
+    let unique = solutions.len() == 1 && !gave_up;
+    match solutions.into_iter().next() {
+        Some(cells) => Ok(Solution { cells, unique }),
+        None if gave_up => Err("too many guesses"),
+        None => Err("no solution"),
+    }
+}
+
+/// The order a solver would write `solution` in from a blank board: every
+/// line logic decides, row or column at a time, then the rows of whatever
+/// took guessing.
+pub fn line_steps(p: &Puzzle, solution: &[Cell]) -> Vec<LineStep> {
+    let width = p.get_width();
+    let mut cells = vec![Cell::Empty; solution.len()];
+    let mut steps = Vec::new();
+    propagate(p, &mut cells, Some(&mut steps), None).ok();
+    for y in 0..p.get_height() {
+        let guessed: LineStep = (0..width)
+            .filter(|x| cells[y * width + x] == Cell::Empty)
+            .map(|x| (x, y, solution[y * width + x]))
+            .collect();
+        if !guessed.is_empty() {
+            steps.push(guessed);
+        }
+    }
+    steps
+}
+
+/// Whether the board's cells so far still fit the clues, following what
+/// they force line after line without guessing or looking at a solution.
+/// Gives up once `budget` is spent.
+pub fn check(p: &Puzzle, budget: Duration) -> Result<(), Stop> {
+    let (width, height) = (p.get_width(), p.get_height());
+    let mut cells: Vec<Cell> = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| p.get_cell(x, y))
+        .collect();
+    propagate(p, &mut cells, None, Some(Instant::now() + budget))
+}
+
+/// Undecided cells a single row or column forces from the board as it
+/// stands, `(x, y, cell)`, rows first. Nothing is looked up in a solution,
+/// so every one can be explained from its line's clues. A board with a
+/// line that can no longer match its clues has no hints, the first such
+/// line is the error.
+pub fn forced(p: &Puzzle) -> Result<Vec<(usize, usize, Cell)>, ClueLine> {
+    let mut cells = Vec::new();
+    for y in 0..p.get_height() {
+        let (clues, row) = (row_clues(p, y), row_cells(p, y));
+        if !line_consistent(&clues, &row) {
+            return Err(ClueLine::Row(y));
+        }
+        let forced = forced_cells(&clues, &row);
+        cells.extend(forced.into_iter().map(|(x, cell)| (x, y, cell)));
+    }
+    for x in 0..p.get_width() {
+        let (clues, column) = (column_clues(p, x), column_cells(p, x));
+        if !line_consistent(&clues, &column) {
+            return Err(ClueLine::Column(x));
+        }
+        let forced = forced_cells(&clues, &column);
+        cells.extend(forced.into_iter().map(|(y, cell)| (x, y, cell)));
+    }
+    Ok(cells)
+}
+
+/// Applies what each row and column forces until nothing more follows.
+fn propagate(
+    p: &Puzzle,
+    cells: &mut [Cell],
+    mut steps: Option<&mut Vec<LineStep>>,
+    deadline: Option<Instant>,
+) -> Result<(), Stop> {
+    let (width, height) = (p.get_width(), p.get_height());
+    let out_of_time = || deadline.map_or(false, |deadline| Instant::now() >= deadline);
+    loop {
+        let mut progress = false;
+        for y in 0..height {
+            if out_of_time() {
+                return Err(Stop::OutOfTime);
+            }
+            let clues = row_clues(p, y);
+            let row = &mut cells[y * width..(y + 1) * width];
+            if !line_consistent(&clues, row) {
+                return Err(Stop::Contradiction(ClueLine::Row(y)));
+            }
+            let forced = forced_cells(&clues, row);
+            for (x, cell) in &forced {
+                row[*x] = *cell;
+            }
+            progress |= !forced.is_empty();
+            if let (false, Some(steps)) = (forced.is_empty(), steps.as_mut()) {
+                steps.push(forced.into_iter().map(|(x, cell)| (x, y, cell)).collect());
+            }
+        }
+        for x in 0..width {
+            if out_of_time() {
+                return Err(Stop::OutOfTime);
+            }
+            let clues = column_clues(p, x);
+            let column: Vec<Cell> = (0..height).map(|y| cells[y * width + x]).collect();
+            if !line_consistent(&clues, &column) {
+                return Err(Stop::Contradiction(ClueLine::Column(x)));
+            }
+            let forced = forced_cells(&clues, &column);
+            for (y, cell) in &forced {
+                cells[y * width + x] = *cell;
+            }
+            progress |= !forced.is_empty();
+            if let (false, Some(steps)) = (forced.is_empty(), steps.as_mut()) {
+                steps.push(forced.into_iter().map(|(y, cell)| (x, y, cell)).collect());
+            }
+        }
+        if !progress {
+            return Ok(());
+        }
+    }
+}
+
+// region:      TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::harness::clue_string;
+
+    fn puzzle(rows: &[&[u32]], columns: &[&[u32]]) -> Puzzle {
+        Puzzle::from_string(clue_string(rows, columns).as_str())
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    // X.X
+    // XXX
+    // X.X
+    fn plus() -> Puzzle {
+        puzzle(&[&[1, 1], &[3], &[1, 1]], &[&[3], &[1], &[3]])
+    }
+
+    #[test]
+    fn line_logic_solves_a_unique_puzzle() {
+        let solution = solve(&plus()).ok().unwrap();
+        assert!(solution.unique);
+        let filled: String = solution
+            .cells
+            .iter()
+            .map(|cell| if *cell == Cell::Filled { '1' } else { '0' })
+            .collect();
+        assert_eq!(filled, "101111101");
+    }
+
+    #[test]
+    fn a_second_solution_makes_it_not_unique() {
+        let solution = solve(&puzzle(&[&[1], &[1]], &[&[1], &[1]])).ok().unwrap();
+        assert!(!solution.unique);
+    }
+
+    #[test]
+    fn forced_cells_come_from_single_lines() {
+        let cells = forced(&plus()).ok().unwrap();
+        for x in 0..3 {
+            assert!(cells.contains(&(x, 1, Cell::Filled)));
+        }
+        for y in 0..3 {
+            assert!(cells.contains(&(0, y, Cell::Filled)));
+            assert!(cells.contains(&(2, y, Cell::Filled)));
+        }
+        // the outer rows cross their middle cell, which the middle column
+        // alone can't place
+        assert!(cells.contains(&(1, 0, Cell::Crossed)));
+        assert!(cells.contains(&(1, 2, Cell::Crossed)));
+    }
+
+    #[test]
+    fn contradictory_boards_are_errors_not_hints() {
+        let mut p = plus();
+        p.set_cell(1, 1, Cell::Crossed);
+        assert!(matches!(forced(&p), Err(ClueLine::Row(1))));
+        assert!(matches!(
+            check(&p, Duration::from_secs(1)),
+            Err(Stop::Contradiction(ClueLine::Row(1)))
+        ));
+    }
+}
+
+// endregion
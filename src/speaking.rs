@@ -0,0 +1,134 @@
+// region:      IMPORTS
+
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::bridge::reply_error;
+use crate::{BridgeCommandEvent, WASMSendChannel};
+
+// endregion
+
+// region:      RESOURCES
+
+/// Players the host page's voice chat hears talking right now, by the names
+/// the other co-op messages use. Wherever a player's name is drawn, it is
+/// marked while they speak.
+#[derive(Resource, Default)]
+pub struct Speaking(pub HashSet<String>);
+
+impl Speaking {
+    pub fn is_speaking(&self, player: &str) -> bool {
+        self.0.contains(player)
+    }
+
+    /// `player`'s name as drawn, marked while they speak.
+    pub fn label(&self, player: &str) -> String {
+        match self.is_speaking(player) {
+            true => format!("{} (speaking)", player),
+            false => player.to_string(),
+        }
+    }
+
+    /// A line of text that starts with a player's name, marked the same way.
+    pub fn label_line(&self, line: &str) -> String {
+        let speaker = self.0.iter().find(|player| {
+            line.strip_prefix(player.as_str())
+                .map_or(false, |rest| rest.is_empty() || rest.starts_with(' '))
+        });
+        match speaker {
+            Some(player) => format!("{}{}", self.label(player), &line[player.len()..]),
+            None => line.to_string(),
+        }
+    }
+}
+
+// endregion
+
+pub struct SpeakingPlugin;
+
+impl Plugin for SpeakingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Speaking>()
+            .add_system(speaking_command_system);
+    }
+}
+
+/// `("speaking", "player,1")` when the voice chat starts hearing `player`,
+/// `("speaking", "player,0")` when they stop.
+fn speaking_command_system(
+    send_channel: Res<WASMSendChannel>,
+    mut speaking: ResMut<Speaking>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+) {
+    for event in bridge_command_event_reader.iter() {
+        if event.command != "speaking" {
+            continue;
+        }
+        let (player, talking) = match event.data.rsplit_once(',') {
+            Some((player, "1")) if !player.is_empty() => (player, true),
+            Some((player, "0")) if !player.is_empty() => (player, false),
+            _ => {
+                reply_error(
+                    &send_channel,
+                    format!("speaking: invalid speaking {}", event.data),
+                );
+                continue;
+            }
+        };
+        // only touch the resource on a real change, names redraw on it
+        if talking != speaking.is_speaking(player) {
+            match talking {
+                true => speaking.0.insert(player.to_string()),
+                false => speaking.0.remove(player),
+            };
+        }
+    }
+}
+
+// region:      TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::harness::{clue_string, Harness};
+
+    #[test]
+    fn speaking_messages_set_and_clear_the_flag() {
+        let mut harness = Harness::new();
+        let clues = clue_string(&[&[1], &[1]], &[&[1], &[1]]);
+        assert!(harness.join(clues.as_str(), "0000"));
+        harness.replies();
+
+        harness.send("speaking", "Ana,1");
+        harness.send("speaking", "Bo, Jr.,1");
+        harness.tick();
+        let speaking = harness.app.world.resource::<Speaking>();
+        assert!(speaking.is_speaking("Ana") && speaking.is_speaking("Bo, Jr."));
+
+        harness.send("speaking", "Ana,0");
+        harness.send("speaking", "Ana,yes");
+        harness.send("speaking", ",1");
+        harness.tick();
+        assert!(!harness.app.world.resource::<Speaking>().is_speaking("Ana"));
+        let errors = harness
+            .replies()
+            .iter()
+            .filter(|(command, _)| command == "error")
+            .count();
+        assert_eq!(errors, 2);
+    }
+
+    #[test]
+    fn lines_starting_with_a_speaker_are_marked() {
+        let mut speaking = Speaking::default();
+        speaking.0.insert(String::from("Ana"));
+        assert_eq!(speaking.label("Ana"), "Ana (speaking)");
+        assert_eq!(
+            speaking.label_line("Ana filled B2"),
+            "Ana (speaking) filled B2"
+        );
+        assert_eq!(speaking.label_line("Anabel filled B2"), "Anabel filled B2");
+        assert_eq!(speaking.label_line("Hint used"), "Hint used");
+    }
+}
+
+// endregion
@@ -0,0 +1,141 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+
+use crate::board::{Board, BoardLoadedEvent};
+use crate::camera::BoardView;
+use crate::hud_scale::HudScale;
+use crate::layers::HUD_BANNER_Z;
+use crate::options::BoardOptions;
+use crate::stats::Stats;
+use crate::storage;
+use crate::timer::SolveTimer;
+use crate::win::PuzzleSolvedEvent;
+use crate::{GameTextures, WASMSendChannel, WinSize};
+
+// endregion
+
+// region:      CONSTANTS
+
+pub const MAX_STARS: u32 = 3;
+const BANNER_FONT_SIZE: f32 = 40.;
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct StarBanner;
+
+// endregion
+
+pub struct StarsPlugin;
+
+impl Plugin for StarsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(star_rating_system);
+    }
+}
+
+/// Thresholds come from the `star_time`, `star_hints` and `star_mistakes`
+/// options, each `three,two`: at or under the first keeps three stars, at or
+/// under the second two, anything more leaves one. Missing thresholds don't
+/// cost stars.
+pub fn star_rating(options: &BoardOptions, seconds: f64, hints: u32, mistakes: u32) -> u32 {
+    let stars_for = |key: &str, value: f64| {
+        let mut thresholds = options
+            .get(key)
+            .into_iter()
+            .flat_map(|value| value.split(','))
+            .filter_map(|threshold| threshold.trim().parse::<f64>().ok());
+        match (thresholds.next(), thresholds.next()) {
+            (Some(three), _) if value <= three => MAX_STARS,
+            (Some(_), Some(two)) if value <= two => 2,
+            (Some(_), _) => 1,
+            _ => MAX_STARS,
+        }
+    };
+    stars_for("star_time", seconds)
+        .min(stars_for("star_hints", hints as f64))
+        .min(stars_for("star_mistakes", mistakes as f64))
+}
+
+/// Rates each solve, keeps the best rating per puzzle id and replies
+/// `("stars", json)`.
+fn star_rating_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    board: Res<Board>,
+    stats: Res<Stats>,
+    view: Res<BoardView>,
+    hud_scale: Res<HudScale>,
+    win_size: Res<WinSize>,
+    options: Res<BoardOptions>,
+    solve_timer: Res<SolveTimer>,
+    game_textures: Res<GameTextures>,
+    send_channel: Res<WASMSendChannel>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut puzzle_solved_event_reader: EventReader<PuzzleSolvedEvent>,
+    banner_query: Query<Entity, With<StarBanner>>,
+) {
+    if board_loaded_event_reader.iter().last().is_some() {
+        for entity in banner_query.iter() {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    for _ in puzzle_solved_event_reader.iter() {
+        let stars = star_rating(
+            &options,
+            solve_timer.elapsed(&time),
+            stats.hints_used,
+            stats.penalties,
+        );
+
+        let key = format!("stars.{}", board.id);
+        let best = storage::load(&key)
+            .and_then(|saved| saved.parse::<u32>().ok())
+            .unwrap_or(0);
+        if stars > best {
+            storage::save(&key, &stars.to_string());
+        }
+        send_channel.tx.send((
+            String::from("stars"),
+            format!(
+                "{{\"id\":\"{}\",\"stars\":{},\"best\":{},\"elapsed_ms\":{},\"hints_used\":{},\"mistakes\":{}}}",
+                board.id,
+                stars,
+                stars.max(best),
+                solve_timer.elapsed_ms(&time),
+                stats.hints_used,
+                stats.penalties
+            ),
+        ));
+
+        // earned stars as asterisks, the clue font has no star glyph
+        let banner = format!(
+            "{}{}",
+            "* ".repeat(stars as usize),
+            "- ".repeat((MAX_STARS - stars) as usize)
+        );
+        let position = view.screen_to_world(Vec2::new(
+            win_size.w / 2.,
+            win_size.h - BANNER_FONT_SIZE * hud_scale.0,
+        ));
+        commands
+            .spawn(Text2dBundle {
+                text: Text::from_section(
+                    banner.trim_end(),
+                    TextStyle {
+                        font: game_textures.font.clone(),
+                        font_size: BANNER_FONT_SIZE * hud_scale.world(&view),
+                        color: Color::GOLD,
+                    },
+                )
+                .with_alignment(TextAlignment::CENTER),
+                transform: Transform::from_xyz(position.x, position.y, HUD_BANNER_Z),
+                ..Default::default()
+            })
+            .insert(StarBanner);
+    }
+}
@@ -0,0 +1,140 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+
+use crate::bridge::reply_error;
+use crate::camera::BoardView;
+use crate::hud_scale::HudScale;
+use crate::layers::HUD_Z;
+use crate::timer::SolveTimer;
+use crate::{BridgeCommandEvent, GameTextures, WASMSendChannel, WinSize};
+
+// endregion
+
+// region:      CONSTANTS
+
+const COUNTDOWN_FONT_SIZE: f32 = 96.;
+const GO_SECONDS: f64 = 0.6;
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct CountdownText;
+
+// endregion
+
+// region:      RESOURCES
+
+/// Race start set with `("start_at", epoch_ms)`. The board takes no player
+/// input until then, every client counting down against its own wall clock.
+#[derive(Resource, Default)]
+pub struct StartLock {
+    pub at: Option<f64>,
+}
+
+impl StartLock {
+    pub fn locked(&self) -> bool {
+        self.at.map_or(false, |at| epoch_ms() < at)
+    }
+}
+
+pub fn epoch_ms() -> f64 {
+    js_sys::Date::now()
+}
+
+// endregion
+
+pub struct StartPlugin;
+
+impl Plugin for StartPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StartLock>()
+            .add_system(start_at_command_system)
+            .add_system(countdown_system.after(start_at_command_system));
+    }
+}
+
+fn start_at_command_system(
+    send_channel: Res<WASMSendChannel>,
+    mut start_lock: ResMut<StartLock>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+) {
+    for event in bridge_command_event_reader.iter() {
+        if event.command != "start_at" {
+            continue;
+        }
+        match event.data.trim().parse::<f64>() {
+            Ok(at) if at.is_finite() => start_lock.at = Some(at),
+            _ => reply_error(
+                &send_channel,
+                format!("start_at: invalid timestamp {}", event.data),
+            ),
+        }
+    }
+}
+
+/// Shows 3-2-1 while locked, then Go! as the board unlocks. The solve clock
+/// starts at the unlock time rather than when the puzzle loaded.
+fn countdown_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    view: Res<BoardView>,
+    hud_scale: Res<HudScale>,
+    win_size: Res<WinSize>,
+    game_textures: Res<GameTextures>,
+    mut start_lock: ResMut<StartLock>,
+    mut solve_timer: ResMut<SolveTimer>,
+    mut text_query: Query<(Entity, &mut Text, &mut Transform), With<CountdownText>>,
+) {
+    let at = match start_lock.at {
+        Some(at) => at,
+        None => return,
+    };
+    let remaining = (at - epoch_ms()) / 1000.;
+
+    if remaining <= -GO_SECONDS {
+        start_lock.at = None;
+        for (entity, _, _) in text_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+    let message = if remaining > 0. {
+        format!("{}", remaining.ceil())
+    } else {
+        String::from("Go!")
+    };
+    if remaining <= 0. && solve_timer.finished.is_none() {
+        // backdated to the exact start, whenever this frame ran
+        let started = time.elapsed_seconds_f64() + remaining;
+        if solve_timer.started < started {
+            solve_timer.started = started;
+        }
+    }
+
+    let center = view.screen_to_world(Vec2::new(win_size.w / 2., win_size.h / 2.));
+    if let Some((_, mut text, mut transform)) = text_query.iter_mut().next() {
+        if text.sections[0].value != message {
+            text.sections[0].value = message;
+        }
+        transform.translation = center.extend(HUD_Z);
+        return;
+    }
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                message,
+                TextStyle {
+                    font: game_textures.font.clone(),
+                    font_size: COUNTDOWN_FONT_SIZE * hud_scale.world(&view),
+                    color: Color::WHITE,
+                },
+            )
+            .with_alignment(TextAlignment::CENTER),
+            transform: Transform::from_translation(center.extend(HUD_Z)),
+            ..Default::default()
+        })
+        .insert(CountdownText);
+}
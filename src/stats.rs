@@ -0,0 +1,110 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+
+use crate::board::{cell_to_char, Board, BoardLoadedEvent};
+use crate::randomizer::Orientation;
+use crate::telemetry::HintUsedEvent;
+use crate::timer::SolveTimer;
+use crate::{BridgeCommandEvent, WASMSendChannel};
+use picross_handler::Cell;
+
+// endregion
+
+// region:      RESOURCES
+
+pub struct ConflictRecord {
+    pub x: usize,
+    pub y: usize,
+    /// Seconds since the puzzle loaded.
+    pub t: f64,
+    pub local: Cell,
+    pub remote: Cell,
+}
+
+/// Per puzzle statistics, reset on every join and sent to the host as JSON
+/// in reply to `("stats", "")`.
+#[derive(Resource, Default)]
+pub struct Stats {
+    pub conflicts: Vec<ConflictRecord>,
+    pub penalties: u32,
+    pub penalty_seconds: f64,
+    pub hints_used: u32,
+    /// Whether the probability overlay assist was on at any point.
+    pub probability_overlay: bool,
+    /// How the board was randomized, cells above are in that layout.
+    pub orientation: Orientation,
+}
+
+impl Stats {
+    pub fn to_json(&self, time: &Time, solve_timer: &SolveTimer) -> String {
+        let conflicts: Vec<String> = self
+            .conflicts
+            .iter()
+            .map(|conflict| {
+                format!(
+                    "{{\"x\":{},\"y\":{},\"t_ms\":{},\"local\":\"{}\",\"remote\":\"{}\"}}",
+                    conflict.x,
+                    conflict.y,
+                    (conflict.t * 1000.) as u64,
+                    cell_to_char(conflict.local),
+                    cell_to_char(conflict.remote)
+                )
+            })
+            .collect();
+
+        format!(
+            "{{\"elapsed_ms\":{},\"conflicts\":[{}],\"penalties\":{},\"penalty_ms\":{},\"hints_used\":{},\"assists\":{{\"probability_overlay\":{}}},\"orientation\":{}}}",
+            solve_timer.elapsed_ms(time),
+            conflicts.join(","),
+            self.penalties,
+            (self.penalty_seconds * 1000.) as u64,
+            self.hints_used,
+            self.probability_overlay,
+            self.orientation.to_json()
+        )
+    }
+}
+
+// endregion
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Stats>()
+            .add_system(reset_stats_system)
+            .add_system(stats_command_system);
+    }
+}
+
+fn reset_stats_system(
+    board: Res<Board>,
+    mut stats: ResMut<Stats>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut hint_used_event_reader: EventReader<HintUsedEvent>,
+) {
+    for _ in board_loaded_event_reader.iter() {
+        *stats = Stats {
+            orientation: board.orientation,
+            ..Default::default()
+        };
+    }
+    stats.hints_used += hint_used_event_reader.iter().count() as u32;
+}
+
+fn stats_command_system(
+    time: Res<Time>,
+    stats: Res<Stats>,
+    solve_timer: Res<SolveTimer>,
+    send_channel: Res<WASMSendChannel>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+) {
+    for event in bridge_command_event_reader.iter() {
+        if event.command == "stats" {
+            send_channel
+                .tx
+                .send((String::from("stats"), stats.to_json(&time, &solve_timer)));
+        }
+    }
+}
@@ -0,0 +1,35 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+use web_sys::Storage;
+
+// endregion
+
+// thin wrapper over localStorage, every key is prefixed to stay out of the
+// host page's way
+
+const KEY_PREFIX: &str = "picross_w.";
+
+fn local_storage() -> Option<Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+pub fn load(key: &str) -> Option<String> {
+    local_storage()?
+        .get_item(&format!("{}{}", KEY_PREFIX, key))
+        .ok()?
+}
+
+pub fn save(key: &str, value: &str) {
+    if let Some(storage) = local_storage() {
+        if let Err(err) = storage.set_item(&format!("{}{}", KEY_PREFIX, key), value) {
+            warn!("Unable to save {}: {:?}", key, err);
+        }
+    }
+}
+
+pub fn remove(key: &str) {
+    if let Some(storage) = local_storage() {
+        storage.remove_item(&format!("{}{}", KEY_PREFIX, key)).ok();
+    }
+}
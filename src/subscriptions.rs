@@ -0,0 +1,229 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use wasm_bindgen::JsValue;
+
+use crate::board::{Board, BoardLoadedEvent, CellChangedEvent};
+use crate::lines::{column_cells, column_clues, line_satisfied, row_cells, row_clues};
+use crate::{trace, WASMSendChannel, GLOBAL_RECEIVER};
+
+// endregion
+
+// region:      GLOBAL
+
+/// Host callbacks registered with `subscribe_wasm`, called from
+/// `subscription_dispatch_system`.
+pub static mut GLOBAL_SUBSCRIPTIONS: Option<Mutex<Subscriptions>> = None;
+
+// endregion
+
+// region:      CONSTANTS
+
+/// Classes a subscription can ask for, each a set of outgoing commands.
+const CLASSES: [(&str, &[&str]); 5] = [
+    ("solve", &["w", "stars", "suggest", "team"]),
+    ("progress", &["progress"]),
+    ("error", &["error"]),
+    ("moves", &["c"]),
+    ("stats", &["stats", "t", "weekly", "weekly_summary"]),
+];
+
+/// Share of the lines done at which `("progress", percent)` is sent.
+const MILESTONES: [u32; 4] = [25, 50, 75, 100];
+
+// no subscription took these and the host may never poll for them
+const MAX_UNCLAIMED: usize = 1000;
+
+// endregion
+
+// region:      RESOURCES
+
+pub struct Subscription {
+    id: u32,
+    commands: Vec<String>,
+    callback: js_sys::Function,
+}
+
+#[derive(Default)]
+pub struct Subscriptions {
+    next_id: u32,
+    list: Vec<Subscription>,
+    /// Messages drained while dispatching that no subscription took, handed
+    /// out by `recv_wasm` before anything newer.
+    unclaimed: VecDeque<(String, String)>,
+}
+
+/// Highest milestone reached on the current board.
+#[derive(Resource, Default)]
+pub struct Milestone(u32);
+
+// endregion
+
+pub struct SubscriptionPlugin;
+
+impl Plugin for SubscriptionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Milestone>()
+            .add_system(milestone_system)
+            .add_system_to_stage(CoreStage::Last, subscription_dispatch_system);
+    }
+}
+
+pub fn init() {
+    unsafe {
+        GLOBAL_SUBSCRIPTIONS = Some(Mutex::new(Subscriptions::default()));
+    }
+}
+
+fn with_subscriptions<T>(f: impl FnOnce(&mut Subscriptions) -> T) -> Option<T> {
+    let subscriptions = unsafe { GLOBAL_SUBSCRIPTIONS.as_ref() };
+    subscriptions
+        .and_then(|subscriptions| subscriptions.lock().ok())
+        .map(|mut subscriptions| f(&mut subscriptions))
+}
+
+/// Registers `callback(command, data)` for the messages `filter_json` asks
+/// for, `{"classes":["solve","error"],"commands":["hb"]}`. Classes are
+/// `solve`, `progress`, `error`, `moves` and `stats`, commands name any
+/// outgoing message. Returns the id to unsubscribe with.
+pub fn subscribe(filter_json: &str, callback: js_sys::Function) -> Result<u32, String> {
+    let json = js_sys::JSON::parse(filter_json)
+        .ok()
+        .filter(|json| json.is_object())
+        .ok_or_else(|| format!("subscribe: invalid json {}", filter_json))?;
+    let strings = |key: &str| -> Vec<String> {
+        js_sys::Reflect::get(&json, &JsValue::from_str(key))
+            .ok()
+            .filter(js_sys::Array::is_array)
+            .map(|array| {
+                js_sys::Array::from(&array)
+                    .iter()
+                    .filter_map(|value| value.as_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let mut commands = strings("commands");
+    for class in strings("classes") {
+        match CLASSES.iter().find(|(name, _)| *name == class) {
+            Some((_, members)) => commands.extend(members.iter().map(|c| c.to_string())),
+            None => return Err(format!("subscribe: unknown class {}", class)),
+        }
+    }
+    if commands.is_empty() {
+        return Err(String::from("subscribe: filter matches nothing"));
+    }
+
+    with_subscriptions(|subscriptions| {
+        subscriptions.next_id += 1;
+        let id = subscriptions.next_id;
+        subscriptions.list.push(Subscription {
+            id,
+            commands,
+            callback,
+        });
+        id
+    })
+    .ok_or_else(|| String::from("subscribe: not initialized"))
+}
+
+/// False when no subscription had that id.
+pub fn unsubscribe(id: u32) -> bool {
+    with_subscriptions(|subscriptions| {
+        let before = subscriptions.list.len();
+        subscriptions
+            .list
+            .retain(|subscription| subscription.id != id);
+        subscriptions.list.len() != before
+    })
+    .unwrap_or(false)
+}
+
+/// The oldest message no subscription took, for `recv_wasm`.
+pub fn unclaimed() -> Option<(String, String)> {
+    with_subscriptions(|subscriptions| subscriptions.unclaimed.pop_front()).flatten()
+}
+
+pub fn unclaimed_len() -> usize {
+    with_subscriptions(|subscriptions| subscriptions.unclaimed.len()).unwrap_or(0)
+}
+
+/// Hands each outgoing message to the callbacks subscribed to it instead of
+/// `recv_wasm`, the rest stay there in order. Subscribed messages skip the
+/// connection simulator.
+fn subscription_dispatch_system() {
+    let rx = match unsafe { GLOBAL_RECEIVER.as_ref() }.and_then(|rx| rx.lock().ok()) {
+        Some(rx) => rx.clone(),
+        None => return,
+    };
+    // callbacks run after the lock is let go, they may well subscribe or poll
+    let mut calls = Vec::new();
+    with_subscriptions(|subscriptions| {
+        if subscriptions.list.is_empty() {
+            return;
+        }
+        for (command, data) in rx.try_iter() {
+            let callbacks: Vec<js_sys::Function> = subscriptions
+                .list
+                .iter()
+                .filter(|subscription| subscription.commands.contains(&command))
+                .map(|subscription| subscription.callback.clone())
+                .collect();
+            if !callbacks.is_empty() {
+                calls.push((command, data, callbacks));
+                continue;
+            }
+            if subscriptions.unclaimed.len() >= MAX_UNCLAIMED {
+                subscriptions.unclaimed.pop_front();
+            }
+            subscriptions.unclaimed.push_back((command, data));
+        }
+    });
+
+    for (command, data, callbacks) in calls {
+        trace::record(false, &command, &data);
+        for callback in callbacks {
+            let result = callback.call2(
+                &JsValue::NULL,
+                &JsValue::from_str(&command),
+                &JsValue::from_str(&data),
+            );
+            if let Err(err) = result {
+                warn!("subscription callback failed: {:?}", err);
+            }
+        }
+    }
+}
+
+/// `("progress", percent)` the first time the share of rows and columns
+/// matching their clues reaches each milestone on a board.
+fn milestone_system(
+    board: Res<Board>,
+    send_channel: Res<WASMSendChannel>,
+    mut milestone: ResMut<Milestone>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut cell_changed_event_reader: EventReader<CellChangedEvent>,
+) {
+    if board_loaded_event_reader.iter().last().is_some() {
+        milestone.0 = 0;
+    }
+    if cell_changed_event_reader.iter().last().is_none() {
+        return;
+    }
+    let p = &board.p;
+    let rows = (0..p.get_height()).filter(|y| line_satisfied(&row_clues(p, *y), &row_cells(p, *y)));
+    let columns =
+        (0..p.get_width()).filter(|x| line_satisfied(&column_clues(p, *x), &column_cells(p, *x)));
+    let lines = (p.get_width() + p.get_height()).max(1);
+    let percent = ((rows.count() + columns.count()) * 100 / lines) as u32;
+    let reached = MILESTONES.iter().rev().find(|m| percent >= **m);
+    if let Some(reached) = reached.filter(|m| **m > milestone.0) {
+        milestone.0 = *reached;
+        send_channel
+            .tx
+            .send((String::from("progress"), reached.to_string()));
+    }
+}
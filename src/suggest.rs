@@ -0,0 +1,200 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+use picross_handler::Cell;
+
+use crate::board::{Board, BoardLoadedEvent};
+use crate::camera::BoardView;
+use crate::hud_scale::HudScale;
+use crate::layers::HUD_BANNER_Z;
+use crate::lines::{column_clues, forced_cells, row_clues};
+use crate::options::BoardOptions;
+use crate::stats::Stats;
+use crate::timer::SolveTimer;
+use crate::win::PuzzleSolvedEvent;
+use crate::{GameTextures, WASMSendChannel, WinSize};
+
+// endregion
+
+// region:      CONSTANTS
+
+// expected solve time, overridable with the par_time option
+const SECONDS_PER_CELL: f64 = 1.5;
+const SECONDS_PER_PASS: f64 = 8.;
+const GUESSING_FACTOR: f64 = 2.; // puzzles line logic alone can't finish
+
+// of the expected time
+const FAST_PACE: f64 = 0.6;
+const SLOW_PACE: f64 = 1.8;
+const MANY_HINTS: u32 = 3;
+const MANY_MISTAKES: u32 = 3;
+
+const SUGGESTION_FONT_SIZE: f32 = 20.;
+const SUGGESTION_OFFSET: f32 = 80.; // under the star banner
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct SuggestionText;
+
+// endregion
+
+// region:      RESOURCES
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Suggestion {
+    Harder,
+    Similar,
+    Easier,
+}
+
+impl Suggestion {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Suggestion::Harder => "harder",
+            Suggestion::Similar => "similar",
+            Suggestion::Easier => "easier",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Suggestion::Harder => "Next: a harder puzzle",
+            Suggestion::Similar => "Next: one like this",
+            Suggestion::Easier => "Next: an easier puzzle",
+        }
+    }
+}
+
+/// How long the current puzzle should take, estimated when it loads.
+#[derive(Resource, Default)]
+pub struct Difficulty {
+    pub expected_seconds: f64,
+}
+
+impl Difficulty {
+    /// Solves the clues a line at a time, each pass applying every cell a
+    /// row or column forces on its own, until a pass forces nothing. Every
+    /// pass adds to the time, and so does guessing when passes can't finish.
+    fn estimate(board: &Board, options: &BoardOptions) -> Self {
+        let p = &board.p;
+        let (width, height) = (p.get_width(), p.get_height());
+        let mut cells = vec![Cell::Empty; width * height];
+        let mut passes = 0;
+        loop {
+            let mut forced = 0;
+            for y in 0..height {
+                let row = &mut cells[y * width..(y + 1) * width];
+                for (x, cell) in forced_cells(&row_clues(p, y), row) {
+                    row[x] = cell;
+                    forced += 1;
+                }
+            }
+            for x in 0..width {
+                let column: Vec<Cell> = (0..height).map(|y| cells[y * width + x]).collect();
+                for (y, cell) in forced_cells(&column_clues(p, x), &column) {
+                    cells[y * width + x] = cell;
+                    forced += 1;
+                }
+            }
+            if forced == 0 {
+                break;
+            }
+            passes += 1;
+        }
+        let mut expected =
+            (width * height) as f64 * SECONDS_PER_CELL + passes as f64 * SECONDS_PER_PASS;
+        if cells.contains(&Cell::Empty) {
+            expected *= GUESSING_FACTOR;
+        }
+        Self {
+            expected_seconds: options.get_f32("par_time", expected as f32) as f64,
+        }
+    }
+
+    /// Quick and clean solves ask for more, slow or heavily helped ones for
+    /// less. Penalty seconds don't count towards the pace, the mistakes
+    /// behind them already do.
+    fn suggest(&self, seconds: f64, stats: &Stats) -> Suggestion {
+        let pace = seconds / self.expected_seconds.max(1.);
+        let helped = stats.hints_used > 0 || stats.probability_overlay;
+        if pace <= FAST_PACE && !helped && stats.penalties == 0 {
+            Suggestion::Harder
+        } else if pace >= SLOW_PACE
+            || stats.hints_used >= MANY_HINTS
+            || stats.penalties >= MANY_MISTAKES
+        {
+            Suggestion::Easier
+        } else {
+            Suggestion::Similar
+        }
+    }
+}
+
+// endregion
+
+pub struct SuggestPlugin;
+
+impl Plugin for SuggestPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Difficulty>().add_system(suggest_system);
+    }
+}
+
+/// Replies `("suggest", "harder" | "similar" | "easier")` on every solve,
+/// for hosts picking the next puzzle of a pack, and shows it under the stars.
+#[allow(clippy::too_many_arguments)]
+fn suggest_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    board: Res<Board>,
+    stats: Res<Stats>,
+    view: Res<BoardView>,
+    hud_scale: Res<HudScale>,
+    win_size: Res<WinSize>,
+    options: Res<BoardOptions>,
+    solve_timer: Res<SolveTimer>,
+    game_textures: Res<GameTextures>,
+    send_channel: Res<WASMSendChannel>,
+    mut difficulty: ResMut<Difficulty>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut puzzle_solved_event_reader: EventReader<PuzzleSolvedEvent>,
+    text_query: Query<Entity, With<SuggestionText>>,
+) {
+    if board_loaded_event_reader.iter().last().is_some() {
+        for entity in text_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        *difficulty = Difficulty::estimate(&board, &options);
+    }
+
+    for _ in puzzle_solved_event_reader.iter() {
+        let seconds = solve_timer.elapsed(&time) - solve_timer.penalty;
+        let suggestion = difficulty.suggest(seconds, &stats);
+        send_channel
+            .tx
+            .send((String::from("suggest"), String::from(suggestion.as_str())));
+
+        let position = view.screen_to_world(Vec2::new(
+            win_size.w / 2.,
+            win_size.h - SUGGESTION_OFFSET * hud_scale.0,
+        ));
+        commands
+            .spawn(Text2dBundle {
+                text: Text::from_section(
+                    suggestion.label(),
+                    TextStyle {
+                        font: game_textures.font.clone(),
+                        font_size: SUGGESTION_FONT_SIZE * hud_scale.world(&view),
+                        color: Color::WHITE,
+                    },
+                )
+                .with_alignment(TextAlignment::CENTER),
+                transform: Transform::from_xyz(position.x, position.y, HUD_BANNER_Z),
+                ..Default::default()
+            })
+            .insert(SuggestionText);
+    }
+}
@@ -0,0 +1,275 @@
+// region:      IMPORTS
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use picross_handler::Cell;
+use std::cell::RefCell;
+use std::sync::Mutex;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+
+use crate::board::{
+    cell_to_char, char_to_cell, Board, BoardLoadedEvent, CellChangedEvent, ChangeSource,
+};
+use crate::board_commands::BoardCommand;
+use crate::options::BoardOptions;
+use crate::pencil::PencilMarks;
+use crate::start::epoch_ms;
+
+// endregion
+
+// region:      GLOBAL
+
+/// Messages from the other tabs, queued by the channel's `onmessage` outside
+/// of any system.
+static INBOX: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// A `BroadcastChannel` is a JS object and can't live in a resource.
+struct Channel {
+    channel: web_sys::BroadcastChannel,
+    _listener: Closure<dyn FnMut(web_sys::MessageEvent)>,
+}
+
+thread_local! {
+    static CHANNEL: RefCell<Option<Channel>> = RefCell::new(None);
+}
+
+// endregion
+
+// region:      CONSTANTS
+
+const CHANNEL_PREFIX: &str = "picross-w.";
+
+// endregion
+
+// region:      RESOURCES
+
+/// Keeps the same puzzle open in several tabs of one browser in step without
+/// a server, turned on by the host with the `tab_sync` option for boards that
+/// have no room. Moves and pencil marks are broadcast with the time they were
+/// made and the latest one wins a cell, so a tab overruled within the
+/// conflict window flashes the cell like a server conflict would.
+#[derive(Resource, Default)]
+pub struct TabSync {
+    /// The board id the channel is open for.
+    open: Option<String>,
+    /// When each cell was last written, here or in another tab.
+    stamps: HashMap<(usize, usize), f64>,
+    /// Pencil marks as last broadcast or received.
+    marks: HashSet<(usize, usize)>,
+}
+
+impl TabSync {
+    fn newest(&self) -> f64 {
+        self.stamps.values().copied().fold(0., f64::max)
+    }
+}
+
+// endregion
+
+pub struct TabSyncPlugin;
+
+impl Plugin for TabSyncPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TabSync>()
+            .add_system(tab_channel_system)
+            .add_system(tab_receive_system.after(tab_channel_system))
+            .add_system(tab_send_system.after(tab_receive_system));
+    }
+}
+
+fn post(message: &str) {
+    CHANNEL.with(|channel| {
+        if let Some(open) = channel.borrow().as_ref() {
+            if let Err(err) = open.channel.post_message(&JsValue::from_str(message)) {
+                warn!("Unable to post to the other tabs: {:?}", err);
+            }
+        }
+    });
+}
+
+fn close_channel() {
+    CHANNEL.with(|channel| {
+        if let Some(open) = channel.borrow_mut().take() {
+            open.channel.close();
+        }
+    });
+}
+
+fn open_channel(id: &str) -> bool {
+    let channel = match web_sys::BroadcastChannel::new(&format!("{}{}", CHANNEL_PREFIX, id)) {
+        Ok(channel) => channel,
+        Err(err) => {
+            warn!("BroadcastChannel unavailable: {:?}", err);
+            return false;
+        }
+    };
+    let listener =
+        Closure::<dyn FnMut(web_sys::MessageEvent)>::new(|event: web_sys::MessageEvent| {
+            if let (Some(message), Ok(mut inbox)) = (event.data().as_string(), INBOX.lock()) {
+                inbox.push(message);
+            }
+        });
+    channel.set_onmessage(Some(listener.as_ref().unchecked_ref()));
+    CHANNEL.with(|open| {
+        *open.borrow_mut() = Some(Channel {
+            channel,
+            _listener: listener,
+        })
+    });
+    true
+}
+
+/// One channel per puzzle id, reopened when the board or the option changes.
+/// A freshly opened tab asks the others where they are.
+fn tab_channel_system(
+    board: Res<Board>,
+    options: Res<BoardOptions>,
+    marks: Res<PencilMarks>,
+    mut tab_sync: ResMut<TabSync>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+) {
+    let loaded = board_loaded_event_reader.iter().count() > 0;
+    if !(loaded || options.is_changed()) {
+        return;
+    }
+    let wanted = match options.get_bool("tab_sync", false) && !board.id.is_empty() {
+        true => Some(board.id.clone()),
+        false => None,
+    };
+    if !loaded && wanted == tab_sync.open {
+        return;
+    }
+
+    close_channel();
+    tab_sync.stamps.clear();
+    tab_sync.marks = marks.0.clone();
+    tab_sync.open = None;
+    if let Some(id) = wanted {
+        if open_channel(&id) {
+            tab_sync.open = Some(id);
+            post("?");
+        }
+    }
+}
+
+/// Applies what the other tabs sent:
+///   `c;x;y;cell;t` a move, kept if it is newer than this tab's write
+///   `p;x;y;0|1` a pencil mark
+///   `?` a new tab asking for the board, answered with
+///   `b;t;cells` the whole board as of the sender's latest write
+fn tab_receive_system(
+    board: Res<Board>,
+    mut tab_sync: ResMut<TabSync>,
+    mut marks: ResMut<PencilMarks>,
+    mut board_command_event_writer: EventWriter<BoardCommand>,
+) {
+    let messages: Vec<String> = match INBOX.lock() {
+        Ok(mut inbox) => inbox.drain(..).collect(),
+        Err(_) => return,
+    };
+    if tab_sync.open.is_none() {
+        return;
+    }
+    let (width, height) = (board.p.get_width(), board.p.get_height());
+    let mut set = |tab_sync: &mut TabSync, x: usize, y: usize, cell: Cell, t: f64| {
+        if tab_sync.stamps.get(&(x, y)).map_or(false, |own| *own > t) {
+            return;
+        }
+        tab_sync.stamps.insert((x, y), t);
+        if board.p.get_cell(x, y) != cell {
+            board_command_event_writer.send(BoardCommand {
+                x,
+                y,
+                cell,
+                source: ChangeSource::Remote,
+            });
+        }
+    };
+
+    for message in messages {
+        let fields: Vec<&str> = message.split(';').collect();
+        match fields.as_slice() {
+            ["c", x, y, cell, t] => {
+                let parsed = (
+                    x.parse::<usize>(),
+                    y.parse::<usize>(),
+                    cell.chars().next().and_then(char_to_cell),
+                    t.parse::<f64>(),
+                );
+                if let (Ok(x), Ok(y), Some(cell), Ok(t)) = parsed {
+                    if x < width && y < height {
+                        set(&mut tab_sync, x, y, cell, t);
+                    }
+                }
+            }
+            ["p", x, y, on] => {
+                if let (Ok(x), Ok(y)) = (x.parse::<usize>(), y.parse::<usize>()) {
+                    match *on == "1" {
+                        true => marks.0.insert((x, y)),
+                        false => marks.0.remove(&(x, y)),
+                    };
+                    // received, not to be sent back
+                    tab_sync.marks = marks.0.clone();
+                }
+            }
+            ["?"] => {
+                let cells: String = (0..height)
+                    .flat_map(|y| (0..width).map(move |x| (x, y)))
+                    .map(|(x, y)| cell_to_char(board.p.get_cell(x, y)))
+                    .collect();
+                post(&format!("b;{};{}", tab_sync.newest(), cells));
+            }
+            ["b", t, cells] => {
+                let t = match t.parse::<f64>() {
+                    Ok(t) if t > tab_sync.newest() => t,
+                    _ => continue,
+                };
+                if cells.len() != width * height {
+                    continue;
+                }
+                for (i, c) in cells.chars().enumerate() {
+                    if let Some(cell) = char_to_cell(c) {
+                        set(&mut tab_sync, i % width, i / width, cell, t);
+                    }
+                }
+            }
+            _ => warn!("Invalid tab sync message: {}", message),
+        }
+    }
+}
+
+/// Broadcasts this tab's own moves and pencil marks.
+fn tab_send_system(
+    marks: Res<PencilMarks>,
+    mut tab_sync: ResMut<TabSync>,
+    mut cell_changed_event_reader: EventReader<CellChangedEvent>,
+) {
+    let open = tab_sync.open.is_some();
+    for event in cell_changed_event_reader.iter() {
+        if !open || event.source != ChangeSource::Player {
+            continue;
+        }
+        let t = epoch_ms();
+        tab_sync.stamps.insert((event.x, event.y), t);
+        post(&format!(
+            "c;{};{};{};{}",
+            event.x,
+            event.y,
+            cell_to_char(event.new),
+            t
+        ));
+    }
+
+    if !open || !marks.is_changed() || marks.0 == tab_sync.marks {
+        return;
+    }
+    for (x, y) in marks.0.difference(&tab_sync.marks) {
+        post(&format!("p;{};{};1", x, y));
+    }
+    for (x, y) in tab_sync.marks.difference(&marks.0) {
+        post(&format!("p;{};{};0", x, y));
+    }
+    tab_sync.marks = marks.0.clone();
+}
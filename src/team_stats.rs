@@ -0,0 +1,293 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+use picross_handler::Cell;
+
+use crate::board::{char_to_cell, Board, BoardLoadedEvent, CellChangedEvent};
+use crate::camera::BoardView;
+use crate::history::History;
+use crate::hud_scale::HudScale;
+use crate::layers::HUD_Z;
+use crate::lines::{column_clues, line_satisfied, row_clues};
+use crate::marks::ClueLine;
+use crate::options::BoardOptions;
+use crate::win::PuzzleSolvedEvent;
+use crate::{BridgeCommandEvent, GameTextures, WASMSendChannel, WinSize};
+
+// endregion
+
+// region:      CONSTANTS
+
+const PANEL_FONT_SIZE: f32 = 16.;
+const PANEL_MARGIN: f32 = 8.;
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct TeamStatsText;
+
+// endregion
+
+// region:      RESOURCES
+
+/// One side's part of the solve. Updates don't say which teammate made a
+/// change, so the sides are this player and everyone else.
+#[derive(Default, Clone, Copy)]
+struct Share {
+    /// Filled cells on the board whose last change was this side's.
+    filled: usize,
+    moves: usize,
+    /// Lines this side's move completed, counted again if reopened.
+    lines: usize,
+    /// Cells on the board that disagree with the `solution` option.
+    mistakes: usize,
+}
+
+/// The team panel in the bottom right corner, toggled with T. Rebuilt from
+/// the history log whenever a cell changes while it is shown.
+#[derive(Resource, Default)]
+pub struct TeamStats {
+    pub visible: bool,
+    /// Whether anyone else changed a cell this puzzle.
+    multiplayer: bool,
+    you: Share,
+    team: Share,
+}
+
+impl TeamStats {
+    /// Replays the log, crediting each cell and completed line to whoever
+    /// changed it last.
+    fn compute(&mut self, board: &Board, history: &History, solution: Option<&str>) {
+        let p = &board.p;
+        let (width, height) = history.size();
+        let mut cells = history.cells_at(0);
+        if cells.len() != width * height || (width, height) != (p.get_width(), p.get_height()) {
+            return;
+        }
+        let satisfied = |cells: &[Cell], line: ClueLine| match line {
+            ClueLine::Row(y) => {
+                line_satisfied(&row_clues(p, y), &cells[y * width..(y + 1) * width])
+            }
+            ClueLine::Column(x) => {
+                let column: Vec<Cell> = (0..height).map(|y| cells[y * width + x]).collect();
+                line_satisfied(&column_clues(p, x), &column)
+            }
+        };
+
+        let (mut you, mut team) = (Share::default(), Share::default());
+        // who last changed each cell, none for cells as loaded
+        let mut owners: Vec<Option<bool>> = vec![None; cells.len()];
+        for entry in history.entries.iter() {
+            let i = entry.y * width + entry.x;
+            if i >= cells.len() {
+                continue;
+            }
+            let share = match entry.from_player {
+                true => &mut you,
+                false => &mut team,
+            };
+            share.moves += 1;
+            let lines = [ClueLine::Row(entry.y), ClueLine::Column(entry.x)];
+            let before = lines.map(|line| satisfied(&cells, line));
+            cells[i] = entry.cell;
+            owners[i] = Some(entry.from_player);
+            for (line, was) in lines.into_iter().zip(before) {
+                if !was && satisfied(&cells, line) {
+                    share.lines += 1;
+                }
+            }
+        }
+
+        let solution: Vec<Option<Cell>> = solution
+            .map(|solution| solution.chars().map(char_to_cell).collect())
+            .unwrap_or_default();
+        for (i, (cell, owner)) in cells.iter().zip(owners).enumerate() {
+            let share = match owner {
+                Some(true) => &mut you,
+                Some(false) => &mut team,
+                None => continue,
+            };
+            if *cell == Cell::Filled {
+                share.filled += 1;
+            }
+            // a cross where the solution fills, or a fill where it doesn't
+            let wrong = match solution.get(i).copied().flatten() {
+                Some(Cell::Filled) => *cell == Cell::Crossed,
+                Some(_) => *cell == Cell::Filled,
+                None => false,
+            };
+            share.mistakes += wrong as usize;
+        }
+
+        self.multiplayer = history.entries.iter().any(|entry| !entry.from_player);
+        self.you = you;
+        self.team = team;
+    }
+
+    fn rows<'a>(&self, options: &'a BoardOptions) -> [(&'a str, Share); 2] {
+        [
+            (options.get("player_name").unwrap_or("You"), self.you),
+            (
+                options.get("teammate_name").unwrap_or("Teammate"),
+                self.team,
+            ),
+        ]
+    }
+
+    /// `{"filled":12,"players":[{"name":"You","filled":7,...},...]}`, with
+    /// `mistakes` null unless the `solution` option is known.
+    fn to_json(&self, options: &BoardOptions, checked: bool) -> String {
+        let players: Vec<String> = self
+            .rows(options)
+            .iter()
+            .map(|(name, share)| {
+                let mistakes = match checked {
+                    true => share.mistakes.to_string(),
+                    false => String::from("null"),
+                };
+                format!(
+                    "{{\"name\":\"{}\",\"filled\":{},\"share\":{:.3},\"moves\":{},\"lines\":{},\"mistakes\":{}}}",
+                    name.replace('\\', "\\\\").replace('"', "\\\""),
+                    share.filled,
+                    self.share(share),
+                    share.moves,
+                    share.lines,
+                    mistakes
+                )
+            })
+            .collect();
+        format!(
+            "{{\"filled\":{},\"players\":[{}]}}",
+            self.you.filled + self.team.filled,
+            players.join(",")
+        )
+    }
+
+    fn share(&self, share: &Share) -> f32 {
+        let total = self.you.filled + self.team.filled;
+        match total {
+            0 => 0.,
+            _ => share.filled as f32 / total as f32,
+        }
+    }
+}
+
+// endregion
+
+pub struct TeamStatsPlugin;
+
+impl Plugin for TeamStatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TeamStats>()
+            .add_system(team_stats_system)
+            .add_system(team_stats_draw_system.after(team_stats_system));
+    }
+}
+
+/// The `solution` option, when it fits the board, makes mistakes countable.
+fn solution<'a>(options: &'a BoardOptions, board: &Board) -> Option<&'a str> {
+    let cells = board.p.get_width() * board.p.get_height();
+    options
+        .get("solution")
+        .filter(|solution| solution.len() == cells)
+}
+
+/// Keeps the panel current while it is shown, and sends `("team", json)`
+/// when a puzzle others helped with is solved or on `("team", "")`.
+#[allow(clippy::too_many_arguments)]
+fn team_stats_system(
+    keys: Res<Input<KeyCode>>,
+    board: Res<Board>,
+    history: Res<History>,
+    options: Res<BoardOptions>,
+    send_channel: Res<WASMSendChannel>,
+    mut team_stats: ResMut<TeamStats>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut cell_changed_event_reader: EventReader<CellChangedEvent>,
+    mut puzzle_solved_event_reader: EventReader<PuzzleSolvedEvent>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+) {
+    if keys.just_pressed(KeyCode::T) {
+        team_stats.visible = !team_stats.visible;
+    }
+    let loaded = board_loaded_event_reader.iter().last().is_some();
+    let changed = cell_changed_event_reader.iter().last().is_some();
+    let solved = puzzle_solved_event_reader.iter().last().is_some();
+    let requested = bridge_command_event_reader
+        .iter()
+        .filter(|event| event.command == "team")
+        .count()
+        > 0;
+
+    let solution = solution(&options, &board);
+    let stale = loaded || changed || keys.just_pressed(KeyCode::T);
+    if (stale && team_stats.visible) || solved || requested {
+        team_stats.compute(&board, &history, solution);
+    }
+    if (solved && team_stats.multiplayer) || requested {
+        send_channel.tx.send((
+            String::from("team"),
+            team_stats.to_json(&options, solution.is_some()),
+        ));
+    }
+}
+
+fn team_stats_draw_system(
+    mut commands: Commands,
+    board: Res<Board>,
+    view: Res<BoardView>,
+    win_size: Res<WinSize>,
+    options: Res<BoardOptions>,
+    hud_scale: Res<HudScale>,
+    team_stats: Res<TeamStats>,
+    game_textures: Res<GameTextures>,
+    text_query: Query<Entity, With<TeamStatsText>>,
+) {
+    let resized = view.is_changed() || win_size.is_changed() || hud_scale.is_changed();
+    if !(team_stats.is_changed() || resized || options.is_changed()) {
+        return;
+    }
+    for entity in text_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    if !team_stats.visible {
+        return;
+    }
+
+    let checked = solution(&options, &board).is_some();
+    let mut lines = vec![format!(
+        "Team  {} filled",
+        team_stats.you.filled + team_stats.team.filled
+    )];
+    for (name, share) in team_stats.rows(&options) {
+        let mut line = format!(
+            "{}  {}  {:.0}%  {} lines",
+            name,
+            share.filled,
+            team_stats.share(&share) * 100.,
+            share.lines
+        );
+        if checked {
+            line.push_str(&format!("  {} mistakes", share.mistakes));
+        }
+        lines.push(line);
+    }
+
+    let style = TextStyle {
+        font: game_textures.font.clone(),
+        font_size: PANEL_FONT_SIZE * hud_scale.world(&view),
+        color: Color::WHITE,
+    };
+    let margin = PANEL_MARGIN * hud_scale.0;
+    let corner = view.screen_to_world(Vec2::new(win_size.w - margin, margin));
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(lines.join("\n"), style)
+                .with_alignment(TextAlignment::BOTTOM_RIGHT),
+            transform: Transform::from_xyz(corner.x, corner.y, HUD_Z),
+            ..Default::default()
+        })
+        .insert(TeamStatsText);
+}
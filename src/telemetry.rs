@@ -0,0 +1,121 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+
+use crate::board::{Board, BoardLoadedEvent, CellChangedEvent, ChangeSource};
+use crate::timer::SolveTimer;
+use crate::win::PuzzleSolvedEvent;
+use crate::{BridgeCommandEvent, WASMSendChannel};
+
+// endregion
+
+// region:      RESOURCES
+
+/// Analytics events are only sent once the host opts in with
+/// `("telemetry", "1")`, and `("telemetry", "0")` turns them off again.
+#[derive(Resource, Default)]
+pub struct Telemetry {
+    pub enabled: bool,
+    first_move_ms: Option<u64>,
+    hints_used: usize,
+}
+
+// endregion
+
+// region:      EVENTS
+
+/// Sent by anything handing the player a hint.
+pub struct HintUsedEvent;
+
+// endregion
+
+pub struct TelemetryPlugin;
+
+impl Plugin for TelemetryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Telemetry>()
+            .add_event::<HintUsedEvent>()
+            .add_system(telemetry_command_system)
+            .add_system(telemetry_event_system);
+    }
+}
+
+fn telemetry_command_system(
+    mut telemetry: ResMut<Telemetry>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+) {
+    for event in bridge_command_event_reader.iter() {
+        if event.command == "telemetry" {
+            telemetry.enabled = event.data == "1";
+        }
+    }
+}
+
+fn telemetry_event_system(
+    time: Res<Time>,
+    board: Res<Board>,
+    solve_timer: Res<SolveTimer>,
+    send_channel: Res<WASMSendChannel>,
+    mut telemetry: ResMut<Telemetry>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut cell_changed_event_reader: EventReader<CellChangedEvent>,
+    mut hint_used_event_reader: EventReader<HintUsedEvent>,
+    mut puzzle_solved_event_reader: EventReader<PuzzleSolvedEvent>,
+) {
+    let enabled = telemetry.enabled;
+    let emit = |event: &str, fields: String| {
+        if enabled {
+            send_channel.tx.send((
+                String::from("t"),
+                format!("{{\"event\":\"{}\"{}}}", event, fields),
+            ));
+        }
+    };
+
+    for _ in board_loaded_event_reader.iter() {
+        telemetry.first_move_ms = None;
+        telemetry.hints_used = 0;
+        emit(
+            "puzzle_loaded",
+            format!(
+                ",\"width\":{},\"height\":{}",
+                board.p.get_width(),
+                board.p.get_height()
+            ),
+        );
+    }
+
+    for event in cell_changed_event_reader.iter() {
+        if event.source == ChangeSource::Player && telemetry.first_move_ms.is_none() {
+            let elapsed_ms = solve_timer.elapsed_ms(&time);
+            telemetry.first_move_ms = Some(elapsed_ms);
+            emit("first_move", format!(",\"elapsed_ms\":{}", elapsed_ms));
+        }
+    }
+
+    for _ in hint_used_event_reader.iter() {
+        telemetry.hints_used += 1;
+        emit(
+            "hint_used",
+            format!(
+                ",\"elapsed_ms\":{},\"hints_used\":{}",
+                solve_timer.elapsed_ms(&time),
+                telemetry.hints_used
+            ),
+        );
+    }
+
+    for _ in puzzle_solved_event_reader.iter() {
+        let elapsed_ms = solve_timer.elapsed_ms(&time);
+        let first_move_ms = telemetry.first_move_ms.unwrap_or(elapsed_ms);
+        emit(
+            "solved",
+            format!(
+                ",\"elapsed_ms\":{},\"solving_ms\":{},\"hints_used\":{}",
+                elapsed_ms,
+                elapsed_ms.saturating_sub(first_move_ms),
+                telemetry.hints_used
+            ),
+        );
+    }
+}
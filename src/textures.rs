@@ -0,0 +1,452 @@
+// region:      IMPORTS
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use image::{imageops, imageops::FilterType, RgbaImage};
+
+use crate::board::Board;
+use crate::camera::BoardView;
+use crate::options::BoardOptions;
+use crate::{GameTextures, TILE_SIZE};
+
+// endregion
+
+// region:      CONSTANTS
+
+const BORDER_PIXELS: f32 = 5.;
+const CROSS_INSET: f32 = 10.; // from the tile edge to the end of a stroke
+const CROSS_WIDTH: f32 = 13.;
+// the heart curve spans about 2.3 units, its middle sits 0.125 above 0
+const HEART_SPAN: f32 = 2.3;
+const HEART_RISE: f32 = 0.125;
+
+const BORDER_COLOR: Color = Color::rgb(0.514, 0.514, 0.514);
+const EMPTY_COLOR: Color = Color::WHITE;
+const FILLED_COLOR: Color = Color::rgb(0.118, 0.118, 0.118);
+const CROSS_COLOR: Color = Color::rgb(0.514, 0.514, 0.514);
+const CLUE_COLOR: Color = Color::rgb(0.863, 0.863, 0.863);
+
+const PNG_PATHS: [&str; 4] = [
+    "tiles/tile_empty.png",
+    "tiles/tile_filled.png",
+    "tiles/tile_crossed.png",
+    "tiles/tile_clue.png",
+];
+
+// texture sizes to pick from, the smallest one covering a tile on screen
+const TILE_RESOLUTIONS: [u32; 5] = [25, 50, 100, 200, 400];
+
+// endregion
+
+// region:      RESOURCES
+
+#[derive(Clone, Copy)]
+pub enum TileKind {
+    Empty,
+    Filled,
+    Crossed,
+    Clue,
+}
+
+const TILE_KINDS: [TileKind; 4] = [
+    TileKind::Empty,
+    TileKind::Filled,
+    TileKind::Crossed,
+    TileKind::Clue,
+];
+
+/// Colors baked into the generated tiles, themed with the `tile_border_color`,
+/// `tile_empty_color`, `tile_filled_color`, `tile_cross_color` and
+/// `tile_clue_color` options.
+#[derive(Clone, Copy, PartialEq)]
+pub struct TileColors {
+    pub border: Color,
+    pub empty: Color,
+    pub filled: Color,
+    pub cross: Color,
+    pub clue: Color,
+}
+
+impl Default for TileColors {
+    fn default() -> Self {
+        Self {
+            border: BORDER_COLOR,
+            empty: EMPTY_COLOR,
+            filled: FILLED_COLOR,
+            cross: CROSS_COLOR,
+            clue: CLUE_COLOR,
+        }
+    }
+}
+
+impl TileColors {
+    fn from_options(options: &BoardOptions) -> Self {
+        let defaults = Self::default();
+        Self {
+            border: options.get_color("tile_border_color", defaults.border),
+            empty: options.get_color("tile_empty_color", defaults.empty),
+            filled: options.get_color("tile_filled_color", defaults.filled),
+            cross: options.get_color("tile_cross_color", defaults.cross),
+            clue: options.get_color("tile_clue_color", defaults.clue),
+        }
+    }
+}
+
+/// Shape of the filled tiles, from the `cell_shape` option a join can carry
+/// for a themed puzzle: `square`, `circle`, `heart`, or the path of a PNG in
+/// the assets folder. Crossed and empty tiles always stay standard.
+#[derive(Clone, PartialEq)]
+pub enum CellShape {
+    Square,
+    Circle,
+    Heart,
+    Png(String),
+}
+
+impl CellShape {
+    fn from_options(options: &BoardOptions) -> Self {
+        match options.get("cell_shape") {
+            None | Some("square") => CellShape::Square,
+            Some("circle") => CellShape::Circle,
+            Some("heart") => CellShape::Heart,
+            Some(path) if path.ends_with(".png") => CellShape::Png(path.to_string()),
+            Some(other) => {
+                warn!("Unknown cell_shape: {}", other);
+                CellShape::Square
+            }
+        }
+    }
+}
+
+/// The PNGs under `assets/tiles` are only fetched when the `tile_textures=png`
+/// option asks for them. Once loaded they are copied over the generated
+/// images, so spawned tiles keep their handles either way.
+#[derive(Resource, Default)]
+pub struct PngTiles {
+    handles: Option<[Handle<Image>; 4]>,
+    applied: bool,
+}
+
+/// A `cell_shape` PNG, copied over the filled tile the same way once it has
+/// loaded, and again after every rebake.
+#[derive(Resource, Default)]
+pub struct ShapeTile {
+    path: Option<String>,
+    handle: Option<Handle<Image>>,
+    applied: bool,
+}
+
+/// Side of the tile textures in texels. Sprites are sized in world units, so
+/// only the sharpness changes: small cells on huge boards get small textures
+/// instead of shimmering from a 100px one, and zoomed in cells get big ones.
+#[derive(Resource)]
+pub struct TileResolution(pub u32);
+
+impl Default for TileResolution {
+    fn default() -> Self {
+        Self(TILE_SIZE.0 as u32)
+    }
+}
+
+// endregion
+
+pub struct TexturePlugin;
+
+impl Plugin for TexturePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PngTiles>()
+            .init_resource::<ShapeTile>()
+            .init_resource::<TileResolution>()
+            .add_system(tile_resolution_system)
+            .add_system(tile_theme_system.after(tile_resolution_system))
+            .add_system(png_tiles_system.after(tile_theme_system))
+            .add_system(shape_tile_system.after(png_tiles_system));
+    }
+}
+
+/// One of the four tile textures, `size` texels square. The strokes keep
+/// their proportions from the 100px design, `shape` only changes the fill.
+pub fn tile_image(kind: TileKind, colors: &TileColors, shape: &CellShape, size: u32) -> Image {
+    let extent = size as f32;
+    let k = extent / TILE_SIZE.0;
+    let (border, inset, width) = (BORDER_PIXELS * k, CROSS_INSET * k, CROSS_WIDTH * k);
+    let start = Vec2::splat(inset);
+    let end = Vec2::splat(extent - inset);
+
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let p = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+            let edge = p.x.min(p.y).min(extent - p.x).min(extent - p.y);
+            let fill = match kind {
+                TileKind::Empty => colors.empty,
+                TileKind::Filled => mix(
+                    colors.empty,
+                    colors.filled,
+                    shape_coverage(shape, p, extent, inset),
+                ),
+                TileKind::Clue => colors.clue,
+                TileKind::Crossed => {
+                    let flipped = Vec2::new(p.x, extent - p.y);
+                    let distance =
+                        segment_distance(p, start, end).min(segment_distance(flipped, start, end));
+                    // a pixel of antialiasing along the strokes
+                    let coverage = (width / 2. - distance + 0.5).clamp(0., 1.);
+                    mix(colors.empty, colors.cross, coverage)
+                }
+            };
+            // whole texel borders at 100px, blended when scaled to a fraction
+            let coverage = (border - edge + 0.5).clamp(0., 1.);
+            data.extend_from_slice(&rgba_u8(mix(fill, colors.border, coverage)));
+        }
+    }
+
+    Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+/// Generated tiles, added to `images` in the `GameTextures` field order.
+pub fn generate_tile_textures(images: &mut Assets<Image>) -> [Handle<Image>; 4] {
+    let colors = TileColors::default();
+    let size = TileResolution::default().0;
+    TILE_KINDS.map(|kind| images.add(tile_image(kind, &colors, &CellShape::Square, size)))
+}
+
+/// How much of the texel around `p` the fill covers. Shapes keep the cross
+/// inset from the tile edge, a PNG shape is drawn square until it loads.
+fn shape_coverage(shape: &CellShape, p: Vec2, extent: f32, inset: f32) -> f32 {
+    match shape {
+        CellShape::Square | CellShape::Png(_) => 1.,
+        CellShape::Circle => {
+            let radius = extent / 2. - inset;
+            (radius - p.distance(Vec2::splat(extent / 2.)) + 0.5).clamp(0., 1.)
+        }
+        CellShape::Heart => {
+            // no handy distance to the curve, the texel is sampled 4x4 instead
+            let scale = HEART_SPAN / (extent - 2. * inset);
+            let inside = (0..16)
+                .filter(|i| {
+                    let offset = Vec2::new((i % 4) as f32 + 0.5, (i / 4) as f32 + 0.5) / 4.;
+                    let q = p + offset - 0.5;
+                    // texel rows run top down, the curve's y up
+                    let (u, v) = (
+                        (q.x - extent / 2.) * scale,
+                        (extent / 2. - q.y) * scale + HEART_RISE,
+                    );
+                    (u * u + v * v - 1.).powi(3) - u * u * v.powi(3) <= 0.
+                })
+                .count();
+            inside as f32 / 16.
+        }
+    }
+}
+
+/// Smallest of `TILE_RESOLUTIONS` with at least a texel per physical pixel.
+fn resolution_for(pixels: f32) -> u32 {
+    TILE_RESOLUTIONS
+        .into_iter()
+        .find(|size| *size as f32 >= pixels)
+        .unwrap_or(TILE_RESOLUTIONS[TILE_RESOLUTIONS.len() - 1])
+}
+
+/// A loaded PNG tile shrunk to `size`, the GPU has no mipmaps to do it.
+/// Bigger sizes get the PNG as it is, it can't be made sharper.
+fn fit_png(image: Image, size: u32) -> Image {
+    let extent = image.texture_descriptor.size;
+    if image.texture_descriptor.format != TextureFormat::Rgba8UnormSrgb
+        || extent.width <= size
+        || extent.height <= size
+    {
+        return image;
+    }
+    let source = match RgbaImage::from_raw(extent.width, extent.height, image.data.clone()) {
+        Some(source) => source,
+        None => return image,
+    };
+    let resized = imageops::resize(&source, size, size, FilterType::Triangle);
+    Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        resized.into_raw(),
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+fn segment_distance(p: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = b - a;
+    let t = ((p - a).dot(ab) / ab.length_squared()).clamp(0., 1.);
+    p.distance(a + ab * t)
+}
+
+fn mix(a: Color, b: Color, t: f32) -> Color {
+    let [ar, ag, ab, aa] = a.as_rgba_f32();
+    let [br, bg, bb, ba] = b.as_rgba_f32();
+    Color::rgba(
+        ar + (br - ar) * t,
+        ag + (bg - ag) * t,
+        ab + (bb - ab) * t,
+        aa + (ba - aa) * t,
+    )
+}
+
+fn rgba_u8(color: Color) -> [u8; 4] {
+    color
+        .as_rgba_f32()
+        .map(|channel| (channel.clamp(0., 1.) * 255.).round() as u8)
+}
+
+fn handles(game_textures: &GameTextures) -> [&Handle<Image>; 4] {
+    [
+        &game_textures.tile_empty,
+        &game_textures.tile_filled,
+        &game_textures.tile_crossed,
+        &game_textures.tile_clue,
+    ]
+}
+
+/// Picks the tile resolution from how big a tile is on screen, so the
+/// textures are only rebaked when zooming or resizing crosses a threshold.
+fn tile_resolution_system(
+    board: Res<Board>,
+    view: Res<BoardView>,
+    windows: Res<Windows>,
+    mut resolution: ResMut<TileResolution>,
+) {
+    if !(board.is_changed() || view.is_changed() || windows.is_changed()) {
+        return;
+    }
+    let scale_factor = windows
+        .get_primary()
+        .map_or(1., |window| window.scale_factor() as f32);
+    let pixels = board.pixels_per_tile / view.scale * scale_factor;
+    if !pixels.is_finite() || pixels <= 0. {
+        return;
+    }
+    let size = resolution_for(pixels);
+    if resolution.0 != size {
+        resolution.0 = size;
+    }
+}
+
+/// Rebakes the generated tiles when the theme options or the resolution
+/// change, or starts fetching the PNGs when they are asked for.
+fn tile_theme_system(
+    asset_server: Res<AssetServer>,
+    options: Res<BoardOptions>,
+    resolution: Res<TileResolution>,
+    game_textures: Res<GameTextures>,
+    mut images: ResMut<Assets<Image>>,
+    mut png_tiles: ResMut<PngTiles>,
+    mut shape_tile: ResMut<ShapeTile>,
+    mut last_theme: Local<Option<(TileColors, CellShape)>>,
+) {
+    if !(options.is_changed() || resolution.is_changed()) {
+        return;
+    }
+
+    let shape = CellShape::from_options(&options);
+    let path = match &shape {
+        CellShape::Png(path) => Some(path.clone()),
+        _ => None,
+    };
+    if path != shape_tile.path {
+        shape_tile.handle = path.as_ref().map(|path| asset_server.load(path.as_str()));
+        shape_tile.path = path;
+        shape_tile.applied = false;
+    }
+
+    if options.get("tile_textures") == Some("png") {
+        if png_tiles.handles.is_none() {
+            png_tiles.handles = Some(PNG_PATHS.map(|path| asset_server.load(path)));
+        }
+        // copied again at the new size
+        png_tiles.applied = false;
+        shape_tile.applied = false;
+        return;
+    }
+
+    let colors = TileColors::from_options(&options);
+    // coming back from the PNGs always rebakes
+    let from_png = png_tiles.handles.take().is_some();
+    let theme = (colors, shape);
+    if !from_png && !resolution.is_changed() && last_theme.as_ref() == Some(&theme) {
+        return;
+    }
+    for (kind, handle) in TILE_KINDS.iter().zip(handles(&game_textures)) {
+        if let Some(image) = images.get_mut(handle) {
+            *image = tile_image(*kind, &colors, &theme.1, resolution.0);
+        }
+    }
+    *last_theme = Some(theme);
+    shape_tile.applied = false;
+}
+
+/// Copies the PNGs over the generated images once they have all loaded.
+fn png_tiles_system(
+    game_textures: Res<GameTextures>,
+    resolution: Res<TileResolution>,
+    mut images: ResMut<Assets<Image>>,
+    mut png_tiles: ResMut<PngTiles>,
+) {
+    if png_tiles.applied {
+        return;
+    }
+    let loaded: Vec<Image> = match &png_tiles.handles {
+        Some(pngs) => pngs
+            .iter()
+            .filter_map(|handle| images.get(handle).cloned())
+            .collect(),
+        None => return,
+    };
+    if loaded.len() < PNG_PATHS.len() {
+        return;
+    }
+
+    png_tiles.applied = true;
+    for (image, handle) in loaded.into_iter().zip(handles(&game_textures)) {
+        if let Some(target) = images.get_mut(handle) {
+            *target = fit_png(image, resolution.0);
+        }
+    }
+}
+
+/// Copies a `cell_shape` PNG over the filled tile, after the PNG tiles if
+/// those are on.
+fn shape_tile_system(
+    game_textures: Res<GameTextures>,
+    resolution: Res<TileResolution>,
+    png_tiles: Res<PngTiles>,
+    mut images: ResMut<Assets<Image>>,
+    mut shape_tile: ResMut<ShapeTile>,
+) {
+    if shape_tile.applied || (png_tiles.handles.is_some() && !png_tiles.applied) {
+        return;
+    }
+    let image = match shape_tile
+        .handle
+        .as_ref()
+        .and_then(|handle| images.get(handle))
+    {
+        Some(image) => image.clone(),
+        None => return,
+    };
+
+    shape_tile.applied = true;
+    if let Some(target) = images.get_mut(&game_textures.tile_filled) {
+        *target = fit_png(image, resolution.0);
+    }
+}
@@ -0,0 +1,135 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+use image::{ImageOutputFormat, RgbaImage};
+use picross_handler::Cell;
+use std::io::Cursor;
+
+use crate::bridge::reply_error;
+use crate::history::History;
+use crate::options::BoardOptions;
+use crate::win::{PuzzleSolvedEvent, Solved};
+use crate::{BridgeCommandEvent, WASMSendChannel};
+
+// endregion
+
+// region:      CONSTANTS
+
+const DEFAULT_FRAMES: usize = 24;
+const MAX_FRAMES: usize = 120;
+const CELL_PIXELS: u32 = 8;
+const GRID_PIXELS: u32 = 1;
+
+const FILLED_COLOR: [u8; 4] = [30, 30, 30, 255];
+const EMPTY_COLOR: [u8; 4] = [245, 245, 245, 255];
+const CROSSED_COLOR: [u8; 4] = [200, 200, 200, 255];
+const GRID_COLOR: [u8; 4] = [150, 150, 150, 255];
+
+// endregion
+
+pub struct TimelapsePlugin;
+
+impl Plugin for TimelapsePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(timelapse_system);
+    }
+}
+
+/// Sends `("timelapse", json)` with a list of base64 PNG frames of the solve,
+/// on `("timelapse", frames)` once the puzzle is solved, or right when it is
+/// solved if the `timelapse` option asks for a frame count. Sites assemble
+/// the frames into a GIF themselves.
+fn timelapse_system(
+    history: Res<History>,
+    solved: Res<Solved>,
+    options: Res<BoardOptions>,
+    send_channel: Res<WASMSendChannel>,
+    mut puzzle_solved_event_reader: EventReader<PuzzleSolvedEvent>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+) {
+    let mut requested = Vec::new();
+    if puzzle_solved_event_reader.iter().last().is_some() {
+        let frames = options.get_f32("timelapse", 0.) as usize;
+        if frames > 0 {
+            requested.push(frames);
+        }
+    }
+    for event in bridge_command_event_reader.iter() {
+        if event.command != "timelapse" {
+            continue;
+        }
+        if !solved.0 {
+            reply_error(&send_channel, String::from("timelapse: puzzle not solved"));
+            continue;
+        }
+        match event.data.as_str() {
+            "" => requested.push(DEFAULT_FRAMES),
+            data => match data.parse::<usize>() {
+                Ok(frames) if frames > 0 => requested.push(frames),
+                _ => reply_error(
+                    &send_channel,
+                    format!("timelapse: invalid frame count {}", data),
+                ),
+            },
+        }
+    }
+
+    for frames in requested {
+        let frames: Vec<String> = timelapse_frames(&history, frames)
+            .iter()
+            .map(|frame| format!("\"{}\"", frame))
+            .collect();
+        send_channel
+            .tx
+            .send((String::from("timelapse"), format!("[{}]", frames.join(","))));
+    }
+}
+
+/// `count` snapshots evenly spaced over the solve's duration, the first one
+/// the board as it was loaded and the last one the solved board.
+pub fn timelapse_frames(history: &History, count: usize) -> Vec<String> {
+    let (width, height) = history.size();
+    let entries = &history.entries;
+    let count = count.clamp(1, MAX_FRAMES);
+    let duration = entries.last().map_or(0., |entry| entry.t);
+    let mut next = 0;
+    let mut frames = Vec::with_capacity(count);
+    for i in 0..count {
+        let t = match count {
+            1 => duration,
+            _ => duration * i as f64 / (count - 1) as f64,
+        };
+        // the last frame takes every entry, even ones stamped at the same time
+        while next < entries.len() && (entries[next].t <= t || i == count - 1) {
+            next += 1;
+        }
+        frames.push(frame_png(&history.cells_at(next), width, height));
+    }
+    frames
+}
+
+/// One frame as base64 PNG, just the grid without clues.
+fn frame_png(cells: &[Cell], width: usize, height: usize) -> String {
+    let step = CELL_PIXELS + GRID_PIXELS;
+    let image = RgbaImage::from_fn(
+        width as u32 * step + GRID_PIXELS,
+        height as u32 * step + GRID_PIXELS,
+        |px, py| {
+            if px % step < GRID_PIXELS || py % step < GRID_PIXELS {
+                return image::Rgba(GRID_COLOR);
+            }
+            // pixel rows run top down, board rows bottom up
+            let (x, y) = ((px / step) as usize, height - 1 - (py / step) as usize);
+            image::Rgba(match cells[y * width + x] {
+                Cell::Filled => FILLED_COLOR,
+                Cell::Crossed => CROSSED_COLOR,
+                Cell::Empty => EMPTY_COLOR,
+            })
+        },
+    );
+    let mut png = Vec::new();
+    if let Err(err) = image.write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png) {
+        warn!("timelapse frame failed to encode: {}", err);
+    }
+    base64::encode(png)
+}
@@ -0,0 +1,60 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+
+use crate::board::BoardLoadedEvent;
+use crate::win::PuzzleSolvedEvent;
+
+// endregion
+
+// region:      RESOURCES
+
+/// Solve clock, started when a puzzle loads and stopped when it is solved.
+#[derive(Resource, Default)]
+pub struct SolveTimer {
+    pub started: f64,
+    pub finished: Option<f64>,
+    /// Seconds added for mistakes in race mode.
+    pub penalty: f64,
+}
+
+impl SolveTimer {
+    /// Seconds spent on the current puzzle, penalties included.
+    pub fn elapsed(&self, time: &Time) -> f64 {
+        self.finished.unwrap_or(time.elapsed_seconds_f64()) - self.started + self.penalty
+    }
+
+    pub fn elapsed_ms(&self, time: &Time) -> u64 {
+        (self.elapsed(time) * 1000.).max(0.) as u64
+    }
+}
+
+// endregion
+
+pub struct TimerPlugin;
+
+impl Plugin for TimerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SolveTimer>()
+            .add_system(solve_timer_system);
+    }
+}
+
+fn solve_timer_system(
+    time: Res<Time>,
+    mut solve_timer: ResMut<SolveTimer>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut puzzle_solved_event_reader: EventReader<PuzzleSolvedEvent>,
+) {
+    for _ in board_loaded_event_reader.iter() {
+        solve_timer.started = time.elapsed_seconds_f64();
+        solve_timer.finished = None;
+        solve_timer.penalty = 0.;
+    }
+
+    for _ in puzzle_solved_event_reader.iter() {
+        if solve_timer.finished.is_none() {
+            solve_timer.finished = Some(time.elapsed_seconds_f64());
+        }
+    }
+}
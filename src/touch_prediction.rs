@@ -0,0 +1,108 @@
+// region:      IMPORTS
+
+use bevy::{input::touch::Touches, prelude::*};
+
+use crate::board::{Board, CurrentAction, InputEvent, LineLock};
+use crate::camera::BoardView;
+use crate::screens::Screen;
+use crate::settings::Settings;
+
+// endregion
+
+// region:      CONSTANTS
+
+/// Weight of the newest frame in the smoothed stroke velocity.
+const VELOCITY_SMOOTHING: f32 = 0.5;
+
+// endregion
+
+// region:      RESOURCES
+
+/// Velocity of the touch stroke in progress, in screen units per second.
+#[derive(Resource, Default)]
+pub struct TouchStroke {
+    last: Option<Vec2>,
+    velocity: Vec2,
+}
+
+// endregion
+
+pub struct TouchPredictionPlugin;
+
+impl Plugin for TouchPredictionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TouchStroke>()
+            .add_system(touch_prediction_system);
+    }
+}
+
+/// Browsers deliver touch moves late enough that a fast stroke paints behind
+/// the finger. While a touch drags, this also paints the cell the stroke is
+/// heading into `touch_prediction_ms` from now. Strokes are straight lines
+/// on a picross board, so only the stroke's main axis is extrapolated.
+fn touch_prediction_system(
+    time: Res<Time>,
+    touches: Res<Touches>,
+    buttons: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    board: Res<Board>,
+    view: Res<BoardView>,
+    settings: Res<Settings>,
+    screen: Res<State<Screen>>,
+    current_action: Res<CurrentAction>,
+    line_lock: Res<LineLock>,
+    mut stroke: ResMut<TouchStroke>,
+    mut input_event_writer: EventWriter<InputEvent>,
+) {
+    let cursor = windows.get_primary().and_then(|w| w.cursor_position());
+    let dragging = touches.iter().next().is_some()
+        && buttons.pressed(MouseButton::Left)
+        && *screen.current() == Screen::Board;
+    let cursor = match cursor.filter(|_| dragging && settings.touch_prediction_ms > 0.) {
+        Some(cursor) => cursor,
+        None => {
+            *stroke = TouchStroke::default();
+            return;
+        }
+    };
+
+    let last = stroke.last.replace(cursor);
+    let delta = time.delta_seconds();
+    let last = match last.filter(|_| delta > 0.) {
+        Some(last) => last,
+        None => return,
+    };
+    let velocity = (cursor - last) / delta;
+    stroke.velocity = stroke.velocity.lerp(velocity, VELOCITY_SMOOTHING);
+
+    let mut ahead = stroke.velocity * settings.touch_prediction_ms / 1000.;
+    if ahead.x.abs() >= ahead.y.abs() {
+        ahead.y = 0.;
+    } else {
+        ahead.x = 0.;
+    }
+
+    let to_tile = |screen: Vec2| {
+        let pos = (view.screen_to_world(screen) - Vec2::new(board.origin.0, board.origin.1))
+            / board.pixels_per_tile;
+        (pos.x.floor(), pos.y.floor())
+    };
+    // a locked drag is painted, and so predicted, along its line
+    let (x, y) = to_tile(cursor);
+    let (x, y) = line_lock.apply(x, y);
+    let (ahead_x, ahead_y) = to_tile(cursor + ahead);
+    let (ahead_x, ahead_y) = line_lock.apply(ahead_x, ahead_y);
+    // only strokes through the grid, and only once they reach a new cell
+    if (ahead_x, ahead_y) == (x, y)
+        || board.tile_to_cell(x, y).is_none()
+        || board.tile_to_cell(ahead_x, ahead_y).is_none()
+    {
+        return;
+    }
+    input_event_writer.send(InputEvent {
+        x: ahead_x,
+        y: ahead_y,
+        action: current_action.0,
+        from_player: true,
+    });
+}
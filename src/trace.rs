@@ -0,0 +1,182 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use wasm_bindgen::JsValue;
+
+use crate::bridge::reply_error;
+use crate::options::BoardOptions;
+use crate::start::epoch_ms;
+use crate::{send_wasm, BridgeCommandEvent, WASMSendChannel};
+
+// endregion
+
+// region:      GLOBAL
+
+/// Every bridge message in either direction, oldest first. Filled from the
+/// wasm exports, outside of any system.
+pub static mut GLOBAL_TRACE: Option<Mutex<VecDeque<TraceEntry>>> = None;
+
+// endregion
+
+// region:      CONSTANTS
+
+const TRACE_CAPACITY: usize = 1000;
+
+// endregion
+
+// region:      RESOURCES
+
+pub struct TraceEntry {
+    pub t: f64,
+    pub incoming: bool,
+    pub command: String,
+    pub data: String,
+}
+
+/// Incoming messages of an imported trace, played back with their original
+/// spacing.
+#[derive(Resource, Default)]
+pub struct Replay {
+    queue: VecDeque<TraceEntry>,
+    /// When the replay started, and when the first replayed message was traced.
+    started: f64,
+    origin: f64,
+}
+
+// endregion
+
+pub struct TracePlugin;
+
+impl Plugin for TracePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Replay>()
+            .add_system(replay_command_system)
+            .add_system(replay_system.after(replay_command_system));
+    }
+}
+
+pub fn init() {
+    unsafe {
+        GLOBAL_TRACE = Some(Mutex::new(VecDeque::with_capacity(TRACE_CAPACITY)));
+    }
+}
+
+pub fn record(incoming: bool, command: &str, data: &str) {
+    let trace = unsafe { GLOBAL_TRACE.as_ref() };
+    if let Some(mut trace) = trace.and_then(|trace| trace.lock().ok()) {
+        if trace.len() >= TRACE_CAPACITY {
+            trace.pop_front();
+        }
+        trace.push_back(TraceEntry {
+            t: epoch_ms(),
+            incoming,
+            command: command.to_string(),
+            data: data.to_string(),
+        });
+    }
+}
+
+/// The trace as `[{"t":ms,"dir":"in"|"out","cmd":..,"data":..}]`.
+pub fn to_json() -> String {
+    let trace = unsafe { GLOBAL_TRACE.as_ref() };
+    let entries: Vec<String> = match trace.and_then(|trace| trace.lock().ok()) {
+        Some(trace) => trace
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{{\"t\":{},\"dir\":\"{}\",\"cmd\":{},\"data\":{}}}",
+                    entry.t,
+                    if entry.incoming { "in" } else { "out" },
+                    json_string(&entry.command),
+                    json_string(&entry.data)
+                )
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+    format!("[{}]", entries.join(","))
+}
+
+pub fn json_string(value: &str) -> String {
+    js_sys::JSON::stringify(&JsValue::from_str(value))
+        .map(String::from)
+        .unwrap_or_else(|_| String::from("\"\""))
+}
+
+/// Incoming messages of a dumped trace, from its first join on so the
+/// replay starts from a fresh board.
+fn parse_trace(data: &str) -> Result<VecDeque<TraceEntry>, String> {
+    let json = js_sys::JSON::parse(data)
+        .ok()
+        .filter(|json| js_sys::Array::is_array(json))
+        .ok_or_else(|| String::from("replay: invalid trace json"))?;
+    let field =
+        |entry: &JsValue, key: &str| js_sys::Reflect::get(entry, &JsValue::from_str(key)).ok();
+
+    let mut queue = VecDeque::new();
+    for entry in js_sys::Array::from(&json).iter() {
+        let text = |key: &str| field(&entry, key).and_then(|value| value.as_string());
+        if text("dir").as_deref() != Some("in") {
+            continue;
+        }
+        let (command, data) = match (text("cmd"), text("data")) {
+            (Some(command), Some(data)) => (command, data),
+            _ => return Err(String::from("replay: trace entry without cmd or data")),
+        };
+        // a replay inside a trace would start over forever
+        if command == "replay" || (queue.is_empty() && command != "j") {
+            continue;
+        }
+        queue.push_back(TraceEntry {
+            t: field(&entry, "t")
+                .and_then(|value| value.as_f64())
+                .unwrap_or(0.),
+            incoming: true,
+            command,
+            data,
+        });
+    }
+    if queue.is_empty() {
+        return Err(String::from("replay: trace has no join"));
+    }
+    Ok(queue)
+}
+
+fn replay_command_system(
+    send_channel: Res<WASMSendChannel>,
+    mut options: ResMut<BoardOptions>,
+    mut replay: ResMut<Replay>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+) {
+    for event in bridge_command_event_reader.iter() {
+        if event.command != "replay" {
+            continue;
+        }
+        match parse_trace(&event.data) {
+            Ok(queue) => {
+                // options from the session being replaced would leak into the replay
+                options.0.clear();
+                replay.origin = queue.front().map_or(0., |entry| entry.t);
+                replay.queue = queue;
+                replay.started = epoch_ms();
+            }
+            Err(err) => reply_error(&send_channel, err),
+        }
+    }
+}
+
+/// Feeds due messages back in as if the host had sent them, so the replay
+/// shows up in the trace like the original session did.
+fn replay_system(mut replay: ResMut<Replay>) {
+    if replay.queue.is_empty() {
+        return;
+    }
+    let due = replay.origin + epoch_ms() - replay.started;
+    while replay.queue.front().map_or(false, |entry| entry.t <= due) {
+        if let Some(entry) = replay.queue.pop_front() {
+            send_wasm(&entry.command, &entry.data);
+        }
+    }
+}
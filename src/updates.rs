@@ -0,0 +1,321 @@
+// region:      IMPORTS
+
+use bevy::{prelude::*, sprite::Anchor, utils::Instant};
+use std::collections::VecDeque;
+
+use picross_handler::Cell;
+
+use crate::board::{
+    cell_texture, char_to_cell, Board, BoardLoadedEvent, CellChangedEvent, ChangeSource, Loading,
+    Tile, TileIndex,
+};
+use crate::board_commands::BoardCommand;
+use crate::bridge::reply_error;
+use crate::layers::EFFECT_Z;
+use crate::settings::Settings;
+use crate::{BoardUpdateEvent, GameTextures, WASMSendChannel};
+
+// endregion
+
+// region:      CONSTANTS
+
+const VISUAL_BUDGET_MS: f64 = 2.;
+const VISUAL_BATCH: usize = 64; // tiles between budget checks
+const SHIMMER_SPEED: f32 = 1.5; // board widths per second
+const SHIMMER_WIDTH: f32 = 0.15; // fraction of the board width
+const SHIMMER_ALPHA: f32 = 0.25;
+
+// updates changing more cells than this are spread out instead of applied at once
+const BURST_CELLS: usize = 16;
+const BURST_SECONDS: f32 = 0.75; // however big the burst
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct UpdateShimmer;
+
+// endregion
+
+// region:      RESOURCES
+
+/// A big remote update still being applied, a few cells a frame so frame
+/// times stay even and teammates' work can be seen landing. The rate is set
+/// by each update so any burst is done in `BURST_SECONDS`.
+#[derive(Resource, Default)]
+pub struct RemoteQueue {
+    pub cells: VecDeque<(usize, usize, Cell)>,
+    /// Cells a second.
+    rate: f32,
+    /// Fraction of a cell owed from earlier frames.
+    carry: f32,
+}
+
+/// Cells whose state has been applied to `Board` but whose tiles still show
+/// the old texture.
+#[derive(Resource, Default)]
+pub struct PendingVisuals {
+    pub cells: VecDeque<(usize, usize)>,
+}
+
+// endregion
+
+pub struct UpdatePlugin;
+
+impl Plugin for UpdatePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingVisuals>()
+            .init_resource::<RemoteQueue>()
+            .add_system(board_update_event_system)
+            .add_system(remote_queue_system.after(board_update_event_system))
+            .add_system(apply_visuals_system.after(board_update_event_system))
+            .add_system(update_shimmer_system.after(apply_visuals_system));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn board_update_event_system(
+    board: Res<Board>,
+    send_channel: Res<WASMSendChannel>,
+    mut pending_visuals: ResMut<PendingVisuals>,
+    mut remote_queue: ResMut<RemoteQueue>,
+    mut board_command_event_writer: EventWriter<BoardCommand>,
+    mut board_update_event_reader: EventReader<BoardUpdateEvent>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    loading: Res<Loading>,
+    mut deferred: Local<Vec<String>>,
+) {
+    // a fresh board spawns with the right textures
+    if board_loaded_event_reader.iter().last().is_some() {
+        pending_visuals.cells.clear();
+        remote_queue.cells.clear();
+    }
+
+    // updates meant for a board that is still being parsed wait for it
+    deferred.extend(
+        board_update_event_reader
+            .iter()
+            .map(|event| event.0.clone()),
+    );
+    if loading.0 {
+        return;
+    }
+
+    let width = board.p.get_width();
+    let expected = width * board.p.get_height();
+    let mut latest = None;
+    for cells in deferred.drain(..) {
+        if cells.chars().count() != expected {
+            reply_error(
+                &send_channel,
+                format!(
+                    "update: {} cells, expected {}",
+                    cells.chars().count(),
+                    expected
+                ),
+            );
+            continue;
+        }
+        if let Some(c) = cells.chars().find(|c| char_to_cell(*c).is_none()) {
+            reply_error(&send_channel, format!("update: invalid cell {:?}", c));
+            continue;
+        }
+        latest = Some(cells);
+    }
+
+    // every update is the whole board, the commands are only applied after
+    // this system so just the newest one is diffed against the board, and
+    // it replaces whatever is left of an earlier burst
+    if let Some(cells) = latest {
+        // only differing cells become commands, tiles catch up over the next frames
+        let mut changes = Vec::new();
+        for (i, c) in cells.chars().enumerate() {
+            // updates are in the puzzle's own layout, like the join
+            let (x, y) = board
+                .orientation
+                .from_original_pos(i, width, board.p.get_height());
+            // every char was checked above
+            let cell = match char_to_cell(c) {
+                Some(cell) => cell,
+                None => continue,
+            };
+            if board.p.get_cell(x, y) != cell {
+                changes.push((x, y, cell));
+            }
+        }
+        remote_queue.cells.clear();
+        if changes.len() <= BURST_CELLS {
+            board_command_event_writer.send_batch(changes.into_iter().map(|(x, y, cell)| {
+                BoardCommand {
+                    x,
+                    y,
+                    cell,
+                    source: ChangeSource::Remote,
+                }
+            }));
+            return;
+        }
+        remote_queue.rate = changes.len() as f32 / BURST_SECONDS;
+        remote_queue.carry = 0.;
+        remote_queue.cells.extend(changes);
+    }
+}
+
+/// Applies this frame's share of a queued burst. Cells the player has
+/// changed since are theirs, the burst leaves them alone.
+fn remote_queue_system(
+    time: Res<Time>,
+    mut remote_queue: ResMut<RemoteQueue>,
+    mut cell_changed_event_reader: EventReader<CellChangedEvent>,
+    mut board_command_event_writer: EventWriter<BoardCommand>,
+) {
+    let played: Vec<(usize, usize)> = cell_changed_event_reader
+        .iter()
+        .filter(|event| event.source == ChangeSource::Player)
+        .map(|event| (event.x, event.y))
+        .collect();
+    if remote_queue.cells.is_empty() {
+        return;
+    }
+    if !played.is_empty() {
+        remote_queue
+            .cells
+            .retain(|(x, y, _)| !played.contains(&(*x, *y)));
+    }
+
+    let due = remote_queue.carry + remote_queue.rate * time.delta_seconds();
+    let count = (due as usize).max(1).min(remote_queue.cells.len());
+    remote_queue.carry = (due - count as f32).max(0.);
+    let batch: Vec<(usize, usize, Cell)> = remote_queue.cells.drain(..count).collect();
+    board_command_event_writer.send_batch(batch.into_iter().map(|(x, y, cell)| BoardCommand {
+        x,
+        y,
+        cell,
+        source: ChangeSource::Remote,
+    }));
+}
+
+fn apply_visuals_system(
+    board: Res<Board>,
+    tile_index: Res<TileIndex>,
+    game_textures: Res<GameTextures>,
+    mut pending_visuals: ResMut<PendingVisuals>,
+    mut tile_query: Query<&mut Handle<Image>, With<Tile>>,
+) {
+    if pending_visuals.cells.is_empty() {
+        return;
+    }
+
+    let start = Instant::now();
+    'budget: loop {
+        for _ in 0..VISUAL_BATCH {
+            let (x, y) = match pending_visuals.cells.pop_front() {
+                Some(cell) => cell,
+                None => break 'budget,
+            };
+            if x >= board.p.get_width() || y >= board.p.get_height() {
+                continue;
+            }
+            let (tile_x, tile_y) = board.cell_to_tile(x, y);
+            let entity = tile_index.0.get(&(tile_x as usize, tile_y as usize));
+            if let Some(mut texture) = entity.and_then(|e| tile_query.get_mut(*e).ok()) {
+                *texture = cell_texture(&game_textures, board.p.get_cell(x, y));
+            }
+        }
+        if start.elapsed().as_secs_f64() * 1000. > VISUAL_BUDGET_MS {
+            break;
+        }
+    }
+}
+
+fn update_shimmer_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    board: Res<Board>,
+    settings: Res<Settings>,
+    pending_visuals: Res<PendingVisuals>,
+    remote_queue: Res<RemoteQueue>,
+    mut shimmer_query: Query<(Entity, &mut Transform, &mut Sprite), With<UpdateShimmer>>,
+) {
+    let catching_up = !(pending_visuals.cells.is_empty() && remote_queue.cells.is_empty());
+    if !catching_up || settings.do_not_disturb {
+        for (entity, _, _) in shimmer_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+
+    // a soft band sweeping across the board while tiles or a burst are still
+    // catching up
+    let board_width = board.w as f32 * board.pixels_per_tile;
+    let board_height = board.h as f32 * board.pixels_per_tile;
+    let band_width = board_width * SHIMMER_WIDTH;
+    let progress = (time.elapsed_seconds() * SHIMMER_SPEED).fract();
+    let translation = Vec3::new(
+        board.origin.0 + progress * (board_width - band_width),
+        board.origin.1,
+        EFFECT_Z,
+    );
+    let size = Vec2::new(band_width, board_height);
+
+    if let Some((_, mut transform, mut sprite)) = shimmer_query.iter_mut().next() {
+        transform.translation = translation;
+        sprite.custom_size = Some(size);
+        return;
+    }
+
+    commands
+        .spawn(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(1., 1., 1., SHIMMER_ALPHA),
+                custom_size: Some(size),
+                anchor: Anchor::BottomLeft,
+                ..Default::default()
+            },
+            transform: Transform::from_translation(translation),
+            ..Default::default()
+        })
+        .insert(UpdateShimmer);
+}
+
+// region:      TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::harness::{clue_string, plus_puzzle, Harness, SETTLE_TICKS};
+
+    #[test]
+    fn big_remote_update_is_spread_over_several_frames() {
+        let mut harness = Harness::new();
+        let full: &[u32] = &[5];
+        let clues = clue_string(&[full; 5], &[full; 5]);
+        assert!(harness.join(clues.as_str(), &"0".repeat(25)));
+
+        harness.send("u", &"1".repeat(25));
+        harness.ticks(SETTLE_TICKS);
+        assert_ne!(harness.cells(), "1".repeat(25));
+
+        harness.ticks(60);
+        assert_eq!(harness.cells(), "1".repeat(25));
+    }
+
+    #[test]
+    fn update_with_wrong_size_is_ignored() {
+        let mut harness = Harness::new();
+        assert!(harness.join(plus_puzzle().as_str(), "000000000"));
+
+        harness.replies();
+        harness.send("u", "1111");
+        harness.ticks(SETTLE_TICKS);
+
+        assert_eq!(harness.cells(), "000000000");
+        assert!(harness
+            .replies()
+            .iter()
+            .any(|(command, _)| command == "error"));
+    }
+}
+
+// endregion
@@ -0,0 +1,188 @@
+// region:      IMPORTS
+
+use bevy::{prelude::*, sprite::Anchor, utils::HashSet};
+
+use crate::board::{Board, BoardLoadedEvent, CellChangedEvent};
+use crate::layers::HIGHLIGHT_Z;
+use crate::marks::ClueLine;
+use crate::options::BoardOptions;
+use crate::rules::{LineState, Rules};
+
+// endregion
+
+// region:      CONSTANTS
+
+const STRIP_WIDTH: f32 = 0.1; // fraction of a tile
+const SATISFIED_COLOR: Color = Color::rgba(0.2, 0.8, 0.3, 0.9);
+const INCOMPLETE_COLOR: Color = Color::rgba(0.95, 0.8, 0.2, 0.9);
+const CONTRADICTORY_COLOR: Color = Color::rgba(0.9, 0.2, 0.2, 0.9);
+
+// endregion
+
+// region:      COMPONENTS
+
+/// One line's segment of the strip along the right (rows) and bottom
+/// (columns) edges of the grid.
+#[derive(Component)]
+pub struct ValiditySegment(pub ClueLine);
+
+// endregion
+
+// region:      RESOURCES
+
+#[derive(Resource, Default)]
+pub struct LineStates {
+    pub rows: Vec<LineState>,
+    pub columns: Vec<LineState>,
+}
+
+// endregion
+
+pub struct ValidityPlugin;
+
+impl Plugin for ValidityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LineStates>()
+            .add_system(line_state_system)
+            .add_system(validity_strip_system.after(line_state_system));
+    }
+}
+
+fn state_color(state: LineState) -> Color {
+    match state {
+        LineState::Satisfied => SATISFIED_COLOR,
+        LineState::Incomplete => INCOMPLETE_COLOR,
+        LineState::Contradictory => CONTRADICTORY_COLOR,
+    }
+}
+
+/// Rechecks only the rows and columns a change touched, everything on load
+/// or when the rules change.
+fn line_state_system(
+    board: Res<Board>,
+    rules: Res<Rules>,
+    mut line_states: ResMut<LineStates>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut cell_changed_event_reader: EventReader<CellChangedEvent>,
+) {
+    let (width, height) = (board.p.get_width(), board.p.get_height());
+    if board_loaded_event_reader.iter().last().is_some() || rules.is_changed() {
+        // every line is rechecked anyway
+        cell_changed_event_reader.iter().last();
+        line_states.rows = (0..height)
+            .map(|y| rules.0.row_state(&board.p, y))
+            .collect();
+        line_states.columns = (0..width)
+            .map(|x| rules.0.column_state(&board.p, x))
+            .collect();
+        return;
+    }
+
+    let mut rows = HashSet::default();
+    let mut columns = HashSet::default();
+    for event in cell_changed_event_reader.iter() {
+        rows.insert(event.y);
+        columns.insert(event.x);
+    }
+    // only write back real changes so the strip is left alone otherwise
+    for y in rows.into_iter().filter(|y| *y < height) {
+        let state = rules.0.row_state(&board.p, y);
+        if line_states.rows.get(y) != Some(&state) {
+            line_states.rows[y] = state;
+        }
+    }
+    for x in columns.into_iter().filter(|x| *x < width) {
+        let state = rules.0.column_state(&board.p, x);
+        if line_states.columns.get(x) != Some(&state) {
+            line_states.columns[x] = state;
+        }
+    }
+}
+
+fn segment_placement(board: &Board, line: ClueLine) -> (Vec3, Vec2) {
+    let thickness = board.pixels_per_tile * STRIP_WIDTH;
+    let left = board.grid_left() as f32;
+    match line {
+        // inside the right edge of the grid, level with the row
+        ClueLine::Row(y) => {
+            let right = left + board.p.get_width() as f32;
+            let mut position = board.tile_translation(right, y as f32, HIGHLIGHT_Z);
+            position.x -= thickness;
+            (position, Vec2::new(thickness, board.pixels_per_tile))
+        }
+        // inside the bottom edge of the grid, under the column
+        ClueLine::Column(x) => (
+            board.tile_translation(left + x as f32, 0., HIGHLIGHT_Z),
+            Vec2::new(board.pixels_per_tile, thickness),
+        ),
+    }
+}
+
+/// Shown unless the `validity_strip` option is off.
+fn validity_strip_system(
+    mut commands: Commands,
+    board: Res<Board>,
+    options: Res<BoardOptions>,
+    line_states: Res<LineStates>,
+    mut segment_query: Query<(
+        Entity,
+        &ValiditySegment,
+        &mut Sprite,
+        &mut Transform,
+        &mut Visibility,
+    )>,
+) {
+    let visible = options.get_bool("validity_strip", true);
+    let lines = line_states.rows.len() + line_states.columns.len();
+
+    // a new board brings a different number of lines
+    if line_states.is_changed() && segment_query.iter().count() != lines {
+        for (entity, _, _, _, _) in segment_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        let rows = (0..line_states.rows.len()).map(ClueLine::Row);
+        let columns = (0..line_states.columns.len()).map(ClueLine::Column);
+        for line in rows.chain(columns) {
+            let (position, size) = segment_placement(&board, line);
+            commands
+                .spawn(SpriteBundle {
+                    sprite: Sprite {
+                        color: line_color(&line_states, line),
+                        custom_size: Some(size),
+                        anchor: Anchor::BottomLeft,
+                        ..Default::default()
+                    },
+                    transform: Transform::from_translation(position),
+                    visibility: Visibility {
+                        is_visible: visible,
+                    },
+                    ..Default::default()
+                })
+                .insert(ValiditySegment(line));
+        }
+        return;
+    }
+
+    if !(line_states.is_changed() || board.is_changed() || options.is_changed()) {
+        return;
+    }
+    for (_, segment, mut sprite, mut transform, mut visibility) in segment_query.iter_mut() {
+        let color = line_color(&line_states, segment.0);
+        if sprite.color != color {
+            sprite.color = color;
+        }
+        // follow the board when it is resized
+        let (position, size) = segment_placement(&board, segment.0);
+        transform.translation = position;
+        sprite.custom_size = Some(size);
+        visibility.is_visible = visible;
+    }
+}
+
+fn line_color(line_states: &LineStates, line: ClueLine) -> Color {
+    let state = match line {
+        ClueLine::Row(y) => line_states.rows.get(y),
+        ClueLine::Column(x) => line_states.columns.get(x),
+    };
+    state.copied().map_or(Color::NONE, state_color)
+}
@@ -0,0 +1,297 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+use std::sync::Mutex;
+
+use crate::board::{row_band, Board, SpawnTilesEvent};
+use crate::bridge::reply_error;
+use crate::camera::{rotation_fit_system, BoardView};
+use crate::clean_view::CleanView;
+use crate::options::BoardOptions;
+use crate::settings::{CompactClues, Settings};
+use crate::{BridgeCommandEvent, WASMSendChannel, WinSize};
+
+// endregion
+
+// region:      GLOBAL
+
+/// The view as `get_view_wasm` hands it out, kept current by
+/// `view_state_system` since the export can't reach the app.
+pub static mut GLOBAL_VIEW: Option<Mutex<String>> = None;
+
+// endregion
+
+// region:      CONSTANTS
+
+const MIN_ZOOM: f32 = 0.25;
+const MAX_ZOOM: f32 = 16.;
+
+// endregion
+
+// region:      RESOURCES
+
+/// A view to restore: how far in, the cell in the middle in the puzzle's own
+/// layout, and the compact modes.
+#[derive(Clone, PartialEq)]
+pub struct ViewTarget {
+    zoom: f32,
+    cell: (usize, usize),
+    compact_clues: Option<CompactClues>,
+    clean: Option<bool>,
+}
+
+/// A restored view waiting for the board, and for the relayout a changed
+/// `compact_clues` causes, since a new layout resets the camera.
+#[derive(Resource, Default)]
+pub struct ViewRestore {
+    pending: Option<ViewTarget>,
+}
+
+// endregion
+
+pub struct ViewStatePlugin;
+
+impl Plugin for ViewStatePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ViewRestore>()
+            .add_system(view_command_system)
+            .add_system(
+                view_restore_system
+                    .after(view_command_system)
+                    .after(rotation_fit_system),
+            )
+            .add_system(view_state_system.after(view_restore_system));
+    }
+}
+
+pub fn init() {
+    unsafe {
+        GLOBAL_VIEW = Some(Mutex::new(String::new()));
+    }
+}
+
+pub fn get() -> String {
+    unsafe { GLOBAL_VIEW.as_ref() }
+        .and_then(|view| view.lock().ok())
+        .map(|view| view.clone())
+        .unwrap_or_default()
+}
+
+/// Top level `"key":value` pairs of a flat json object, values raw. Arrays
+/// are kept whole, nested objects aren't supported.
+fn json_fields(data: &str) -> Option<Vec<(&str, &str)>> {
+    let body = data.trim().strip_prefix('{')?.strip_suffix('}')?;
+    let mut fields = Vec::new();
+    let (mut depth, mut start) = (0, 0);
+    for (i, c) in body
+        .char_indices()
+        .chain(std::iter::once((body.len(), ',')))
+    {
+        match c {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            ',' if depth == 0 => {
+                let (key, value) = body[start..i].split_once(':')?;
+                let key = key.trim().strip_prefix('"')?.strip_suffix('"')?;
+                fields.push((key, value.trim()));
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    Some(fields)
+}
+
+/// `{"zoom":2,"cell":[4,7],"compact_clues":"auto","clean":false}`, every
+/// field but `cell` optional. Zoom 1 is the whole board. Read by hand rather
+/// than with `JSON.parse`, the view being flat, so it also runs natively.
+pub fn parse(data: &str) -> Result<ViewTarget, String> {
+    let fields = json_fields(data)
+        .filter(|fields| !fields.is_empty())
+        .ok_or_else(|| format!("view: invalid json {}", data))?;
+    let field = |key: &str| {
+        fields
+            .iter()
+            .find(|(field, _)| *field == key)
+            .map(|(_, value)| *value)
+    };
+    let cell = field("cell")
+        .and_then(|cell| cell.strip_prefix('[')?.strip_suffix(']'))
+        .and_then(|cell| {
+            let (x, y) = cell.split_once(',')?;
+            Some((x.trim().parse::<f64>().ok()?, y.trim().parse::<f64>().ok()?))
+        })
+        .filter(|(x, y)| x.is_finite() && y.is_finite())
+        .ok_or_else(|| String::from("view: missing cell"))?;
+    let compact_clues = match field("compact_clues") {
+        Some(value) => Some(
+            value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .and_then(CompactClues::parse)
+                .ok_or_else(|| format!("view: invalid compact_clues {}", value))?,
+        ),
+        None => None,
+    };
+    Ok(ViewTarget {
+        zoom: field("zoom")
+            .and_then(|zoom| zoom.parse::<f32>().ok())
+            .filter(|zoom| zoom.is_finite())
+            .unwrap_or(1.)
+            .clamp(MIN_ZOOM, MAX_ZOOM),
+        cell: (cell.0.max(0.) as usize, cell.1.max(0.) as usize),
+        compact_clues,
+        clean: match field("clean") {
+            Some("true") => Some(true),
+            Some("false") => Some(false),
+            _ => None,
+        },
+    })
+}
+
+/// `("view", json)` restores a view from `get_view_wasm`, this player's own
+/// after a reload or one another player shared.
+fn view_command_system(
+    send_channel: Res<WASMSendChannel>,
+    mut settings: ResMut<Settings>,
+    mut clean_view: ResMut<CleanView>,
+    mut restore: ResMut<ViewRestore>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+) {
+    for event in bridge_command_event_reader.iter() {
+        if event.command != "view" {
+            continue;
+        }
+        let target = match parse(&event.data) {
+            Ok(target) => target,
+            Err(err) => {
+                reply_error(&send_channel, err);
+                continue;
+            }
+        };
+        // the sharer's layout, leaving this player's own setting as it was
+        if let Some(compact_clues) = target.compact_clues {
+            if settings.compact() != compact_clues {
+                settings.view_compact_clues = Some(compact_clues);
+            }
+        }
+        if let Some(clean) = target.clean {
+            if clean_view.active != clean {
+                clean_view.active = clean;
+            }
+        }
+        restore.pending = Some(target);
+    }
+}
+
+fn view_restore_system(
+    board: Res<Board>,
+    options: Res<BoardOptions>,
+    settings: Res<Settings>,
+    win_size: Res<WinSize>,
+    mut view: ResMut<BoardView>,
+    mut restore: ResMut<ViewRestore>,
+    mut spawn_tiles_event_reader: EventReader<SpawnTilesEvent>,
+) {
+    // a layout spawned this frame may not have reset the camera yet
+    let respawned = spawn_tiles_event_reader.iter().count() > 0;
+    let target = match &restore.pending {
+        Some(target) => target.clone(),
+        None => return,
+    };
+    let (w, h) = (board.p.get_width(), board.p.get_height());
+    let layout = row_band(&board.p, &options, &settings, win_size.as_ref());
+    // nothing loaded yet, or the tiles are about to be laid out again
+    if respawned || w == 0 || h == 0 || layout != (board.row_band, board.stacked_rows) {
+        return;
+    }
+    restore.pending = None;
+
+    let (x, y) = (target.cell.0.min(w - 1), target.cell.1.min(h - 1));
+    let (x, y) = board.orientation.to_transformed(x, y, w, h);
+    let center = board
+        .tile_translation(
+            board.grid_left() as f32 + x as f32 + 0.5,
+            y as f32 + 0.5,
+            0.,
+        )
+        .truncate();
+    let scale = 1. / target.zoom;
+    view.scale = scale;
+    view.offset = center - Vec2::new(win_size.w, win_size.h) * scale / 2.;
+}
+
+/// Keeps `GLOBAL_VIEW` in step with the camera and the compact modes.
+fn view_state_system(
+    board: Res<Board>,
+    view: Res<BoardView>,
+    settings: Res<Settings>,
+    clean_view: Res<CleanView>,
+    win_size: Res<WinSize>,
+    mut spawn_tiles_event_reader: EventReader<SpawnTilesEvent>,
+) {
+    let respawned = spawn_tiles_event_reader.iter().count() > 0;
+    if !(view.is_changed() || settings.is_changed() || clean_view.is_changed() || respawned) {
+        return;
+    }
+    let (w, h) = (board.p.get_width(), board.p.get_height());
+    if w == 0 || h == 0 || board.pixels_per_tile <= 0. {
+        return;
+    }
+
+    let center = view.screen_to_world(Vec2::new(win_size.w, win_size.h) / 2.);
+    let grid = board
+        .tile_translation(board.grid_left() as f32, 0., 0.)
+        .truncate();
+    let cell = ((center - grid) / board.pixels_per_tile).floor();
+    let x = (cell.x.max(0.) as usize).min(w - 1);
+    let y = (cell.y.max(0.) as usize).min(h - 1);
+    let (x, y) = board.orientation.to_original(x, y, w, h);
+    let json = format!(
+        "{{\"zoom\":{},\"cell\":[{},{}],\"compact_clues\":\"{}\",\"clean\":{}}}",
+        1. / view.scale,
+        x,
+        y,
+        settings.compact().as_str(),
+        clean_view.active
+    );
+    if let Some(mut global) = unsafe { GLOBAL_VIEW.as_ref() }.and_then(|view| view.lock().ok()) {
+        *global = json;
+    }
+}
+
+// region:      TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::harness::{plus_puzzle, Harness, SETTLE_TICKS};
+
+    #[test]
+    fn shared_view_zooms_in_without_changing_the_setting() {
+        let mut harness = Harness::new();
+        assert!(harness.join(plus_puzzle().as_str(), "000000000"));
+
+        harness.send(
+            "view",
+            r#"{"zoom":2,"cell":[1,1],"compact_clues":"always","clean":false}"#,
+        );
+        harness.ticks(2 * SETTLE_TICKS);
+        assert_eq!(harness.app.world.resource::<BoardView>().scale, 0.5);
+        assert!(harness.board().stacked_rows);
+        // the sharer's layout is for this view, the player's own stays saved
+        let settings = harness.app.world.resource::<Settings>();
+        assert!(settings.compact_clues == CompactClues::Auto);
+        assert!(settings.compact() == CompactClues::Always);
+
+        harness.replies();
+        harness.send("view", "{\"zoom\":2}");
+        harness.ticks(SETTLE_TICKS);
+        assert!(harness
+            .replies()
+            .iter()
+            .any(|(command, _)| command == "error"));
+    }
+}
+
+// endregion
@@ -0,0 +1,343 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+use picross_handler::{Cell, Puzzle};
+use wasm_bindgen::JsValue;
+
+use crate::board::fnv1a;
+use crate::flip_book::FlipBook;
+use crate::lines::filled_runs;
+use crate::options::BoardOptions;
+use crate::playlist::Playlist;
+use crate::solver::solve;
+use crate::start::epoch_ms;
+use crate::stats::Stats;
+use crate::storage;
+use crate::win::PuzzleSolvedEvent;
+use crate::win_goal::WinGoal;
+use crate::{BridgeCommandEvent, NewBoardEvent, WASMSendChannel};
+
+// endregion
+
+// region:      CONSTANTS
+
+/// Side of each of the week's puzzles, Monday's first.
+const WEEKLY_SIZES: [usize; 7] = [5, 5, 8, 8, 10, 10, 10];
+/// Share of filled cells a generated puzzle aims for.
+const FILL_DENSITY: u64 = 55;
+/// Boards generated for a puzzle before settling for one with several
+/// solutions.
+const MAX_TRIES: usize = 20;
+
+const POINTS_PER_CELL: u32 = 10;
+const HINT_COST: u32 = 25;
+const MISTAKE_COST: u32 = 10;
+/// A solve keeps at least this share of its cells' points, however many
+/// hints and mistakes it took.
+const MIN_POINTS_SHARE: u32 = 4;
+const STREAK_BONUS: u32 = 50;
+/// Days of streak past which the bonus stops growing.
+const MAX_STREAK_DAYS: u32 = 7;
+
+const WEEK_KEY_PREFIX: &str = "weekly.";
+const STREAK_KEY: &str = "weekly_streak";
+
+const DAY_MS: f64 = 86_400_000.;
+// 1970-01-01 was a Thursday, weeks start on Monday
+const EPOCH_WEEKDAY: i64 = 3;
+
+// endregion
+
+// region:      RESOURCES
+
+/// Days in a row with a weekly puzzle solved, kept across weeks.
+#[derive(Clone, Copy, Default)]
+struct Streak {
+    last_day: i64,
+    days: u32,
+    best: u32,
+}
+
+impl Streak {
+    fn load() -> Self {
+        let saved = storage::load(STREAK_KEY).unwrap_or_default();
+        let mut fields = saved.split(',').map(|field| field.parse().ok());
+        let mut next = || fields.next().flatten();
+        match (next(), next(), next()) {
+            (Some(last_day), Some(days), Some(best)) => Self {
+                last_day,
+                days: days as u32,
+                best: best as u32,
+            },
+            _ => Self::default(),
+        }
+    }
+
+    fn save(&self) {
+        storage::save(
+            STREAK_KEY,
+            &format!("{},{},{}", self.last_day, self.days, self.best),
+        );
+    }
+
+    /// The streak with a solve on `day` counted.
+    fn with(self, day: i64) -> Self {
+        let days = match day - self.last_day {
+            0 if self.days > 0 => self.days,
+            1 => self.days + 1,
+            _ => 1,
+        };
+        Self {
+            last_day: day,
+            days,
+            best: self.best.max(days),
+        }
+    }
+
+    /// The streak as it stands on `day`, broken if yesterday had no solve.
+    fn on(self, day: i64) -> u32 {
+        match day - self.last_day {
+            0 | 1 => self.days,
+            _ => 0,
+        }
+    }
+}
+
+/// This week's challenge: puzzles generated from the week's number, so every
+/// player with the same `weekly_salt` gets the same ones without a server,
+/// and the points each earned on its first solve, 0 while unsolved.
+#[derive(Resource, Default)]
+pub struct WeeklyChallenge {
+    week: i64,
+    pub puzzles: Vec<String>,
+    points: Vec<u32>,
+}
+
+impl WeeklyChallenge {
+    fn key(&self) -> String {
+        format!("{}{}", WEEK_KEY_PREFIX, self.week)
+    }
+
+    fn load(week: i64, salt: &str) -> Self {
+        let mut challenge = Self {
+            week,
+            puzzles: weekly_puzzles(week, salt),
+            points: vec![0; WEEKLY_SIZES.len()],
+        };
+        let saved = storage::load(&challenge.key()).unwrap_or_default();
+        for (points, saved) in challenge.points.iter_mut().zip(saved.split(',')) {
+            *points = saved.parse().unwrap_or(0);
+        }
+        challenge
+    }
+
+    fn save(&self) {
+        let points: Vec<String> = self.points.iter().map(u32::to_string).collect();
+        storage::save(&self.key(), &points.join(","));
+    }
+
+    fn score(&self) -> u32 {
+        self.points.iter().sum()
+    }
+
+    /// The week's standing as json, with a line of text to share it by.
+    fn summary_json(&self, streak: Streak, today: i64) -> String {
+        let solved = self.points.iter().filter(|points| **points > 0).count();
+        let starts = iso_date(self.week * 7 - EPOCH_WEEKDAY);
+        let points: Vec<String> = self.points.iter().map(u32::to_string).collect();
+        let text = format!(
+            "Picross W week of {}: {}/{} solved, {} points, {} day streak",
+            starts,
+            solved,
+            self.puzzles.len(),
+            self.score(),
+            streak.on(today)
+        );
+        format!(
+            "{{\"week\":{},\"starts\":\"{}\",\"puzzles\":{},\"solved\":{},\"score\":{},\"points\":[{}],\"streak\":{},\"best_streak\":{},\"text\":\"{}\"}}",
+            self.week,
+            starts,
+            self.puzzles.len(),
+            solved,
+            self.score(),
+            points.join(","),
+            streak.on(today),
+            streak.best,
+            text
+        )
+    }
+}
+
+// endregion
+
+pub struct WeeklyPlugin;
+
+impl Plugin for WeeklyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WeeklyChallenge>()
+            .add_system(weekly_command_system)
+            .add_system(weekly_solved_system);
+    }
+}
+
+/// Days since the epoch in the player's time zone, so the week turns over at
+/// their midnight.
+fn local_day() -> i64 {
+    let offset_minutes = js_sys::Date::new_0().get_timezone_offset();
+    ((epoch_ms() - offset_minutes * 60_000.) / DAY_MS).floor() as i64
+}
+
+fn week_of(day: i64) -> i64 {
+    (day + EPOCH_WEEKDAY).div_euclid(7)
+}
+
+/// `2026-10-12` for a day since the epoch.
+fn iso_date(day: i64) -> String {
+    let iso: String = js_sys::Date::new(&JsValue::from_f64(day as f64 * DAY_MS))
+        .to_iso_string()
+        .into();
+    iso.chars().take(10).collect()
+}
+
+/// xorshift, seeded from the week so the puzzles come out the same anywhere
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+/// Clue string of a square board, `cells` indexed by `y * size + x`.
+fn clues_of(cells: &[Cell], size: usize) -> String {
+    let line = |cells: Vec<Cell>| {
+        let runs: Vec<String> = filled_runs(&cells).iter().map(usize::to_string).collect();
+        match runs.is_empty() {
+            true => String::from("0"),
+            false => runs.join(","),
+        }
+    };
+    let mut lines: Vec<String> = (0..size)
+        .map(|y| line((0..size).map(|x| cells[y * size + x]).collect()))
+        .collect();
+    lines.push(String::from("break"));
+    lines.extend((0..size).map(|x| line((0..size).map(|y| cells[y * size + x]).collect())));
+    lines.join("\n")
+}
+
+/// The week's puzzles. Boards are drawn at random until one has a single
+/// solution, the last drawn being kept if none does.
+fn weekly_puzzles(week: i64, salt: &str) -> Vec<String> {
+    let mut rng = Rng(fnv1a(&format!("{}week{}", salt, week)) | 1);
+    WEEKLY_SIZES
+        .iter()
+        .map(|size| {
+            let mut clues = String::new();
+            for _ in 0..MAX_TRIES {
+                let cells: Vec<Cell> = (0..size * size)
+                    .map(|_| match rng.next() % 100 < FILL_DENSITY {
+                        true => Cell::Filled,
+                        false => Cell::Crossed,
+                    })
+                    .collect();
+                clues = clues_of(&cells, *size);
+                let unique = Puzzle::from_string(clues.as_str())
+                    .ok()
+                    .and_then(|p| solve(&p).ok())
+                    .map_or(false, |solution| solution.unique);
+                if unique {
+                    break;
+                }
+            }
+            clues
+        })
+        .collect()
+}
+
+/// `("weekly", "")` plays this week's challenge from its first unsolved
+/// puzzle on, in a playlist, and replies `("weekly", summary)`.
+/// `("weekly_summary", "")` replies the summary alone to share.
+#[allow(clippy::too_many_arguments)]
+fn weekly_command_system(
+    options: Res<BoardOptions>,
+    send_channel: Res<WASMSendChannel>,
+    mut challenge: ResMut<WeeklyChallenge>,
+    mut playlist: ResMut<Playlist>,
+    mut flip_book: ResMut<FlipBook>,
+    mut win_goal: ResMut<WinGoal>,
+    mut bridge_command_event_reader: EventReader<BridgeCommandEvent>,
+    mut new_board_event_writer: EventWriter<NewBoardEvent>,
+) {
+    for event in bridge_command_event_reader.iter() {
+        if event.command != "weekly" && event.command != "weekly_summary" {
+            continue;
+        }
+        let today = local_day();
+        let week = week_of(today);
+        if challenge.puzzles.is_empty() || challenge.week != week {
+            *challenge = WeeklyChallenge::load(week, options.get("weekly_salt").unwrap_or(""));
+        }
+        let summary = challenge.summary_json(Streak::load(), today);
+        if event.command == "weekly_summary" {
+            send_channel
+                .tx
+                .send((String::from("weekly_summary"), summary));
+            continue;
+        }
+
+        let mut puzzles = challenge.puzzles.clone();
+        let first = puzzles.remove(0);
+        playlist.start(first, puzzles);
+        flip_book.start(Vec::new());
+        win_goal.start(None);
+        // every one solved plays the week again from Monday, for no points
+        let next = challenge.points.iter().position(|points| *points == 0);
+        if let Some(new_board) = playlist.jump(next.unwrap_or(0)) {
+            new_board_event_writer.send(new_board);
+        }
+        send_channel.tx.send((String::from("weekly"), summary));
+    }
+}
+
+/// Scores the first solve of each of the week's puzzles: its cells' points
+/// less hints and mistakes, plus a bonus for each day of the streak. Replies
+/// `("weekly", summary)`.
+fn weekly_solved_system(
+    stats: Res<Stats>,
+    playlist: Res<Playlist>,
+    send_channel: Res<WASMSendChannel>,
+    mut challenge: ResMut<WeeklyChallenge>,
+    mut puzzle_solved_event_reader: EventReader<PuzzleSolvedEvent>,
+) {
+    for _ in puzzle_solved_event_reader.iter() {
+        let clues = match playlist.puzzles.get(playlist.current) {
+            Some(clues) => clues,
+            None => continue,
+        };
+        let index = match challenge.puzzles.iter().position(|puzzle| puzzle == clues) {
+            Some(index) => index,
+            None => continue,
+        };
+        if challenge.points[index] > 0 {
+            continue;
+        }
+
+        let today = local_day();
+        let streak = Streak::load().with(today);
+        streak.save();
+        let size = WEEKLY_SIZES[index] as u32;
+        let base = size * size * POINTS_PER_CELL;
+        let cost = stats.hints_used * HINT_COST + stats.penalties * MISTAKE_COST;
+        let points = base.saturating_sub(cost).max(base / MIN_POINTS_SHARE)
+            + STREAK_BONUS * (streak.days - 1).min(MAX_STREAK_DAYS);
+        challenge.points[index] = points;
+        challenge.save();
+        send_channel.tx.send((
+            String::from("weekly"),
+            challenge.summary_json(streak, today),
+        ));
+    }
+}
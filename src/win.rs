@@ -0,0 +1,87 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+
+use crate::board::{Board, BoardLoadedEvent, CellChangedEvent};
+use crate::rules::Rules;
+use crate::timer::SolveTimer;
+use crate::win_goal::WinGoal;
+use crate::WASMSendChannel;
+
+// endregion
+
+// region:      RESOURCES
+
+#[derive(Resource, Default)]
+pub struct Solved(pub bool);
+
+// endregion
+
+// region:      EVENTS
+
+pub struct PuzzleSolvedEvent;
+
+// endregion
+
+pub struct WinPlugin;
+
+impl Plugin for WinPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Solved>()
+            .add_event::<PuzzleSolvedEvent>()
+            .add_system(check_win_system)
+            .add_system(win_reply_system.after(check_win_system));
+    }
+}
+
+/// Tells the host about every solve, however it was decided, with
+/// `("w", {"id":..,"elapsed_ms":..})`.
+fn win_reply_system(
+    time: Res<Time>,
+    board: Res<Board>,
+    solve_timer: Res<SolveTimer>,
+    send_channel: Res<WASMSendChannel>,
+    mut puzzle_solved_event_reader: EventReader<PuzzleSolvedEvent>,
+) {
+    for _ in puzzle_solved_event_reader.iter() {
+        send_channel.tx.send((
+            String::from("w"),
+            format!(
+                "{{\"id\":\"{}\",\"elapsed_ms\":{}}}",
+                board.id,
+                solve_timer.elapsed_ms(&time)
+            ),
+        ));
+    }
+}
+
+pub fn check_win_system(
+    board: Res<Board>,
+    rules: Res<Rules>,
+    goal: Res<WinGoal>,
+    mut solved: ResMut<Solved>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut cell_changed_event_reader: EventReader<CellChangedEvent>,
+    mut puzzle_solved_event_writer: EventWriter<PuzzleSolvedEvent>,
+) {
+    // the join's own win condition decides instead
+    if goal.condition.is_some() {
+        return;
+    }
+    // joining a room that is already solved shouldn't count as a solve
+    if board_loaded_event_reader.iter().last().is_some() {
+        solved.0 = rules.0.solved(&board.p);
+        return;
+    }
+
+    // one check per frame however many cells changed
+    let changed = cell_changed_event_reader.iter().count() > 0;
+    if solved.0 || !changed {
+        return;
+    }
+
+    if rules.0.solved(&board.p) {
+        solved.0 = true;
+        puzzle_solved_event_writer.send(PuzzleSolvedEvent);
+    }
+}
@@ -0,0 +1,585 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+use picross_handler::Cell;
+
+use crate::board::{Board, BoardLoadedEvent, CellChangedEvent};
+use crate::bridge::reply_error;
+use crate::options::BoardOptions;
+use crate::rules::{RuleSet, Rules};
+use crate::solver::solve;
+use crate::timer::SolveTimer;
+use crate::win::{check_win_system, PuzzleSolvedEvent, Solved};
+use crate::WASMSendChannel;
+
+// endregion
+
+// region:      CONSTANTS
+
+const MAX_GOAL_LEN: usize = 256;
+const MAX_GOAL_DEPTH: usize = 16; // nested `!` and brackets
+
+// endregion
+
+// region:      CONDITIONS
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Metric {
+    Filled,
+    /// Filled cells the solution has filled too.
+    Correct,
+    Wrong,
+    /// Rows and columns matching their clues.
+    Lines,
+    /// Seconds on the solve clock, penalties included.
+    Time,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Comparison {
+    Less,
+    AtMost,
+    Equal,
+    AtLeast,
+    Greater,
+}
+
+/// A parsed `win=` section of the join payload, e.g.
+/// `region(0,0,4,4) | correct=20 & time<=300`. `&` binds tighter than `|`,
+/// `!` negates and brackets group. Regions are `x0,y0,x1,y1` inclusive, in
+/// the puzzle's own layout like the cells string.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Condition {
+    /// Every line matches its clues, the usual win.
+    Solved,
+    /// Every cell of the rectangle agrees with the solution.
+    Region(usize, usize, usize, usize),
+    Compare(Metric, Comparison, u64),
+    Not(Box<Condition>),
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+}
+
+impl Condition {
+    pub fn needs_solution(&self) -> bool {
+        match self {
+            Condition::Region(..)
+            | Condition::Compare(Metric::Correct, ..)
+            | Condition::Compare(Metric::Wrong, ..) => true,
+            Condition::Solved | Condition::Compare(..) => false,
+            Condition::Not(condition) => condition.needs_solution(),
+            Condition::All(conditions) | Condition::Any(conditions) => {
+                conditions.iter().any(Condition::needs_solution)
+            }
+        }
+    }
+
+    /// Whether every region lies on a `width` by `height` board.
+    pub fn fits(&self, width: usize, height: usize) -> bool {
+        match self {
+            Condition::Region(_, _, x1, y1) => *x1 < width && *y1 < height,
+            Condition::Solved | Condition::Compare(..) => true,
+            Condition::Not(condition) => condition.fits(width, height),
+            Condition::All(conditions) | Condition::Any(conditions) => conditions
+                .iter()
+                .all(|condition| condition.fits(width, height)),
+        }
+    }
+
+    pub fn met(&self, facts: &Facts) -> bool {
+        match self {
+            Condition::Solved => facts.solved,
+            Condition::Region(x0, y0, x1, y1) => {
+                (*y0..=*y1).all(|y| (*x0..=*x1).all(|x| facts.matches[y * facts.width + x]))
+            }
+            Condition::Compare(metric, comparison, n) => {
+                let value = match metric {
+                    Metric::Filled => facts.filled as f64,
+                    Metric::Correct => facts.correct as f64,
+                    Metric::Wrong => facts.wrong as f64,
+                    Metric::Lines => facts.lines as f64,
+                    Metric::Time => facts.seconds,
+                };
+                let n = *n as f64;
+                match comparison {
+                    Comparison::Less => value < n,
+                    Comparison::AtMost => value <= n,
+                    Comparison::Equal => value == n,
+                    Comparison::AtLeast => value >= n,
+                    Comparison::Greater => value > n,
+                }
+            }
+            Condition::Not(condition) => !condition.met(facts),
+            Condition::All(conditions) => conditions.iter().all(|condition| condition.met(facts)),
+            Condition::Any(conditions) => conditions.iter().any(|condition| condition.met(facts)),
+        }
+    }
+}
+
+pub fn parse_goal(script: &str) -> Result<Condition, String> {
+    if script.len() > MAX_GOAL_LEN {
+        return Err(format!("win: longer than {} characters", MAX_GOAL_LEN));
+    }
+    let mut parser = Parser {
+        text: script.as_bytes(),
+        pos: 0,
+        depth: 0,
+    };
+    let condition = parser.any()?;
+    parser.skip_spaces();
+    match parser.text.get(parser.pos) {
+        None => Ok(condition),
+        Some(byte) => Err(format!(
+            "win: unexpected {} at {}",
+            *byte as char, parser.pos
+        )),
+    }
+}
+
+struct Parser<'a> {
+    text: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn skip_spaces(&mut self) {
+        while self.text.get(self.pos) == Some(&b' ') {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        self.skip_spaces();
+        let found = self.text.get(self.pos) == Some(&byte);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), String> {
+        match self.eat(byte) {
+            true => Ok(()),
+            false => Err(format!("win: expected {} at {}", byte as char, self.pos)),
+        }
+    }
+
+    /// `a | b | ...`
+    fn any(&mut self) -> Result<Condition, String> {
+        let mut conditions = vec![self.all()?];
+        while self.eat(b'|') {
+            conditions.push(self.all()?);
+        }
+        Ok(match conditions.len() {
+            1 => conditions.remove(0),
+            _ => Condition::Any(conditions),
+        })
+    }
+
+    /// `a & b & ...`
+    fn all(&mut self) -> Result<Condition, String> {
+        let mut conditions = vec![self.unary()?];
+        while self.eat(b'&') {
+            conditions.push(self.unary()?);
+        }
+        Ok(match conditions.len() {
+            1 => conditions.remove(0),
+            _ => Condition::All(conditions),
+        })
+    }
+
+    fn unary(&mut self) -> Result<Condition, String> {
+        self.depth += 1;
+        if self.depth > MAX_GOAL_DEPTH {
+            return Err(String::from("win: nested too deep"));
+        }
+        let condition = if self.eat(b'!') {
+            Condition::Not(Box::new(self.unary()?))
+        } else if self.eat(b'(') {
+            let condition = self.any()?;
+            self.expect(b')')?;
+            condition
+        } else {
+            self.atom()?
+        };
+        self.depth -= 1;
+        Ok(condition)
+    }
+
+    fn atom(&mut self) -> Result<Condition, String> {
+        self.skip_spaces();
+        let start = self.pos;
+        while matches!(self.text.get(self.pos), Some(byte) if byte.is_ascii_lowercase()) {
+            self.pos += 1;
+        }
+        let word = std::str::from_utf8(&self.text[start..self.pos]).unwrap_or_default();
+        let metric = match word {
+            "solved" => return Ok(Condition::Solved),
+            "region" => {
+                self.expect(b'(')?;
+                let x0 = self.number()?;
+                self.expect(b',')?;
+                let y0 = self.number()?;
+                self.expect(b',')?;
+                let x1 = self.number()?;
+                self.expect(b',')?;
+                let y1 = self.number()?;
+                self.expect(b')')?;
+                if x0 > x1 || y0 > y1 {
+                    return Err(format!("win: empty region at {}", start));
+                }
+                return Ok(Condition::Region(
+                    x0 as usize,
+                    y0 as usize,
+                    x1 as usize,
+                    y1 as usize,
+                ));
+            }
+            "filled" => Metric::Filled,
+            "correct" => Metric::Correct,
+            "wrong" => Metric::Wrong,
+            "lines" => Metric::Lines,
+            "time" => Metric::Time,
+            "" => return Err(format!("win: expected a condition at {}", start)),
+            word => return Err(format!("win: unknown condition {}", word)),
+        };
+        let comparison = self.comparison()?;
+        Ok(Condition::Compare(metric, comparison, self.number()?))
+    }
+
+    fn comparison(&mut self) -> Result<Comparison, String> {
+        self.skip_spaces();
+        let rest = &self.text[self.pos..];
+        let (comparison, len) = if rest.starts_with(b"<=") {
+            (Comparison::AtMost, 2)
+        } else if rest.starts_with(b">=") {
+            (Comparison::AtLeast, 2)
+        } else if rest.starts_with(b"<") {
+            (Comparison::Less, 1)
+        } else if rest.starts_with(b">") {
+            (Comparison::Greater, 1)
+        } else if rest.starts_with(b"=") {
+            (Comparison::Equal, 1)
+        } else {
+            return Err(format!("win: expected a comparison at {}", self.pos));
+        };
+        self.pos += len;
+        Ok(comparison)
+    }
+
+    fn number(&mut self) -> Result<u64, String> {
+        self.skip_spaces();
+        let start = self.pos;
+        while matches!(self.text.get(self.pos), Some(byte) if byte.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.text[start..self.pos])
+            .unwrap_or_default()
+            .parse::<u64>()
+            .map_err(|_| format!("win: expected a number at {}", start))
+    }
+}
+
+/// What conditions are checked against, read off the board once per check.
+pub struct Facts {
+    solved: bool,
+    filled: usize,
+    correct: usize,
+    wrong: usize,
+    lines: usize,
+    seconds: f64,
+    width: usize,
+    /// Per cell in the puzzle's own layout, whether it agrees with the
+    /// solution. All false without one.
+    matches: Vec<bool>,
+}
+
+impl Facts {
+    /// `solution` is in the board's layout, filled cells true.
+    pub fn read(
+        board: &Board,
+        rules: &dyn RuleSet,
+        solution: Option<&[bool]>,
+        seconds: f64,
+    ) -> Self {
+        let p = &board.p;
+        let (width, height) = (p.get_width(), p.get_height());
+        let mut facts = Facts {
+            solved: rules.solved(p),
+            filled: 0,
+            correct: 0,
+            wrong: 0,
+            lines: (0..height).filter(|y| rules.row_satisfied(p, *y)).count()
+                + (0..width).filter(|x| rules.column_satisfied(p, *x)).count(),
+            seconds,
+            width,
+            matches: vec![false; width * height],
+        };
+        for i in 0..width * height {
+            let (x, y) = (i % width, i / width);
+            let filled = p.get_cell(x, y) == Cell::Filled;
+            facts.filled += filled as usize;
+            let solution = match solution {
+                Some(solution) => solution[i],
+                None => continue,
+            };
+            match (filled, solution) {
+                (true, true) => facts.correct += 1,
+                (true, false) => facts.wrong += 1,
+                _ => (),
+            }
+            let (x, y) = board.orientation.to_original(x, y, width, height);
+            facts.matches[y * width + x] = filled == solution;
+        }
+        facts
+    }
+}
+
+// endregion
+
+// region:      RESOURCES
+
+/// Win condition the join payload brought instead of solving the whole
+/// board, for event and minigame rooms. It carries over to queued puzzles
+/// until the next join.
+#[derive(Resource, Default)]
+pub struct WinGoal {
+    pub condition: Option<Condition>,
+    /// Board layout, filled cells true, for conditions that compare cells.
+    solution: Option<Vec<bool>>,
+    met: Option<bool>,
+}
+
+impl WinGoal {
+    pub fn start(&mut self, condition: Option<Condition>) {
+        self.condition = condition;
+        self.solution = None;
+        self.met = None;
+    }
+}
+
+// endregion
+
+pub struct WinGoalPlugin;
+
+impl Plugin for WinGoalPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WinGoal>()
+            .add_system(win_goal_system.after(check_win_system));
+    }
+}
+
+/// The `solution` option, or the solver's when the clues allow just one.
+fn solution_cells(board: &Board, options: &BoardOptions) -> Option<Vec<bool>> {
+    let (width, height) = (board.p.get_width(), board.p.get_height());
+    if let Some(solution) = options
+        .get("solution")
+        .filter(|solution| solution.len() == width * height)
+    {
+        // the solution is in the puzzle's own layout, not the randomized one
+        let solution = solution.as_bytes();
+        return Some(
+            (0..width * height)
+                .map(|i| {
+                    let (x, y) = board
+                        .orientation
+                        .to_original(i % width, i / width, width, height);
+                    solution[y * width + x] == b'1'
+                })
+                .collect(),
+        );
+    }
+    solve(&board.p)
+        .ok()
+        .filter(|solution| solution.unique)
+        .map(|solution| {
+            solution
+                .cells
+                .into_iter()
+                .map(|cell| cell == Cell::Filled)
+                .collect()
+        })
+}
+
+/// Decides the win while a goal is set, `check_win_system` stands aside.
+/// Sends `("goal", "met")` or `("goal", "unmet")` for each board and
+/// whenever that flips, conditions on time are checked every second.
+#[allow(clippy::too_many_arguments)]
+fn win_goal_system(
+    time: Res<Time>,
+    board: Res<Board>,
+    rules: Res<Rules>,
+    options: Res<BoardOptions>,
+    solve_timer: Res<SolveTimer>,
+    send_channel: Res<WASMSendChannel>,
+    mut goal: ResMut<WinGoal>,
+    mut solved: ResMut<Solved>,
+    mut last_second: Local<u64>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut cell_changed_event_reader: EventReader<CellChangedEvent>,
+    mut puzzle_solved_event_writer: EventWriter<PuzzleSolvedEvent>,
+) {
+    let loaded = board_loaded_event_reader.iter().last().is_some();
+    if loaded && goal.condition.is_some() {
+        let (width, height) = (board.p.get_width(), board.p.get_height());
+        let condition = goal.condition.clone().unwrap_or(Condition::Solved);
+        goal.met = None;
+        goal.solution = None;
+        let err = if !condition.fits(width, height) {
+            Some(format!(
+                "win: region outside the {}x{} board",
+                width, height
+            ))
+        } else if condition.needs_solution() {
+            goal.solution = solution_cells(&board, &options);
+            goal.solution
+                .is_none()
+                .then(|| String::from("win: needs the solution option or clues with one solution"))
+        } else {
+            None
+        };
+        if let Some(err) = err {
+            // played by the rules instead
+            reply_error(&send_channel, err);
+            goal.condition = None;
+            solved.0 = rules.0.solved(&board.p);
+            return;
+        }
+    }
+
+    let condition = match goal.condition.as_ref() {
+        Some(condition) => condition,
+        None => return,
+    };
+    let changed = cell_changed_event_reader.iter().count() > 0;
+    let seconds = solve_timer.elapsed(&time);
+    if !(loaded || changed || seconds as u64 != *last_second) {
+        return;
+    }
+    *last_second = seconds as u64;
+
+    let facts = Facts::read(&board, rules.0.as_ref(), goal.solution.as_deref(), seconds);
+    let met = condition.met(&facts);
+    if goal.met != Some(met) {
+        goal.met = Some(met);
+        let status = match met {
+            true => "met",
+            false => "unmet",
+        };
+        send_channel
+            .tx
+            .send((String::from("goal"), String::from(status)));
+    }
+
+    // joining a room that already met it shouldn't count as a win
+    if loaded {
+        solved.0 = met;
+    } else if met && !solved.0 {
+        solved.0 = true;
+        puzzle_solved_event_writer.send(PuzzleSolvedEvent);
+    }
+}
+
+// region:      TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::BoardAction;
+    use crate::harness::{plus_puzzle, Harness};
+
+    #[test]
+    fn win_condition_from_the_join_decides_the_win() {
+        let mut harness = Harness::new();
+        // the top row alone, the rest of the board can stay blank
+        let cells = "000000000SPLITSPLITwin=region(0,0,2,0)";
+        assert!(harness.join(plus_puzzle().as_str(), cells));
+        let unmet = (String::from("goal"), String::from("unmet"));
+        assert!(harness.replies().contains(&unmet));
+
+        harness.input_cell(0, 0, BoardAction::Fill);
+        assert!(!harness.solved());
+        harness.input_cell(2, 0, BoardAction::Fill);
+        assert!(harness.solved());
+        let met = (String::from("goal"), String::from("met"));
+        assert!(harness.replies().contains(&met));
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or_and_brackets_group() {
+        let filled = Condition::Compare(Metric::Filled, Comparison::Equal, 3);
+        let lines = Condition::Compare(Metric::Lines, Comparison::Greater, 1);
+        assert_eq!(
+            parse_goal("solved | filled=3 & lines>1"),
+            Ok(Condition::Any(vec![
+                Condition::Solved,
+                Condition::All(vec![filled.clone(), lines.clone()]),
+            ]))
+        );
+        assert_eq!(
+            parse_goal("(solved | filled = 3) & lines > 1"),
+            Ok(Condition::All(vec![
+                Condition::Any(vec![Condition::Solved, filled]),
+                lines,
+            ]))
+        );
+        assert_eq!(
+            parse_goal("!!solved"),
+            Ok(Condition::Not(Box::new(Condition::Not(Box::new(
+                Condition::Solved
+            )))))
+        );
+    }
+
+    #[test]
+    fn every_comparison_parses() {
+        let comparisons = [
+            ("time<60", Metric::Time, Comparison::Less),
+            ("time<=60", Metric::Time, Comparison::AtMost),
+            ("wrong=60", Metric::Wrong, Comparison::Equal),
+            ("correct>=60", Metric::Correct, Comparison::AtLeast),
+            ("filled>60", Metric::Filled, Comparison::Greater),
+        ];
+        for (script, metric, comparison) in comparisons {
+            assert_eq!(
+                parse_goal(script),
+                Ok(Condition::Compare(metric, comparison, 60))
+            );
+        }
+    }
+
+    #[test]
+    fn malformed_goals_are_errors() {
+        for script in [
+            "",
+            "solved solved",
+            "time",
+            "time<",
+            "time<x",
+            "region(1,0,0,0)",
+            "region(0,0,1)",
+            "(solved",
+            "solved)",
+            "unknown>1",
+            "solved |",
+        ] {
+            assert!(parse_goal(script).is_err(), "{}", script);
+        }
+        let deep = format!("{}solved", "!".repeat(MAX_GOAL_DEPTH + 1));
+        assert!(parse_goal(&deep).is_err());
+        let long = vec!["solved"; MAX_GOAL_LEN].join("|");
+        assert!(parse_goal(&long).is_err());
+    }
+
+    #[test]
+    fn regions_need_the_solution_and_must_fit_the_board() {
+        let goal = parse_goal("region(0,0,2,2) | time<60").unwrap();
+        assert!(goal.needs_solution());
+        assert!(goal.fits(3, 3));
+        assert!(!goal.fits(2, 3));
+        assert!(!parse_goal("filled>=3 & !solved").unwrap().needs_solution());
+    }
+}
+
+// endregion
@@ -0,0 +1,365 @@
+// region:      IMPORTS
+
+use bevy::prelude::*;
+
+use crate::bests::PersonalBests;
+use crate::board::{cell_to_char, Board, BoardLoadedEvent};
+use crate::camera::BoardView;
+use crate::campaign::thumbnail_image;
+use crate::celebration::{celebration_start_system, Celebration};
+use crate::export::picture_png;
+use crate::hud_scale::HudScale;
+use crate::layers::{WIN_LABEL_Z, WIN_PANEL_Z, WIN_Z};
+use crate::options::BoardOptions;
+use crate::playlist::Playlist;
+use crate::screens::Screen;
+use crate::stars::{star_rating, MAX_STARS};
+use crate::stats::Stats;
+use crate::timer::SolveTimer;
+use crate::win::PuzzleSolvedEvent;
+#[cfg(feature = "replays")]
+use crate::BridgeCommandEvent;
+use crate::{GameTextures, WASMSendChannel, WinSize};
+
+// endregion
+
+// region:      CONSTANTS
+
+const PICTURE_SIZE: f32 = 0.4; // of the shorter window side
+const PICTURE_Y: f32 = 0.58; // of the window height
+const STARS_Y: f32 = 0.88;
+const STATS_Y: f32 = 0.3;
+const BESTS_Y: f32 = 0.22;
+const BUTTONS_Y: f32 = 0.14;
+const BUTTON_WIDTH: f32 = 0.26; // of the window width
+const BUTTON_HEIGHT: f32 = 44.;
+const BUTTON_GAP: f32 = 12.;
+
+const STARS_FONT_SIZE: f32 = 40.;
+const STATS_FONT_SIZE: f32 = 20.;
+const BUTTON_FONT_SIZE: f32 = 18.;
+
+const BACKDROP_COLOR: Color = Color::rgba(0.12, 0.12, 0.14, 0.96);
+const BUTTON_COLOR: Color = Color::rgb(0.3, 0.45, 0.7);
+
+// endregion
+
+// region:      COMPONENTS
+
+#[derive(Component)]
+pub struct WinScreenEntity;
+
+// endregion
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum WinButton {
+    Download,
+    /// Plays back timelapse frames, so only built with them.
+    #[cfg(feature = "replays")]
+    Replay,
+    Next,
+}
+
+impl WinButton {
+    fn all() -> Vec<WinButton> {
+        vec![
+            WinButton::Download,
+            #[cfg(feature = "replays")]
+            WinButton::Replay,
+            WinButton::Next,
+        ]
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            WinButton::Download => "Download image (D)",
+            #[cfg(feature = "replays")]
+            WinButton::Replay => "Replay (R)",
+            WinButton::Next => "Next puzzle (Space)",
+        }
+    }
+
+    fn key(&self) -> KeyCode {
+        match self {
+            WinButton::Download => KeyCode::D,
+            #[cfg(feature = "replays")]
+            WinButton::Replay => KeyCode::R,
+            WinButton::Next => KeyCode::Space,
+        }
+    }
+}
+
+pub struct WinScreenPlugin;
+
+impl Plugin for WinScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(win_screen_open_system.after(celebration_start_system))
+            .add_system_set(SystemSet::on_enter(Screen::Win).with_system(win_screen_enter_system))
+            .add_system_set(SystemSet::on_update(Screen::Win).with_system(win_screen_input_system))
+            .add_system_set(SystemSet::on_exit(Screen::Win).with_system(win_screen_exit_system));
+        #[cfg(feature = "replays")]
+        app.add_system_set(SystemSet::on_update(Screen::Win).with_system(win_screen_replay_system));
+    }
+}
+
+/// Shows after a solve on the board, unless the `win_screen` option is off
+/// or a playlist is about to move on by itself, once the celebration on the
+/// board is over. A new board closes it.
+fn win_screen_open_system(
+    options: Res<BoardOptions>,
+    playlist: Res<Playlist>,
+    celebration: Res<Celebration>,
+    mut screen: ResMut<State<Screen>>,
+    mut pending: Local<bool>,
+    mut board_loaded_event_reader: EventReader<BoardLoadedEvent>,
+    mut puzzle_solved_event_reader: EventReader<PuzzleSolvedEvent>,
+) {
+    let loaded = board_loaded_event_reader.iter().last().is_some();
+    let solved = puzzle_solved_event_reader.iter().last().is_some();
+    if loaded {
+        *pending = false;
+        if *screen.current() == Screen::Win {
+            screen.set(Screen::Board).ok();
+        }
+        return;
+    }
+    let playlist_continues = playlist.len() > 1 && playlist.current + 1 < playlist.len();
+    if solved && !playlist_continues && options.get_bool("win_screen", true) {
+        *pending = true;
+    }
+    if !*pending || celebration.playing() {
+        return;
+    }
+    *pending = false;
+    if *screen.current() == Screen::Board {
+        screen.set(Screen::Win).ok();
+    }
+}
+
+/// Button `i` of `count` as (center, size) in screen units, in a row across
+/// the bottom of the window.
+fn button_layout(i: usize, count: usize, win_size: &WinSize, hud_scale: f32) -> (Vec2, Vec2) {
+    let size = Vec2::new(win_size.w * BUTTON_WIDTH, BUTTON_HEIGHT * hud_scale);
+    let gap = BUTTON_GAP * hud_scale;
+    let row_width = count as f32 * (size.x + gap) - gap;
+    let left = (win_size.w - row_width) / 2.;
+    let center = Vec2::new(
+        left + i as f32 * (size.x + gap) + size.x / 2.,
+        win_size.h * BUTTONS_Y,
+    );
+    (center, size)
+}
+
+fn contains((center, size): (Vec2, Vec2), point: Vec2) -> bool {
+    ((point - center).abs() - size / 2.).max_element() <= 0.
+}
+
+#[allow(clippy::too_many_arguments)]
+fn win_screen_enter_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    board: Res<Board>,
+    stats: Res<Stats>,
+    view: Res<BoardView>,
+    win_size: Res<WinSize>,
+    hud_scale: Res<HudScale>,
+    options: Res<BoardOptions>,
+    solve_timer: Res<SolveTimer>,
+    bests: Res<PersonalBests>,
+    game_textures: Res<GameTextures>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let at = |x: f32, y: f32| view.screen_to_world(Vec2::new(x, y));
+    let text = |value: String, font_size: f32, color: Color| {
+        Text::from_section(
+            value,
+            TextStyle {
+                font: game_textures.font.clone(),
+                font_size: font_size * hud_scale.world(&view),
+                color,
+            },
+        )
+        .with_alignment(TextAlignment::CENTER)
+    };
+
+    // backdrop hiding the board underneath
+    let center = at(win_size.w / 2., win_size.h / 2.);
+    commands
+        .spawn(SpriteBundle {
+            sprite: Sprite {
+                color: BACKDROP_COLOR,
+                custom_size: Some(Vec2::new(win_size.w, win_size.h) * view.scale),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(center.x, center.y, WIN_Z),
+            ..Default::default()
+        })
+        .insert(WinScreenEntity);
+
+    // the solved picture, its longer side filling the slot
+    let p = &board.p;
+    let cells: String = (0..p.get_height())
+        .flat_map(|y| (0..p.get_width()).map(move |x| cell_to_char(p.get_cell(x, y))))
+        .collect();
+    let record = format!("{};{};{}", p.get_width(), p.get_height(), cells);
+    if let Some(image) = thumbnail_image(&record) {
+        let size = image.size();
+        let side = win_size.w.min(win_size.h) * PICTURE_SIZE * view.scale;
+        let position = at(win_size.w / 2., win_size.h * PICTURE_Y);
+        commands
+            .spawn(SpriteBundle {
+                texture: images.add(image),
+                sprite: Sprite {
+                    custom_size: Some(size / size.max_element() * side),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(position.x, position.y, WIN_PANEL_Z),
+                ..Default::default()
+            })
+            .insert(WinScreenEntity);
+    }
+
+    // earned stars as asterisks, like the banner
+    let seconds = solve_timer.elapsed(&time);
+    let stars = star_rating(&options, seconds, stats.hints_used, stats.penalties);
+    let banner = format!(
+        "{}{}",
+        "* ".repeat(stars as usize),
+        "- ".repeat((MAX_STARS - stars) as usize)
+    );
+    let elapsed_ms = solve_timer.elapsed_ms(&time);
+    let summary = format!(
+        "Solved in {}:{:02}\nhints: {}  mistakes: {}",
+        elapsed_ms / 60000,
+        elapsed_ms / 1000 % 60,
+        stats.hints_used,
+        stats.penalties
+    );
+    let personal_best = match (bests.current, bests.new_best_time()) {
+        (Some(best), true) => format!("New best time!  {}", best.summary()),
+        (Some(best), false) => best.summary(),
+        (None, _) => String::new(),
+    };
+    let lines = [
+        (
+            banner.trim_end().to_string(),
+            STARS_FONT_SIZE,
+            STARS_Y,
+            Color::GOLD,
+        ),
+        (summary, STATS_FONT_SIZE, STATS_Y, Color::WHITE),
+        (personal_best, STATS_FONT_SIZE, BESTS_Y, Color::GOLD),
+    ];
+    for (value, font_size, y, color) in lines {
+        let position = at(win_size.w / 2., win_size.h * y);
+        commands
+            .spawn(Text2dBundle {
+                text: text(value, font_size, color),
+                transform: Transform::from_xyz(position.x, position.y, WIN_PANEL_Z),
+                ..Default::default()
+            })
+            .insert(WinScreenEntity);
+    }
+
+    let buttons = WinButton::all();
+    for (i, button) in buttons.iter().enumerate() {
+        let (center, size) = button_layout(i, buttons.len(), &win_size, hud_scale.0);
+        let position = at(center.x, center.y);
+        commands
+            .spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: BUTTON_COLOR,
+                    custom_size: Some(size * view.scale),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(position.x, position.y, WIN_PANEL_Z),
+                ..Default::default()
+            })
+            .insert(WinScreenEntity);
+        commands
+            .spawn(Text2dBundle {
+                text: text(button.label().to_string(), BUTTON_FONT_SIZE, Color::WHITE),
+                transform: Transform::from_xyz(position.x, position.y, WIN_LABEL_Z),
+                ..Default::default()
+            })
+            .insert(WinScreenEntity);
+    }
+}
+
+/// Button clicked or pressed by its key this frame.
+fn activated_button(
+    keys: &Input<KeyCode>,
+    buttons: &Input<MouseButton>,
+    windows: &Windows,
+    win_size: &WinSize,
+    hud_scale: &HudScale,
+) -> Option<WinButton> {
+    let all = WinButton::all();
+    let cursor = windows
+        .get_primary()
+        .and_then(|window| window.cursor_position())
+        .filter(|_| buttons.just_pressed(MouseButton::Left));
+    let clicked = all.iter().enumerate().find(|(i, _)| {
+        let layout = button_layout(*i, all.len(), win_size, hud_scale.0);
+        cursor.map_or(false, |cursor| contains(layout, cursor))
+    });
+    let pressed = all.iter().find(|button| keys.just_pressed(button.key()));
+    clicked.map(|(_, button)| button).or(pressed).copied()
+}
+
+/// Download replies `("image", base64 png)` with the solved picture and
+/// next asks the host for another puzzle with `("next", "")`. Escape or
+/// Return goes back to the board.
+fn win_screen_input_system(
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    win_size: Res<WinSize>,
+    hud_scale: Res<HudScale>,
+    board: Res<Board>,
+    send_channel: Res<WASMSendChannel>,
+    mut screen: ResMut<State<Screen>>,
+) {
+    if keys.any_just_pressed([KeyCode::Escape, KeyCode::Return]) {
+        screen.set(Screen::Board).ok();
+        return;
+    }
+
+    match activated_button(&keys, &buttons, &windows, &win_size, &hud_scale) {
+        Some(WinButton::Download) => {
+            send_channel
+                .tx
+                .send((String::from("image"), picture_png(&board.p)));
+        }
+        Some(WinButton::Next) => {
+            send_channel.tx.send((String::from("next"), String::new()));
+        }
+        // see win_screen_replay_system
+        _ => (),
+    }
+}
+
+/// Replay asks the timelapse for `("timelapse", json)` frames.
+#[cfg(feature = "replays")]
+fn win_screen_replay_system(
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    win_size: Res<WinSize>,
+    hud_scale: Res<HudScale>,
+    mut bridge_command_event_writer: EventWriter<BridgeCommandEvent>,
+) {
+    if activated_button(&keys, &buttons, &windows, &win_size, &hud_scale) == Some(WinButton::Replay)
+    {
+        bridge_command_event_writer.send(BridgeCommandEvent {
+            command: String::from("timelapse"),
+            data: String::new(),
+        });
+    }
+}
+
+fn win_screen_exit_system(mut commands: Commands, win_query: Query<Entity, With<WinScreenEntity>>) {
+    for entity in win_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}